@@ -1,11 +1,10 @@
 package main
 
 import (
-	"flag"
 	"github.com/solidcopy/bcbc/internal/app/bcbc"
+	"os"
 )
 
 func main() {
-	flag.Parse()
-	bcbc.Execute(flag.Args())
+	os.Exit(bcbc.Execute(os.Args[1:]))
 }
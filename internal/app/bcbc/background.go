@@ -0,0 +1,17 @@
+package bcbc
+
+// バックグラウンドモードなら処理の優先度を下げる。
+// 下げられなくてもハッシュ計算は続ける。
+func initBackground() {
+	if !config.background {
+		return
+	}
+
+	if err := lowerPriority(); err != nil {
+		logf.Println("優先度を下げられませんでした。通常の優先度で実行します。")
+		logf.Println(err)
+		return
+	}
+
+	logf.Println("バックグラウンドモードで実行します。")
+}
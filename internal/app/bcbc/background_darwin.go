@@ -0,0 +1,17 @@
+// +build darwin
+
+package bcbc
+
+import "syscall"
+
+// setpriorityのmacOS独自の定数
+const (
+	prioDarwinProcess = 4
+	prioDarwinBG      = 0x1000
+)
+
+// プロセスをバックグラウンドのQoSにする。
+// CPUの優先度が下がり、ディスクのI/Oも抑制される。
+func lowerPriority() error {
+	return syscall.Setpriority(prioDarwinProcess, 0, prioDarwinBG)
+}
@@ -0,0 +1,47 @@
+// +build linux
+
+package bcbc
+
+import (
+	"os"
+	"strconv"
+	"syscall"
+)
+
+// ioprio_setの定数
+const (
+	ioprioWhoProcess = 1
+	ioprioClassIdle  = 3
+	ioprioClassShift = 13
+)
+
+// 最も低いnice値
+const lowestNice = 19
+
+// CPUのnice値とI/Oの優先度(idleクラス)を下げる。
+// Linuxではどちらもスレッドごとの設定なので、既存の全てのスレッドに設定する。
+// 以降に作成されるスレッドは作成元のスレッドの設定を引き継ぐ。
+func lowerPriority() error {
+	tasks, err := os.ReadDir("/proc/self/task")
+	if err != nil {
+		return err
+	}
+
+	for _, task := range tasks {
+		tid, err := strconv.Atoi(task.Name())
+		if err != nil {
+			continue
+		}
+
+		if err := syscall.Setpriority(syscall.PRIO_PROCESS, tid, lowestNice); err != nil {
+			return err
+		}
+
+		_, _, errno := syscall.Syscall(syscall.SYS_IOPRIO_SET, ioprioWhoProcess, uintptr(tid), ioprioClassIdle<<ioprioClassShift)
+		if errno != 0 {
+			return errno
+		}
+	}
+
+	return nil
+}
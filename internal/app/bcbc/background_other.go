@@ -0,0 +1,10 @@
+// +build !linux,!darwin,!windows
+
+package bcbc
+
+import "errors"
+
+// その他のOSでは優先度を下げられない。
+func lowerPriority() error {
+	return errors.New("このOSではバックグラウンドモードに対応していません。")
+}
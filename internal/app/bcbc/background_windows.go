@@ -0,0 +1,24 @@
+// +build windows
+
+package bcbc
+
+import "syscall"
+
+// SetPriorityClassのバックグラウンド処理モードの開始
+const processModeBackgroundBegin = 0x00100000
+
+// プロセスをバックグラウンド処理モードにする。
+// CPU、I/O、メモリの優先度が下がる。
+func lowerPriority() error {
+	kernel32 := syscall.NewLazyDLL("kernel32.dll")
+	process, err := syscall.GetCurrentProcess()
+	if err != nil {
+		return err
+	}
+
+	ret, _, err := kernel32.NewProc("SetPriorityClass").Call(uintptr(process), processModeBackgroundBegin)
+	if ret == 0 {
+		return err
+	}
+	return nil
+}
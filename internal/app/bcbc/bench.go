@@ -0,0 +1,234 @@
+package bcbc
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"math/rand"
+	"os"
+	"path/filepath"
+	"runtime"
+	"sort"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// ハッシュのスループットを測るときに繰り返しハッシュ計算するデータのサイズ
+const benchHashDataSize = 64 << 20
+
+// ハッシュのスループットを測る時間
+const benchHashDuration = 2 * time.Second
+
+// 読み込みのスループットを測るバッファサイズ
+var benchBufferSizes = []int{MinBufferSize, 256 << 10, 1 << 20, 4 << 20, BufferSize}
+
+// バッファサイズの違いを誤差とみなす割合
+const benchTolerance = 0.05
+
+// 「4G」のように単位を付けたサイズを解析する。単位は1024の累乗とする。
+func parseByteSize(value string) (uint64, error) {
+	text := strings.ToUpper(strings.TrimSpace(value))
+	text = strings.TrimSuffix(strings.TrimSuffix(text, "B"), "I")
+
+	multiplier := uint64(1)
+	if text != "" {
+		if shift := strings.IndexByte("KMGT", text[len(text)-1]); shift >= 0 {
+			multiplier = 1 << (10 * uint(shift+1))
+			text = text[:len(text)-1]
+		}
+	}
+
+	n, err := strconv.ParseUint(text, 10, 64)
+	if err != nil || n == 0 {
+		return 0, fmt.Errorf("サイズの指定が不正です。: %s", value)
+	}
+	return n * multiplier, nil
+}
+
+// benchサブコマンド。
+// メモリ上のデータのハッシュ計算と、指定されたパスの読み込みのスループットをバッファサイズごとに測り、推奨する設定を表示する。
+// パスがディレクトリならテストファイルを書き込んで読み込み、終わったら削除する。ファイルならそのファイルを読み込む。
+func executeBench(args []string) {
+	flagSet := flag.NewFlagSet("bench", flag.ExitOnError)
+	sizeText := flagSet.String("size", "1G", "ディレクトリに書き込むテストファイルのサイズ。(例: 4G)")
+	algorithmText := flagSet.String("algorithm", "", "測定するハッシュアルゴリズム。カンマ区切りで複数指定できる。指定しなければ-algorithmのアルゴリズム。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, flagSet.NArg() > 1, "測定するパスを1つ指定してください。\n")
+	size, err := parseByteSize(*sizeText)
+	fatalMessageError(ErrConfig, err, "テストファイルのサイズの指定が不正です。\n")
+	algorithms := config.algorithms
+	if *algorithmText != "" {
+		algorithms = checkAlgorithms(strings.Split(*algorithmText, ","))
+	}
+	target := "."
+	if flagSet.NArg() == 1 {
+		target = flagSet.Arg(0)
+	}
+
+	logf.Println("性能の測定を開始します。")
+	defer logf.Println("性能の測定を終了しました。")
+
+	hashThroughputs := benchHashing(algorithms)
+	readThroughputs := benchReading(target, size)
+
+	printBenchRecommendations(algorithms, hashThroughputs, readThroughputs)
+}
+
+// アルゴリズムごとにメモリ上のデータをハッシュ計算するスループットを測る。
+func benchHashing(algorithms []string) []float64 {
+	data := make([]byte, benchHashDataSize)
+	rand.New(rand.NewSource(1)).Read(data)
+
+	throughputs := make([]float64, len(algorithms))
+	for i, algorithm := range algorithms {
+		hasher := hasherFactories[algorithm]()
+		var hashed uint64
+		startTime := time.Now()
+		for time.Since(startTime) < benchHashDuration && !interrupted() {
+			for offset := 0; offset < len(data); offset += BufferSize {
+				end := offset + BufferSize
+				if end > len(data) {
+					end = len(data)
+				}
+				hasher.Write(data[offset:end])
+			}
+			hasher.Sum(nil)
+			hasher.Reset()
+			hashed += uint64(len(data))
+		}
+
+		throughputs[i] = float64(hashed) / time.Since(startTime).Seconds()
+		logf.Printf("ハッシュ計算(%s): %s\n", algorithm, formatThroughput(throughputs[i]))
+	}
+
+	return throughputs
+}
+
+// バッファサイズごとに、ページキャッシュを使わずにファイルを読み込むスループットを測る。
+func benchReading(target string, size uint64) map[int]float64 {
+	stat, err := os.Stat(target)
+	fatalMessageError(ErrIO, err, "測定するパスが見つかりません。: %s\n", target)
+
+	file := target
+	if stat.IsDir() {
+		file = filepath.Join(target, "bcbc-bench.tmp")
+		writeBenchFile(file, size)
+		defer os.Remove(file)
+	} else {
+		size = uint64(stat.Size())
+		fatalMessageIf(ErrConfig, size == 0, "測定するファイルが空です。: %s\n", target)
+	}
+
+	throughputs := make(map[int]float64)
+	for _, bufferSize := range benchBufferSizes {
+		if interrupted() {
+			break
+		}
+		throughput, err := measureReading(file, size, bufferSize)
+		fatalMessageError(ErrIO, err, "テストファイルの読み込みに失敗しました。: %s\n", file)
+		throughputs[bufferSize] = throughput
+		logf.Printf("読み込み(バッファ%s): %s\n", formatSize(float64(bufferSize)), formatThroughput(throughput))
+	}
+
+	return throughputs
+}
+
+// 無作為なデータのテストファイルを書き込む。
+// 圧縮するファイルシステムでも実際に読み込むように、ゼロではないデータにする。
+func writeBenchFile(file string, size uint64) {
+	logf.Printf("%sのテストファイルを書き込みます。: %s\n", formatSize(float64(size)), file)
+
+	data := make([]byte, BufferSize)
+	rand.New(rand.NewSource(1)).Read(data)
+
+	fileOut, err := os.Create(file)
+	fatalMessageError(ErrIO, err, "テストファイルを作成できませんでした。: %s\n", file)
+	defer fileOut.Close()
+
+	for written := uint64(0); written < size && !interrupted(); {
+		chunk := data
+		if size-written < uint64(len(chunk)) {
+			chunk = chunk[:size-written]
+		}
+		n, err := fileOut.Write(chunk)
+		if err != nil {
+			os.Remove(file)
+		}
+		fatalMessageError(ErrIO, err, "テストファイルの書き込みに失敗しました。: %s\n", file)
+		written += uint64(n)
+	}
+
+	err = fileOut.Sync()
+	fatalMessageError(ErrIO, err, "テストファイルの書き込みに失敗しました。: %s\n", file)
+}
+
+// 指定されたバッファサイズでファイル全体を読み込んだスループットを返す。
+func measureReading(file string, size uint64, bufferSize int) (float64, error) {
+	fileIn, err := openDirect(file, size)
+	if err != nil {
+		return 0, err
+	}
+	defer fileIn.Close()
+
+	buffer := alignedBuffer(bufferSize)
+	var offset int64
+	startTime := time.Now()
+	for !interrupted() {
+		n, err := fileIn.ReadAt(buffer, offset)
+		offset += int64(n)
+		if err == io.EOF || (err == nil && n == 0) {
+			break
+		}
+		if err != nil {
+			return 0, err
+		}
+	}
+
+	return float64(offset) / time.Since(startTime).Seconds(), nil
+}
+
+// 測定結果から推奨する設定を表示する。
+func printBenchRecommendations(algorithms []string, hashThroughputs []float64, readThroughputs map[int]float64) {
+	if len(readThroughputs) == 0 {
+		return
+	}
+
+	// 全アルゴリズムを1つのゴルーチンで順に計算するので、1回の読み込みあたりの時間は各アルゴリズムの時間の和になる
+	var secondsPerByte float64
+	for _, throughput := range hashThroughputs {
+		secondsPerByte += 1 / throughput
+	}
+	hashThroughput := 1 / secondsPerByte
+
+	bufferSizes := make([]int, 0, len(readThroughputs))
+	for bufferSize := range readThroughputs {
+		bufferSizes = append(bufferSizes, bufferSize)
+	}
+	sort.Ints(bufferSizes)
+	best := bufferSizes[0]
+	for _, bufferSize := range bufferSizes {
+		if readThroughputs[bufferSize] > readThroughputs[best] {
+			best = bufferSize
+		}
+	}
+	readThroughput := readThroughputs[best]
+
+	logf.Printf("最も速い読み込みバッファ: %s (%s)\n", formatSize(float64(best)), formatThroughput(readThroughput))
+	if current, measured := readThroughputs[BufferSize]; measured && current >= readThroughput*(1-benchTolerance) {
+		logf.Printf("既定の読み込みバッファ(%s)との差は誤差の範囲です。\n", formatSize(BufferSize))
+	}
+
+	logf.Printf("ハッシュ計算(%s): %s\n", strings.Join(algorithms, ","), formatThroughput(hashThroughput))
+	if hashThroughput < readThroughput {
+		logf.Println("ハッシュ計算が読み込みより遅いので、CPUが律速になります。巨大なファイルが多ければmd5-treeかsha256-treeで複数のコアを使えます。")
+	}
+
+	// 1台のディスクの読み込みに必要なCPUの割合から、CPUが律速にならずに並行して計算できるディスクの数を求める
+	parallelDisks := int(float64(runtime.NumCPU()) * hashThroughput / readThroughput)
+	if parallelDisks < 1 {
+		parallelDisks = 1
+	}
+	logf.Printf("推奨する同時にハッシュ計算するディスクの数: -max-parallel-disks %d (%d個のCPUコア)\n", parallelDisks, runtime.NumCPU())
+}
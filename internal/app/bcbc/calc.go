@@ -1,107 +1,263 @@
 package bcbc
 
 import (
-	"bufio"
-	"fmt"
 	"log"
-	"os"
-	"path"
-	"path/filepath"
-	"regexp"
-	"sort"
+	"time"
 )
 
 // ロガー。
 // 標準出力とログファイルにログを出力する。
-var logf *log.Logger
+// ログファイルを作成するまでは標準出力だけに出力する。
+var logf = newMessageLogger(log.New(consoleOut, "", log.LstdFlags))
+
+// 終了コード
+const (
+	// ExitSuccess 正常終了
+	ExitSuccess = 0
+	// ExitFileErrors ハッシュ計算できなかったファイルがある
+	ExitFileErrors = 1
+	// ExitFatal 設定の誤りなどで処理を続行できなかった
+	ExitFatal = 2
+	// ExitInterrupted 中断された
+	ExitInterrupted = 130
+)
 
 // Execute エントリーポイント。
-func Execute(diskRoots []string) {
+// 引数はプログラム名を除いたコマンドライン引数で、先頭のコマンドラインオプションを解析する。
+// 残りの引数の先頭がサブコマンド名ならそのサブコマンドを実行し、それ以外はディスクルートとしてハッシュ計算を行う。
+// 実行結果を表す終了コードを返す。
+func Execute(args []string) int {
+	commandLine = newCommandLine()
+	commandLine.Parse(args)
+	errorCount, err := execute(commandLine.Args())
+
+	switch {
+	case IsKind(err, ErrInterrupted):
+		return ExitInterrupted
+	case err != nil:
+		return ExitFatal
+	case errorCount > 0:
+		return ExitFileErrors
+	default:
+		return ExitSuccess
+	}
+}
+
+// サブコマンドで問題が見つかったファイルとディスクの数。
+// 0でなければ終了コードで知らせる。
+var commandErrors int
+
+// 初期処理をしてサブコマンドかハッシュ計算を実行する。
+// エラーが発生したファイルとディスクの数を返す。
+func execute(args []string) (errorCount int, err error) {
+	defer recoverFatal(&err)
 
 	// 初期処理
+	// 既定値設定ファイルのBCBCホームディレクトリ、言語、ログの形式も使うように、既定値設定ファイルを先に読み込む
+	// 既定値設定ファイルでBCBCホームディレクトリが変わったら、そのホームディレクトリの既定値設定ファイルで残りの既定値を補う
 	initEnvs()
+	initDefaults()
+	if initEnvs() {
+		initDefaults()
+	}
+	initLanguage()
+	initLogFormat()
+	initHome()
 	logFileOut := initLogger()
 	defer logFileOut.Close()
+	initOptions()
+	initSigning()
+	initEncryption()
+	initGroups()
+	initBackground()
+	watchInterrupt()
+	watchPause()
+	startMetricsServer()
+
+	if len(args) > 0 {
+		if command, found := commands[args[0]]; found {
+			command.run(args[1:])
+			return commandErrors, nil
+		}
+	}
+
+	return executeHashing(args), nil
+}
+
+// RunOptions ライブラリとして実行するときのオプション
+type RunOptions struct {
+	HomeDir    string
+	DiskRoots  []string
+	Algorithms []string
+	Store      string
+	StoreURL   string
+	Symlinks   string
+	MaxDepth   int
+	Logger     *log.Logger
+}
+
+// Run ライブラリとしてハッシュ計算とハッシュファイル統合を実行する。
+// 致命的なエラーが発生してもプログラムを終了せずにエラーを返す。
+// 設定はパッケージで共有しているので、複数のRunを並行して実行してはいけない。
+func Run(options RunOptions) (err error) {
+	defer recoverFatal(&err)
+
+	config.homeDir = options.HomeDir
+	// 端末に直接書き込まずに、進捗も含めて全て呼び出し元のロガーに出力する
+	logf = newMessageLogger(options.Logger)
+	progressLogger = options.Logger
+	statusArea = nil
+	config.algorithms = checkAlgorithms(options.Algorithms)
+	config.store = checkStore(options.Store)
+	config.storeURL, config.storeUser = checkStoreURL(config.store, options.StoreURL)
+	config.symlinks = checkSymlinks(options.Symlinks)
+	config.maxDepth = options.MaxDepth
+	initGroups()
+
+	executeHashing(options.DiskRoots)
+
+	return nil
+}
+
+// フィルター設定を読み込んでハッシュ計算とハッシュファイル統合を実行する。
+// 通知設定があれば結果を通知する。
+// エラーが発生したファイルとディスクの数を返す。
+func executeHashing(diskRoots []string) (errorCount int) {
 	initFilters()
+	initFilterExplain()
+	defer filterExplain.close()
+	initNotify()
+	defer notifyResult(&errorCount)
+	warnStaleGroups()
 
-	executeHashCalculation(diskRoots)
+	errorCount = executeHashCalculation(diskRoots)
 	executeHashFileIntegration()
+	updateIndex()
+
+	return errorCount
 }
 
 // ハッシュ計算を実行する。
-func executeHashCalculation(diskRoots []string) {
+// エラーが発生したファイルとディスクの数を返す。
+func executeHashCalculation(diskRoots []string) int {
 
 	logf.Println("ハッシュ計算を開始します。")
 	defer logf.Println("ハッシュ計算を終了しました。")
 
 	diskFiles := findDiskFiles(diskRoots)
-	fatalMessageIf(len(diskFiles) == 0, "diskファイルが見つかりませんでした。\n")
+	fatalMessageIf(ErrDiskFile, len(diskFiles) == 0, "diskファイルが見つかりませんでした。\n")
 
-	progressChannel := make(chan ProgressInfo)
 	completionChannel := make(chan CompletionMessage)
 	diskInfoList := makeDiskInfoList(diskFiles)
-	go watchProgress(len(diskInfoList), progressChannel)
-
-	for i := range diskInfoList {
-		go hashRoutine(&diskInfoList[i], progressChannel, completionChannel)
-	}
+	checkOutputSpace(diskInfoList)
+	stats = newHashStats(config.slowReport)
+	missingFiles = newMissingFileReport()
+	errorNotifier = newErrorThresholdNotifier()
+	progressChannel, stopProgressMonitor := startProgressMonitor(len(diskInfoList))
 
-	// 全ハッシュルーチンの終了を待つ
-	for range diskInfoList {
-		if completion := <-completionChannel; completion.err != nil {
-			logf.Printf("ディスク(%s)のハッシュ計算中に問題が発生しました。\n", completion.diskId)
-			logf.Println(completion.err)
+	// 同時にハッシュ計算するディスクの数を制限して、1つ終わるごとに次のディスクを開始する
+	started := 0
+	startNextDisk := func() {
+		if started < len(diskInfoList) && !interrupted() {
+			go hashRoutine(&diskInfoList[started], progressChannel, completionChannel)
+			started++
 		}
 	}
-}
 
-// ハッシュファイル統合を実行する。
-func executeHashFileIntegration() {
+	parallelDisks := config.maxParallelDisks
+	if parallelDisks == 0 || parallelDisks > len(diskInfoList) {
+		parallelDisks = len(diskInfoList)
+	}
+	for i := 0; i < parallelDisks; i++ {
+		startNextDisk()
+	}
 
-	logf.Println("ハッシュファイルの統合を開始します。")
-	defer logf.Println("ハッシュファイルの統合を終了しました。")
+	errorCount := 0
+	wasInterrupted := false
+	var hashedDiskIds []string
+	var fatalErr error
 
-	mergedHashMap := make(map[string][]string)
+	// 開始した全ハッシュルーチンの終了を待つ
+	// 致命的なエラーが発生したら残りのルーチンを打ち切り、それらの終了も待ってから伝える
+	for completed := 0; completed < started; completed++ {
+		completion := <-completionChannel
+		errorCount += completion.fileErrors
+		errorNotifier.updateFileErrors(completion.diskId, completion.fileErrors)
 
-	outputFiles, err := filepath.Glob(path.Join(config.outDir(), "*"))
-	fatalMessageError(err, "出力ファイルの一覧取得に失敗しました。\n")
+		switch {
+		case completion.err == nil:
+			hashedDiskIds = append(hashedDiskIds, completion.diskId)
+		case completion.fatal:
+			if fatalErr == nil {
+				fatalErr = completion.err
+				abortRoutines()
+			}
+		case IsKind(completion.err, ErrInterrupted):
+			wasInterrupted = true
+		default:
+			errorCount++
+			errorNotifier.addDiskError()
+			diskLog := logf.with(logFields{DiskID: completion.diskId})
+			diskLog.Printf("ディスク(%s)のハッシュ計算中に問題が発生しました。\n", completion.diskId)
+			diskLog.Println(completion.err)
+		}
 
-	hashFilePattern := regexp.MustCompile("^([A-Z])\\d+$")
+		startNextDisk()
 
-	for _, outputFile := range outputFiles {
-		fileName := filepath.Base(outputFile)
-		subMatches := hashFilePattern.FindStringSubmatch(fileName)
-		if len(subMatches) == 0 {
-			continue
-		}
+		metrics.updateErrors(errorCount)
+	}
 
-		group := subMatches[1]
+	// 全ハッシュルーチンが終了したので進捗監視ルーチンも終了させる
+	if err := stopProgressMonitor(); err != nil {
+		errorCount++
+		logf.Println("進捗の監視中に問題が発生しました。")
+		logf.Println(err)
+	}
 
-		mergedHashes := mergedHashMap[group]
+	statusArea.finish()
 
-		hashFileIn, err := os.Open(outputFile)
-		fatalMessageError(err, "ハッシュファイルの読み込みに失敗しました。: %s\n", outputFile)
-		for hashFileScanner := bufio.NewScanner(hashFileIn); hashFileScanner.Scan(); {
-			line := hashFileScanner.Text()
-			if line != "" {
-				mergedHashes = append(mergedHashes, line)
-			}
-		}
-		mergedHashMap[group] = mergedHashes
+	if fatalErr != nil {
+		// 打ち切ったルーチンは全て終了したので、次の処理のために打ち切りを解除する
+		resetAbort()
+		panic(fatalErr)
 	}
 
-	for group, mergedHashes := range mergedHashMap {
-		sort.Strings(mergedHashes)
-		mergedHashFile := path.Join(config.outDir(), group)
+	stats.report()
+	missingFiles.report()
+
+	updateRegistry(hashedDiskIds, func(record *DiskRecord) {
+		record.LastHashAt = time.Now()
+	})
 
-		mergedHashFileOut, err := os.OpenFile(mergedHashFile, os.O_CREATE|os.O_WRONLY|os.O_TRUNC, 0644)
-		fatalMessageError(err, "統合ハッシュファイルの作成に失敗しました。\n")
+	fatalMessageIf(ErrInterrupted, wasInterrupted || started < len(diskInfoList), "ハッシュ計算を中断しました。\n")
 
-		for _, line := range mergedHashes {
-			_, err := fmt.Fprintln(mergedHashFileOut, line)
-			fatalMessageError(err, "統合ハッシュファイルの書き込みに失敗しました。\n")
+	return errorCount
+}
+
+// ハッシュファイル統合を実行する。
+func executeHashFileIntegration() {
+
+	logf.Println("ハッシュファイルの統合を開始します。")
+	defer logf.Println("ハッシュファイルの統合を終了しました。")
+
+	for _, algorithm := range config.algorithms {
+		store := hashStoreFor(algorithm)
+		for group, diskIds := range groupDiskIds(store) {
+			mergeHashFiles(store, group, diskIds)
 		}
+	}
+}
+
+// ハッシュファイルがあるディスクIDを統合先のグループごとに分類する。
+func groupDiskIds(store HashStore) map[string][]string {
+	diskIds, err := store.diskIds()
+	fatalMessageError(ErrIO, err, "出力ファイルの一覧取得に失敗しました。\n")
 
-		mergedHashFileOut.Close()
+	groups := make(map[string][]string)
+	for _, diskId := range diskIds {
+		group := diskGroupOf(diskId)
+		groups[group] = append(groups[group], diskId)
 	}
+
+	return groups
 }
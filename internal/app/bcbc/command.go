@@ -0,0 +1,36 @@
+package bcbc
+
+// サブコマンドの定義
+type subcommand struct {
+	run func(args []string)
+	// helpで表示する説明
+	summary string
+}
+
+// サブコマンド
+var commands = map[string]subcommand{
+	"status":        {executeStatus, "統合ハッシュファイルが最新か確認する。-auto-mergeで古いものを統合し直す。"},
+	"replay":        {executeReplay, "進捗ログからディスクごとの処理の経過を再構成して表示する。"},
+	"verify":        {executeVerify, "ハッシュファイルの記録とファイルの現在のハッシュを比較する。"},
+	"doctor":        {executeDoctor, "BCBCホームディレクトリの構成、設定、ロックファイル、ハッシュファイルを診断する。"},
+	"daemon":        {executeDaemon, "常駐してスケジュール設定ファイルに従ってハッシュ計算と検証を定期的に実行する。"},
+	"watch":         {executeWatch, "ディスクを監視して、追加や変更されたファイルのハッシュをハッシュファイルに追記する。"},
+	"init-disk":     {executeInitDisk, "ディスクルートにdiskファイルを作成してディスク登録簿に登録する。"},
+	"disks":         {executeDisks, "ディスク登録簿の一覧表示(list)、詳細表示(show)、説明と保管場所の設定(set)を行う。"},
+	"find":          {executeFind, "パスかハッシュが一致する記録を探して、どのディスクにあるか表示する。"},
+	"fsck-hashfile": {executeFsckHashFile, "ハッシュファイルの形式を検証し、不正な行と重複した記録を削除する。"},
+	"setup":         {executeSetup, "BCBCホームディレクトリと設定ファイルの雛形を作成し、実行できる環境か確認する。"},
+	"filter-check":  {executeFilterCheck, "フィルター設定ファイルを検証し、指定したパスに一致するフィルターの行を表示する。"},
+	"snapshots":     {executeSnapshots, "ディスクのスナップショットの一覧と違いを表示する。"},
+	"history-diff":  {executeHistoryDiff, "ディスクの2つの時点の記録を比べて、追加、削除、ハッシュが変わったファイルを表示する。"},
+	"sign-key":      {executeSignKey, "ハッシュファイルに署名するEd25519の鍵を作成する。"},
+	"serve":         {executeServe, "他のマシンのbcbcのハッシュファイルを集める収集サーバーを開始する。"},
+	"dupes":         {executeDupes, "全てのディスクから同じハッシュのファイルを探して、減らせるバイト数が多い順に表示する。"},
+	"stats":         {executeStats, "ディスクごとと全体のファイル数、合計サイズ、重複の割合などを表示する。"},
+	"scrub":         {executeScrub, "記録されたファイルの一部を無作為に抽出して検証する。"},
+	"unverified":    {executeUnverified, "しばらく検証していないファイルと一度も検証していないファイルを数える。"},
+	"copy":          {executeCopy, "ハッシュを計算しながらファイルをコピーし、コピー先を読み直して確かめる。"},
+	"compare-trees": {executeCompareTrees, "2つのディレクトリのファイルを読み込んで比べる。"},
+	"bench":         {executeBench, "ハッシュ計算と読み込みのスループットを測り、推奨する設定を表示する。"},
+	"selftest":      {executeSelftest, "既知のハッシュと読み込みの経路ごとのハッシュ計算を確かめる。"},
+}
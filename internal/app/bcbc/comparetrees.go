@@ -0,0 +1,147 @@
+package bcbc
+
+import (
+	"flag"
+	"os"
+	"sort"
+	"time"
+)
+
+// compare-treesサブコマンド。
+// 2つのディレクトリをフィルター設定に従って走査し、片方にしかないファイルと、
+// 両方にあって内容が異なるファイルを報告する。ハッシュファイルは使わずに両方のファイルを読み込んで比べる。
+// ミラーしているディスクを直接比べるために使う。
+func executeCompareTrees(args []string) {
+	flagSet := flag.NewFlagSet("compare-trees", flag.ExitOnError)
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, flagSet.NArg() != 2, "比べる2つのディレクトリを指定してください。\n")
+	initFilters()
+
+	roots := make([]*DiskInfo, 2)
+	for i, root := range flagSet.Args() {
+		rootPath := normalizeRootPath(root)
+		stat, err := os.Stat(rootPath)
+		fatalMessageIf(ErrConfig, err != nil || !stat.IsDir(), "ディレクトリが見つかりません。: %s\n", root)
+		roots[i] = &DiskInfo{index: i, id: string(rune('A' + i)), rootPath: rootPath}
+	}
+
+	logf.Printf("比較を開始します。: %s と %s\n", roots[0].rootPath, roots[1].rootPath)
+	defer logf.Println("比較を終了しました。")
+
+	trees := make([]map[string]*FileInfo, 2)
+	for i, root := range roots {
+		trees[i] = listTargetFiles(root)
+	}
+
+	var onlyA, onlyB, common []string
+	for normPath := range trees[0] {
+		if _, found := trees[1][normPath]; found {
+			common = append(common, normPath)
+		} else {
+			onlyA = append(onlyA, normPath)
+		}
+	}
+	for normPath := range trees[1] {
+		if _, found := trees[0][normPath]; !found {
+			onlyB = append(onlyB, normPath)
+		}
+	}
+	sort.Strings(onlyA)
+	sort.Strings(onlyB)
+	sort.Strings(common)
+
+	for _, normPath := range onlyA {
+		logf.Printf("%sにだけあります。: %s\n", roots[0].rootPath, normPath)
+	}
+	for _, normPath := range onlyB {
+		logf.Printf("%sにだけあります。: %s\n", roots[1].rootPath, normPath)
+	}
+
+	var totalSize uint64
+	for _, normPath := range common {
+		size, _ := trees[0][normPath].size()
+		totalSize += size * 2
+	}
+
+	progressChannel, stopProgressMonitor := startProgressMonitor(1)
+	progressInfo := ProgressInfo{
+		diskInfo:  roots[0],
+		fileCount: ProgressCount{uint64(len(common)), 0},
+		sizeCount: ProgressCount{totalSize, 0},
+		startTime: time.Now(),
+	}
+	progressChannel <- progressInfo
+
+	mismatches := 0
+	for _, normPath := range common {
+		if interrupted() {
+			break
+		}
+
+		if status := compareFiles(trees[0][normPath], trees[1][normPath], &progressInfo, progressChannel); status != "" {
+			logf.Printf("%s: %s\n", status, normPath)
+			mismatches++
+		}
+		progressInfo.fileCount.Increment(1)
+	}
+
+	progressChannel <- progressInfo
+	if err := stopProgressMonitor(); err != nil {
+		logf.Println("進捗の監視中に問題が発生しました。")
+		logf.Println(err)
+	}
+	statusArea.finish()
+
+	fatalMessageIf(ErrInterrupted, interrupted(), "比較を中断しました。\n")
+
+	commandErrors += len(onlyA) + len(onlyB) + mismatches
+	logf.Printf("比較結果: 共通%s 内容の相違%s 片方にだけある%s\n", formatQuantity(uint64(len(common)), "件", "件"),
+		formatQuantity(uint64(mismatches), "件", "件"), formatQuantity(uint64(len(onlyA)+len(onlyB)), "件", "件"))
+}
+
+// ディレクトリを走査してフィルター設定で対象になるファイルをパスで引けるようにする。
+func listTargetFiles(root *DiskInfo) map[string]*FileInfo {
+	files := make(map[string]*FileInfo)
+	listFiles(root.rootPath, func(file string) bool {
+		fi := &FileInfo{}
+		fi.init(root, file)
+		if filterFile(fi.normPath) {
+			files[fi.normPath] = fi
+		}
+		return !interrupted()
+	})
+	return files
+}
+
+// 2つのファイルの内容を比べて、異なれば検証結果の状態を返す。同じなら空文字列を返す。
+// サイズが異なれば読み込まない。
+func compareFiles(a *FileInfo, b *FileInfo, progressInfo *ProgressInfo, progressChannel chan ProgressInfo) string {
+	sizeA, _ := a.size()
+	sizeB, _ := b.size()
+	if sizeA != sizeB {
+		progressInfo.sizeCount.Increment(sizeA + sizeA)
+		return VerifySizeChanged
+	}
+
+	var hashes [2][][]byte
+	for i, fi := range []*FileInfo{a, b} {
+		hash, damaged, err := calcHash(fi.realPath, sizeA, config.algorithms, *progressInfo, progressChannel)
+		progressInfo.sizeCount.Increment(sizeA)
+		if IsKind(err, ErrInterrupted) {
+			return ""
+		}
+		if err != nil || len(damaged) > 0 {
+			if err != nil {
+				logf.Println(err)
+			}
+			return VerifyError
+		}
+		hashes[i] = hash
+	}
+
+	if !equalHashes(hashes[0], hashes[1]) {
+		return VerifyMismatch
+	}
+	return ""
+}
@@ -0,0 +1,87 @@
+package bcbc
+
+import (
+	"compress/gzip"
+	"io"
+	"os"
+	"strings"
+)
+
+// ハッシュファイルの圧縮形式
+const (
+	// CompressNone 圧縮しない
+	CompressNone = ""
+	// CompressGzip gzipで圧縮する
+	CompressGzip = "gzip"
+)
+
+// gzipで圧縮したハッシュファイルの拡張子
+const compressedExtension = ".gz"
+
+// 圧縮形式の指定を検証する。
+func checkCompress(compress string) string {
+	valid := compress == CompressNone || compress == CompressGzip
+	fatalMessageIf(ErrConfig, !valid, "圧縮形式の指定が不正です。: %s\n", compress)
+	return compress
+}
+
+// 書き込むハッシュファイルの拡張子を返す。
+func compressedSuffix() string {
+	if config.compress == CompressGzip {
+		return compressedExtension
+	}
+	return ""
+}
+
+// ハッシュファイルの実際のパスを返す。
+// 圧縮していないファイルがあればそれを、なければ圧縮したファイルを返す。
+// どちらもなければ圧縮していないファイルのパスを返す。
+// 以前は圧縮したハッシュファイルへの追記を圧縮していないファイルに展開してから行っていたので、
+// 両方あれば圧縮していないほうが新しい。
+func existingHashFile(file string) string {
+	if _, err := os.Stat(file); err == nil {
+		return file
+	}
+	if _, err := os.Stat(file + compressedExtension); err == nil {
+		return file + compressedExtension
+	}
+	return file
+}
+
+// ハッシュファイルを開く。拡張子が.gzなら展開しながら読み込む。
+func openHashFile(file string) (io.ReadCloser, error) {
+	in, err := os.Open(file)
+	if err != nil {
+		return nil, err
+	}
+	if !strings.HasSuffix(file, compressedExtension) {
+		return in, nil
+	}
+
+	gzipIn, err := gzip.NewReader(in)
+	if err != nil {
+		in.Close()
+		return nil, err
+	}
+	return &gzipFile{gzipIn, in}, nil
+}
+
+// 展開しながら読み込むファイル
+type gzipFile struct {
+	*gzip.Reader
+	file *os.File
+}
+
+func (f *gzipFile) Close() error {
+	err := f.Reader.Close()
+	if closeErr := f.file.Close(); err == nil {
+		err = closeErr
+	}
+	return err
+}
+
+// ハッシュファイルとその署名ファイルを削除する。
+func removeHashFile(file string) {
+	os.Remove(file)
+	removeSignature(file)
+}
@@ -0,0 +1,49 @@
+package bcbc
+
+import (
+	"bufio"
+	"fmt"
+	"os"
+	"strings"
+	"sync"
+)
+
+// 確認で表示する変更内容の件数の上限
+const confirmPreviewLimit = 20
+
+// 複数のディスクの確認が混ざらないように確認を1つずつ行うためのミューテックス
+var confirmMutex sync.Mutex
+
+// 確認の回答を読み込む標準入力
+var confirmReader = bufio.NewReader(os.Stdin)
+
+// ハッシュファイルから記録を削除する前に削除する記録を表示して確認する。
+// -confirmが指定されていないか削除する記録がなければ確認せずにtrueを返す。
+func confirmRemoval(target string, removedPaths []string) bool {
+	if !*confirmOption || len(removedPaths) == 0 {
+		return true
+	}
+
+	confirmMutex.Lock()
+	defer confirmMutex.Unlock()
+
+	logf.Printf("%sから%d件の記録を削除します。\n", target, len(removedPaths))
+	for i, removedPath := range removedPaths {
+		if i >= confirmPreviewLimit {
+			logf.Printf("  ...他%d件\n", len(removedPaths)-i)
+			break
+		}
+		logf.Printf("  - %s\n", removedPath)
+	}
+
+	fmt.Print(tr("削除してよろしいですか? [y/N]: "))
+	answer, _ := confirmReader.ReadString('\n')
+	answer = strings.ToLower(strings.TrimSpace(answer))
+
+	confirmed := answer == "y" || answer == "yes"
+	if !confirmed {
+		logf.Printf("%sの記録は削除せずに残します。\n", target)
+	}
+
+	return confirmed
+}
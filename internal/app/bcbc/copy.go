@@ -0,0 +1,262 @@
+package bcbc
+
+import (
+	"encoding/hex"
+	"flag"
+	"hash"
+	"io"
+	"os"
+	"path/filepath"
+	"sort"
+	"time"
+)
+
+// copyサブコマンド。
+// ディスクのハッシュ計算の対象のファイルをコピー先にコピーしながらハッシュを計算し、
+// コピー先を読み直して同じハッシュになるか確かめる。
+// コピー元のハッシュファイルを更新し、コピー先もdiskファイルがあるディスクならそのハッシュファイルも更新する。
+func executeCopy(args []string) {
+	flagSet := flag.NewFlagSet("copy", flag.ExitOnError)
+	overwrite := flagSet.Bool("overwrite", false, "コピー先に既にあるファイルも上書きする。指定しなければ既にあるファイルはコピーしない。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, flagSet.NArg() != 2, "コピー元のディスクルートとコピー先のディレクトリを指定してください。\n")
+	initFilters()
+
+	source := &makeDiskInfoList(findDiskFiles(flagSet.Args()[:1]))[0]
+	destinationRoot := normalizeRootPath(flagSet.Arg(1))
+	err := os.MkdirAll(destinationRoot, 0755)
+	fatalMessageError(ErrIO, err, "コピー先のディレクトリを作成できませんでした。: %s\n", destinationRoot)
+
+	// コピー先がディスクならそのハッシュファイルも書く
+	var destination *DiskInfo
+	if _, err := os.Stat(filepath.Join(destinationRoot, "disk")); err == nil {
+		destination = &makeDiskInfoList([]string{filepath.Join(destinationRoot, "disk")})[0]
+		destination.index = 1
+		fatalMessageIf(ErrConfig, destination.id == source.id, "コピー元とコピー先が同じディスクIDです。: %s\n", source.id)
+	}
+
+	for _, diskInfo := range []*DiskInfo{source, destination} {
+		if diskInfo == nil {
+			continue
+		}
+		err := lockDisk(diskInfo)
+		fatalMessageError(ErrLocked, err, "ハッシュファイルをロックできませんでした。: %s\n", diskInfo.id)
+		defer unlockDisk(diskInfo)
+	}
+
+	logf.Printf("コピーを開始します。: %s -> %s\n", source.rootPath, destinationRoot)
+	defer logf.Println("コピーを終了しました。")
+
+	// コピーする前に対象のファイルと合計サイズを調べる
+	var targets []FileInfo
+	var totalSize uint64
+	listFiles(source.rootPath, func(file string) bool {
+		var fi FileInfo
+		fi.init(source, file)
+		if filterFile(fi.normPath) {
+			size, _ := fi.size()
+			totalSize += size
+			targets = append(targets, fi)
+		}
+		return !interrupted()
+	})
+
+	progressChannel, stopProgressMonitor := startProgressMonitor(1)
+	// コピーと読み直しで2回読むので、進捗は合計サイズの2倍にする
+	progressInfo := ProgressInfo{
+		diskInfo:  source,
+		fileCount: ProgressCount{uint64(len(targets)), 0},
+		sizeCount: ProgressCount{totalSize * 2, 0},
+		startTime: time.Now(),
+	}
+	progressChannel <- progressInfo
+
+	// コピー元のディスクのアルゴリズムで計算し、コピー先にはそのうちコピー先のディスクで使うものだけを記録する
+	algorithms := source.hashAlgorithms()
+	sourceRecords := make([][]HashRecord, len(algorithms))
+	destinationRecords := make([][]HashRecord, len(algorithms))
+	copied, skipped, problems := 0, 0, 0
+
+	// 中断されてもコピーできたファイルのハッシュは記録する
+	saveRecords := func() {
+		mergeCopiedRecords(source, algorithms, sourceRecords)
+		if destination != nil {
+			mergeCopiedRecords(destination, algorithms, destinationRecords)
+		}
+	}
+
+	for i := range targets {
+		if interrupted() {
+			stopProgressMonitor()
+			saveRecords()
+			fatalMessageIf(ErrInterrupted, true, "コピーを中断しました。\n")
+		}
+
+		fi := &targets[i]
+		size, _ := fi.size()
+		destinationPath := filepath.Join(destinationRoot, filepath.FromSlash(fi.normPath))
+
+		if _, err := os.Stat(destinationPath); err == nil && !*overwrite {
+			skipped++
+			progressInfo.fileCount.Increment(1)
+			progressInfo.sizeCount.Increment(size * 2)
+			continue
+		}
+
+		hashes, err := copyFileHashing(fi.realPath, destinationPath, fi.stat(), algorithms)
+		progressInfo.sizeCount.Increment(size)
+		progressChannel <- progressInfo
+		if err != nil {
+			logf.Printf("コピーできませんでした。: %s\n", fi.realPath)
+			logf.Println(err)
+			problems++
+			progressInfo.fileCount.Increment(1)
+			progressInfo.sizeCount.Increment(size)
+			continue
+		}
+
+		// コピー先を読み直して比べる
+		copiedHashes, damaged, err := calcHash(destinationPath, size, algorithms, progressInfo, progressChannel)
+		progressInfo.fileCount.Increment(1)
+		progressInfo.sizeCount.Increment(size)
+		if IsKind(err, ErrInterrupted) {
+			continue
+		}
+		if err != nil || len(damaged) > 0 || !equalHashes(hashes, copiedHashes) {
+			logf.Printf("コピー先のハッシュがコピー元と一致しません。: %s\n", destinationPath)
+			if err != nil {
+				logf.Println(err)
+			}
+			problems++
+			continue
+		}
+
+		var destinationInfo FileInfo
+		if destination != nil {
+			destinationInfo.init(destination, destinationPath)
+		}
+		for j, algorithm := range algorithms {
+			digest := hex.EncodeToString(hashes[j])
+			sourceRecords[j] = append(sourceRecords[j], fi.newRecord(algorithm, digest))
+			if destination != nil {
+				destinationRecords[j] = append(destinationRecords[j], destinationInfo.newRecord(algorithm, digest))
+			}
+		}
+		copied++
+	}
+
+	progressChannel <- progressInfo
+	if err := stopProgressMonitor(); err != nil {
+		logf.Println("進捗の監視中に問題が発生しました。")
+		logf.Println(err)
+	}
+	statusArea.finish()
+
+	saveRecords()
+	commandErrors += problems
+
+	logf.Printf("%sをコピーしました。(既にあったので%sをコピーせず、%sで問題が発生しました。)\n",
+		formatQuantity(uint64(copied), "件", "件"), formatQuantity(uint64(skipped), "件", "件"), formatQuantity(uint64(problems), "件", "件"))
+}
+
+// ファイルをコピーしながらハッシュを計算する。
+// コピー先には一時ファイルに書き込んでから置き換え、更新日時とパーミッションをコピー元に合わせる。
+func copyFileHashing(sourcePath string, destinationPath string, stat os.FileInfo, algorithms []string) ([][]byte, error) {
+	in, err := os.Open(sourcePath)
+	if err != nil {
+		return nil, err
+	}
+	defer in.Close()
+
+	if err := os.MkdirAll(filepath.Dir(destinationPath), 0755); err != nil {
+		return nil, err
+	}
+
+	mode := os.FileMode(0644)
+	if stat != nil {
+		mode = stat.Mode().Perm()
+	}
+	tempFile := destinationPath + ".bcbc-copy"
+	out, err := os.OpenFile(tempFile, os.O_CREATE|os.O_WRONLY|os.O_TRUNC, mode)
+	if err != nil {
+		return nil, err
+	}
+
+	hashers := make([]hash.Hash, 0, len(algorithms))
+	writers := []io.Writer{out}
+	for _, algorithm := range algorithms {
+		hasher := hasherFactories[algorithm]()
+		hashers = append(hashers, hasher)
+		writers = append(writers, hasher)
+	}
+
+	_, err = io.Copy(io.MultiWriter(writers...), in)
+	if err == nil {
+		err = out.Sync()
+	}
+	if closeErr := out.Close(); err == nil {
+		err = closeErr
+	}
+	if err == nil && stat != nil {
+		err = os.Chtimes(tempFile, stat.ModTime(), stat.ModTime())
+	}
+	if err == nil {
+		err = os.Rename(tempFile, destinationPath)
+	}
+	if err != nil {
+		os.Remove(tempFile)
+		return nil, err
+	}
+
+	hashes := make([][]byte, len(hashers))
+	for i, hasher := range hashers {
+		hashes[i] = hasher.Sum(nil)
+	}
+	return hashes, nil
+}
+
+// 全てのアルゴリズムのハッシュが一致するか判定する。
+func equalHashes(a [][]byte, b [][]byte) bool {
+	if len(a) != len(b) {
+		return false
+	}
+	for i := range a {
+		if hex.EncodeToString(a[i]) != hex.EncodeToString(b[i]) {
+			return false
+		}
+	}
+	return true
+}
+
+// コピーしたファイルの記録でディスクのハッシュファイルの記録を置き換えて書き直す。
+// ディスクで使わないアルゴリズムの記録は書き込まない。
+func mergeCopiedRecords(diskInfo *DiskInfo, algorithms []string, newRecords [][]HashRecord) {
+	diskAlgorithms := make(map[string]bool)
+	for _, algorithm := range diskInfo.hashAlgorithms() {
+		diskAlgorithms[algorithm] = true
+	}
+
+	for i, algorithm := range algorithms {
+		if len(newRecords[i]) == 0 || !diskAlgorithms[algorithm] {
+			continue
+		}
+
+		store := hashStoreFor(algorithm)
+		hashMap := makeHashMap(diskInfo, store)
+		for _, record := range newRecords[i] {
+			hashMap[record.path] = record
+		}
+
+		records := make([]HashRecord, 0, len(hashMap))
+		for _, record := range hashMap {
+			records = append(records, record)
+		}
+		sort.Slice(records, func(i, j int) bool {
+			return records[i].path < records[j].path
+		})
+
+		err := store.rewrite(diskInfo.id, records)
+		fatalMessageError(ErrIO, err, "ハッシュファイルの書き込みに失敗しました。: %s\n", diskInfo.id)
+	}
+}
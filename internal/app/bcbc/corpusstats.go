@@ -0,0 +1,179 @@
+package bcbc
+
+import (
+	"flag"
+	"fmt"
+	"path"
+	"sort"
+	"strings"
+)
+
+// ハッシュファイルの記録の集計
+type corpusStats struct {
+	files uint64
+	// サイズを記録しているファイルの数と合計サイズ
+	sizedFiles uint64
+	bytes      uint64
+	// 拡張子ごとのファイル数
+	extensions map[string]uint64
+	// ハッシュごとのファイル数とサイズ
+	hashes map[string]uint64
+	sizes  map[string]int64
+	// サイズが大きい順のファイル
+	largest []largestFile
+	top     int
+}
+
+// サイズが大きいファイル
+type largestFile struct {
+	diskId string
+	record HashRecord
+}
+
+func newCorpusStats(top int) *corpusStats {
+	return &corpusStats{
+		extensions: make(map[string]uint64),
+		hashes:     make(map[string]uint64),
+		sizes:      make(map[string]int64),
+		top:        top,
+	}
+}
+
+// 記録を集計に加える。
+func (c *corpusStats) add(diskId string, record HashRecord) {
+	c.files++
+
+	extension := strings.ToLower(path.Ext(record.path))
+	if extension == "" {
+		extension = "(なし)"
+	}
+	c.extensions[extension]++
+
+	// 読み込めない範囲があったハッシュは内容を表さないので重複の判定に使わない
+	if !strings.HasSuffix(record.hash, incompleteHashSuffix) {
+		c.hashes[record.hash]++
+		c.sizes[record.hash] = record.size
+	}
+
+	if record.size < 0 {
+		return
+	}
+	c.sizedFiles++
+	c.bytes += uint64(record.size)
+
+	if c.top == 0 {
+		return
+	}
+	i := sort.Search(len(c.largest), func(i int) bool {
+		return c.largest[i].record.size < record.size
+	})
+	if i >= c.top {
+		return
+	}
+	c.largest = append(c.largest, largestFile{})
+	copy(c.largest[i+1:], c.largest[i:])
+	c.largest[i] = largestFile{diskId, record}
+	if len(c.largest) > c.top {
+		c.largest = c.largest[:c.top]
+	}
+}
+
+// 重複しているファイルの数と、重複を除けば減らせるバイト数を返す。
+func (c *corpusStats) duplicates() (uint64, uint64) {
+	var files, bytes uint64
+	for hash, count := range c.hashes {
+		if count > 1 {
+			files += count - 1
+			if size := c.sizes[hash]; size > 0 {
+				bytes += uint64(size) * (count - 1)
+			}
+		}
+	}
+	return files, bytes
+}
+
+// 集計の要約を1行にする。
+func (c *corpusStats) summary() string {
+	duplicateFiles, duplicateBytes := c.duplicates()
+	ratio := 0.0
+	if c.files > 0 {
+		ratio = float64(duplicateFiles) / float64(c.files) * 100
+	}
+
+	size := formatSize(float64(c.bytes))
+	if c.sizedFiles < c.files {
+		size += fmt.Sprintf(" (サイズ未記録%s)", formatQuantity(c.files-c.sizedFiles, "件", "件"))
+	}
+
+	return fmt.Sprintf("%s %s 重複%s(%.1f%%, %s)", formatQuantity(c.files, "件", "件"), size,
+		formatQuantity(duplicateFiles, "件", "件"), ratio, formatSize(float64(duplicateBytes)))
+}
+
+// statsサブコマンド。
+// ハッシュファイルの記録からディスクごとと全体のファイル数、合計サイズ、重複の割合、最後のハッシュ計算日時と、
+// 全体のサイズが大きいファイルと拡張子ごとのファイル数を表示する。
+// -indexが指定されていて索引があれば索引から集計する。
+func executeStats(args []string) {
+	flagSet := flag.NewFlagSet("stats", flag.ExitOnError)
+	top := flagSet.Int("top", 10, "表示するサイズが大きいファイルの数。")
+	extensions := flagSet.Int("extensions", 10, "ファイル数を表示する拡張子の数。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, *top < 0 || *extensions < 0, "表示する数の指定が不正です。\n")
+
+	// 複数のアルゴリズムで記録していても同じファイルなので、最初のアルゴリズムのハッシュファイルで集計する
+	algorithm := config.algorithms[0]
+	store := hashStoreFor(algorithm)
+	index := loadIndexIfEnabled()
+	registry := loadRegistry()
+
+	diskIds, err := store.diskIds()
+	fatalMessageError(ErrIO, err, "出力ファイルの一覧取得に失敗しました。\n")
+
+	total := newCorpusStats(*top)
+	for _, diskId := range diskIds {
+		records := index.load(store, algorithm, diskId)
+
+		disk := newCorpusStats(0)
+		for _, record := range records {
+			disk.add(diskId, record)
+			total.add(diskId, record)
+		}
+
+		lastHashAt := "-"
+		if record, found := registry[diskId]; found {
+			lastHashAt = formatDate(record.LastHashAt)
+		}
+		logf.Printf("%-6s %s 最終ハッシュ計算: %s\n", diskId, disk.summary(), lastHashAt)
+	}
+
+	logf.Printf("全体: ディスク%d台 %s\n", len(diskIds), total.summary())
+
+	if len(total.largest) > 0 {
+		logf.Println("サイズが大きいファイル:")
+		for _, file := range total.largest {
+			logf.Printf("  %10s %s: %s\n", formatSize(float64(file.record.size)), file.diskId, file.record.path)
+		}
+	}
+
+	if *extensions > 0 && len(total.extensions) > 0 {
+		names := make([]string, 0, len(total.extensions))
+		for name := range total.extensions {
+			names = append(names, name)
+		}
+		sort.Slice(names, func(i, j int) bool {
+			if total.extensions[names[i]] != total.extensions[names[j]] {
+				return total.extensions[names[i]] > total.extensions[names[j]]
+			}
+			return names[i] < names[j]
+		})
+		if len(names) > *extensions {
+			names = names[:*extensions]
+		}
+
+		logf.Println("拡張子ごとのファイル数:")
+		for _, name := range names {
+			logf.Printf("  %-10s %s\n", name, formatCount(total.extensions[name]))
+		}
+	}
+}
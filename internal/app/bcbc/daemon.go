@@ -0,0 +1,180 @@
+package bcbc
+
+import (
+	"bufio"
+	"encoding/json"
+	"flag"
+	"os"
+	"path/filepath"
+	"strings"
+	"time"
+)
+
+// スケジュールの操作
+const (
+	// ScheduleHash ハッシュ計算とハッシュファイル統合
+	ScheduleHash = "hash"
+	// ScheduleVerify 検証
+	ScheduleVerify = "verify"
+)
+
+// 間隔の別名
+var scheduleIntervalAliases = map[string]time.Duration{
+	"hourly":  time.Hour,
+	"daily":   24 * time.Hour,
+	"weekly":  7 * 24 * time.Hour,
+	"monthly": 30 * 24 * time.Hour,
+}
+
+// スケジュールの1件
+type scheduleEntry struct {
+	operation string
+	diskRoot  string
+	interval  time.Duration
+}
+
+// 最後に実行した日時を記録するキー
+func (e scheduleEntry) key() string {
+	return e.operation + " " + e.diskRoot
+}
+
+// スケジュール設定ファイルを読み込む。
+// 1行に「操作 ディスクルート 間隔」を空白区切りで書く。
+// 操作はhashかverify、間隔はGoの時間の形式(例: 36h)かhourly、daily、weekly、monthly。
+func loadSchedule() []scheduleEntry {
+	scheduleConfigFile := filepath.Join(config.configDir(), "schedule.conf")
+	scheduleFileIn, err := os.Open(scheduleConfigFile)
+	fatalMessageError(ErrConfig, err, "スケジュール設定ファイルが見つかりません。: %s\n", scheduleConfigFile)
+	defer scheduleFileIn.Close()
+
+	var entries []scheduleEntry
+
+	scanner := bufio.NewScanner(scheduleFileIn)
+	for i := 1; scanner.Scan(); i++ {
+		line := strings.TrimSpace(scanner.Text())
+		if line == "" || line[0] == '#' {
+			continue
+		}
+
+		fields := strings.Fields(line)
+		fatalMessageIf(ErrConfig, len(fields) != 3, "スケジュール設定ファイルの形式が不正です。: %d行目: %s\n", i, line)
+
+		operation := fields[0]
+		valid := operation == ScheduleHash || operation == ScheduleVerify
+		fatalMessageIf(ErrConfig, !valid, "スケジュール設定ファイルの操作が不正です。: %d行目: %s\n", i, line)
+
+		interval, found := scheduleIntervalAliases[fields[2]]
+		if !found {
+			interval, err = time.ParseDuration(fields[2])
+			fatalMessageIf(ErrConfig, err != nil || interval <= 0, "スケジュール設定ファイルの間隔が不正です。: %d行目: %s\n", i, line)
+		}
+
+		entries = append(entries, scheduleEntry{operation, fields[1], interval})
+	}
+	fatalMessageError(ErrConfig, scanner.Err(), "スケジュール設定ファイルが読み込めませんでした。: %s\n", scheduleConfigFile)
+
+	fatalMessageIf(ErrConfig, len(entries) == 0, "スケジュール設定ファイルにスケジュールがありません。: %s\n", scheduleConfigFile)
+
+	return entries
+}
+
+// スケジュールを最後に実行した日時を記録するファイルのパスを返す。
+func scheduleStateFile() string {
+	return filepath.Join(config.outDir(), "daemon.state")
+}
+
+// スケジュールを最後に実行した日時を読み込む。
+func loadScheduleState() map[string]time.Time {
+	state := make(map[string]time.Time)
+
+	data, err := os.ReadFile(scheduleStateFile())
+	if err == nil {
+		err = json.Unmarshal(data, &state)
+	}
+	if err != nil && !os.IsNotExist(err) {
+		logf.Printf("スケジュールの実行記録が読み込めなかったので、全てのスケジュールを実行します。: %s\n", scheduleStateFile())
+		logf.Println(err)
+	}
+
+	return state
+}
+
+// スケジュールを最後に実行した日時を書き込む。
+func saveScheduleState(state map[string]time.Time) {
+	data, err := json.MarshalIndent(state, "", "  ")
+	if err == nil {
+		err = os.WriteFile(scheduleStateFile(), data, 0644)
+	}
+	if err != nil {
+		logf.Printf("スケジュールの実行記録の書き込みに失敗しました。: %s\n", scheduleStateFile())
+		logf.Println(err)
+	}
+}
+
+// daemonサブコマンド。
+// 常駐してスケジュール設定ファイルに従ってハッシュ計算と検証を定期的に実行する。
+// 中断シグナルを受け取るまで終了しない。
+func executeDaemon(args []string) {
+	flagSet := flag.NewFlagSet("daemon", flag.ExitOnError)
+	checkInterval := flagSet.Duration("check-interval", time.Minute, "実行するスケジュールがあるか確認する間隔。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, *checkInterval <= 0, "確認間隔の指定が不正です。: %s\n", *checkInterval)
+
+	entries := loadSchedule()
+	state := loadScheduleState()
+
+	logf.Printf("常駐を開始します。スケジュール: %d件\n", len(entries))
+	defer logf.Println("常駐を終了しました。")
+
+	for {
+		for _, entry := range entries {
+			if interrupted() {
+				return
+			}
+			if time.Since(state[entry.key()]) < entry.interval {
+				continue
+			}
+
+			// 失敗したか中断されたスケジュールは、次の確認で実行し直すように記録しない
+			if !runScheduleEntry(entry) {
+				continue
+			}
+
+			state[entry.key()] = time.Now()
+			saveScheduleState(state)
+		}
+
+		select {
+		case <-interruptChannel:
+			return
+		case <-time.After(*checkInterval):
+		}
+	}
+}
+
+// スケジュールを1件実行する。
+// 致命的なエラーが発生しても常駐は続ける。
+// 最後まで実行できたらtrueを返す。
+func runScheduleEntry(entry scheduleEntry) bool {
+	logf.Printf("スケジュールを実行します。: %s\n", entry.key())
+
+	var err error
+	func() {
+		defer recoverFatal(&err)
+
+		switch entry.operation {
+		case ScheduleHash:
+			executeHashing([]string{entry.diskRoot})
+		case ScheduleVerify:
+			executeVerify([]string{entry.diskRoot})
+		}
+	}()
+
+	if err != nil && !IsKind(err, ErrInterrupted) {
+		logf.Printf("スケジュールの実行中に問題が発生しました。: %s\n", entry.key())
+		logf.Println(err)
+	}
+
+	return err == nil
+}
@@ -0,0 +1,50 @@
+package bcbc
+
+import (
+	"bufio"
+	"flag"
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// 既定値設定ファイルを読み込み、コマンドラインオプションの既定値にする。
+// 項目名はコマンドラインオプションの名前と同じで、コマンドラインで指定したオプションが優先される。
+// 既定値設定ファイルがなければ何もしない。
+func initDefaults() {
+	defaultsConfigFile := filepath.Join(config.configDir(), "bcbc.conf")
+	defaultsFileIn, err := os.Open(defaultsConfigFile)
+	if os.IsNotExist(err) {
+		return
+	}
+	fatalMessageError(ErrConfig, err, "既定値設定ファイルが読み込めませんでした。: %s\n", defaultsConfigFile)
+	defer defaultsFileIn.Close()
+
+	// コマンドラインで指定されたオプション
+	specified := make(map[string]bool)
+	commandLine.Visit(func(f *flag.Flag) {
+		specified[f.Name] = true
+	})
+
+	scanner := bufio.NewScanner(defaultsFileIn)
+	for i := 1; scanner.Scan(); i++ {
+		line := strings.TrimSpace(scanner.Text())
+		if line == "" || line[0] == '#' {
+			continue
+		}
+
+		separator := strings.Index(line, "=")
+		fatalMessageIf(ErrConfig, separator < 0, "既定値設定ファイルの形式が不正です。: %d行目: %s\n", i, line)
+		key := strings.TrimSpace(line[:separator])
+		value := strings.TrimSpace(line[separator+1:])
+
+		fatalMessageIf(ErrConfig, commandLine.Lookup(key) == nil, "既定値設定ファイルに不明な項目があります。: %d行目: %s\n", i, line)
+		if specified[key] {
+			continue
+		}
+
+		err := commandLine.Set(key, value)
+		fatalMessageError(ErrConfig, err, "既定値設定ファイルの値が不正です。: %d行目: %s\n", i, line)
+	}
+	fatalMessageError(ErrConfig, scanner.Err(), "既定値設定ファイルが読み込めませんでした。: %s\n", defaultsConfigFile)
+}
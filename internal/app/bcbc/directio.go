@@ -0,0 +1,48 @@
+package bcbc
+
+import (
+	"io"
+	"os"
+	"sync"
+	"unsafe"
+)
+
+// 直接I/Oで読み込むバッファのアライメント
+const directIOAlignment = 4096
+
+// ハッシュ対象ファイル
+type targetFile interface {
+	io.ReaderAt
+	io.Closer
+}
+
+// 直接I/Oで読み込めるように、アライメントを揃えたバッファを作成する。
+func alignedBuffer(size int) []byte {
+	buffer := make([]byte, size+directIOAlignment)
+	skip := 0
+	if rem := int(uintptr(unsafe.Pointer(&buffer[0])) & (directIOAlignment - 1)); rem != 0 {
+		skip = directIOAlignment - rem
+	}
+	return buffer[skip : skip+size : skip+size]
+}
+
+// ハッシュ対象ファイルを開く。
+// 直接I/Oモードならページキャッシュを使わずに読み込むように開く。
+func openTarget(file string, size uint64) (targetFile, error) {
+	if !config.directIO {
+		return os.Open(file)
+	}
+	return openDirect(file, size)
+}
+
+// 直接I/Oを使えなかったことを警告したか
+var directIOFallbackOnce sync.Once
+
+// 直接I/Oを使えずに通常の読み込みに切り替えたことを警告する。
+// ファイルごとに警告すると多すぎるので最初の1回だけ警告する。
+func warnDirectIOFallback(file string, err error) {
+	directIOFallbackOnce.Do(func() {
+		logf.Printf("直接I/Oで読み込めないので、通常の読み込みに切り替えます。: %s\n", file)
+		logf.Println(err)
+	})
+}
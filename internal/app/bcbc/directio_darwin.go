@@ -0,0 +1,24 @@
+// +build darwin
+
+package bcbc
+
+import (
+	"os"
+	"syscall"
+)
+
+// F_NOCACHEを設定してファイルを開く。
+// macOSではアライメントを揃えなくてもよい。
+func openDirect(file string, size uint64) (targetFile, error) {
+	fileIn, err := os.Open(file)
+	if err != nil {
+		return nil, err
+	}
+
+	_, _, errno := syscall.Syscall(syscall.SYS_FCNTL, fileIn.Fd(), syscall.F_NOCACHE, 1)
+	if errno != 0 {
+		warnDirectIOFallback(file, errno)
+	}
+
+	return fileIn, nil
+}
@@ -0,0 +1,77 @@
+// +build linux
+
+package bcbc
+
+import (
+	"errors"
+	"io"
+	"os"
+	"syscall"
+)
+
+// O_DIRECTで開いたハッシュ対象ファイル。
+// 読み込む位置と長さがアライメントに揃っていないと読み込めないので、
+// ファイルの末尾より後ろは読み込まずにEOFを返す。
+type directFile struct {
+	*os.File
+	size int64
+	// 直接I/Oで読み込めなかったので通常の読み込みに切り替えたか
+	buffered bool
+}
+
+// O_DIRECTでファイルを開く。
+// ファイルシステムがO_DIRECTに対応していなければ通常の読み込みで開く。
+func openDirect(file string, size uint64) (targetFile, error) {
+	fileIn, err := os.OpenFile(file, os.O_RDONLY|syscall.O_DIRECT, 0)
+	if errors.Is(err, syscall.EINVAL) {
+		warnDirectIOFallback(file, err)
+		return os.Open(file)
+	}
+	if err != nil {
+		return nil, err
+	}
+
+	return &directFile{File: fileIn, size: int64(size)}, nil
+}
+
+// 1回のpreadで読み込む。
+// アライメントが揃わずに読み込めなければ、開き直して通常の読み込みに切り替える。
+func (f *directFile) ReadAt(p []byte, off int64) (int, error) {
+	if f.buffered {
+		return f.File.ReadAt(p, off)
+	}
+	if off >= f.size {
+		return 0, io.EOF
+	}
+
+	n, err := syscall.Pread(int(f.Fd()), p, off)
+	if err == syscall.EINVAL {
+		warnDirectIOFallback(f.Name(), err)
+		if err := f.reopenBuffered(); err != nil {
+			return 0, err
+		}
+		return f.File.ReadAt(p, off)
+	}
+	if err != nil {
+		return 0, &os.PathError{Op: "read", Path: f.Name(), Err: err}
+	}
+	if n == 0 {
+		return 0, io.EOF
+	}
+
+	return n, nil
+}
+
+// O_DIRECTを付けずに開き直す。
+func (f *directFile) reopenBuffered() error {
+	fileIn, err := os.Open(f.Name())
+	if err != nil {
+		return err
+	}
+
+	f.File.Close()
+	f.File = fileIn
+	f.buffered = true
+
+	return nil
+}
@@ -0,0 +1,14 @@
+// +build !linux,!darwin
+
+package bcbc
+
+import (
+	"errors"
+	"os"
+)
+
+// その他のOSでは直接I/Oに対応していないので通常の読み込みで開く。
+func openDirect(file string, size uint64) (targetFile, error) {
+	warnDirectIOFallback(file, errors.New("このOSでは直接I/Oに対応していません。"))
+	return os.Open(file)
+}
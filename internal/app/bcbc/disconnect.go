@@ -0,0 +1,44 @@
+package bcbc
+
+import (
+	"os"
+	"path/filepath"
+)
+
+// ディスクが取り外されたとみなす連続した読み込みエラーの数
+const disconnectErrorThreshold = 10
+
+// ハッシュ計算中のディスクの取り外しの検出。
+// USBのディスクが抜かれると全てのファイルの読み込みがエラーになるので、
+// diskファイルにアクセスできなくなるか読み込みエラーが続いたら取り外されたとみなす。
+// マウントポイントのディレクトリは取り外した後も残るので、ディスクルートではなくdiskファイルを調べる。
+type disconnectionDetector struct {
+	diskInfo *DiskInfo
+	// 連続した読み込みエラーの数
+	consecutiveErrors int
+}
+
+// ディスクの取り外しの検出を準備する。
+func newDisconnectionDetector(diskInfo *DiskInfo) *disconnectionDetector {
+	return &disconnectionDetector{diskInfo: diskInfo}
+}
+
+// 読み込みエラーが発生したときに、ディスクが取り外されたか判定する。
+// 取り外されていればエラーを返す。
+func (d *disconnectionDetector) check(err error) error {
+	d.consecutiveErrors++
+
+	if _, statErr := os.Stat(filepath.Join(d.diskInfo.rootPath, "disk")); statErr != nil {
+		return newError(ErrDisconnected, statErr, "ディスクが取り外されました。: %s", d.diskInfo.rootPath)
+	}
+	if d.consecutiveErrors >= disconnectErrorThreshold {
+		return newError(ErrDisconnected, err, "読み込みエラーが%d回続いたのでディスクが取り外されたとみなします。: %s", d.consecutiveErrors, d.diskInfo.rootPath)
+	}
+
+	return nil
+}
+
+// ファイルを読み込めたので連続した読み込みエラーの数を戻す。
+func (d *disconnectionDetector) reset() {
+	d.consecutiveErrors = 0
+}
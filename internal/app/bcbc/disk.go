@@ -1,84 +1,366 @@
 package bcbc
 
 import (
+	"golang.org/x/text/unicode/norm"
 	"io/fs"
 	"os"
 	"path"
+	"path/filepath"
 	"regexp"
+	"runtime"
+	"strconv"
+	"strings"
 )
 
 // diskファイルを探して一覧を作成する。
+// -scan-mountsが指定されていれば、マウントされているボリュームのルートにあるdiskファイルも加える。
 func findDiskFiles(diskRoots []string) []string {
 	var diskFiles []string
 
-	if len(diskRoots) == 0 {
-		diskFile, err := findDiskFileFromCurrent()
-		fatalMessageError(err, "diskファイルが見つかりませんでした。\n")
-		diskFiles = []string{diskFile}
-	} else {
+	switch {
+	case len(diskRoots) > 0:
 		diskFiles = make([]string, 0, len(diskRoots))
 		for _, dr := range diskRoots {
-			diskFile := path.Join(dr, "disk")
+			rootPath, subpaths := splitRootSubpaths(dr)
+			diskFile := filepath.Join(normalizeRootPath(rootPath), "disk")
 			diskFiles = append(diskFiles, diskFile)
+			if subpaths != nil {
+				diskSubpaths[diskFile] = subpaths
+			}
 		}
+	case !*scanMountsOption:
+		diskFile, err := findDiskFileFromCurrent()
+		fatalMessageError(ErrDiskFile, err, "diskファイルが見つかりませんでした。\n")
+		diskFiles = []string{diskFile}
+	}
+
+	if *scanMountsOption {
+		diskFiles = appendMountedDiskFiles(diskFiles)
 	}
 
 	return diskFiles
 }
 
+// ディスクルートの引数で指定された走査範囲。キーはdiskファイルのパス。
+var diskSubpaths = make(map[string][]string)
+
+// 「ディスクルート:サブディレクトリ,サブディレクトリ...」の形式の引数をディスクルートと走査範囲に分ける。
+// 走査範囲はディスクルートからのスラッシュ区切りの相対パスにする。指定されていなければnilを返す。
+// Windowsのドライブ文字の:と、:の直後がパスの区切り文字のものは区切りとみなさない。
+func splitRootSubpaths(arg string) (string, []string) {
+	separator := strings.LastIndex(arg, ":")
+	if separator < 0 || separator == len(arg)-1 || os.IsPathSeparator(arg[separator+1]) ||
+		(runtime.GOOS == "windows" && separator == 1) {
+		return arg, nil
+	}
+
+	var subpaths []string
+	for _, subpath := range strings.Split(arg[separator+1:], ",") {
+		subpath = path.Clean(filepath.ToSlash(strings.TrimSpace(subpath)))
+		fatalMessageIf(ErrConfig, subpath == "." || subpath == ".." || strings.HasPrefix(subpath, "../") || path.IsAbs(subpath),
+			"走査範囲にはディスクルートの中の相対パスを指定してください。: %s\n", arg)
+		subpaths = append(subpaths, norm.NFC.String(subpath))
+	}
+
+	return arg[:separator], subpaths
+}
+
 // カレントディレクトリの起点としてdiskファイルを探す。
+// ルートディレクトリ(Windowsではドライブのルート)まで親ディレクトリを遡る。
 func findDiskFileFromCurrent() (string, error) {
 	dir, err := os.Getwd()
-	fatalMessageError(err, "カレントディレクトリが取得できませんでした。\n")
+	fatalMessageError(ErrIO, err, "カレントディレクトリが取得できませんでした。\n")
+	dir = normalizeRootPath(dir)
 
 	for {
-		diskFile := path.Join(dir, "disk")
+		diskFile := filepath.Join(dir, "disk")
 
 		if _, err := os.Stat(diskFile); err == nil {
 			return diskFile, nil
 		}
 
-		if dir == "/" {
+		parent := filepath.Dir(dir)
+		if parent == dir {
 			break
 		}
 
-		dir = path.Dir(dir)
+		dir = parent
 	}
 
 	return "", fs.ErrNotExist
 }
 
+// Windowsの拡張パスのプレフィックス
+const extendedPathPrefix = `\\?\`
+
+// ディスクルートのパスを正規化する。
+// Windowsではドライブ文字の拡張パス(\\?\D:\...)を通常のパスにする。
+// 長いパスはosパッケージが必要に応じて拡張パスに変換するので、拡張パスのままにする必要はない。
+func normalizeRootPath(rootPath string) string {
+	if runtime.GOOS == "windows" && strings.HasPrefix(rootPath, extendedPathPrefix) {
+		rest := rootPath[len(extendedPathPrefix):]
+		if len(rest) >= 2 && rest[1] == ':' {
+			rootPath = rest
+		}
+	}
+	return filepath.Clean(rootPath)
+}
+
 // DiskInfo ディスク情報
 type DiskInfo struct {
 	index    int
 	id       string
 	rootPath string
+	// 読み込みのスループットの制限。制限しなければnil。
+	limiter *rateLimiter
+	// diskファイルに書かれた表示用のラベル
+	label string
+	// diskファイルで指定された統合先のグループ。指定されていなければ空。
+	group string
+	// diskファイルで指定されたハッシュアルゴリズム。指定されていなければnil。
+	algorithms []string
+	// diskファイルで指定されたフィルター設定ファイルを加えたフィルター。指定されていなければnil。
+	filters []Filter
+	// 走査するディレクトリのディスクルートからの相対パス。ディスク全体を走査するならnil。
+	subpaths []string
+}
+
+// ディスク内のパスが走査範囲に含まれるか判定する。
+func (di *DiskInfo) covers(normPath string) bool {
+	if di == nil || len(di.subpaths) == 0 {
+		return true
+	}
+	for _, subpath := range di.subpaths {
+		if normPath == subpath || strings.HasPrefix(normPath, subpath+"/") {
+			return true
+		}
+	}
+	return false
+}
+
+// ディスクのハッシュ計算に使うハッシュアルゴリズムを返す。
+func (di *DiskInfo) hashAlgorithms() []string {
+	if di == nil || di.algorithms == nil {
+		return config.algorithms
+	}
+	return di.algorithms
 }
 
+// ディスクのファイルに適用するフィルターを返す。
+func (di *DiskInfo) filterList() []Filter {
+	if di == nil || di.filters == nil {
+		return config.filters
+	}
+	return di.filters
+}
+
+// ディスクのファイルがハッシュ対象であるかフィルター設定から判定する。
+func (di *DiskInfo) filterFile(normPath string) bool {
+	filter, found := matchFilter(di.filterList(), normPath)
+	return found && filter.inclusion
+}
+
+// 1行目がディスクIDの形式(形式1)のdiskファイルのパターン
+var diskIdLinePattern = regexp.MustCompile("\\A([A-Z]+\\d+)")
+
 // diskファイルの一覧からディスク情報のスライスを作成する。
 func makeDiskInfoList(diskFiles []string) []DiskInfo {
 	diskInfoList := make([]DiskInfo, 0, len(diskFiles))
 
-	pattern := regexp.MustCompile("\\A([A-Z]\\d+)")
-
 	for _, diskFile := range diskFiles {
-		diskFileData, err := os.ReadFile(diskFile)
-		fatalMessageError(err, "diskファイルが読み込めませんでした。\n")
+		id, settings := readDiskFile(diskFile)
+		rootPath := filepath.Dir(diskFile)
 
-		match := pattern.FindStringSubmatch(string(diskFileData))
-		fatalMessageIf(match == nil, "diskファイルの内容が不正です。: %s\n", diskFile)
+		diskInfo := DiskInfo{
+			index:    len(diskInfoList),
+			id:       id,
+			rootPath: rootPath,
+			label:    settings["label"],
+			group:    settings["group"],
+			subpaths: diskSubpaths[diskFile],
+		}
 
-		index := len(diskInfoList)
-		id := match[0]
-		rootPath := path.Dir(diskFile)
+		throttle := config.throttle
+		if value, found := settings["throttle"]; found {
+			var err error
+			throttle, err = strconv.ParseFloat(value, 64)
+			fatalMessageIf(ErrDiskFile, err != nil || throttle < 0, "diskファイルのthrottleの指定が不正です。: %s\n", diskFile)
+		}
+		diskInfo.limiter = newRateLimiter(throttle)
+
+		if diskInfo.group != "" {
+			checkGroupName(diskInfo.group)
+		}
+		if value, found := settings["algorithm"]; found {
+			diskInfo.algorithms = diskAlgorithms(diskFile, value)
+		}
+		if value, found := settings["filter"]; found {
+			diskInfo.filters = diskFilters(rootPath, value)
+		}
 
-		diskInfoList = append(diskInfoList, DiskInfo{index, id, rootPath})
+		diskInfoList = append(diskInfoList, diskInfo)
 	}
 
+	checkDuplicateDiskIds(diskInfoList)
+	recordDiskSettings(diskInfoList)
+
 	return diskInfoList
 }
 
-// hashFile ハッシュファイルのパスを返す。
-func (di *DiskInfo) hashFile() string {
-	return path.Join(config.outDir(), di.id)
+// diskファイルを読み込んでディスクIDとディスクごとの設定を返す。
+//
+// 形式1は1行目にディスクIDだけを書き、2行目以降に「項目=値」で設定を書く。
+// 形式2は全ての行に「項目=値」で書き、ディスクIDもidの項目に書く。
+//   id=A1
+//   label=写真 2019-2021
+//   group=Photo
+func readDiskFile(diskFile string) (string, map[string]string) {
+	diskFileData, err := os.ReadFile(diskFile)
+	fatalMessageError(ErrDiskFile, err, "diskファイルが読み込めませんでした。\n")
+	lines := strings.Split(string(diskFileData), "\n")
+
+	if id := diskIdLinePattern.FindString(lines[0]); id != "" {
+		return id, parseDiskSettings(diskFile, lines[1:], 2)
+	}
+
+	settings := parseDiskSettings(diskFile, lines, 1)
+	id := settings["id"]
+	fatalMessageIf(ErrDiskFile, !diskIdPattern.MatchString(id), "diskファイルの内容が不正です。: %s\n", diskFile)
+
+	return id, settings
+}
+
+// diskファイルに「項目=値」で書かれたディスクごとの設定を読み込む。
+// 空行と#で始まる行は読み飛ばす。firstLineはlinesの先頭の行番号。
+// 「=」がない行は新しいバージョンで書かれたdiskファイルでも読めるように、警告して読み飛ばす。
+func parseDiskSettings(diskFile string, lines []string, firstLine int) map[string]string {
+	settings := make(map[string]string)
+
+	for i, line := range lines {
+		line = strings.TrimSpace(line)
+		if line == "" || line[0] == '#' {
+			continue
+		}
+
+		separator := strings.Index(line, "=")
+		if separator < 0 {
+			logf.Printf("[警告] diskファイルに「項目=値」ではない行があるので読み飛ばします。: %s : %d行目\n", diskFile, firstLine+i)
+			continue
+		}
+		key := strings.TrimSpace(line[:separator])
+		value := strings.TrimSpace(line[separator+1:])
+
+		if _, known := diskSettingKeys[key]; !known {
+			logf.Printf("diskファイルに不明な項目があります。: %s : %s\n", diskFile, key)
+		}
+		settings[key] = value
+	}
+
+	return settings
+}
+
+// diskファイルに書けるディスクごとの設定の項目
+var diskSettingKeys = map[string]bool{
+	"id":        true,
+	"label":     true,
+	"group":     true,
+	"throttle":  true,
+	"filter":    true,
+	"algorithm": true,
+}
+
+// diskファイルで指定されたハッシュアルゴリズムを検証する。
+// 統合やサブコマンドは-algorithmのアルゴリズムごとに処理するので、その中から選ばなければならない。
+func diskAlgorithms(diskFile string, value string) []string {
+	algorithms := checkAlgorithms(strings.Split(value, ","))
+	for _, algorithm := range algorithms {
+		enabled := false
+		for _, a := range config.algorithms {
+			enabled = enabled || a == algorithm
+		}
+		fatalMessageIf(ErrDiskFile, !enabled, "diskファイルのalgorithmに-algorithmで指定していないアルゴリズムがあります。: %s : %s\n", diskFile, algorithm)
+	}
+	return algorithms
+}
+
+// diskファイルで指定されたフィルター設定ファイルを読み込む。
+// 相対パスはディスクルートからのパスとする。
+// ディスクのフィルターはコマンドラインのフィルターの後、共通のフィルター設定ファイルの前に判定する。
+func diskFilters(rootPath string, filterPath string) []Filter {
+	if !filepath.IsAbs(filterPath) {
+		filterPath = filepath.Join(rootPath, filterPath)
+	}
+
+	filterFileIn, err := os.Open(filterPath)
+	fatalMessageError(ErrDiskFile, err, "diskファイルで指定されたフィルター設定ファイルが読み込めませんでした。: %s\n", filterPath)
+	defer filterFileIn.Close()
+
+	filters, problems, err := readFilters(filterFileIn)
+	fatalMessageError(ErrDiskFile, err, "diskファイルで指定されたフィルター設定ファイルが読み込めませんでした。: %s\n", filterPath)
+	for _, problem := range problems {
+		fatalMessageError(ErrDiskFile, problem.err, "フィルター設定ファイルの形式が不正です。: %s : %d行目: %s\n", filterPath, problem.line, problem.text)
+	}
+
+	// 共通のフィルターはコマンドラインのフィルターの後にフィルター設定ファイルの規則が続いている
+	commandLine := commandLineFilters()
+	result := append(commandLine, filters...)
+	if len(config.filters) >= len(commandLine) {
+		result = append(result, config.filters[len(commandLine):]...)
+	}
+	return result
+}
+
+// diskファイルのラベルとグループをディスク登録簿に記録する。
+// ディスクを接続していないときも同じグループに統合できるように、グループ分けは登録簿のグループも使う。
+func recordDiskSettings(diskInfoList []DiskInfo) {
+	registry := loadRegistry()
+
+	settings := make(map[string]*DiskInfo)
+	var changed []string
+	for i := range diskInfoList {
+		diskInfo := &diskInfoList[i]
+		record, found := registry[diskInfo.id]
+		switch {
+		case !found && diskInfo.label == "" && diskInfo.group == "":
+			continue
+		case found && (diskInfo.label == "" || record.Label == diskInfo.label) && record.Group == diskInfo.group:
+			continue
+		}
+		settings[diskInfo.id] = diskInfo
+		changed = append(changed, diskInfo.id)
+	}
+	if len(changed) == 0 {
+		return
+	}
+
+	updateRegistry(changed, func(record *DiskRecord) {
+		if settings[record.ID].label != "" {
+			record.Label = settings[record.ID].label
+		}
+		record.Group = settings[record.ID].group
+	})
+	initGroups()
+}
+
+// 同じディスクIDのディスクがないか検証する。
+// 同じディスクIDのディスクを並行してハッシュ計算すると同じハッシュファイルに追記して壊してしまう。
+func checkDuplicateDiskIds(diskInfoList []DiskInfo) {
+	rootPaths := make(map[string][]string)
+	var diskIds []string
+	for _, diskInfo := range diskInfoList {
+		if _, found := rootPaths[diskInfo.id]; !found {
+			diskIds = append(diskIds, diskInfo.id)
+		}
+		rootPaths[diskInfo.id] = append(rootPaths[diskInfo.id], diskInfo.rootPath)
+	}
+
+	duplicated := false
+	for _, diskId := range diskIds {
+		if len(rootPaths[diskId]) > 1 {
+			logf.Printf("ディスクID%sが複数のディスクルートにあります。: %s\n", diskId, strings.Join(rootPaths[diskId], ", "))
+			duplicated = true
+		}
+	}
+
+	fatalMessageIf(ErrDiskFile, duplicated, "ディスクIDが重複しています。\n")
 }
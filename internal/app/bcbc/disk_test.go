@@ -0,0 +1,75 @@
+package bcbc
+
+import (
+	"os"
+	"path/filepath"
+	"reflect"
+	"testing"
+)
+
+// 形式1と形式2のどちらのdiskファイルも読めることを確認する。
+func TestReadDiskFile(t *testing.T) {
+	tests := []struct {
+		name     string
+		content  string
+		id       string
+		settings map[string]string
+	}{
+		{"legacy id only", "A1\n", "A1", map[string]string{}},
+		{"legacy without newline", "A1", "A1", map[string]string{}},
+		{"legacy crlf", "A1\r\nlabel=写真\r\n", "A1", map[string]string{"label": "写真"}},
+		{"legacy trailing text", "A1 写真用\n", "A1", map[string]string{}},
+		{"legacy settings", "B12\nlabel=写真 2019-2021\ngroup=Photo\n", "B12", map[string]string{"label": "写真 2019-2021", "group": "Photo"}},
+		{"settings", "id=C3\nlabel=文書\n", "C3", map[string]string{"id": "C3", "label": "文書"}},
+		{"settings with spaces", " id = C3 \n throttle = 50 \n", "C3", map[string]string{"id": "C3", "throttle": "50"}},
+		{"comments and blank lines", "# 写真のディスク\n\nid=D4\n# group=Old\ngroup=Photo\n", "D4", map[string]string{"id": "D4", "group": "Photo"}},
+		{"unknown key", "id=E5\ncolor=red\n", "E5", map[string]string{"id": "E5", "color": "red"}},
+		{"line without separator", "id=F6\nlabel=写真\nsomething\n", "F6", map[string]string{"id": "F6", "label": "写真"}},
+		{"value with separator", "id=G7\nfilter=a=b.txt\n", "G7", map[string]string{"id": "G7", "filter": "a=b.txt"}},
+	}
+
+	for _, test := range tests {
+		t.Run(test.name, func(t *testing.T) {
+			diskFile := filepath.Join(t.TempDir(), "disk")
+			if err := os.WriteFile(diskFile, []byte(test.content), 0644); err != nil {
+				t.Fatal(err)
+			}
+
+			id, settings := readDiskFile(diskFile)
+			if id != test.id || !reflect.DeepEqual(settings, test.settings) {
+				t.Errorf("readDiskFile(%q) = %q, %v, want %q, %v", test.content, id, settings, test.id, test.settings)
+			}
+		})
+	}
+}
+
+// ディスクIDがないdiskファイルはエラーになることを確認する。
+func TestReadDiskFileInvalid(t *testing.T) {
+	tests := []struct {
+		name    string
+		content string
+	}{
+		{"empty", ""},
+		{"lower case id", "a1\n"},
+		{"no id setting", "label=写真\n"},
+		{"invalid id setting", "id=photo\n"},
+	}
+
+	for _, test := range tests {
+		t.Run(test.name, func(t *testing.T) {
+			diskFile := filepath.Join(t.TempDir(), "disk")
+			if err := os.WriteFile(diskFile, []byte(test.content), 0644); err != nil {
+				t.Fatal(err)
+			}
+
+			var err error
+			func() {
+				defer recoverFatal(&err)
+				readDiskFile(diskFile)
+			}()
+			if !IsKind(err, ErrDiskFile) {
+				t.Errorf("readDiskFile(%q) error = %v, want %s", test.content, err, ErrDiskFile)
+			}
+		})
+	}
+}
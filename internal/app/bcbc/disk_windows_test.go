@@ -0,0 +1,137 @@
+//go:build windows
+// +build windows
+
+package bcbc
+
+import (
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestNormalizeRootPathWindows(t *testing.T) {
+	tests := []struct {
+		rootPath string
+		want     string
+	}{
+		{`D:\`, `D:\`},
+		{`\\?\D:\`, `D:\`},
+		{`\\?\D:\photos\2020\`, `D:\photos\2020`},
+		{`D:/photos/2020`, `D:\photos\2020`},
+		{`\\?\UNC\nas\share`, `\\?\UNC\nas\share`},
+	}
+
+	for _, test := range tests {
+		if got := normalizeRootPath(test.rootPath); got != test.want {
+			t.Errorf("normalizeRootPath(%q) = %q, want %q", test.rootPath, got, test.want)
+		}
+	}
+}
+
+func TestSplitRootSubpathsWindows(t *testing.T) {
+	tests := []struct {
+		arg      string
+		root     string
+		subpaths []string
+	}{
+		{`D:`, `D:`, nil},
+		{`D:\`, `D:\`, nil},
+		{`\\?\D:\photos`, `\\?\D:\photos`, nil},
+		{`D:\photos:2020\jan,raw`, `D:\photos`, []string{"2020/jan", "raw"}},
+	}
+
+	for _, test := range tests {
+		root, subpaths := splitRootSubpaths(test.arg)
+		if root != test.root || !reflect.DeepEqual(subpaths, test.subpaths) {
+			t.Errorf("splitRootSubpaths(%q) = %q, %q, want %q, %q", test.arg, root, subpaths, test.root, test.subpaths)
+		}
+	}
+}
+
+func TestFindDiskFilesExtendedPath(t *testing.T) {
+	rootPath := t.TempDir()
+	writeTestDiskFile(t, rootPath)
+
+	diskFiles := findDiskFiles([]string{extendedPathPrefix + rootPath})
+
+	want := filepath.Join(rootPath, "disk")
+	if len(diskFiles) != 1 || diskFiles[0] != want {
+		t.Fatalf("findDiskFiles = %q, want [%q]", diskFiles, want)
+	}
+	if id, _ := readDiskFile(diskFiles[0]); id != "TEST1" {
+		t.Errorf("readDiskFile = %q, want TEST1", id)
+	}
+}
+
+func TestFindDiskFileFromCurrentSubdirectory(t *testing.T) {
+	rootPath := t.TempDir()
+	writeTestDiskFile(t, rootPath)
+	subDir := filepath.Join(rootPath, "photos", "2020")
+	if err := os.MkdirAll(subDir, 0755); err != nil {
+		t.Fatal(err)
+	}
+	chdirForTest(t, subDir)
+
+	diskFile, err := findDiskFileFromCurrent()
+	if err != nil {
+		t.Fatalf("findDiskFileFromCurrent: %v", err)
+	}
+	if want := filepath.Join(rootPath, "disk"); !strings.EqualFold(diskFile, want) {
+		t.Errorf("findDiskFileFromCurrent = %q, want %q", diskFile, want)
+	}
+}
+
+// ドライブのルートまで遡ったら、見つからなくても止まることを確認する。
+func TestFindDiskFileFromCurrentDriveRoot(t *testing.T) {
+	driveRoot := filepath.VolumeName(t.TempDir()) + `\`
+	chdirForTest(t, driveRoot)
+
+	diskFile, err := findDiskFileFromCurrent()
+	if err == nil && !strings.EqualFold(filepath.Dir(diskFile), driveRoot) {
+		t.Errorf("findDiskFileFromCurrent = %q, want a disk file in %q", diskFile, driveRoot)
+	}
+}
+
+func TestFileInfoNormPathWindows(t *testing.T) {
+	tests := []struct {
+		rootPath string
+		realPath string
+		want     string
+	}{
+		{`D:\`, `D:\photos\2020\a.jpg`, "photos/2020/a.jpg"},
+		{`D:\disk1`, `D:\disk1\docs\b.txt`, "docs/b.txt"},
+		{normalizeRootPath(`\\?\D:\disk1`), `D:\disk1\c.txt`, "c.txt"},
+	}
+
+	for _, test := range tests {
+		var fi FileInfo
+		fi.init(&DiskInfo{rootPath: test.rootPath}, test.realPath)
+		if fi.normPath != test.want {
+			t.Errorf("normPath of %q in %q = %q, want %q", test.realPath, test.rootPath, fi.normPath, test.want)
+		}
+	}
+}
+
+func writeTestDiskFile(t *testing.T, rootPath string) {
+	t.Helper()
+	if err := os.WriteFile(filepath.Join(rootPath, "disk"), []byte("TEST1\n"), 0644); err != nil {
+		t.Fatal(err)
+	}
+}
+
+// カレントディレクトリを変更し、テストの終わりに戻す。
+func chdirForTest(t *testing.T, dir string) {
+	t.Helper()
+	current, err := os.Getwd()
+	if err != nil {
+		t.Fatal(err)
+	}
+	if err := os.Chdir(dir); err != nil {
+		t.Fatal(err)
+	}
+	t.Cleanup(func() {
+		os.Chdir(current)
+	})
+}
@@ -0,0 +1,187 @@
+package bcbc
+
+import (
+	"bufio"
+	"encoding/json"
+	"flag"
+	"os"
+	"path/filepath"
+	"strings"
+	"time"
+)
+
+// 環境の診断
+type doctor struct {
+	problems int
+}
+
+// 問題を警告する。
+func (d *doctor) warn(format string, values ...interface{}) {
+	d.problems++
+	logf.Printf(tr("[警告] ")+tr(format), values...)
+}
+
+// 診断を実行する。
+// 致命的なエラーは終了せずに問題として数える。メッセージは発生したときに出力されている。
+func (d *doctor) check(check func()) {
+	defer func() {
+		if r := recover(); r != nil {
+			if _, ok := r.(*Error); !ok {
+				panic(r)
+			}
+			d.problems++
+		}
+	}()
+
+	check()
+}
+
+// doctorサブコマンド。
+// BCBCホームディレクトリの構成、設定、残っているロックファイル、ハッシュファイル、
+// 統合ハッシュファイルの鮮度、長期間ハッシュ計算していないディスクをまとめて診断する。
+func executeDoctor(args []string) {
+	flagSet := flag.NewFlagSet("doctor", flag.ExitOnError)
+	staleDays := flagSet.Int("stale-days", 180, "この日数を超えてハッシュ計算していないディスクを警告する。")
+	flagSet.Parse(args)
+
+	d := &doctor{}
+
+	d.checkLayout()
+	d.check(d.checkFilters)
+	d.checkLocks()
+	for _, algorithm := range config.algorithms {
+		store := hashStoreFor(algorithm)
+		d.checkHashFiles(store, algorithm)
+		d.check(func() { d.checkStaleGroups(store, algorithm) })
+	}
+	d.checkDiskStaleness(time.Duration(*staleDays) * 24 * time.Hour)
+
+	if d.problems == 0 {
+		logf.Println("問題は見つかりませんでした。")
+	} else {
+		logf.Printf("%s件の問題が見つかりました。\n", formatCount(uint64(d.problems)))
+	}
+}
+
+// BCBCホームディレクトリの構成を診断する。
+func (d *doctor) checkLayout() {
+	for _, dir := range []string{config.configDir(), config.logDir(), config.outDir()} {
+		stat, err := os.Stat(dir)
+		if err != nil {
+			d.warn("ディレクトリがありません。: %s\n", dir)
+		} else if !stat.IsDir() {
+			d.warn("ディレクトリではありません。: %s\n", dir)
+		}
+	}
+}
+
+// フィルター設定を診断する。
+func (d *doctor) checkFilters() {
+	initFilters()
+
+	inclusions := 0
+	for _, filter := range config.filters {
+		if filter.inclusion {
+			inclusions++
+		}
+	}
+	if inclusions == 0 {
+		d.warn("フィルター設定に対象にするパターンがないので、ハッシュ計算するファイルがありません。\n")
+	}
+}
+
+// 残っているロックファイルを診断する。
+func (d *doctor) checkLocks() {
+	lockFiles, _ := filepath.Glob(filepath.Join(config.outDir(), "*.lock"))
+	for _, lockFile := range lockFiles {
+		owner, _ := os.ReadFile(lockFile)
+		d.warn("ロックファイルが残っています。実行中でなければ削除してください。: %s (%s)\n", lockFile, strings.TrimSpace(string(owner)))
+	}
+}
+
+// ハッシュファイルを読み込めるか診断して記録の件数を表示する。
+func (d *doctor) checkHashFiles(store HashStore, algorithm string) {
+	diskIds, err := store.diskIds()
+	if err != nil {
+		d.warn("%s\n", err)
+		return
+	}
+
+	for _, diskId := range diskIds {
+		records, err := store.load(diskId)
+		if err != nil {
+			d.warn("%s\n", err)
+			continue
+		}
+
+		incompletes := 0
+		for _, record := range records {
+			if isIncompleteHash(record.hash) {
+				incompletes++
+			}
+		}
+
+		info, err := store.loadInfo(diskId)
+		if err != nil {
+			d.warn("%s\n", err)
+			continue
+		}
+
+		logf.Printf("ハッシュファイル %s (%s): %s件 v%d %s\n", diskId, algorithm, formatCount(uint64(len(records))), info.Version, info.Tool)
+		if info.Version < hashFileVersion {
+			d.warn("古い形式のハッシュファイルです。次のハッシュ計算かfsck-hashfileでv%dに書き直されます。: %s (%s)\n", hashFileVersion, diskId, algorithm)
+		}
+		if incompletes > 0 {
+			d.warn("読み込めない範囲があった不完全なハッシュがあります。: %s (%s) %s件\n", diskId, algorithm, formatCount(uint64(incompletes)))
+		}
+	}
+}
+
+// 古くなっている統合ハッシュファイルを診断する。
+func (d *doctor) checkStaleGroups(store HashStore, algorithm string) {
+	for _, group := range findStaleGroups(store) {
+		d.warn("統合ハッシュファイルが古くなっています。statusサブコマンドの-auto-mergeで統合し直してください。: %s (%s)\n", group, algorithm)
+	}
+}
+
+// 長期間ハッシュ計算していないディスクを診断する。
+// 実行履歴の最後の記録の日時で判定するので、実行履歴がないディスクは診断しない。
+func (d *doctor) checkDiskStaleness(staleAfter time.Duration) {
+	historyFiles, _ := filepath.Glob(filepath.Join(config.outDir(), "*.history"))
+	for _, historyFile := range historyFiles {
+		diskId := strings.TrimSuffix(filepath.Base(historyFile), ".history")
+
+		lastTime, found := lastHistoryTime(historyFile)
+		if !found {
+			d.warn("実行履歴が読み込めませんでした。: %s\n", historyFile)
+			continue
+		}
+
+		if elapsed := time.Since(lastTime); elapsed > staleAfter {
+			d.warn("ディスク%sは%d日間ハッシュ計算していません。\n", diskId, int(elapsed.Hours()/24))
+		}
+	}
+}
+
+// 実行履歴の最後の記録の日時を返す。
+func lastHistoryTime(historyFile string) (time.Time, bool) {
+	historyIn, err := os.Open(historyFile)
+	if err != nil {
+		return time.Time{}, false
+	}
+	defer historyIn.Close()
+
+	var last DiskEnvironment
+	found := false
+
+	scanner := bufio.NewScanner(historyIn)
+	for scanner.Scan() {
+		var environment DiskEnvironment
+		if json.Unmarshal(scanner.Bytes(), &environment) == nil {
+			last = environment
+			found = true
+		}
+	}
+
+	return last.Time, found
+}
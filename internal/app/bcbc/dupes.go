@@ -0,0 +1,86 @@
+package bcbc
+
+import (
+	"flag"
+	"sort"
+)
+
+// 同じハッシュの記録の組
+type duplicateGroup struct {
+	hash string
+	size int64
+	// ディスクIDと記録
+	files []largestFile
+}
+
+// 重複を除けば減らせるバイト数を返す。サイズを記録していなければ0を返す。
+func (g *duplicateGroup) wasted() int64 {
+	if g.size <= 0 {
+		return 0
+	}
+	return g.size * int64(len(g.files)-1)
+}
+
+// dupesサブコマンド。
+// 全てのディスクのハッシュファイルの記録から同じハッシュのファイルを探して、
+// 重複を除けば減らせるバイト数が多い順に表示する。
+// -indexが指定されていて索引があれば索引から探す。
+func executeDupes(args []string) {
+	flagSet := flag.NewFlagSet("dupes", flag.ExitOnError)
+	top := flagSet.Int("top", 20, "表示する重複の組の数。0なら全て表示する。")
+	minSize := flagSet.Int64("min-size", 1, "このサイズ(バイト)以上のファイルだけを重複として数える。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, *top < 0, "表示する数の指定が不正です。\n")
+
+	// 複数のアルゴリズムで記録していても同じファイルなので、最初のアルゴリズムのハッシュファイルで探す
+	algorithm := config.algorithms[0]
+	store := hashStoreFor(algorithm)
+	index := loadIndexIfEnabled()
+
+	diskIds, err := store.diskIds()
+	fatalMessageError(ErrIO, err, "出力ファイルの一覧取得に失敗しました。\n")
+
+	groups := make(map[string]*duplicateGroup)
+	for _, diskId := range diskIds {
+		for _, record := range index.load(store, algorithm, diskId) {
+			// 読み込めない範囲があったハッシュは内容を表さないので重複の判定に使わない
+			if isIncompleteHash(record.hash) || record.size < *minSize {
+				continue
+			}
+			group, found := groups[record.hash]
+			if !found {
+				group = &duplicateGroup{hash: record.hash, size: record.size}
+				groups[record.hash] = group
+			}
+			group.files = append(group.files, largestFile{diskId, record})
+		}
+	}
+
+	var duplicates []*duplicateGroup
+	var wasted int64
+	for _, group := range groups {
+		if len(group.files) > 1 {
+			duplicates = append(duplicates, group)
+			wasted += group.wasted()
+		}
+	}
+	sort.Slice(duplicates, func(i, j int) bool {
+		if duplicates[i].wasted() != duplicates[j].wasted() {
+			return duplicates[i].wasted() > duplicates[j].wasted()
+		}
+		return duplicates[i].hash < duplicates[j].hash
+	})
+
+	logf.Printf("重複しているファイルの組が%d組あります。重複を除けば%s減らせます。\n", len(duplicates), formatSize(float64(wasted)))
+
+	if *top > 0 && len(duplicates) > *top {
+		duplicates = duplicates[:*top]
+	}
+	for _, group := range duplicates {
+		logf.Printf("%s (%s x %d)\n", group.hash, formatSize(float64(group.size)), len(group.files))
+		for _, file := range group.files {
+			logf.Printf("  %s: %s\n", file.diskId, file.record.path)
+		}
+	}
+}
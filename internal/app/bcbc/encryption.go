@@ -0,0 +1,166 @@
+package bcbc
+
+import (
+	"crypto/aes"
+	"crypto/cipher"
+	"crypto/hmac"
+	"crypto/rand"
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/binary"
+	"errors"
+	"os"
+	"path/filepath"
+)
+
+// ハッシュファイルの暗号化の方法
+const (
+	// EncryptNone 暗号化しない
+	EncryptNone = ""
+	// EncryptKey 設定ディレクトリの鍵ファイルの鍵で暗号化する
+	EncryptKey = "key"
+	// EncryptPassphrase 環境変数のパスフレーズから導出した鍵で暗号化する
+	EncryptPassphrase = "passphrase"
+)
+
+// 暗号化したハッシュファイルのヘッダーに書く暗号方式
+const encryptionCipher = "aes-256-gcm"
+
+// EnvPassphrase 環境変数名: ハッシュファイルを暗号化するパスフレーズ
+const EnvPassphrase = "BCBC_PASSPHRASE"
+
+// 暗号化の鍵ファイルとパスフレーズのソルトのファイルの名前
+const (
+	encryptionKeyFile  = "encryption.key"
+	encryptionSaltFile = "encryption.salt"
+)
+
+// パスフレーズから鍵を導出するときの繰り返し回数
+const passphraseIterations = 200000
+
+// 暗号化の鍵の長さ
+const encryptionKeySize = 32
+
+// -encryptの指定に従ってハッシュファイルを暗号化する鍵を用意する。
+// 鍵ファイルやソルトがなければ作成する。
+func initEncryption() {
+	config.encryption = nil
+
+	var key []byte
+	switch *encryptOption {
+	case EncryptNone:
+		return
+	case EncryptKey:
+		key = loadOrCreateSecret(filepath.Join(config.configDir(), encryptionKeyFile), encryptionKeySize)
+	case EncryptPassphrase:
+		passphrase, found := os.LookupEnv(EnvPassphrase)
+		fatalMessageIf(ErrConfig, !found || passphrase == "", "環境変数%sにパスフレーズを設定してください。\n", EnvPassphrase)
+		salt := loadOrCreateSecret(filepath.Join(config.configDir(), encryptionSaltFile), 16)
+		key = pbkdf2SHA256([]byte(passphrase), salt, passphraseIterations, encryptionKeySize)
+	default:
+		fatalMessageIf(ErrConfig, true, "暗号化の指定が不正です。: %s\n", *encryptOption)
+	}
+
+	block, err := aes.NewCipher(key)
+	fatalMessageError(ErrConfig, err, "暗号化の鍵が不正です。\n")
+	aead, err := cipher.NewGCM(block)
+	fatalMessageError(ErrConfig, err, "暗号化の鍵が不正です。\n")
+
+	config.encryption = aead
+}
+
+// base64で保存した秘密の値を読み込む。なければ作成して保存する。
+// 鍵ファイルをなくすと暗号化したハッシュファイルを読めなくなるので、作成したことをログに出す。
+func loadOrCreateSecret(file string, size int) []byte {
+	secret, err := readBase64File(file)
+	if err == nil {
+		fatalMessageIf(ErrConfig, len(secret) != size, "暗号化の鍵ファイルの形式が不正です。: %s\n", file)
+		return secret
+	}
+	fatalMessageIf(ErrConfig, !os.IsNotExist(err), "暗号化の鍵ファイルが読み込めませんでした。: %s\n", file)
+
+	secret = make([]byte, size)
+	_, err = rand.Read(secret)
+	fatalMessageError(ErrIO, err, "暗号化の鍵を作成できませんでした。\n")
+
+	err = os.MkdirAll(filepath.Dir(file), 0755)
+	fatalMessageError(ErrIO, err, "設定ディレクトリを作成できませんでした。: %s\n", filepath.Dir(file))
+	err = os.WriteFile(file, []byte(base64.StdEncoding.EncodeToString(secret)+"\n"), 0600)
+	fatalMessageError(ErrIO, err, "暗号化の鍵ファイルを保存できませんでした。: %s\n", file)
+
+	logf.Printf("暗号化の鍵ファイルを作成しました。なくすとハッシュファイルを読めなくなるので控えておいてください。: %s\n", file)
+	return secret
+}
+
+// 書き込むハッシュファイルの暗号方式を返す。暗号化しなければ空文字列を返す。
+func currentEncryption() string {
+	if config.encryption == nil {
+		return ""
+	}
+	return encryptionCipher
+}
+
+// ハッシュファイルの1行を暗号化する。
+// 追記できるように1行ずつ暗号化し、ノンスと暗号文をbase64にして1行にする。
+func sealHashLine(line string) (string, error) {
+	if config.encryption == nil {
+		return "", errors.New("暗号化の鍵が設定されていません。")
+	}
+
+	nonce := make([]byte, config.encryption.NonceSize())
+	if _, err := rand.Read(nonce); err != nil {
+		return "", err
+	}
+	sealed := config.encryption.Seal(nonce, nonce, []byte(line), nil)
+
+	return base64.StdEncoding.EncodeToString(sealed) + "\n", nil
+}
+
+// 暗号化したハッシュファイルの1行を復号する。
+func openHashLine(line string) (string, error) {
+	if config.encryption == nil {
+		return "", errors.New("暗号化されたハッシュファイルです。-encryptで暗号化の方法を指定してください。")
+	}
+
+	sealed, err := base64.StdEncoding.DecodeString(line)
+	nonceSize := config.encryption.NonceSize()
+	if err != nil || len(sealed) < nonceSize {
+		return "", errors.New("暗号化された行の形式が不正です。")
+	}
+
+	plain, err := config.encryption.Open(nil, sealed[:nonceSize], sealed[nonceSize:], nil)
+	if err != nil {
+		return "", errors.New("復号できませんでした。鍵かパスフレーズが異なる可能性があります。")
+	}
+
+	return string(plain), nil
+}
+
+// PBKDF2(HMAC-SHA256)でパスフレーズから鍵を導出する。
+func pbkdf2SHA256(password []byte, salt []byte, iterations int, keyLength int) []byte {
+	prf := hmac.New(sha256.New, password)
+	key := make([]byte, 0, keyLength+prf.Size())
+	block := make([]byte, 4)
+
+	for index := uint32(1); len(key) < keyLength; index++ {
+		prf.Reset()
+		prf.Write(salt)
+		binary.BigEndian.PutUint32(block, index)
+		prf.Write(block)
+		u := prf.Sum(nil)
+		t := append([]byte(nil), u...)
+
+		for i := 1; i < iterations; i++ {
+			prf.Reset()
+			prf.Write(u)
+			u = prf.Sum(u[:0])
+			for j := range t {
+				t[j] ^= u[j]
+			}
+		}
+
+		key = append(key, t...)
+	}
+
+	return key[:keyLength]
+}
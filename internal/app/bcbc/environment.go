@@ -0,0 +1,49 @@
+package bcbc
+
+import (
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"time"
+)
+
+// DiskEnvironment ハッシュ計算したときのディスクの環境。
+// 過去の検証結果を解釈するときに、当時どのようにマウントされていたかを確認できるように実行履歴に記録する。
+type DiskEnvironment struct {
+	Time           time.Time `json:"time"`
+	Host           string    `json:"host"`
+	RootPath       string    `json:"root"`
+	FilesystemType string    `json:"fs_type,omitempty"`
+	MountPoint     string    `json:"mount_point,omitempty"`
+	MountOptions   string    `json:"mount_options,omitempty"`
+	Device         string    `json:"device,omitempty"`
+	DeviceModel    string    `json:"device_model,omitempty"`
+	TotalBytes     uint64    `json:"total_bytes,omitempty"`
+	FreeBytes      uint64    `json:"free_bytes,omitempty"`
+}
+
+// 実行履歴ファイルのパスを返す。
+func (di *DiskInfo) historyFile() string {
+	return filepath.Join(config.outDir(), di.id+".history")
+}
+
+// ディスクの環境を調べて実行履歴に追記する。
+// 調べられなかった項目は空のまま記録し、記録できなくてもハッシュ計算は続ける。
+func recordEnvironment(diskInfo *DiskInfo) {
+	environment := DiskEnvironment{
+		Time:     time.Now(),
+		Host:     writerHost(),
+		RootPath: diskInfo.rootPath,
+	}
+	inspectDiskEnvironment(diskInfo.rootPath, &environment)
+
+	historyOut, err := os.OpenFile(diskInfo.historyFile(), os.O_CREATE|os.O_WRONLY|os.O_APPEND, 0644)
+	if err == nil {
+		defer historyOut.Close()
+		err = json.NewEncoder(historyOut).Encode(environment)
+	}
+	if err != nil {
+		logf.Printf("実行履歴の書き込みに失敗しました。: %s\n", diskInfo.historyFile())
+		logf.Println(err)
+	}
+}
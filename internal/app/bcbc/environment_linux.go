@@ -0,0 +1,103 @@
+// +build linux
+
+package bcbc
+
+import (
+	"bufio"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+	"syscall"
+)
+
+// ディスクの空き容量とマウント情報を調べる。
+func inspectDiskEnvironment(rootPath string, environment *DiskEnvironment) {
+	var stat syscall.Statfs_t
+	if err := syscall.Statfs(rootPath, &stat); err == nil {
+		environment.TotalBytes = stat.Blocks * uint64(stat.Bsize)
+		environment.FreeBytes = stat.Bavail * uint64(stat.Bsize)
+	}
+
+	realRoot, err := filepath.EvalSymlinks(rootPath)
+	if err != nil {
+		return
+	}
+	realRoot, _ = filepath.Abs(realRoot)
+
+	mountInfoIn, err := os.Open("/proc/self/mountinfo")
+	if err != nil {
+		return
+	}
+	defer mountInfoIn.Close()
+
+	// ディスクルートを含むマウントポイントのうち最も深いものを探す
+	scanner := bufio.NewScanner(mountInfoIn)
+	for scanner.Scan() {
+		sides := strings.SplitN(scanner.Text(), " - ", 2)
+		if len(sides) != 2 {
+			continue
+		}
+		mountFields := strings.Fields(sides[0])
+		fsFields := strings.Fields(sides[1])
+		if len(mountFields) < 6 || len(fsFields) < 2 {
+			continue
+		}
+
+		mountPoint := unescapeMountInfo(mountFields[4])
+		if !containsPath(mountPoint, realRoot) || len(mountPoint) < len(environment.MountPoint) {
+			continue
+		}
+
+		environment.MountPoint = mountPoint
+		environment.MountOptions = mountFields[5]
+		environment.FilesystemType = fsFields[0]
+		environment.Device = unescapeMountInfo(fsFields[1])
+	}
+
+	environment.DeviceModel = deviceModel(environment.Device)
+}
+
+// マウントポイントがパスを含むか判定する。
+func containsPath(mountPoint string, path string) bool {
+	if mountPoint == "/" || mountPoint == path {
+		return true
+	}
+	return strings.HasPrefix(path, mountPoint+"/")
+}
+
+// mountinfoで8進数にエスケープされた空白などを元に戻す。
+func unescapeMountInfo(field string) string {
+	var builder strings.Builder
+	for i := 0; i < len(field); i++ {
+		if field[i] == '\\' && i+3 < len(field) {
+			if c, err := strconv.ParseUint(field[i+1:i+4], 8, 8); err == nil {
+				builder.WriteByte(byte(c))
+				i += 3
+				continue
+			}
+		}
+		builder.WriteByte(field[i])
+	}
+	return builder.String()
+}
+
+// ブロックデバイスの機種名を返す。パーティションなら親のデバイスの機種名を返す。
+func deviceModel(device string) string {
+	if !strings.HasPrefix(device, "/dev/") {
+		return ""
+	}
+
+	sysPath, err := filepath.EvalSymlinks(filepath.Join("/sys/class/block", filepath.Base(device)))
+	if err != nil {
+		return ""
+	}
+
+	for _, dir := range []string{sysPath, filepath.Dir(sysPath)} {
+		if model, err := os.ReadFile(filepath.Join(dir, "device", "model")); err == nil {
+			return strings.TrimSpace(string(model))
+		}
+	}
+
+	return ""
+}
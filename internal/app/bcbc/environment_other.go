@@ -0,0 +1,7 @@
+// +build !linux
+
+package bcbc
+
+// Linux以外ではマウント情報を調べない。
+func inspectDiskEnvironment(rootPath string, environment *DiskEnvironment) {
+}
@@ -1,16 +1,109 @@
 package bcbc
 
-// 引数errorOccuredがtrueなら引数messageをログ出力してプログラムを終了する。
-func fatalMessageIf(errorOccurred bool, format string, values ...interface{}) {
+import (
+	"errors"
+	"fmt"
+	"strings"
+)
+
+// ErrorKind エラーの種類
+type ErrorKind int
+
+const (
+	// ErrIO ファイルの入出力のエラー
+	ErrIO ErrorKind = iota + 1
+	// ErrConfig 設定やオプションのエラー
+	ErrConfig
+	// ErrHashFileFormat ハッシュファイルの形式のエラー
+	ErrHashFileFormat
+	// ErrDiskFile diskファイルのエラー
+	ErrDiskFile
+	// ErrLocked 他の書き込み元がディスクをロックしている
+	ErrLocked
+	// ErrInterrupted 処理が中断された
+	ErrInterrupted
+	// ErrDisconnected ディスクが取り外された
+	ErrDisconnected
+)
+
+func (k ErrorKind) String() string {
+	switch k {
+	case ErrIO:
+		return "IO"
+	case ErrConfig:
+		return "Config"
+	case ErrHashFileFormat:
+		return "HashFileFormat"
+	case ErrDiskFile:
+		return "DiskFile"
+	case ErrLocked:
+		return "Locked"
+	case ErrInterrupted:
+		return "Interrupted"
+	case ErrDisconnected:
+		return "Disconnected"
+	default:
+		return fmt.Sprintf("ErrorKind(%d)", int(k))
+	}
+}
+
+// Error bcbcのエラー
+// 処理を続行できないエラーはこの型の値でパニックし、エントリーポイントで回復してエラーとして扱う。
+type Error struct {
+	Kind    ErrorKind
+	Message string
+	Err     error
+}
+
+func (e *Error) Error() string {
+	if e.Err == nil {
+		return e.Message
+	}
+	return e.Message + ": " + e.Err.Error()
+}
+
+func (e *Error) Unwrap() error {
+	return e.Err
+}
+
+// IsKind 引数errが指定された種類のErrorを含んでいるか判定する。
+func IsKind(err error, kind ErrorKind) bool {
+	var e *Error
+	return errors.As(err, &e) && e.Kind == kind
+}
+
+// 種類とメッセージを指定してエラーを作成する。
+func newError(kind ErrorKind, err error, format string, values ...interface{}) *Error {
+	return &Error{kind, fmt.Sprintf(tr(format), values...), err}
+}
+
+// 引数errorOccuredがtrueなら引数messageをログ出力して処理を中断する。
+func fatalMessageIf(kind ErrorKind, errorOccurred bool, format string, values ...interface{}) {
 	if errorOccurred {
-		logf.Fatalf(format, values...)
+		message := fmt.Sprintf(tr(format), values...)
+		logf.printf(levelError, format, values...)
+		panic(&Error{kind, strings.TrimSuffix(message, "\n"), nil})
 	}
 }
 
-// 引数errorOccuredがtrueなら引数messageをログ出力してプログラムを終了する。
-func fatalMessageError(err error, format string, values ...interface{}) {
+// 引数errがnilでなければ引数messageとエラーをログ出力して処理を中断する。
+func fatalMessageError(kind ErrorKind, err error, format string, values ...interface{}) {
 	if err != nil {
-		logf.Printf(format, values...)
-		logf.Fatalln(err)
+		message := fmt.Sprintf(tr(format), values...)
+		logf.printf(levelError, format, values...)
+		logf.Println(err)
+		panic(&Error{kind, strings.TrimSuffix(message, "\n"), err})
+	}
+}
+
+// 致命的なエラーによるパニックを回復して引数errに設定する。
+// deferで直接呼び出すこと。
+func recoverFatal(err *error) {
+	if r := recover(); r != nil {
+		e, ok := r.(*Error)
+		if !ok {
+			panic(r)
+		}
+		*err = e
 	}
 }
@@ -0,0 +1,74 @@
+package bcbc
+
+import (
+	"errors"
+	"fmt"
+	"io/fs"
+	"os"
+	"path/filepath"
+	"strings"
+	"sync"
+	"time"
+)
+
+// エラーレポートの見出し行
+const errorReportHeader = "# time\tpath\tkind\terror\n"
+
+// エラーレポートへの書き込みを1つずつ行うためのミューテックス
+var errorReportMutex sync.Mutex
+
+// エラーレポートのパスを返す。
+func (di *DiskInfo) errorReportFile() string {
+	return filepath.Join(config.outDir(), di.id+".errors")
+}
+
+// 前回のハッシュ計算のエラーレポートを削除して、今回のエラーを書き込めるようにする。
+func resetErrorReport(diskInfo *DiskInfo) {
+	if err := os.Remove(diskInfo.errorReportFile()); err != nil && !errors.Is(err, fs.ErrNotExist) {
+		fatalMessageError(ErrIO, err, "エラーレポートを削除できませんでした。: %s\n", diskInfo.errorReportFile())
+	}
+}
+
+// ハッシュ計算できなかったファイルをエラーレポートに追記する。
+// 途中で異常終了してもそこまでのエラーが残るように、エラーが発生するたびに書き込む。
+func recordFileError(diskInfo *DiskInfo, normPath string, fileErr error) {
+	errorReportMutex.Lock()
+	defer errorReportMutex.Unlock()
+
+	reportOut, err := os.OpenFile(diskInfo.errorReportFile(), os.O_CREATE|os.O_WRONLY|os.O_APPEND, 0644)
+	fatalMessageError(ErrIO, err, "エラーレポートの書き込みに失敗しました。: %s\n", diskInfo.errorReportFile())
+	defer reportOut.Close()
+
+	if stat, err := reportOut.Stat(); err == nil && stat.Size() == 0 {
+		reportOut.WriteString(errorReportHeader)
+	}
+
+	_, err = fmt.Fprintf(reportOut, "%s\t%s\t%s\t%s\n", time.Now().Format(time.RFC3339), pathEscaper.Replace(normPath),
+		fileErrorKind(fileErr), pathEscaper.Replace(osErrorMessage(fileErr)))
+	fatalMessageError(ErrIO, err, "エラーレポートの書き込みに失敗しました。: %s\n", diskInfo.errorReportFile())
+}
+
+// エラーの種類を返す。
+func fileErrorKind(err error) string {
+	switch {
+	case errors.Is(err, fs.ErrNotExist):
+		return "NotExist"
+	case errors.Is(err, fs.ErrPermission):
+		return "Permission"
+	}
+
+	var e *Error
+	if errors.As(err, &e) {
+		return e.Kind.String()
+	}
+	return ErrIO.String()
+}
+
+// OSのエラーがあればそのメッセージを、なければエラー全体のメッセージを返す。
+func osErrorMessage(err error) string {
+	var pathErr *fs.PathError
+	if errors.As(err, &pathErr) {
+		return pathErr.Err.Error()
+	}
+	return strings.TrimSpace(err.Error())
+}
@@ -0,0 +1,78 @@
+package bcbc
+
+import (
+	"bufio"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sync"
+	"time"
+)
+
+// フィルターの判定の記録。
+// ハッシュ対象にならなかったファイルと、対象外にしたフィルターを判定レポートに書き込む。
+// 対象外のファイルが多いとログが埋もれるので、ログではなく別のファイルに書き込む。
+type filterExplainer struct {
+	mu       sync.Mutex
+	out      *os.File
+	writer   *bufio.Writer
+	path     string
+	excluded int
+}
+
+// フィルターの判定の記録。-explain-filtersが指定されていなければnil。
+var filterExplain *filterExplainer
+
+// -explain-filtersが指定されていれば判定レポートを作成する。
+func initFilterExplain() {
+	filterExplain = nil
+	if !*explainFiltersOption {
+		return
+	}
+
+	reportPath := filepath.Join(config.logDir(), time.Now().Format("20060102150405.filters.log"))
+	reportOut, err := os.Create(reportPath)
+	fatalMessageError(ErrIO, err, "フィルターの判定レポートを作成できませんでした。: %s\n", reportPath)
+
+	logf.Printf("フィルターの判定を記録します。: %s\n", reportPath)
+
+	filterExplain = &filterExplainer{out: reportOut, writer: bufio.NewWriter(reportOut), path: reportPath}
+}
+
+// ハッシュ対象にならなかったファイルを記録する。
+func (e *filterExplainer) exclude(diskId string, normPath string, filter Filter, found bool) {
+	if e == nil {
+		return
+	}
+
+	e.mu.Lock()
+	defer e.mu.Unlock()
+
+	e.excluded++
+	if found {
+		fmt.Fprintf(e.writer, "%s\t%s\t%s: -%s\n", diskId, normPath, filter.source(), filter.pattern)
+	} else {
+		fmt.Fprintf(e.writer, "%s\t%s\tマッチするフィルターなし\n", diskId, normPath)
+	}
+}
+
+// 判定レポートを閉じて、対象外にしたファイルの数をログに出力する。
+func (e *filterExplainer) close() {
+	if e == nil {
+		return
+	}
+
+	e.mu.Lock()
+	defer e.mu.Unlock()
+
+	err := e.writer.Flush()
+	if closeErr := e.out.Close(); err == nil {
+		err = closeErr
+	}
+	if err != nil {
+		logf.Printf("フィルターの判定レポートの書き込みに失敗しました。: %s\n", e.path)
+		logf.Println(err)
+	}
+
+	logf.Printf("フィルターで%d個のファイルを対象外にしました。: %s\n", e.excluded, e.path)
+}
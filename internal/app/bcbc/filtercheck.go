@@ -0,0 +1,61 @@
+package bcbc
+
+import (
+	"flag"
+	"golang.org/x/text/unicode/norm"
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// 複数回指定できる文字列のオプション
+type stringList []string
+
+func (l *stringList) String() string {
+	return strings.Join(*l, ",")
+}
+
+func (l *stringList) Set(value string) error {
+	*l = append(*l, value)
+	return nil
+}
+
+// filter-checkサブコマンド。
+// フィルター設定ファイルの形式が不正な行を全て報告し、
+// -test-pathで指定されたパスについてマッチするフィルターとハッシュ対象になるかを表示する。
+func executeFilterCheck(args []string) {
+	flagSet := flag.NewFlagSet("filter-check", flag.ExitOnError)
+	var testPaths stringList
+	flagSet.Var(&testPaths, "test-path", "判定を表示するディスクルートからの相対パス。複数回指定できる。")
+	flagSet.Parse(args)
+
+	filterFileIn, err := os.Open(filterConfigFile())
+	fatalMessageError(ErrConfig, err, "フィルター設定ファイルが見つかりません。\n")
+	defer filterFileIn.Close()
+
+	filters, problems, err := readFilters(filterFileIn)
+	fatalMessageError(ErrConfig, err, "フィルター設定ファイルが読み込めませんでした。\n")
+	filters = append(commandLineFilters(), filters...)
+
+	for _, problem := range problems {
+		logf.Printf("形式が不正です。: %d行目: %s\n", problem.line, problem.text)
+		logf.Printf("  %s\n", problem.err)
+	}
+	logf.Printf("%d件のフィルターを読み込みました。形式が不正な行は%d行です。\n", len(filters), len(problems))
+
+	for _, testPath := range testPaths {
+		normPath := norm.NFC.String(filepath.ToSlash(testPath))
+
+		filter, found := matchFilter(filters, normPath)
+		switch {
+		case !found:
+			logf.Printf("対象外: %s (マッチするフィルターがありません)\n", normPath)
+		case filter.inclusion:
+			logf.Printf("対象: %s (%s: +%s)\n", normPath, filter.source(), filter.pattern)
+		default:
+			logf.Printf("対象外: %s (%s: -%s)\n", normPath, filter.source(), filter.pattern)
+		}
+	}
+
+	fatalMessageIf(ErrConfig, len(problems) > 0, "フィルター設定ファイルに形式が不正な行があります。\n")
+}
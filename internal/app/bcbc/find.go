@@ -0,0 +1,89 @@
+package bcbc
+
+import (
+	"flag"
+	"regexp"
+	"strings"
+)
+
+// findサブコマンド。
+// 全てのハッシュファイルと統合ハッシュファイルから、パスがパターンに一致するかハッシュが一致する記録を探して、
+// どのディスクにあるか表示する。
+// -indexが指定されていて索引があれば、ハッシュファイルを解析せずに索引から探す。
+func executeFind(args []string) {
+	flagSet := flag.NewFlagSet("find", flag.ExitOnError)
+	hash := flagSet.String("hash", "", "探すファイルのハッシュ。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, (*hash == "") == (flagSet.NArg() != 1), "パスのパターンか-hashのどちらか1つを指定してください。\n")
+
+	var match func(record HashRecord) bool
+	if *hash != "" {
+		digest := strings.ToLower(*hash)
+		match = func(record HashRecord) bool {
+			return strings.TrimSuffix(record.hash, incompleteHashSuffix) == digest
+		}
+	} else {
+		pattern, err := regexp.Compile(flagSet.Arg(0))
+		fatalMessageError(ErrConfig, err, "パターンが不正です。: %s\n", flagSet.Arg(0))
+		match = func(record HashRecord) bool {
+			return pattern.MatchString(record.path)
+		}
+	}
+
+	index := loadIndexIfEnabled()
+	found := 0
+
+	for _, algorithm := range config.algorithms {
+		store := hashStoreFor(algorithm)
+
+		// ディスクのハッシュファイルで見つかった記録は統合ハッシュファイルでは表示しない
+		foundInDisks := make(map[string]bool)
+
+		groups := groupDiskIds(store)
+		for group, diskIds := range groups {
+			for _, diskId := range diskIds {
+				for _, record := range findRecords(index, store, algorithm, diskId, match) {
+					logf.Printf("%s (%s): %s%s\n", diskId, algorithm, record, formatRecordSize(record))
+					foundInDisks[group+":"+record.String()] = true
+					found++
+				}
+			}
+		}
+
+		// ハッシュファイルが削除されて統合ハッシュファイルにだけ残っている記録
+		for group := range groups {
+			for _, record := range findRecords(index, store, algorithm, group, match) {
+				if !foundInDisks[group+":"+record.String()] {
+					logf.Printf("統合ハッシュファイル%s (%s): %s%s\n", group, algorithm, record, formatRecordSize(record))
+					found++
+				}
+			}
+		}
+	}
+
+	logf.Printf("%d件見つかりました。\n", found)
+}
+
+// ハッシュファイルから条件に一致する記録を探す。
+// 索引に登録されていれば索引の記録から探す。
+func findRecords(index *hashIndex, store HashStore, algorithm string, name string, match func(record HashRecord) bool) []HashRecord {
+	records := index.load(store, algorithm, name)
+
+	var matched []HashRecord
+	for _, record := range records {
+		if match(record) {
+			matched = append(matched, record)
+		}
+	}
+
+	return matched
+}
+
+// 記録したサイズを表示用の文字列にする。サイズを記録していなければ空文字列を返す。
+func formatRecordSize(record HashRecord) string {
+	if record.size < 0 {
+		return ""
+	}
+	return " (" + formatSize(float64(record.size)) + ")"
+}
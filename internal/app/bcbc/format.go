@@ -0,0 +1,75 @@
+package bcbc
+
+import (
+	"fmt"
+	"strconv"
+)
+
+// UnitsBinary サイズの単位: 1024の累乗(KiB, MiB, GiB, TiB)
+const UnitsBinary = "binary"
+
+// UnitsDecimal サイズの単位: 1000の累乗(kB, MB, GB, TB)
+const UnitsDecimal = "decimal"
+
+// 単位系ごとの基数と単位
+var sizeUnits = map[string]struct {
+	base  float64
+	names []string
+}{
+	UnitsBinary:  {1024, []string{"B", "KiB", "MiB", "GiB", "TiB", "PiB"}},
+	UnitsDecimal: {1000, []string{"B", "kB", "MB", "GB", "TB", "PB"}},
+}
+
+// 数値を3桁ごとにカンマで区切った文字列にする。
+func formatCount(n uint64) string {
+	digits := strconv.FormatUint(n, 10)
+
+	result := make([]byte, 0, len(digits)+len(digits)/3)
+	for i := range digits {
+		if i > 0 && (len(digits)-i)%3 == 0 {
+			result = append(result, ',')
+		}
+		result = append(result, digits[i])
+	}
+
+	return string(result)
+}
+
+// バイト数を設定された単位系で読みやすい文字列にする。
+func formatSize(bytes float64) string {
+	units, found := sizeUnits[config.units]
+	if !found {
+		units = sizeUnits[UnitsBinary]
+	}
+
+	unit := 0
+	for bytes >= units.base && unit < len(units.names)-1 {
+		bytes /= units.base
+		unit++
+	}
+
+	if unit == 0 {
+		return fmt.Sprintf("%.0f%s", bytes, units.names[unit])
+	}
+	return fmt.Sprintf("%.1f%s", bytes, units.names[unit])
+}
+
+// 1秒あたりのバイト数を読みやすい文字列にする。
+func formatThroughput(bytesPerSecond float64) string {
+	return formatSize(bytesPerSecond) + "/s"
+}
+
+// 数に応じて単数形か複数形を選ぶ。
+// 日本語のように数で形が変わらない言語では両方に同じ語を渡す。
+func plural(n uint64, one string, other string) string {
+	if n == 1 {
+		return one
+	}
+	return other
+}
+
+// 数と数える語を合わせた文字列にする。
+func formatQuantity(n uint64, one string, other string) string {
+	one, other = trUnits(one, other)
+	return formatCount(n) + plural(n, one, other)
+}
@@ -0,0 +1,86 @@
+package bcbc
+
+import (
+	"flag"
+	"golang.org/x/text/unicode/norm"
+	"sort"
+	"strings"
+)
+
+// fsck-hashfileサブコマンド。
+// ディスクのハッシュファイルの形式を検証し、形式が不正な行と重複した記録を削除して、
+// パスの表記を正規化して並べ替えたハッシュファイルに書き直す。
+func executeFsckHashFile(args []string) {
+	flagSet := flag.NewFlagSet("fsck-hashfile", flag.ExitOnError)
+	dryRun := flagSet.Bool("dry-run", false, "修正する内容を表示するだけで書き直さない。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, flagSet.NArg() != 1, "ディスクIDを1つ指定してください。\n")
+	diskId := flagSet.Arg(0)
+	fatalMessageIf(ErrConfig, !diskIdPattern.MatchString(diskId), "ディスクIDの形式が不正です。: %s\n", diskId)
+
+	// ハッシュ計算中のディスクのハッシュファイルは書き直さない
+	diskInfo := &DiskInfo{id: diskId}
+	if !*dryRun {
+		err := lockDisk(diskInfo)
+		fatalMessageError(ErrLocked, err, "ハッシュファイルをロックできませんでした。: %s\n", diskId)
+		defer unlockDisk(diskInfo)
+	}
+
+	for _, algorithm := range config.algorithms {
+		fsckHashFile(hashStoreFor(algorithm), diskId, algorithm, *dryRun)
+	}
+}
+
+// 1つのハッシュファイルを検証して書き直す。
+func fsckHashFile(store HashStore, diskId string, algorithm string, dryRun bool) {
+	records, malformed, err := store.loadLenient(diskId)
+	fatalMessageError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s (%s)\n", diskId, algorithm)
+
+	for _, line := range malformed {
+		logf.Printf("形式が不正な行を削除します。: %s (%s) %d行目\n", diskId, algorithm, line)
+	}
+
+	// パスの表記を正規化する
+	normalized := 0
+	for i := range records {
+		normPath := norm.NFC.String(strings.ReplaceAll(records[i].path, "\\", "/"))
+		if normPath != records[i].path {
+			logf.Printf("パスを正規化します。: %s -> %s\n", records[i].path, normPath)
+			records[i].path = normPath
+			normalized++
+		}
+	}
+
+	// 後から追記された記録ほど新しいので、重複していたら最後の記録を残す
+	sorted := sort.SliceIsSorted(records, func(i, j int) bool {
+		return records[i].path < records[j].path
+	})
+	sort.SliceStable(records, func(i, j int) bool {
+		return records[i].path < records[j].path
+	})
+
+	compacted := make([]HashRecord, 0, len(records))
+	for i, record := range records {
+		if i+1 < len(records) && records[i+1].path == record.path {
+			continue
+		}
+		compacted = append(compacted, record)
+	}
+	duplicates := len(records) - len(compacted)
+
+	logf.Printf("%s (%s): 記録%d件 不正な行%d件 重複%d件 正規化%d件\n",
+		diskId, algorithm, len(compacted), len(malformed), duplicates, normalized)
+
+	if len(malformed) == 0 && duplicates == 0 && normalized == 0 && sorted {
+		logf.Printf("修正する必要はありません。: %s (%s)\n", diskId, algorithm)
+		return
+	}
+	if dryRun {
+		return
+	}
+
+	err = store.rewrite(diskId, compacted)
+	fatalMessageError(ErrIO, err, "ハッシュファイルの書き直しに失敗しました。: %s (%s)\n", diskId, algorithm)
+	logf.Printf("ハッシュファイルを書き直しました。: %s (%s)\n", diskId, algorithm)
+}
@@ -0,0 +1,103 @@
+package bcbc
+
+import (
+	"bufio"
+	"os"
+	"path/filepath"
+	"regexp"
+	"strings"
+)
+
+// 既定のグループ分けのパターン。ディスクIDの先頭の英字をグループ名にする。
+const defaultGroupPattern = `^([A-Z]+)`
+
+// グループ名のパターン
+var groupNamePattern = regexp.MustCompile(`^[A-Za-z][A-Za-z_-]*$`)
+
+// 統合ハッシュファイルのグループ分けの設定
+type groupingConfig struct {
+	// ディスクIDから最初のキャプチャをグループ名として取り出すパターン
+	pattern *regexp.Regexp
+	// 明示的にグループを指定したディスクIDとグループ名。パターンより優先する。
+	explicit map[string]string
+}
+
+// グループ分けの設定ファイルを読み込む。
+// 設定ファイルがなければディスクIDの先頭の英字でグループ分けする。
+//
+// 設定ファイルには次の形式の行を書く。
+//   pattern <正規表現>          ディスクIDから最初のキャプチャをグループ名として取り出す
+//   group <グループ名> <ディスクID>...  ディスクを明示的にグループに入れる
+//
+// diskファイルでグループを指定したディスクはディスク登録簿に記録したグループを優先する。
+func initGroups() {
+	config.grouping = groupingConfig{regexp.MustCompile(defaultGroupPattern), make(map[string]string)}
+	defer applyRegisteredGroups()
+
+	groupConfigFile := filepath.Join(config.configDir(), "group.conf")
+	groupFileIn, err := os.Open(groupConfigFile)
+	if os.IsNotExist(err) {
+		return
+	}
+	fatalMessageError(ErrConfig, err, "グループ設定ファイルが読み込めませんでした。: %s\n", groupConfigFile)
+	defer groupFileIn.Close()
+
+	scanner := bufio.NewScanner(groupFileIn)
+	for i := 1; scanner.Scan(); i++ {
+		line := strings.TrimSpace(scanner.Text())
+		if line == "" || line[0] == '#' {
+			continue
+		}
+
+		fields := strings.Fields(line)
+		switch {
+		case fields[0] == "pattern" && len(fields) == 2:
+			pattern, err := regexp.Compile(fields[1])
+			fatalMessageError(ErrConfig, err, "グループ設定ファイルのパターンが不正です。: %d行目: %s\n", i, line)
+			fatalMessageIf(ErrConfig, pattern.NumSubexp() < 1, "グループ設定ファイルのパターンにキャプチャがありません。: %d行目: %s\n", i, line)
+			config.grouping.pattern = pattern
+
+		case fields[0] == "group" && len(fields) >= 3:
+			group := fields[1]
+			checkGroupName(group)
+			for _, diskId := range fields[2:] {
+				fatalMessageIf(ErrConfig, !diskIdPattern.MatchString(diskId), "グループ設定ファイルのディスクIDが不正です。: %d行目: %s\n", i, diskId)
+				_, duplicated := config.grouping.explicit[diskId]
+				fatalMessageIf(ErrConfig, duplicated, "ディスクIDが複数のグループに指定されています。: %d行目: %s\n", i, diskId)
+				config.grouping.explicit[diskId] = group
+			}
+
+		default:
+			fatalMessageIf(ErrConfig, true, "グループ設定ファイルの形式が不正です。: %d行目: %s\n", i, line)
+		}
+	}
+	fatalMessageError(ErrConfig, scanner.Err(), "グループ設定ファイルが読み込めませんでした。: %s\n", groupConfigFile)
+}
+
+// ディスク登録簿に記録したdiskファイルのグループをグループ分けの設定に加える。
+func applyRegisteredGroups() {
+	for diskId, record := range loadRegistry() {
+		if record.Group != "" {
+			config.grouping.explicit[diskId] = record.Group
+		}
+	}
+}
+
+// グループ名を検証する。
+// 統合ハッシュファイルとディスクのハッシュファイルが同じディレクトリにあるので、ディスクIDと区別できる名前にする。
+func checkGroupName(group string) {
+	fatalMessageIf(ErrConfig, !groupNamePattern.MatchString(group), "グループ名が不正です。英字で始まり英字、_、-だけにしてください。: %s\n", group)
+}
+
+// ディスクIDが属するグループ名を返す。
+func diskGroupOf(diskId string) string {
+	if group, found := config.grouping.explicit[diskId]; found {
+		return group
+	}
+
+	match := config.grouping.pattern.FindStringSubmatch(diskId)
+	fatalMessageIf(ErrConfig, match == nil || match[1] == "", "ディスクIDからグループ名を取り出せませんでした。: %s\n", diskId)
+	checkGroupName(match[1])
+
+	return match[1]
+}
@@ -2,97 +2,440 @@ package bcbc
 
 import (
 	"crypto/md5"
+	"crypto/sha256"
+	"errors"
 	"fmt"
+	"hash"
+	"hash/crc32"
 	"io"
 	"os"
+	"strings"
+	"sync"
+	"syscall"
 	"time"
 )
 
 // CompletionMessage 完了メッセージ
 type CompletionMessage struct {
-	diskId string
-	err    error
+	diskId     string
+	err        error
+	fatal      bool
+	fileErrors int
 }
 
 // ハッシュルーチン。
 func hashRoutine(diskInfo *DiskInfo, progressChannel chan ProgressInfo, completionChannel chan CompletionMessage) {
 
-	err := os.MkdirAll(config.outDir(), 0755)
-	fatalMessageError(err, "出力ディレクトリを作成できませんでした。: %s\n", config.outDir())
+	// 致命的なエラーは完了メッセージでメインルーチンに伝える
+	defer func() {
+		if r := recover(); r != nil {
+			e, ok := r.(*Error)
+			if !ok {
+				panic(r)
+			}
+			completionChannel <- CompletionMessage{diskId: diskInfo.id, err: e, fatal: true}
+		}
+	}()
 
-	hashFileOut, err := os.OpenFile(diskInfo.hashFile(), os.O_CREATE|os.O_APPEND|os.O_WRONLY, 0644)
-	fatalMessageError(err, "ハッシュファイルの書き込みに失敗しました。: %s\n", diskInfo.hashFile())
-	defer hashFileOut.Close()
+	if err := lockDisk(diskInfo); err != nil {
+		completionChannel <- CompletionMessage{diskId: diskInfo.id, err: err}
+		return
+	}
+	defer unlockDisk(diskInfo)
 
-	fileInfoList, totalSize := listFileInfo(diskInfo)
+	recordWriter(diskInfo)
+	recordEnvironment(diskInfo)
+	if len(diskInfo.subpaths) > 0 {
+		logf.Printf("ディスク(%s)は指定されたディレクトリだけを走査します。: %s\n", diskInfo.id, strings.Join(diskInfo.subpaths, ", "))
+	}
+
+	// 前回エラーになったファイルだけを再試行するなら、エラーレポートを消す前に読み込む
+	var retryPaths []string
+	if config.retryFailed {
+		retryPaths = loadErrorReport(diskInfo)
+		logf.Printf("前回エラーになった%sを再試行します。: %s\n", formatQuantity(uint64(len(retryPaths)), "件のファイル", "件のファイル"), diskInfo.id)
+	}
+	resetErrorReport(diskInfo)
+
+	algorithms := diskInfo.hashAlgorithms()
+	appenders := make([]HashAppender, 0, len(algorithms))
+	for _, algorithm := range algorithms {
+		appender, err := hashStoreFor(algorithm).openAppender(diskInfo.id)
+		fatalMessageError(ErrIO, err, "ハッシュファイルの書き込みに失敗しました。: %s\n", diskInfo.id)
+		defer appender.close()
+
+		appenders = append(appenders, appender)
+	}
+
+	discovery := newTargetDiscovery(diskInfo)
+	if config.retryFailed {
+		go discovery.runPaths(retryPaths)
+	} else {
+		go discovery.run()
+	}
+	defer close(discovery.stop)
 
 	progressInfo := ProgressInfo{
 		diskInfo:  diskInfo,
-		fileCount: ProgressCount{uint64(len(fileInfoList)), 0},
-		sizeCount: ProgressCount{totalSize, 0},
 		startTime: time.Now(),
 	}
+
+	// 中断しても再開したときに全体の進捗を表示できるように、最後まで終わらなければ進捗を記録しておく
+	completed := false
+	if !config.retryFailed {
+		resumeProgress(diskInfo, &progressInfo)
+		defer func() {
+			if !completed {
+				saveProgressState(diskInfo, progressInfo)
+			}
+		}()
+	}
+	lastSavedTime := time.Now()
+
+	discovery.updateTotals(&progressInfo)
 	progressChannel <- progressInfo
 
-	for _, fi := range fileInfoList {
+	// 今回計算したハッシュの記録
+	newRecords := make([][]HashRecord, len(algorithms))
+	disconnection := newDisconnectionDetector(diskInfo)
 
-		hash, err := calcHash(fi.realPath, progressInfo, progressChannel)
+	fileErrors := 0
+
+	for fi := range discovery.targets {
+
+		discovery.updateTotals(&progressInfo)
+		progressInfo.fileErrors = fileErrors
+
+		// 読み込みエラーが多すぎるディスクは故障しかけているので、全てのファイルを試さずに打ち切る
+		if config.maxErrors > 0 && fileErrors >= config.maxErrors {
+			logf.with(logFields{DiskID: diskInfo.id}).Printf("[警告] ディスク(%s)でエラーが%d件に達しました。ディスクが故障しかけている可能性があります。\n", diskInfo.id, fileErrors)
+			err := newError(ErrIO, nil, "エラーが多すぎるのでハッシュ計算を打ち切りました。: %s", diskInfo.id)
+			completionChannel <- CompletionMessage{diskId: diskInfo.id, err: err, fileErrors: fileErrors}
+			return
+		}
 
-		progressInfo.fileCount.Increment(uint64(1))
 		size, _ := fi.size()
+		startTime := time.Now()
+		hashes, damaged, err := calcHash(fi.realPath, size, algorithms, progressInfo, progressChannel)
+
+		if IsKind(err, ErrInterrupted) {
+			completionChannel <- CompletionMessage{diskId: diskInfo.id, err: err, fileErrors: fileErrors}
+			return
+		}
+
+		// 計算中にサイズが変わったファイルは合計を実際のサイズに合わせて、記録するサイズも計算後のものにする
+		if after, statErr := os.Stat(fi.realPath); err == nil && statErr == nil && uint64(after.Size()) != size {
+			stats.recordSizeChange(diskInfo.id, fi.normPath, size, uint64(after.Size()))
+			discovery.adjustTotalSize(size, uint64(after.Size()))
+			fi._stat = after
+			size = uint64(after.Size())
+			discovery.updateTotals(&progressInfo)
+		}
+
+		progressInfo.fileCount.Increment(uint64(1))
 		progressInfo.sizeCount.Increment(size)
 
+		if !config.retryFailed && time.Since(lastSavedTime) >= progressStateInterval {
+			saveProgressState(diskInfo, progressInfo)
+			lastSavedTime = time.Now()
+		}
+
 		if err != nil {
-			logf.Printf("ハッシュ計算中にエラーが発生しました。: %s\n", fi.realPath)
-			logf.Println(err)
+			// ディスクが取り外されたらファイルごとのエラーを出し続けずにこのディスクだけ打ち切る
+			if err := disconnection.check(err); err != nil {
+				completionChannel <- CompletionMessage{diskId: diskInfo.id, err: err, fileErrors: fileErrors}
+				return
+			}
+			fileLog := logf.with(logFields{DiskID: diskInfo.id, Path: fi.normPath, Bytes: logCount(size)})
+			fileLog.Printf("ハッシュ計算中にエラーが発生しました。: %s\n", fi.realPath)
+			fileLog.Println(err)
+			recordFileError(diskInfo, fi.normPath, err)
+			fileErrors++
 			continue
 		}
+		disconnection.reset()
 
-		_, err = fmt.Fprintf(hashFileOut, "%s:%x\n", fi.normPath, hash)
-		if err != nil {
-			completionChannel <- CompletionMessage{diskInfo.id, err}
-			return
+		stats.recordFile(diskInfo.id, fi.normPath, size, time.Since(startTime))
+
+		// 読み込めない範囲があったら次回も計算し直すように不完全な印を付ける
+		hashSuffix := ""
+		if len(damaged) > 0 {
+			logf.with(logFields{DiskID: diskInfo.id, Path: fi.normPath, Bytes: logCount(size)}).Printf("読み込めない範囲があったので不完全なハッシュを記録します。: %s\n", fi.realPath)
+			recordDamage(diskInfo, fi.normPath, damaged)
+			hashSuffix = incompleteHashSuffix
+			fileErrors++
 		}
+
+		for i, appender := range appenders {
+			record := fi.newRecord(algorithms[i], fmt.Sprintf("%x", hashes[i])+hashSuffix)
+			err = appender.append(record)
+			if err != nil {
+				err = newError(ErrIO, err, "ハッシュファイルの書き込みに失敗しました。: %s", diskInfo.id)
+				completionChannel <- CompletionMessage{diskId: diskInfo.id, err: err, fileErrors: fileErrors}
+				return
+			}
+			newRecords[i] = append(newRecords[i], record)
+		}
+	}
+
+	if discovery.err != nil {
+		panic(discovery.err)
 	}
 
+	discovery.updateTotals(&progressInfo)
+	progressInfo.fileErrors = fileErrors
 	progressChannel <- progressInfo
+	summaryFields := logFields{DiskID: diskInfo.id, Files: logCount(progressInfo.fileCount.processed), Bytes: logCount(progressInfo.sizeCount.processed)}
+	logf.with(summaryFields).Printf("%sの新しいファイルを計算しました。(全%s中%.1f%%は計算済み%sでした。): %s\n",
+		formatQuantity(progressInfo.fileCount.processed, "件", "件"), formatQuantity(progressInfo.skippedFiles+progressInfo.fileCount.total, "件", "件"),
+		progressInfo.skippedRate()*100, formatSize(float64(progressInfo.skippedBytes)), diskInfo.id)
+
+	// ハッシュファイルに取り込んだジャーナルを削除できるように、書き直す前に閉じる
+	for _, appender := range appenders {
+		err := appender.close()
+		fatalMessageError(ErrIO, err, "ジャーナルの書き込みに失敗しました。: %s\n", diskInfo.id)
+	}
 
-	completionChannel <- CompletionMessage{diskInfo.id, nil}
+	if config.retryFailed {
+		err := foldJournals(diskInfo)
+		fatalMessageError(ErrIO, err, "ジャーナルをハッシュファイルに取り込めませんでした。: %s\n", diskInfo.id)
+	} else {
+		discovery.rewriteHashFiles(newRecords)
+		completed = true
+		removeProgressState(diskInfo)
+	}
+	if *snapshotOption {
+		saveSnapshots(diskInfo)
+	}
+
+	completionChannel <- CompletionMessage{diskId: diskInfo.id, fileErrors: fileErrors}
 }
 
-// BufferSize ファイル読み込み時のバッファサイズ。
+// 空き容量不足を警告する間隔
+const noSpaceAlertInterval = time.Minute
+
+// 空き容量不足のときに書き込みを再試行する間隔
+const noSpaceRetryInterval = 5 * time.Second
+
+// ハッシュファイルに1行書き込む。
+// 出力先の空き容量が不足していたら、定期的に警告しながら空きができるまで待って書き込みを再開する。
+func writeHashLine(hashFileOut *os.File, line string) error {
+	data := []byte(line)
+
+	var lastAlertTime time.Time
+
+	for len(data) > 0 {
+		n, err := hashFileOut.Write(data)
+		data = data[n:]
+
+		if err == nil {
+			continue
+		}
+		if !errors.Is(err, syscall.ENOSPC) {
+			return err
+		}
+
+		if time.Since(lastAlertTime) >= noSpaceAlertInterval {
+			logf.Printf("出力先の空き容量が不足しています。空きができるまで待機します。: %s\n", hashFileOut.Name())
+			lastAlertTime = time.Now()
+		}
+		time.Sleep(noSpaceRetryInterval)
+	}
+
+	if !lastAlertTime.IsZero() {
+		logf.Printf("出力先に空きができたので書き込みを再開しました。: %s\n", hashFileOut.Name())
+	}
+
+	return nil
+}
+
+// AlgorithmMD5 ハッシュアルゴリズム: MD5(既定)
+const AlgorithmMD5 = "md5"
+
+// AlgorithmSHA256 ハッシュアルゴリズム: SHA-256
+const AlgorithmSHA256 = "sha256"
+
+// AlgorithmCRC32 ハッシュアルゴリズム: CRC32(定期的な変更検知用の高速モード)
+const AlgorithmCRC32 = "crc32"
+
+// AlgorithmMD5Tree ハッシュアルゴリズム: MD5のツリーハッシュ(巨大なファイルを複数のコアで計算する)
+const AlgorithmMD5Tree = "md5-tree"
+
+// AlgorithmSHA256Tree ハッシュアルゴリズム: SHA-256のツリーハッシュ
+const AlgorithmSHA256Tree = "sha256-tree"
+
+// アルゴリズム名ごとのハッシュ関数の生成関数
+var hasherFactories = map[string]func() hash.Hash{
+	AlgorithmMD5:        md5.New,
+	AlgorithmSHA256:     sha256.New,
+	AlgorithmCRC32:      func() hash.Hash { return crc32.NewIEEE() },
+	AlgorithmMD5Tree:    func() hash.Hash { return newTreeHash(md5.New) },
+	AlgorithmSHA256Tree: func() hash.Hash { return newTreeHash(sha256.New) },
+}
+
+// BufferSize ファイル読み込み時のバッファサイズの上限。
 const BufferSize = 10 << 20
 
+// MinBufferSize ファイル読み込み時のバッファサイズの下限。
+const MinBufferSize = 64 << 10
+
+// バッファサイズごとの読み込みバッファのプール
+var bufferPools sync.Map
+
+// ファイルサイズに合わせてバッファサイズを決める。
+// 小さいファイルはファイルサイズ以上の最小の2の累乗にして、大きいファイルは上限のサイズにする。
+func bufferSizeFor(fileSize uint64) int {
+	if fileSize >= BufferSize {
+		return BufferSize
+	}
+
+	bufferSize := MinBufferSize
+	for uint64(bufferSize) < fileSize {
+		bufferSize <<= 1
+	}
+	if bufferSize > BufferSize {
+		bufferSize = BufferSize
+	}
+
+	return bufferSize
+}
+
+// 指定されたサイズの読み込みバッファをプールから取得する。
+// 直接I/Oでも読み込めるようにアライメントを揃えておく。
+func getBuffer(bufferSize int) []byte {
+	pool, _ := bufferPools.LoadOrStore(bufferSize, &sync.Pool{
+		New: func() interface{} { return alignedBuffer(bufferSize) },
+	})
+	return pool.(*sync.Pool).Get().([]byte)
+}
+
+// 読み込みバッファをプールに戻す。
+func putBuffer(buffer []byte) {
+	if pool, found := bufferPools.Load(len(buffer)); found {
+		pool.(*sync.Pool).Put(buffer)
+	}
+}
+
 // ファイルのハッシュを計算する。
-func calcHash(file string, progressInfo ProgressInfo, progressInfoChannel chan ProgressInfo) ([]byte, error) {
-	fileIn, err := os.Open(file)
+// 1回の読み込みで指定された全アルゴリズムのハッシュを計算し、指定された順番で返す。
+// サルベージモードでは読み込めなかった範囲を読み飛ばして計算を続け、読み飛ばした範囲も返す。
+func calcHash(file string, size uint64, algorithms []string, progressInfo ProgressInfo, progressInfoChannel chan ProgressInfo) ([][]byte, []byteRange, error) {
+	var fileIn targetFile
+	err := retryRead(file, func() error {
+		var openErr error
+		fileIn, openErr = openTarget(file, size)
+		return openErr
+	})
 	if err != nil {
 		logf.Println("ハッシュ対象ファイルの読み込みに失敗しました。:", file)
-		return nil, err
+		return nil, nil, err
 	}
 	defer fileIn.Close()
+	fileIn = detectSparse(fileIn, size)
 
 	progressInfo.processingFile = file
+	progressInfo.fileBytes = ProgressCount{size, 0}
+
+	bufferSize := bufferSizeFor(size)
+
+	var limiter *rateLimiter
+	if progressInfo.diskInfo != nil {
+		limiter = progressInfo.diskInfo.limiter
+	}
+
+	// 大きいファイルはメモリマップして読み込みバッファへのコピーを省く。
+	// それ以外は先読みして読み込みとハッシュ計算を重ねる。
+	var mapped []byte
+	if useMmap(size) {
+		mapped = mapTarget(fileIn, file, size)
+	}
+	var reader *readAhead
+	if mapped != nil {
+		defer munmapFile(mapped)
+	} else {
+		reader = startReadAhead(fileIn, file, size, bufferSize, limiter)
+		defer reader.stop()
+	}
 
-	buffer := make([]byte, BufferSize)
+	hashers := make([]hash.Hash, 0, len(algorithms))
+	for _, algorithm := range algorithms {
+		hashers = append(hashers, hasherFactories[algorithm]())
+	}
 
-	hasher := md5.New()
+	var offset int64
+	var damaged []byteRange
 
 	for {
-		ret, err := fileIn.Read(buffer)
-		if ret == 0 {
-			break
+		pauseState.wait()
+		if interrupted() {
+			return nil, nil, newError(ErrInterrupted, nil, "ハッシュ計算を中断しました。: %s", file)
 		}
-		if err != nil && err != io.EOF {
-			return nil, err
+
+		var chunk []byte
+		if mapped != nil {
+			chunk = mappedChunk(mapped, offset, bufferSize)
+			limiter.wait(len(chunk))
+		} else {
+			result := reader.next()
+			if result.err != nil {
+				return nil, nil, result.err
+			}
+			damaged = append(damaged, result.damaged...)
+			chunk = result.chunk
+		}
+		if len(chunk) == 0 {
+			break
 		}
 
-		hasher.Write(buffer[:ret])
+		if mapped != nil {
+			if err := hashMappedChunk(hashers, chunk, file, offset); err != nil {
+				return nil, nil, err
+			}
+		} else {
+			for _, hasher := range hashers {
+				hasher.Write(chunk)
+			}
+			reader.release(chunk)
+		}
+		offset += int64(len(chunk))
 
-		progressInfo.sizeCount.Increment(uint64(ret))
+		progressInfo.sizeCount.Increment(uint64(len(chunk)))
+		progressInfo.fileBytes.Increment(uint64(len(chunk)))
+		// 計算中にファイルが大きくなったら、進捗が100%を超えないように合計も増やす
+		if grown := progressInfo.fileBytes.processed; grown > progressInfo.fileBytes.total {
+			progressInfo.sizeCount.total += grown - progressInfo.fileBytes.total
+			progressInfo.fileBytes.total = grown
+		}
 
 		progressInfoChannel <- progressInfo
 	}
 
-	return hasher.Sum(nil), nil
+	hashes := make([][]byte, 0, len(hashers))
+	for _, hasher := range hashers {
+		hashes = append(hashes, hasher.Sum(nil))
+	}
+
+	return hashes, damaged, nil
+}
+
+// 指定された位置からバッファ1つ分を読み込む。
+// サルベージモードでは読み込めなかった範囲をゼロで埋めて読み飛ばし、読み飛ばした範囲も返す。
+func readChunk(fileIn targetFile, file string, buffer []byte, offset int64, size uint64) (int, []byteRange, error) {
+	// 読み込みを再試行できるように読み込む位置を指定する
+	var ret int
+	err := retryRead(file, func() error {
+		var readErr error
+		ret, readErr = fileIn.ReadAt(buffer, offset)
+		if readErr == io.EOF {
+			return nil
+		}
+		return readErr
+	})
+	if err != nil && config.salvage && isTransientError(err) {
+		logf.Printf("読み込めない範囲を読み飛ばします。: %s : %dバイト目から\n", file, offset)
+		ret, damaged := salvageRead(fileIn, buffer, offset, size)
+		return ret, damaged, nil
+	}
+
+	return ret, nil, err
 }
@@ -0,0 +1,304 @@
+package bcbc
+
+import (
+	"fmt"
+	"io"
+	"os"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// 書き込むハッシュファイルの形式のバージョン。
+// v1は「パス:ハッシュ」の行だけで、パスに「:」や改行を含むファイルを記録できなかった。
+// v2は先頭にヘッダー行があり、エスケープしたパスとハッシュをタブで区切る。
+// v3はハッシュの後ろにタブ区切りで「項目=値」の拡張項目を続けられる。
+const hashFileVersion = 3
+
+// v2以降のハッシュファイルの先頭行の接頭辞。後ろにバージョンが続く。
+const hashFileHeaderPrefix = "# bcbc-hashfile "
+
+// Version bcbcのバージョン。リリースするときに-ldflagsで設定する。
+var Version = "dev"
+
+// HashFileInfo ハッシュファイルのヘッダーの情報。
+// ヘッダーがないv1のハッシュファイルはVersionだけが設定される。
+type HashFileInfo struct {
+	Version   int
+	Algorithm string
+	// ディスクのハッシュファイルならディスクID
+	DiskId string
+	// 統合ハッシュファイルならグループ名
+	Group   string
+	Created time.Time
+	Updated time.Time
+	// 書き込んだbcbcのバージョン
+	Tool string
+	// 記録の行の暗号方式。暗号化していなければ空。
+	Encryption string
+	// ツリーハッシュのチャンクのサイズ。ツリーハッシュでなければ0。
+	TreeChunkSize int
+	// 最後に書き込んだ書き込み元。「マシン名 pid=プロセスID」
+	Writer string
+}
+
+// ヘッダーの項目
+const (
+	headerAlgorithm  = "algorithm"
+	headerDisk       = "disk"
+	headerGroup      = "group"
+	headerCreated    = "created"
+	headerUpdated    = "updated"
+	headerTool       = "tool"
+	headerEncryption = "encryption"
+	headerTreeChunk  = "tree-chunk-size"
+	headerWriter     = "writer"
+)
+
+// ハッシュファイルのヘッダーを返す。
+// 先頭行にバージョンを書き、続けて「# 項目: 値」の行で情報を書く。空の項目は書かない。
+func hashFileHeader(info HashFileInfo) string {
+	var builder strings.Builder
+	fmt.Fprintf(&builder, "%s%d\n", hashFileHeaderPrefix, hashFileVersion)
+
+	items := []struct {
+		key   string
+		value string
+	}{
+		{headerAlgorithm, info.Algorithm},
+		{headerDisk, info.DiskId},
+		{headerGroup, info.Group},
+		{headerCreated, formatHeaderTime(info.Created)},
+		{headerUpdated, formatHeaderTime(info.Updated)},
+		{headerTool, info.Tool},
+		{headerEncryption, info.Encryption},
+		{headerTreeChunk, formatHeaderInt(info.TreeChunkSize)},
+		{headerWriter, info.Writer},
+	}
+	for _, item := range items {
+		if item.value != "" {
+			fmt.Fprintf(&builder, "# %s: %s\n", item.key, item.value)
+		}
+	}
+
+	return builder.String()
+}
+
+func formatHeaderTime(t time.Time) string {
+	if t.IsZero() {
+		return ""
+	}
+	return t.Format(time.RFC3339)
+}
+
+func formatHeaderInt(n int) string {
+	if n == 0 {
+		return ""
+	}
+	return strconv.Itoa(n)
+}
+
+// ヘッダーの「# 項目: 値」の行を解析して情報に反映する。
+// 知らない項目は将来のバージョンで追加されたものとして無視する。
+func parseHeaderLine(line string, info *HashFileInfo) {
+	item := strings.TrimSpace(strings.TrimPrefix(line, "#"))
+	separator := strings.Index(item, ":")
+	if separator < 0 {
+		return
+	}
+	key := strings.TrimSpace(item[:separator])
+	value := strings.TrimSpace(item[separator+1:])
+
+	switch key {
+	case headerAlgorithm:
+		info.Algorithm = value
+	case headerDisk:
+		info.DiskId = value
+	case headerGroup:
+		info.Group = value
+	case headerCreated:
+		info.Created, _ = time.Parse(time.RFC3339, value)
+	case headerUpdated:
+		info.Updated, _ = time.Parse(time.RFC3339, value)
+	case headerTool:
+		info.Tool = value
+	case headerEncryption:
+		info.Encryption = value
+	case headerTreeChunk:
+		info.TreeChunkSize, _ = strconv.Atoi(value)
+	case headerWriter:
+		info.Writer = value
+	}
+}
+
+// パスのエスケープ
+var pathEscaper = strings.NewReplacer("\\", "\\\\", "\t", "\\t", "\n", "\\n", "\r", "\\r")
+
+// 記録の拡張項目
+const (
+	attrSize      = "size"
+	attrAllocated = "allocated"
+	attrMtime     = "mtime"
+	attrMode      = "mode"
+	attrOwner     = "owner"
+	attrXattrs    = "xattrs"
+)
+
+// 記録をv3の1行の形式にする。
+// パスの「\」、タブ、改行をエスケープし、ヘッダー行と区別できるように先頭の「#」もエスケープする。
+// 記録している拡張項目だけをハッシュの後ろに書く。
+func formatHashLine(record HashRecord) string {
+	path := pathEscaper.Replace(record.path)
+	if strings.HasPrefix(path, "#") {
+		path = "\\" + path
+	}
+
+	line := path + "\t" + record.hash
+	if record.size >= 0 {
+		line += "\t" + attrSize + "=" + strconv.FormatInt(record.size, 10)
+	}
+	if record.allocated >= 0 {
+		line += "\t" + attrAllocated + "=" + strconv.FormatInt(record.allocated, 10)
+	}
+	if m := record.metadata; m != nil {
+		line += "\t" + attrMtime + "=" + m.modTime.UTC().Format(time.RFC3339Nano)
+		line += "\t" + attrMode + "=" + m.mode
+		if m.owner != "" {
+			line += "\t" + attrOwner + "=" + m.owner
+		}
+	}
+	if record.xattrs != "" {
+		line += "\t" + attrXattrs + "=" + record.xattrs
+	}
+
+	return line + "\n"
+}
+
+// v2のエスケープされたパスを元に戻す。
+func unescapePath(escaped string) (string, bool) {
+	if !strings.Contains(escaped, "\\") {
+		return escaped, true
+	}
+
+	var builder strings.Builder
+	for i := 0; i < len(escaped); i++ {
+		c := escaped[i]
+		if c != '\\' {
+			builder.WriteByte(c)
+			continue
+		}
+
+		i++
+		if i >= len(escaped) {
+			return "", false
+		}
+		switch escaped[i] {
+		case '\\':
+			builder.WriteByte('\\')
+		case 't':
+			builder.WriteByte('\t')
+		case 'n':
+			builder.WriteByte('\n')
+		case 'r':
+			builder.WriteByte('\r')
+		case '#':
+			builder.WriteByte('#')
+		default:
+			return "", false
+		}
+	}
+
+	return builder.String(), true
+}
+
+// 1行を記録として解析する。
+func parseHashLine(line string, version int) (HashRecord, bool) {
+	if version == 1 {
+		// ハッシュに「:」は含まれないので最後の「:」で区切る
+		separator := strings.LastIndex(line, ":")
+		if separator < 0 {
+			return HashRecord{}, false
+		}
+		return newHashRecord(line[:separator], line[separator+1:]), true
+	}
+
+	tokens := strings.Split(line, "\t")
+	if len(tokens) < 2 || (version == 2 && len(tokens) != 2) {
+		return HashRecord{}, false
+	}
+	path, ok := unescapePath(tokens[0])
+	if !ok {
+		return HashRecord{}, false
+	}
+
+	record := newHashRecord(path, tokens[1])
+	for _, attr := range tokens[2:] {
+		if !parseRecordAttr(attr, &record) {
+			return HashRecord{}, false
+		}
+	}
+
+	return record, true
+}
+
+// 拡張項目の「項目=値」を解析して記録に反映する。
+// 知らない項目は将来のバージョンで追加されたものとして無視する。
+func parseRecordAttr(attr string, record *HashRecord) bool {
+	separator := strings.Index(attr, "=")
+	if separator < 0 {
+		return false
+	}
+	key := attr[:separator]
+	value := attr[separator+1:]
+
+	var err error
+	switch key {
+	case attrSize:
+		record.size, err = strconv.ParseInt(value, 10, 64)
+	case attrAllocated:
+		record.allocated, err = strconv.ParseInt(value, 10, 64)
+	case attrMtime:
+		record.ensureMetadata().modTime, err = time.Parse(time.RFC3339Nano, value)
+	case attrMode:
+		record.ensureMetadata().mode = value
+	case attrOwner:
+		record.ensureMetadata().owner = value
+	case attrXattrs:
+		record.xattrs = value
+	}
+
+	return err == nil
+}
+
+// ヘッダー行ならバージョンを返す。
+func parseHashFileHeader(line string) (int, bool) {
+	if !strings.HasPrefix(line, hashFileHeaderPrefix) {
+		return 0, false
+	}
+	version, err := strconv.Atoi(strings.TrimSpace(line[len(hashFileHeaderPrefix):]))
+	if err != nil {
+		return 0, false
+	}
+	return version, true
+}
+
+// ハッシュファイルの形式のバージョンを返す。空のファイルなら0を返す。
+func hashFileVersionOf(file *os.File) (int, error) {
+	buffer := make([]byte, 64)
+	n, err := file.ReadAt(buffer, 0)
+	if n == 0 {
+		if err != nil && err != io.EOF {
+			return 0, err
+		}
+		return 0, nil
+	}
+
+	firstLine := string(buffer[:n])
+	if i := strings.IndexByte(firstLine, '\n'); i >= 0 {
+		firstLine = firstLine[:i]
+	}
+	if version, found := parseHashFileHeader(firstLine); found {
+		return version, nil
+	}
+	return 1, nil
+}
@@ -0,0 +1,132 @@
+package bcbc
+
+import (
+	"flag"
+	"fmt"
+	"path/filepath"
+	"sort"
+)
+
+// helpはサブコマンドの一覧を表示するので、サブコマンドの定義に直接書くと初期化が循環する。
+func init() {
+	commands["help"] = subcommand{executeHelp, "使い方を表示する。-longでオプション、環境変数、ファイルの形式も表示する。"}
+}
+
+// 環境変数と説明
+var environmentVariables = []struct {
+	name        string
+	description string
+}{
+	{EnvHome, "BCBCホームディレクトリ。-homeが指定されていなければ使う。"},
+	{EnvPassphrase, "-encrypt=passphraseのときの暗号化のパスフレーズ。"},
+	{EnvServerToken, "serveの収集サーバーとエージェントが認証に使うトークン。"},
+	{EnvWebDAVUser, "-store=webdavのときのBasic認証のユーザー名。"},
+	{EnvWebDAVPassword, "-store=webdavのときのBasic認証のパスワード。"},
+	{EnvS3AccessKey, "-store=s3のときのアクセスキー。"},
+	{EnvS3SecretKey, "-store=s3のときのシークレットキー。"},
+	{EnvS3SessionToken, "-store=s3のときの一時的な認証情報のセッショントークン。"},
+	{"LC_ALL, LC_MESSAGES, LANG", "-langが指定されていなければメッセージの言語を決める。"},
+}
+
+// helpサブコマンド。
+// 使い方とサブコマンドの一覧を表示する。-longならオプション、環境変数、ファイルの形式も含めた全ての説明を表示する。
+// サブコマンドを指定したらそのサブコマンドの説明を表示する。
+func executeHelp(args []string) {
+	flagSet := flag.NewFlagSet("help", flag.ExitOnError)
+	long := flagSet.Bool("long", false, "オプション、環境変数、ファイルの形式も含めた全ての説明を表示する。")
+	flagSet.Parse(args)
+
+	if flagSet.NArg() > 0 {
+		name := flagSet.Arg(0)
+		command, found := commands[name]
+		fatalMessageIf(ErrConfig, !found, "サブコマンドが見つかりません。: %s\n", name)
+		helpf("bcbc %s: %s\n", name, tr(command.summary))
+		helpf("サブコマンドのオプションはbcbc %s -hで表示します。\n", name)
+		return
+	}
+
+	helpf("使い方:\n")
+	helpf("  bcbc [オプション] [ディスクルート[:サブディレクトリ,...]...]\n")
+	helpf("  bcbc [オプション] <サブコマンド> [サブコマンドのオプション] [引数...]\n")
+	helpf("\nサブコマンド:\n")
+	printCommandList()
+
+	if !*long {
+		helpf("\n全てのオプションとファイルの形式はbcbc help -longで表示します。\n")
+		return
+	}
+
+	helpf("\nオプション:\n")
+	printOptionList()
+	helpf("\n環境変数:\n")
+	for _, variable := range environmentVariables {
+		helpf("  %s\n        %s\n", variable.name, tr(variable.description))
+	}
+	helpf("\nファイル:\n")
+	printFileFormats()
+}
+
+// 翻訳した書式で標準出力に書き込む。
+func helpf(format string, values ...interface{}) {
+	fmt.Fprintf(consoleOut, tr(format), values...)
+}
+
+// サブコマンドを名前順に表示する。
+func printCommandList() {
+	names := make([]string, 0, len(commands))
+	width := 0
+	for name := range commands {
+		names = append(names, name)
+		if len(name) > width {
+			width = len(name)
+		}
+	}
+	sort.Strings(names)
+
+	for _, name := range names {
+		helpf("  %-*s  %s\n", width, name, tr(commands[name].summary))
+	}
+}
+
+// コマンドラインオプションを名前順に表示する。
+// 解析に使っているフラグの定義から、名前、既定値、説明を取り出す。
+func printOptionList() {
+	commandLine.VisitAll(func(f *flag.Flag) {
+		helpf("  -%s\n        %s\n", f.Name, tr(f.Usage))
+		if f.DefValue != "" {
+			helpf("        (既定値: %s)\n", f.DefValue)
+		}
+	})
+}
+
+// 設定ファイルと出力ファイルの場所と形式を表示する。
+func printFileFormats() {
+	files := []struct {
+		path        string
+		description string
+	}{
+		{tr("<ディスクルート>/disk"),
+			"1行目にディスクIDを書き、2行目以降に「項目=値」でthrottle、label、group、filter、algorithmを書く。" +
+				"全ての行を「項目=値」にしてディスクIDをidの項目に書いてもよい。"},
+		{filterConfigFile(),
+			"1行に1つ、+(含める)か-(除外する)に続けてディスクルートからの相対パスの正規表現を書く。上から順に判定して最初に一致した行で決める。"},
+		{filepath.Join(config.configDir(), "bcbc.conf"),
+			"「オプション名=値」でコマンドラインオプションの既定値を書く。"},
+		{filepath.Join(config.configDir(), "group.conf"),
+			"「pattern <正規表現>」でディスクIDからグループ名を取り出し、「group <グループ名> <ディスクID>...」でディスクを明示的にグループに入れる。"},
+		{filepath.Join(config.configDir(), "notify.conf"),
+			"ハッシュ計算の結果の通知先を書く。"},
+		{filepath.Join(config.configDir(), "schedule.conf"),
+			"daemonが定期的に実行するハッシュ計算と検証の予定を書く。"},
+		{filepath.Join(config.outDir(), tr("<ディスクID>")),
+			"ハッシュファイル。1行目は「# bcbc-hashfile <バージョン>」、続く「# 項目: 値」の行がヘッダーで、記録はエスケープしたパスとハッシュをタブで区切る。"},
+		{filepath.Join(config.outDir(), tr("<ディスクID>.journal")),
+			"ハッシュ計算中に記録を追記するジャーナル。ハッシュファイルと同じ形式で、ハッシュ計算の最後にハッシュファイルに取り込んで削除する。"},
+		{registryFile(),
+			"ディスク登録簿。ディスクごとのラベル、グループ、説明、保管場所、最後にハッシュ計算と検証をした日時をJSONで記録する。"},
+	}
+
+	for _, file := range files {
+		helpf("  %s\n        %s\n", file.path, tr(file.description))
+	}
+}
@@ -0,0 +1,97 @@
+package bcbc
+
+import (
+	"flag"
+	"strings"
+	"time"
+)
+
+// 比べる状態の指定: 現在のハッシュファイル
+const whenCurrent = "current"
+
+// 比べる状態の指定: 最新のスナップショット
+const whenLatest = "latest"
+
+// 日時で比べる状態を指定するときの形式
+var whenFormats = []string{snapshotNameFormat, "20060102", "2006-01-02T15:04:05", "2006-01-02"}
+
+// history-diffサブコマンド。
+// ディスクの2つの時点の記録を比べて、追加、削除、ハッシュが変わったファイルを表示する。
+// 時点はcurrent(現在のハッシュファイル)、latest(最新のスナップショット)、スナップショットの名前、日時で指定する。
+// 日時を指定したらその日時以前で最新のスナップショットと比べる。
+func executeHistoryDiff(args []string) {
+	flagSet := flag.NewFlagSet("history-diff", flag.ExitOnError)
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, flagSet.NArg() != 3, "ディスクIDと比べる2つの時点を指定してください。\n")
+	diskId := flagSet.Arg(0)
+	fatalMessageIf(ErrConfig, !diskIdPattern.MatchString(diskId), "ディスクIDの形式が不正です。: %s\n", diskId)
+
+	for _, algorithm := range config.algorithms {
+		store := hashStoreFor(algorithm)
+
+		beforeName, before := loadRecordsAt(store, diskId, flagSet.Arg(1))
+		afterName, after := loadRecordsAt(store, diskId, flagSet.Arg(2))
+
+		logf.Printf("%s (%s): %s -> %s\n", diskId, algorithm, beforeName, afterName)
+		printRecordDiff(diffRecords(before, after))
+	}
+}
+
+// 指定された時点のディスクの記録を読み込む。
+// 読み込んだスナップショットの名前と記録を返す。
+func loadRecordsAt(store HashStore, diskId string, when string) (string, []HashRecord) {
+	if when == whenCurrent {
+		records, err := store.load(diskId)
+		fatalMessageError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s\n", diskId)
+		return whenCurrent, records
+	}
+
+	names, err := store.snapshots(diskId)
+	fatalMessageError(ErrIO, err, "スナップショットの一覧取得に失敗しました。\n")
+	fatalMessageIf(ErrConfig, len(names) == 0, "ディスク%sのスナップショットがありません。\n", diskId)
+
+	name := resolveSnapshotName(names, when)
+	fatalMessageIf(ErrConfig, name == "", "指定された時点のスナップショットが見つかりません。: %s\n", when)
+
+	records, err := store.loadSnapshot(diskId, name)
+	fatalMessageError(ErrIO, err, "スナップショットの読み込みに失敗しました。: %s\n", name)
+
+	return name, records
+}
+
+// 時点の指定に当たるスナップショットの名前を返す。見つからなければ空文字列を返す。
+// namesは古い順に並んでいること。
+func resolveSnapshotName(names []string, when string) string {
+	if when == whenLatest {
+		return names[len(names)-1]
+	}
+
+	for _, name := range names {
+		if name == when {
+			return name
+		}
+	}
+
+	for _, format := range whenFormats {
+		t, err := time.ParseInLocation(format, when, time.Local)
+		if err != nil {
+			continue
+		}
+		// 日付だけならその日の終わりまでを含める
+		if !strings.Contains(format, "15") {
+			t = t.Add(24*time.Hour - time.Second)
+		}
+
+		found := ""
+		for _, name := range names {
+			snapshotTime, err := time.ParseInLocation(snapshotNameFormat, name, time.Local)
+			if err == nil && !snapshotTime.After(t) {
+				found = name
+			}
+		}
+		return found
+	}
+
+	return ""
+}
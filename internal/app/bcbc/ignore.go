@@ -0,0 +1,106 @@
+package bcbc
+
+import (
+	"bufio"
+	"golang.org/x/text/unicode/norm"
+	"os"
+	"path"
+	"strings"
+)
+
+// ディレクトリごとの除外設定ファイルの名前
+const ignoreFileName = ".bcbcignore"
+
+// 除外設定ファイルの1行の規則。
+// .gitignoreと同じように、規則は除外設定ファイルがあるディレクトリ以下に適用する。
+type ignoreRule struct {
+	// 除外設定ファイルがあるディレクトリのディスクルートからの相対パス。ディスクルートなら空。
+	base    string
+	pattern string
+	// !で始まる規則は除外しない
+	negate bool
+	// /で終わる規則はディレクトリだけにマッチする
+	dirOnly bool
+	// /を含む規則は除外設定ファイルがあるディレクトリからの相対パスにマッチし、それ以外は名前にマッチする
+	anchored bool
+}
+
+// 除外設定ファイルを読み込む。
+// 書式は.gitignoreと同じで、パターンはpath.Matchのglobで書く。(**には対応しない)
+// 読み込めなければ警告して規則なしとする。
+func readIgnoreFile(ignoreFile string, base string) []ignoreRule {
+	ignoreFileIn, err := os.Open(ignoreFile)
+	if err != nil {
+		logf.Printf("除外設定ファイルが読み込めませんでした。: %s\n", ignoreFile)
+		logf.Println(err)
+		return nil
+	}
+	defer ignoreFileIn.Close()
+
+	var rules []ignoreRule
+
+	scanner := bufio.NewScanner(ignoreFileIn)
+	for i := 1; scanner.Scan(); i++ {
+		line := norm.NFC.String(strings.TrimRight(scanner.Text(), " \t\r"))
+		if line == "" || line[0] == '#' {
+			continue
+		}
+
+		rule := ignoreRule{base: base}
+		if line[0] == '!' {
+			rule.negate = true
+			line = line[1:]
+		}
+		if strings.HasSuffix(line, "/") {
+			rule.dirOnly = true
+			line = strings.TrimSuffix(line, "/")
+		}
+		if strings.Contains(line, "/") {
+			rule.anchored = true
+			line = strings.TrimPrefix(line, "/")
+		}
+
+		if _, err := path.Match(line, ""); err != nil || line == "" {
+			logf.Printf("除外設定ファイルの形式が不正な行を無視します。: %s : %d行目\n", ignoreFile, i)
+			continue
+		}
+		rule.pattern = line
+
+		rules = append(rules, rule)
+	}
+	if err := scanner.Err(); err != nil {
+		logf.Printf("除外設定ファイルが読み込めませんでした。: %s\n", ignoreFile)
+		logf.Println(err)
+	}
+
+	return rules
+}
+
+// ディスクルートからの相対パスが除外設定ファイルの規則で除外されるか判定する。
+// .gitignoreと同じように、最後にマッチした規則で判定する。
+func ignoredByRules(rules []ignoreRule, relPath string, isDir bool) bool {
+	ignored := false
+
+	for _, rule := range rules {
+		if rule.dirOnly && !isDir {
+			continue
+		}
+
+		target := relPath
+		if rule.base != "" {
+			if !strings.HasPrefix(relPath, rule.base+"/") {
+				continue
+			}
+			target = relPath[len(rule.base)+1:]
+		}
+		if !rule.anchored {
+			target = path.Base(target)
+		}
+
+		if matched, _ := path.Match(rule.pattern, target); matched {
+			ignored = !rule.negate
+		}
+	}
+
+	return ignored
+}
@@ -0,0 +1,238 @@
+package bcbc
+
+import (
+	"bufio"
+	"encoding/gob"
+	"fmt"
+	"os"
+	"path/filepath"
+	"time"
+)
+
+// ハッシュ索引。
+// 全てのハッシュファイルと統合ハッシュファイルの記録と検証日時を1つのファイルにまとめて、find、stats、dupesでハッシュファイルを解析せずに使う。
+type hashIndex struct {
+	// アルゴリズムとハッシュファイルの名前をキーにしたハッシュファイルごとの記録
+	Files map[string]*indexedHashFile
+}
+
+// 索引に登録した1つのハッシュファイル
+type indexedHashFile struct {
+	Algorithm string
+	// ディスクIDか統合ハッシュファイルのグループ名
+	Name   string
+	Merged bool
+	// 登録したときのハッシュファイルのダイジェスト。変わっていなければ登録し直さない。
+	Digest string
+	// 登録したときのハッシュファイルの目印。変わっていなければダイジェストを計算せずに最新とみなす。
+	Stamp string
+	// 登録したときの検証日時の記録の目印。変わっていれば検証日時だけ登録し直す。
+	CheckedStamp string
+	Entries      []indexEntry
+}
+
+// 索引の1件分の記録
+type indexEntry struct {
+	Path string
+	Hash string
+	// 記録していなければ-1
+	Size int64
+	// 記録していなければゼロ値
+	ModTime time.Time
+	// 最後に問題なく検証できた日時。検証していなければゼロ値
+	VerifiedAt time.Time
+}
+
+// 索引のキーを返す。
+func indexKey(algorithm string, name string) string {
+	return algorithm + "/" + name
+}
+
+// 索引のファイルのパスを返す。
+func indexFile() string {
+	return filepath.Join(config.homeDir, "index.gob")
+}
+
+// 記録を索引の記録にする。
+func newIndexEntry(record HashRecord) indexEntry {
+	entry := indexEntry{Path: record.path, Hash: record.hash, Size: record.size}
+	if record.metadata != nil {
+		entry.ModTime = record.metadata.modTime
+	}
+	return entry
+}
+
+// 索引の記録を記録に戻す。
+func (e indexEntry) record() HashRecord {
+	record := newHashRecord(e.Path, e.Hash)
+	record.size = e.Size
+	return record
+}
+
+// -indexが指定されていれば索引を読み込む。索引がなければnilを返す。
+func loadIndexIfEnabled() *hashIndex {
+	if !*indexOption {
+		return nil
+	}
+
+	indexIn, err := os.Open(indexFile())
+	if os.IsNotExist(err) {
+		return nil
+	}
+	fatalMessageError(ErrIO, err, "索引が読み込めませんでした。: %s\n", indexFile())
+	defer indexIn.Close()
+
+	index := &hashIndex{}
+	err = gob.NewDecoder(bufio.NewReader(indexIn)).Decode(index)
+	fatalMessageError(ErrIO, err, "索引の形式が不正です。削除すると次のハッシュ計算で作り直します。: %s\n", indexFile())
+
+	return index
+}
+
+// 索引のハッシュファイルの記録を返す。索引に登録されていなければfalseを返す。
+// 索引を更新した後にハッシュファイルが変わっていれば、古い記録を返さないようにfalseを返してハッシュファイルを読み込ませる。
+// 目印が変わっていなければダイジェストは計算しない。
+func (index *hashIndex) records(store HashStore, algorithm string, name string) ([]HashRecord, bool) {
+	if index == nil {
+		return nil, false
+	}
+	file, found := index.Files[indexKey(algorithm, name)]
+	if !found || !file.fresh(store, name) {
+		return nil, false
+	}
+
+	records := make([]HashRecord, len(file.Entries))
+	for i, entry := range file.Entries {
+		records[i] = entry.record()
+	}
+	return records, true
+}
+
+// 索引に登録したハッシュファイルが今も同じ内容か判定する。
+// 目印が同じなら読み込まずに同じとみなし、目印が変わっていればダイジェストを比べる。
+func (file *indexedHashFile) fresh(store HashStore, name string) bool {
+	if stamp, err := store.stamp(name); err == nil && file.Stamp != "" && stamp == file.Stamp {
+		return true
+	}
+	digest, err := store.digest(name)
+	return err == nil && digest == file.Digest
+}
+
+// 索引に登録されていれば索引から、なければハッシュファイルから記録を読み込む。
+func (index *hashIndex) load(store HashStore, algorithm string, name string) []HashRecord {
+	records, indexed := index.records(store, algorithm, name)
+	if !indexed {
+		var err error
+		records, err = store.load(name)
+		fatalMessageError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s\n", name)
+	}
+	return records
+}
+
+// 検証日時の記録の目印を返す。なければmissingStampを返す。
+func checkedStamp(diskId string) string {
+	stat, err := os.Stat((&DiskInfo{id: diskId}).checkedFile())
+	if err != nil {
+		return missingStamp
+	}
+	return fmt.Sprintf("%d %d", stat.Size(), stat.ModTime().UnixNano())
+}
+
+// 索引の記録に検証日時を設定する。
+func setVerifiedTimes(entries []indexEntry, diskId string) {
+	checkedTimes := loadCheckedTimes(&DiskInfo{id: diskId})
+	for i := range entries {
+		entries[i].VerifiedAt = checkedTimes[entries[i].Path]
+	}
+}
+
+// -indexが指定されていれば索引を更新する。
+// 前回から内容が変わったハッシュファイルだけ読み込み、なくなったハッシュファイルは索引から削除する。
+func updateIndex() {
+	if !*indexOption {
+		return
+	}
+
+	// 出力ディレクトリを共有する別々に実行したbcbcが同時に索引を書き込まないようにする
+	lockFile := indexFile() + ".lock"
+	err := waitLock(lockFile)
+	fatalMessageError(ErrLocked, err, "索引をロックできませんでした。: %s\n", indexFile())
+	defer unlockFile(lockFile)
+
+	previous := loadIndexIfEnabled()
+	if previous == nil {
+		previous = &hashIndex{Files: make(map[string]*indexedHashFile)}
+	}
+	index := &hashIndex{Files: make(map[string]*indexedHashFile)}
+
+	updated := 0
+	for _, algorithm := range config.algorithms {
+		store := hashStoreFor(algorithm)
+		for group, diskIds := range groupDiskIds(store) {
+			names := append([]string{group}, diskIds...)
+			for i, name := range names {
+				merged := i == 0
+				if merged {
+					if _, err := store.loadInfo(name); err != nil {
+						continue
+					}
+				}
+
+				stamp, err := store.stamp(name)
+				fatalMessageError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s\n", name)
+				checked := missingStamp
+				if !merged {
+					checked = checkedStamp(name)
+				}
+
+				key := indexKey(algorithm, name)
+				if file, found := previous.Files[key]; found && file.fresh(store, name) {
+					file.Stamp = stamp
+					if file.CheckedStamp != checked {
+						setVerifiedTimes(file.Entries, name)
+						file.CheckedStamp = checked
+					}
+					index.Files[key] = file
+					continue
+				}
+
+				digest, err := store.digest(name)
+				fatalMessageError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s\n", name)
+				records, err := store.load(name)
+				fatalMessageError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s\n", name)
+
+				entries := make([]indexEntry, len(records))
+				for j, record := range records {
+					entries[j] = newIndexEntry(record)
+				}
+				if !merged {
+					setVerifiedTimes(entries, name)
+				}
+				index.Files[key] = &indexedHashFile{algorithm, name, merged, digest, stamp, checked, entries}
+				updated++
+			}
+		}
+	}
+
+	tempFile := indexFile() + ".tmp"
+	indexOut, err := os.OpenFile(tempFile, os.O_CREATE|os.O_WRONLY|os.O_TRUNC, 0644)
+	fatalMessageError(ErrIO, err, "索引の書き込みに失敗しました。: %s\n", indexFile())
+
+	writer := bufio.NewWriter(indexOut)
+	err = gob.NewEncoder(writer).Encode(index)
+	if err == nil {
+		err = writer.Flush()
+	}
+	if closeErr := indexOut.Close(); err == nil {
+		err = closeErr
+	}
+	if err == nil {
+		err = os.Rename(tempFile, indexFile())
+	}
+	if err != nil {
+		os.Remove(tempFile)
+	}
+	fatalMessageError(ErrIO, err, "索引の書き込みに失敗しました。: %s\n", indexFile())
+
+	logf.Printf("索引を更新しました。(ハッシュファイル%d個中%d個を登録し直しました。)\n", len(index.Files), updated)
+}
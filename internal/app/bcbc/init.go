@@ -2,11 +2,17 @@ package bcbc
 
 import (
 	"bufio"
+	"crypto/cipher"
+	"crypto/ed25519"
+	"errors"
+	"flag"
+	"fmt"
 	"golang.org/x/text/unicode/norm"
 	"io"
 	"log"
+	"net/url"
 	"os"
-	"path"
+	"path/filepath"
 	"regexp"
 	"strings"
 	"time"
@@ -14,8 +20,54 @@ import (
 
 // Config 設定
 type Config struct {
-	homeDir string
-	filters []Filter
+	homeDir    string
+	filters    []Filter
+	algorithms []string
+	store      string
+	storeURL   string
+	// 保存先のURLから取り除いたユーザー名とパスワード。なければnil。
+	storeUser *url.Userinfo
+	s3Region  string
+	symlinks  string
+	maxDepth  int
+	units     string
+	// 読み込みエラーの再試行回数と最初の待ち時間
+	retries      int
+	retryBackoff time.Duration
+	salvage      bool
+	notify       *notifyConfig
+	metricsPort  int
+	grouping     groupingConfig
+	// 同時にハッシュ計算するディスクの最大数。0なら制限しない。
+	maxParallelDisks int
+	// ディスクごとの読み込みのスループットの上限(MB/s)。0なら制限しない。
+	throttle float64
+	// CPUとI/Oの優先度を下げて実行するか
+	background bool
+	// ページキャッシュを使わずに読み込むか
+	directIO bool
+	// メモリマップで読み込む最小のファイルサイズ(MB)。0ならメモリマップしない。
+	mmapMinSize int
+	// スループットが遅かったファイルとディレクトリの報告数
+	slowReport int
+	// ハッシュと一緒にファイルのメタデータを記録するか
+	metadata bool
+	// ハッシュと一緒に拡張属性のダイジェストを記録するか
+	xattrs bool
+	// ハッシュファイルに署名する秘密鍵。署名しなければnil。
+	signingKey ed25519.PrivateKey
+	// ハッシュファイルを暗号化する暗号。暗号化しなければnil。
+	encryption cipher.AEAD
+	// ハッシュファイルの圧縮形式。圧縮しなければ空。
+	compress string
+	// ディスクから無くなったファイルの記録を削除するか
+	prune bool
+	// 前回エラーになったファイルだけをハッシュ計算するか
+	retryFailed bool
+	// ディスクのハッシュ計算を打ち切るエラーの数。0なら打ち切らない。
+	maxErrors int
+	// ログの形式
+	logFormat string
 }
 
 // 設定
@@ -23,42 +75,146 @@ var config Config
 
 // ログディレクトリを返す。
 func (c *Config) logDir() string {
-	return path.Join(config.homeDir, "log")
+	return filepath.Join(config.homeDir, "log")
 }
 
 // 出力ディレクトリを返す。
 func (c *Config) outDir() string {
-	return path.Join(config.homeDir, "out")
+	return filepath.Join(config.homeDir, "out")
+}
+
+// ハッシュファイルの出力ディレクトリを返す。
+// MD5以外のアルゴリズムのハッシュファイルは混在しないようにアルゴリズム名のサブディレクトリに出力する。
+func (c *Config) hashDir(algorithm string) string {
+	if algorithm == AlgorithmMD5 {
+		return c.outDir()
+	}
+	return filepath.Join(c.outDir(), algorithm)
 }
 
 // 設定ディレクトリを返す。
 func (c *Config) configDir() string {
-	return path.Join(config.homeDir, "configs")
+	return filepath.Join(config.homeDir, "configs")
 }
 
 // EnvHome 環境変数名: BCBCホームディレクトリ
 const EnvHome = "BCBCHOME"
 
-// 環境変数を取得する。
-func initEnvs() {
-	value, found := os.LookupEnv(EnvHome)
-	if !found {
-		log.Fatalf("環境変数%sが設定されていません。\n", EnvHome)
+// 既定のBCBCホームディレクトリのユーザーのホームディレクトリからの相対パス
+const defaultHomeDir = ".bcbc"
+
+// BCBCホームディレクトリを決める。
+// -homeオプション(既定値設定ファイルのhomeを含む)、環境変数、ユーザーのホームディレクトリの.bcbcの順に探す。
+// BCBCホームディレクトリが変わったらtrueを返す。
+func initEnvs() bool {
+	homeDir := config.homeDir
+	if *homeOption != "" {
+		config.homeDir = *homeOption
+	} else if value, found := os.LookupEnv(EnvHome); found && value != "" {
+		config.homeDir = value
+	} else {
+		userHomeDir, err := os.UserHomeDir()
+		fatalMessageError(ErrConfig, err, "環境変数%sが設定されておらず、ユーザーのホームディレクトリも取得できませんでした。\n", EnvHome)
+		config.homeDir = filepath.Join(userHomeDir, defaultHomeDir)
+	}
+	return config.homeDir != homeDir
+}
+
+// BCBCホームディレクトリがなければ作成して、フィルター設定ファイルの雛形を置く。
+func initHome() {
+	if createHomeSkeleton() {
+		logf.Printf("BCBCホームディレクトリを作成しました。: %s\n", config.homeDir)
+		logf.Printf("ハッシュ計算の対象は%sで設定してください。\n", filepath.Join(config.configDir(), "filter.conf"))
 	}
-	config.homeDir = value
+}
+
+// コマンドラインオプションを設定に反映する。
+func initOptions() {
+	config.algorithms = checkAlgorithms(strings.Split(*algorithmOption, ","))
+	config.store = checkStore(*storeOption)
+	config.storeURL, config.storeUser = checkStoreURL(config.store, *storeURLOption)
+	config.s3Region = *s3RegionOption
+	config.symlinks = checkSymlinks(*symlinksOption)
+	config.maxDepth = *maxDepthOption
+	config.units = *unitsOption
+	_, known := sizeUnits[config.units]
+	fatalMessageIf(ErrConfig, !known, "サイズの単位系の指定が不正です。: %s\n", config.units)
+	fatalMessageIf(ErrConfig, config.maxDepth < 0, "最大深さの指定が不正です。: %d\n", config.maxDepth)
+	config.retries = *retriesOption
+	config.retryBackoff = *retryBackoffOption
+	config.salvage = *salvageOption
+	config.metricsPort = *metricsPortOption
+	config.maxParallelDisks = *maxParallelDisksOption
+	config.throttle = *throttleOption
+	config.background = *backgroundOption
+	config.directIO = *directIOOption
+	config.mmapMinSize = *mmapMinSizeOption
+	config.slowReport = *slowReportOption
+	config.metadata = *metadataOption
+	config.xattrs = *xattrsOption
+	config.compress = checkCompress(*compressOption)
+	config.prune = *pruneOption
+	config.retryFailed = *retryFailedOption
+	config.maxErrors = *maxErrorsOption
+	fatalMessageIf(ErrConfig, config.maxErrors < 0, "エラーの上限の指定が不正です。: %d\n", config.maxErrors)
+	fatalMessageIf(ErrConfig, config.slowReport < 0, "報告数の指定が不正です。: %d\n", config.slowReport)
+	fatalMessageIf(ErrConfig, config.mmapMinSize < 0, "メモリマップで読み込む最小のファイルサイズの指定が不正です。: %d\n", config.mmapMinSize)
+	fatalMessageIf(ErrConfig, config.throttle < 0, "スループットの上限の指定が不正です。: %g\n", config.throttle)
+	fatalMessageIf(ErrConfig, config.maxParallelDisks < 0, "同時にハッシュ計算するディスクの最大数の指定が不正です。: %d\n", config.maxParallelDisks)
+	fatalMessageIf(ErrConfig, config.metricsPort < 0 || config.metricsPort > 65535, "メトリクスのポートの指定が不正です。: %d\n", config.metricsPort)
+	fatalMessageIf(ErrConfig, config.retries < 0, "再試行回数の指定が不正です。: %d\n", config.retries)
+	fatalMessageIf(ErrConfig, config.retryBackoff < 0, "再試行の待ち時間の指定が不正です。: %s\n", config.retryBackoff)
+}
+
+// シンボリックリンクの扱いの指定を検証する。
+func checkSymlinks(symlinks string) string {
+	if symlinks == "" {
+		return SymlinksFollow
+	}
+	valid := symlinks == SymlinksSkip || symlinks == SymlinksFollow
+	fatalMessageIf(ErrConfig, !valid, "シンボリックリンクの扱いの指定が不正です。: %s\n", symlinks)
+	return symlinks
+}
+
+// ハッシュファイルの保存先の指定を検証する。
+func checkStore(store string) string {
+	if store == "" {
+		return StoreFile
+	}
+	_, known := hashStoreFactories[store]
+	fatalMessageIf(ErrConfig, !known, "ハッシュファイルの保存先の指定が不正です。: %s\n", store)
+	return store
+}
+
+// ハッシュアルゴリズムの指定を検証する。
+func checkAlgorithms(algorithms []string) []string {
+	fatalMessageIf(ErrConfig, len(algorithms) == 0, "ハッシュアルゴリズムが指定されていません。\n")
+
+	result := make([]string, 0, len(algorithms))
+	for _, algorithm := range algorithms {
+		algorithm = strings.TrimSpace(algorithm)
+		_, known := hasherFactories[algorithm]
+		fatalMessageIf(ErrConfig, !known, "ハッシュアルゴリズムの指定が不正です。: %s\n", algorithm)
+		for _, a := range result {
+			fatalMessageIf(ErrConfig, a == algorithm, "ハッシュアルゴリズムが重複しています。: %s\n", algorithm)
+		}
+		result = append(result, algorithm)
+	}
+
+	return result
 }
 
 // ロガーを初期化する
 func initLogger() *os.File {
 	err := os.MkdirAll(config.logDir(), 0755)
-	fatalMessageError(err, "ログディレクトリを作成できませんでした。: %s\n", config.logDir())
+	fatalMessageError(ErrIO, err, "ログディレクトリを作成できませんでした。: %s\n", config.logDir())
 
 	logFileName := time.Now().Format("20060102150405.log")
-	logFilePath := path.Join(config.logDir(), logFileName)
+	logFilePath := filepath.Join(config.logDir(), logFileName)
 	logFileOut, err := os.OpenFile(logFilePath, os.O_CREATE|os.O_WRONLY, 0644)
-	fatalMessageError(err, "ログファイルを作成できませんでした。: %s\n", logFilePath)
+	fatalMessageError(ErrIO, err, "ログファイルを作成できませんでした。: %s\n", logFilePath)
 
-	logf = log.New(io.MultiWriter(os.Stdout, logFileOut), "", log.LstdFlags)
+	logf = newMessageLogger(log.New(io.MultiWriter(consoleOut, logFileOut), "", log.LstdFlags))
 
 	return logFileOut
 }
@@ -67,17 +223,94 @@ func initLogger() *os.File {
 type Filter struct {
 	pattern   *regexp.Regexp
 	inclusion bool
+	// フィルター設定ファイルの行番号。コマンドラインで指定されたフィルターは0。
+	line int
+}
+
+// フィルター設定ファイルの形式が不正な行
+type filterProblem struct {
+	line int
+	text string
+	err  error
+}
+
+// フィルター設定ファイルのパスを返す。
+func filterConfigFile() string {
+	return filepath.Join(config.configDir(), "filter.conf")
 }
 
 // フィルター設定を読み込む。
 func initFilters() {
-
-	filterConfigFile := path.Join(config.configDir(), "filter.conf")
-	filterFileIn, err := os.Open(filterConfigFile)
-	fatalMessageError(err, "フィルター設定ファイルが見つかりません。\n")
+	filterFileIn, err := os.Open(filterConfigFile())
+	fatalMessageError(ErrConfig, err, "フィルター設定ファイルが見つかりません。\n")
 	defer filterFileIn.Close()
 
-	config.filters = make([]Filter, 0)
+	filters, problems, err := readFilters(filterFileIn)
+	fatalMessageError(ErrConfig, err, "フィルター設定ファイルが読み込めませんでした。\n")
+	for _, problem := range problems {
+		fatalMessageError(ErrConfig, problem.err, "フィルター設定ファイルの形式が不正です。: %d行目: %s\n", problem.line, problem.text)
+	}
+
+	config.filters = append(commandLineFilters(), filters...)
+}
+
+// -includeと-excludeのオプションを指定された順に並べたフィルターの行
+type filterOptionList struct {
+	lines []string
+}
+
+// 行頭の符号を付けてフィルターの行を追加するオプションの値
+type filterOptionValue struct {
+	list *filterOptionList
+	sign string
+}
+
+func (v filterOptionValue) String() string {
+	return ""
+}
+
+func (v filterOptionValue) Set(value string) error {
+	v.list.lines = append(v.list.lines, v.sign+value)
+	return nil
+}
+
+// -includeと-excludeのオプションを登録する。
+// 含めるものと除外するものを混ぜて指定しても、指定された順に判定する。
+func newFilterOptions(includeUsage string, excludeUsage string) *filterOptionList {
+	list := &filterOptionList{}
+	optionDefinitions = append(optionDefinitions, func(flagSet *flag.FlagSet) {
+		flagSet.Var(filterOptionValue{list, "+"}, "include", includeUsage)
+		flagSet.Var(filterOptionValue{list, "-"}, "exclude", excludeUsage)
+	})
+	return list
+}
+
+// コマンドラインで指定されたフィルターを返す。
+// 行番号は0にして、フィルター設定ファイルの行と区別する。
+func commandLineFilters() []Filter {
+	filters := make([]Filter, 0, len(filterOptions.lines))
+	for _, line := range filterOptions.lines {
+		line = norm.NFC.String(line)
+		pattern, err := regexp.Compile(line[1:])
+		fatalMessageError(ErrConfig, err, "フィルターのオプションの正規表現が不正です。: %s\n", line[1:])
+		filters = append(filters, Filter{pattern, line[0] == '+', 0})
+	}
+	return filters
+}
+
+// フィルターを定義した場所を返す。
+func (f Filter) source() string {
+	if f.line == 0 {
+		return "コマンドライン"
+	}
+	return fmt.Sprintf("%d行目", f.line)
+}
+
+// フィルター設定ファイルを解析する。
+// 形式が不正な行があっても最後まで解析して、不正な行を全て返す。
+func readFilters(filterFileIn io.Reader) ([]Filter, []filterProblem, error) {
+	filters := make([]Filter, 0)
+	var problems []filterProblem
 
 	filterFileScanner := bufio.NewScanner(filterFileIn)
 	for i := 1; filterFileScanner.Scan(); i++ {
@@ -88,14 +321,20 @@ func initFilters() {
 			continue
 		}
 
-		malformed := len(line) < 2 || (line[0] != '+' && line[0] != '-')
-		fatalMessageIf(malformed, "フィルター設定ファイルの形式が不正です。: %d行目: %s\n", i, line)
+		if len(line) < 2 || (line[0] != '+' && line[0] != '-') {
+			problems = append(problems, filterProblem{i, line, errors.New("行頭を'+'か'-'にして、2文字目以降に正規表現の式を書いてください。")})
+			continue
+		}
 
 		pattern, err := regexp.Compile(line[1:])
-		fatalMessageError(err, "フィルター設定ファイルの形式が不正です。: %d行目: %s\n", i, line)
+		if err != nil {
+			problems = append(problems, filterProblem{i, line, err})
+			continue
+		}
 
 		inclusion := line[0] == '+'
-		filter := Filter{pattern, inclusion}
-		config.filters = append(config.filters, filter)
+		filters = append(filters, Filter{pattern, inclusion, i})
 	}
+
+	return filters, problems, filterFileScanner.Err()
 }
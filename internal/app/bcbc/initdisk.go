@@ -0,0 +1,66 @@
+package bcbc
+
+import (
+	"flag"
+	"os"
+	"path/filepath"
+	"time"
+)
+
+// init-diskサブコマンド。
+// ディスクIDを検証してディスクルートにdiskファイルを作成し、ディスク登録簿に登録する。
+func executeInitDisk(args []string) {
+	flagSet := flag.NewFlagSet("init-disk", flag.ExitOnError)
+	label := flagSet.String("label", "", "ディスク登録簿に記録するディスクのラベル。")
+	serial := flagSet.String("serial", "", "ディスク登録簿に記録するディスクのシリアル番号。")
+	description := flagSet.String("description", "", "ディスク登録簿に記録するディスクの説明。")
+	location := flagSet.String("location", "", "ディスク登録簿に記録するディスクの保管場所。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, flagSet.NArg() != 2, "ディスクルートとディスクIDを指定してください。\n")
+	rootPath := normalizeRootPath(flagSet.Arg(0))
+	diskId := flagSet.Arg(1)
+
+	fatalMessageIf(ErrConfig, !diskIdPattern.MatchString(diskId), "ディスクIDの形式が不正です。: %s\n", diskId)
+
+	stat, err := os.Stat(rootPath)
+	fatalMessageError(ErrConfig, err, "ディスクルートが見つかりません。: %s\n", rootPath)
+	fatalMessageIf(ErrConfig, !stat.IsDir(), "ディスクルートがディレクトリではありません。: %s\n", rootPath)
+
+	diskFile := filepath.Join(rootPath, "disk")
+	_, err = os.Stat(diskFile)
+	fatalMessageIf(ErrDiskFile, err == nil, "diskファイルが既にあります。: %s\n", diskFile)
+
+	// 出力ディレクトリにハッシュファイルがあるかディスク登録簿にあれば使用済みのディスクID
+	for _, algorithm := range config.algorithms {
+		diskIds, err := hashStoreFor(algorithm).diskIds()
+		fatalMessageError(ErrIO, err, "出力ファイルの一覧取得に失敗しました。\n")
+		for _, id := range diskIds {
+			fatalMessageIf(ErrConfig, id == diskId, "ディスクIDは既に使われています。: %s\n", diskId)
+		}
+	}
+	lockRegistry()
+	defer unlockRegistry()
+	registry := loadRegistry()
+	_, registered := registry[diskId]
+	fatalMessageIf(ErrConfig, registered, "ディスクIDは既にディスク登録簿に登録されています。: %s\n", diskId)
+
+	err = os.WriteFile(diskFile, []byte(diskId+"\n"), 0644)
+	fatalMessageError(ErrDiskFile, err, "diskファイルを作成できませんでした。: %s\n", diskFile)
+	logf.Printf("diskファイルを作成しました。: %s\n", diskFile)
+
+	var environment DiskEnvironment
+	inspectDiskEnvironment(rootPath, &environment)
+
+	registry[diskId] = &DiskRecord{
+		ID:            diskId,
+		Label:         *label,
+		Description:   *description,
+		Location:      *location,
+		Serial:        *serial,
+		CapacityBytes: environment.TotalBytes,
+		RegisteredAt:  time.Now(),
+	}
+	saveRegistry(registry)
+	logf.Printf("ディスク登録簿に登録しました。: %s\n", diskId)
+}
@@ -0,0 +1,35 @@
+// +build !windows
+
+package bcbc
+
+import (
+	"os"
+	"syscall"
+)
+
+// ファイルの識別子
+type fileId struct {
+	device uint64
+	inode  uint64
+}
+
+// 複数のハードリンクがあるファイルならデバイスとiノードの識別子を返す。
+func hardLinkIdOf(stat os.FileInfo) (fileId, bool) {
+	sys, ok := stat.Sys().(*syscall.Stat_t)
+	if !ok || sys.Nlink <= 1 {
+		return fileId{}, false
+	}
+	return fileId{uint64(sys.Dev), uint64(sys.Ino)}, true
+}
+
+// 2つのパスが同じデバイスにあるか判定する。調べられなければ異なるとみなす。
+func sameDevice(a string, b string) bool {
+	aStat, errA := os.Stat(a)
+	bStat, errB := os.Stat(b)
+	if errA != nil || errB != nil {
+		return false
+	}
+	aSys, okA := aStat.Sys().(*syscall.Stat_t)
+	bSys, okB := bStat.Sys().(*syscall.Stat_t)
+	return okA && okB && aSys.Dev == bSys.Dev
+}
@@ -0,0 +1,30 @@
+// +build windows
+
+package bcbc
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// ファイルの識別子
+type fileId struct {
+	device uint64
+	inode  uint64
+}
+
+// Windowsではハードリンクを識別しない。
+func hardLinkIdOf(stat os.FileInfo) (fileId, bool) {
+	return fileId{}, false
+}
+
+// 2つのパスが同じドライブにあるか判定する。
+func sameDevice(a string, b string) bool {
+	aAbs, errA := filepath.Abs(a)
+	bAbs, errB := filepath.Abs(b)
+	if errA != nil || errB != nil {
+		return false
+	}
+	return strings.EqualFold(filepath.VolumeName(aAbs), filepath.VolumeName(bAbs))
+}
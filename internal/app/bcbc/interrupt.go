@@ -0,0 +1,53 @@
+package bcbc
+
+import (
+	"os"
+	"os/signal"
+	"syscall"
+)
+
+// 中断シグナルを受け取ると閉じるチャネル
+var interruptChannel = make(chan struct{})
+
+// 中断シグナルを監視する。
+// シグナルを受け取ったらハッシュルーチンが区切りのいいところで中断するように中断チャネルを閉じる。
+func watchInterrupt() {
+	signalChannel := make(chan os.Signal, 1)
+	signal.Notify(signalChannel, os.Interrupt, syscall.SIGTERM)
+
+	go func() {
+		<-signalChannel
+		logf.Println("中断シグナルを受け取りました。ハッシュ計算を中断します。")
+		close(interruptChannel)
+	}()
+}
+
+// 致命的なエラーで残りのルーチンを打ち切るときに閉じるチャネル。
+// 打ち切られたルーチンは中断シグナルと同じように区切りのいいところで終了する。
+var abortChannel = make(chan struct{})
+
+// 残りのルーチンを打ち切る。
+func abortRoutines() {
+	select {
+	case <-abortChannel:
+	default:
+		close(abortChannel)
+	}
+}
+
+// 打ち切りを解除する。打ち切ったルーチンが全て終了してから呼ぶ。
+func resetAbort() {
+	abortChannel = make(chan struct{})
+}
+
+// 中断シグナルを受け取っているか、打ち切られているか判定する。
+func interrupted() bool {
+	select {
+	case <-interruptChannel:
+		return true
+	case <-abortChannel:
+		return true
+	default:
+		return false
+	}
+}
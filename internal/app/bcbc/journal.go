@@ -0,0 +1,70 @@
+package bcbc
+
+import (
+	"os"
+)
+
+// ハッシュ計算中に追記する記録を書き込むジャーナルの拡張子。
+// ハッシュファイルは計算の最後に書き直すまで変更しないので、追記が中断されても壊れるのはジャーナルだけになる。
+const journalExtension = ".journal"
+
+// ディスクのジャーナルのパスを返す。
+func (s *fileHashStore) journalFile(diskId string) string {
+	return s.file(diskId + journalExtension)
+}
+
+// ジャーナルがあるか返す。
+func (s *fileHashStore) hasJournal(diskId string) bool {
+	_, err := os.Stat(s.journalFile(diskId))
+	return err == nil
+}
+
+// ハッシュファイルの記録にジャーナルの記録を反映する。
+// 同じパスの記録は後に書いたものだけを残す。
+func applyJournal(records []HashRecord, journal []HashRecord) []HashRecord {
+	if len(journal) == 0 {
+		return records
+	}
+
+	indexes := make(map[string]int, len(records)+len(journal))
+	for i, record := range records {
+		indexes[record.path] = i
+	}
+	for _, record := range journal {
+		if i, found := indexes[record.path]; found {
+			records[i] = record
+			continue
+		}
+		indexes[record.path] = len(records)
+		records = append(records, record)
+	}
+
+	return records
+}
+
+func (s *fileHashStore) foldJournal(diskId string) error {
+	return s.foldJournalWith(s, diskId)
+}
+
+// ジャーナルの記録をハッシュファイルに取り込む。
+// 保存先に送るストアでは取り込んだハッシュファイルも送るように、読み込みと書き直しはouterで行う。
+func (s *fileHashStore) foldJournalWith(outer HashStore, diskId string) error {
+	if !s.hasJournal(diskId) {
+		return nil
+	}
+	records, err := outer.load(diskId)
+	if err != nil {
+		return err
+	}
+	return outer.rewrite(diskId, records)
+}
+
+// ディスクの全てのアルゴリズムのジャーナルをハッシュファイルに取り込む。
+func foldJournals(diskInfo *DiskInfo) error {
+	for _, algorithm := range diskInfo.hashAlgorithms() {
+		if err := hashStoreFor(algorithm).foldJournal(diskInfo.id); err != nil {
+			return err
+		}
+	}
+	return nil
+}
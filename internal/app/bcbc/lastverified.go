@@ -0,0 +1,146 @@
+package bcbc
+
+import (
+	"bufio"
+	"flag"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// 検証日時の記録のパスを返す。
+// ファイルごとに最後に問題なく検証できた日時を記録する。
+func (di *DiskInfo) checkedFile() string {
+	return filepath.Join(config.outDir(), di.id+".checked")
+}
+
+// 検証日時の記録を読み込む。記録がなければ空を返す。
+func loadCheckedTimes(diskInfo *DiskInfo) map[string]time.Time {
+	result := make(map[string]time.Time)
+
+	checkedIn, err := os.Open(diskInfo.checkedFile())
+	if err != nil {
+		return result
+	}
+	defer checkedIn.Close()
+
+	for scanner := bufio.NewScanner(checkedIn); scanner.Scan(); {
+		tokens := strings.SplitN(scanner.Text(), "\t", 2)
+		if len(tokens) != 2 {
+			continue
+		}
+		if checked, err := time.Parse(time.RFC3339, tokens[0]); err == nil {
+			result[tokens[1]] = checked
+		}
+	}
+
+	return result
+}
+
+// 検証日時の記録を書き込む。ハッシュファイルにないファイルの記録は書き込まない。
+func saveCheckedTimes(diskInfo *DiskInfo, checkedTimes map[string]time.Time, recorded map[string]HashRecord) {
+	normPaths := make([]string, 0, len(checkedTimes))
+	for normPath := range checkedTimes {
+		if _, found := recorded[normPath]; found {
+			normPaths = append(normPaths, normPath)
+		}
+	}
+	sort.Strings(normPaths)
+
+	content := strings.Builder{}
+	for _, normPath := range normPaths {
+		fmt.Fprintf(&content, "%s\t%s\n", checkedTimes[normPath].UTC().Format(time.RFC3339), normPath)
+	}
+
+	tempFile := diskInfo.checkedFile() + ".tmp"
+	err := os.WriteFile(tempFile, []byte(content.String()), 0644)
+	if err == nil {
+		err = os.Rename(tempFile, diskInfo.checkedFile())
+	}
+	fatalMessageError(ErrIO, err, "検証日時の記録の書き込みに失敗しました。: %s\n", diskInfo.checkedFile())
+}
+
+// 経過時間の指定を解析する。time.ParseDurationの形式に加えて、日数の「365d」も受け付ける。
+func parseAge(age string) (time.Duration, error) {
+	if strings.HasSuffix(age, "d") {
+		days, err := strconv.ParseFloat(strings.TrimSuffix(age, "d"), 64)
+		if err != nil || days < 0 {
+			return 0, fmt.Errorf("経過時間の指定が不正です。: %s", age)
+		}
+		return time.Duration(days * float64(24*time.Hour)), nil
+	}
+
+	duration, err := time.ParseDuration(age)
+	if err != nil || duration < 0 {
+		return 0, fmt.Errorf("経過時間の指定が不正です。: %s", age)
+	}
+	return duration, nil
+}
+
+// unverifiedサブコマンド。
+// 最後に問題なく検証してから指定した時間が経ったファイルと、一度も検証していないファイルをディスクごとに数える。
+// 「全てのファイルを1年に1回は検証する」といった運用の確認に使う。
+func executeUnverified(args []string) {
+	flagSet := flag.NewFlagSet("unverified", flag.ExitOnError)
+	olderThan := flagSet.String("older-than", "365d", "この時間より前に検証したファイルを報告する。(例: 365d、720h)")
+	list := flagSet.Bool("list", false, "該当するファイルのパスも表示する。")
+	flagSet.Parse(args)
+
+	threshold, err := parseAge(*olderThan)
+	fatalMessageError(ErrConfig, err, "経過時間の指定が不正です。\n")
+	deadline := time.Now().Add(-threshold)
+
+	store := hashStoreFor(config.algorithms[0])
+	diskIds := flagSet.Args()
+	if len(diskIds) == 0 {
+		diskIds, err = store.diskIds()
+		fatalMessageError(ErrIO, err, "出力ファイルの一覧取得に失敗しました。\n")
+	}
+
+	var totalFiles, totalOverdue uint64
+	for _, diskId := range diskIds {
+		fatalMessageIf(ErrConfig, !diskIdPattern.MatchString(diskId), "ディスクIDの形式が不正です。: %s\n", diskId)
+
+		records, err := store.load(diskId)
+		fatalMessageError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s\n", diskId)
+		checkedTimes := loadCheckedTimes(&DiskInfo{id: diskId})
+
+		var never, overdue []string
+		seen := make(map[string]bool, len(records))
+		for _, record := range records {
+			if seen[record.path] {
+				continue
+			}
+			seen[record.path] = true
+
+			checked, found := checkedTimes[record.path]
+			switch {
+			case !found:
+				never = append(never, record.path)
+			case checked.Before(deadline):
+				overdue = append(overdue, record.path)
+			}
+		}
+
+		logf.Printf("%-6s 記録%s 未検証%s 期限切れ%s\n", diskId, formatQuantity(uint64(len(seen)), "件", "件"),
+			formatQuantity(uint64(len(never)), "件", "件"), formatQuantity(uint64(len(overdue)), "件", "件"))
+		if *list {
+			for _, normPath := range never {
+				logf.Printf("  未検証: %s\n", normPath)
+			}
+			for _, normPath := range overdue {
+				logf.Printf("  %s: %s\n", formatDate(checkedTimes[normPath]), normPath)
+			}
+		}
+
+		totalFiles += uint64(len(seen))
+		totalOverdue += uint64(len(never) + len(overdue))
+	}
+
+	logf.Printf("%s中%sが%sより前に検証されていません。\n", formatQuantity(totalFiles, "件", "件"),
+		formatQuantity(totalOverdue, "件", "件"), deadline.Format("2006-01-02"))
+}
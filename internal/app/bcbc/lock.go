@@ -0,0 +1,227 @@
+package bcbc
+
+import (
+	"fmt"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// ロックファイルのパスを返す。
+// 同じ出力ディレクトリを複数のマシンで共有するときに、同じディスクへの同時書き込みを防ぐ。
+func (di *DiskInfo) lockFile() string {
+	return filepath.Join(config.outDir(), di.id+".lock")
+}
+
+// 最後にハッシュファイルを書き込んだマシンを記録するファイルのパスを返す。
+func (di *DiskInfo) writerFile() string {
+	return filepath.Join(config.outDir(), di.id+".writer")
+}
+
+// 書き込み元のマシン名を返す。
+func writerHost() string {
+	host, err := os.Hostname()
+	if err != nil {
+		return "unknown"
+	}
+	return host
+}
+
+// このプロセスを表す書き込み元を「マシン名 pid=プロセスID」で返す。
+// ロックファイルとハッシュファイルのヘッダーに書き込む。
+func writerId() string {
+	return fmt.Sprintf("%s pid=%d", writerHost(), os.Getpid())
+}
+
+// ロックファイルに書き込んだ書き込み元がこのマシンで実行中ではなければtrueを返す。
+// 書き込み元は「マシン名 pid=プロセスID 日時」で、別のマシンの書き込み元は実行中か確かめられないのでfalseを返す。
+func staleLockOwner(owner string) bool {
+	fields := strings.Fields(owner)
+	if len(fields) < 2 || fields[0] != writerHost() || !strings.HasPrefix(fields[1], "pid=") {
+		return false
+	}
+	pid, err := strconv.Atoi(strings.TrimPrefix(fields[1], "pid="))
+	if err != nil || pid == os.Getpid() {
+		return false
+	}
+	return !processExists(pid)
+}
+
+// ロックファイルを作成した日時からの経過時間を返す。分からなければ0を返す。
+func lockAge(lockFile string) time.Duration {
+	stat, err := os.Stat(lockFile)
+	if err != nil {
+		return 0
+	}
+	return time.Since(stat.ModTime()).Round(time.Minute)
+}
+
+// ロックファイルを削除する。
+// 読み込んでから削除するまでに他の書き込み元が作り直していたら削除しない。
+func removeLockFile(lockFile string, owner string) {
+	current, err := os.ReadFile(lockFile)
+	if err != nil || strings.TrimSpace(string(current)) != owner {
+		return
+	}
+	if err := os.Remove(lockFile); err != nil && !os.IsNotExist(err) {
+		logf.Printf("ロックファイルの削除に失敗しました。: %s\n", lockFile)
+		logf.Println(err)
+	}
+}
+
+// ロックファイルを作成して書き込み元を書き込む。
+// 既にロックファイルがあればfalseと、ロックしている書き込み元を返す。
+// このマシンで異常終了した書き込み元のロックファイルが残っていたら、削除してから作成し直す。
+func createLockFile(lockFile string) (bool, string, error) {
+	lockFileOut, err := os.OpenFile(lockFile, os.O_CREATE|os.O_EXCL|os.O_WRONLY, 0644)
+	if os.IsExist(err) {
+		content, _ := os.ReadFile(lockFile)
+		owner := strings.TrimSpace(string(content))
+		if !staleLockOwner(owner) {
+			return false, owner, nil
+		}
+		logf.Printf("[警告] 実行中ではない書き込み元のロックファイルが残っているので削除します。: %s (%s)\n", lockFile, owner)
+		removeLockFile(lockFile, owner)
+		lockFileOut, err = os.OpenFile(lockFile, os.O_CREATE|os.O_EXCL|os.O_WRONLY, 0644)
+		if os.IsExist(err) {
+			content, _ := os.ReadFile(lockFile)
+			return false, strings.TrimSpace(string(content)), nil
+		}
+	}
+	if err != nil {
+		return false, "", newError(ErrIO, err, "ロックファイルを作成できませんでした。: %s", lockFile)
+	}
+	defer lockFileOut.Close()
+
+	_, err = fmt.Fprintf(lockFileOut, "%s %s\n", writerId(), time.Now().Format(time.RFC3339))
+	if err != nil {
+		return false, "", newError(ErrIO, err, "ロックファイルの書き込みに失敗しました。: %s", lockFile)
+	}
+
+	return true, "", nil
+}
+
+// ディスクをロックする。
+// 他の書き込み元がロックしていたらロックしている書き込み元を含むエラーを返す。
+// ディスクごとにロックするので、別々に実行したbcbcが同じ出力ディレクトリで別のディスクをハッシュ計算できる。
+func lockDisk(diskInfo *DiskInfo) error {
+	err := os.MkdirAll(config.outDir(), 0755)
+	if err != nil {
+		return newError(ErrIO, err, "出力ディレクトリを作成できませんでした。: %s", config.outDir())
+	}
+
+	locked, owner, err := createLockFile(diskInfo.lockFile())
+	if err == nil && !locked && *forceUnlockOption {
+		logf.Printf("[警告] -force-unlockが指定されたので、ディスク%sのロックファイルを削除します。: %s\n", diskInfo.id, owner)
+		removeLockFile(diskInfo.lockFile(), owner)
+		locked, owner, err = createLockFile(diskInfo.lockFile())
+	}
+	if err != nil {
+		return err
+	}
+	if !locked {
+		return newError(ErrLocked, nil, "ディスク%sは他の書き込み元がロックしています。(%s前から) 実行中でなければ-force-unlockを指定してください。: %s",
+			diskInfo.id, lockAge(diskInfo.lockFile()), owner)
+	}
+
+	return nil
+}
+
+// ディスクのロックを解除する。
+func unlockDisk(diskInfo *DiskInfo) {
+	err := os.Remove(diskInfo.lockFile())
+	if err != nil {
+		logf.Printf("ロックファイルの削除に失敗しました。: %s\n", diskInfo.lockFile())
+		logf.Println(err)
+	}
+}
+
+// 書き込み元を記録する。
+// 前回と異なるマシンから書き込む場合は、ディスクIDが重複している可能性があるので警告する。
+func recordWriter(diskInfo *DiskInfo) {
+	host := writerHost()
+
+	previous, err := os.ReadFile(diskInfo.writerFile())
+	if err == nil {
+		previousHost := strings.TrimSpace(string(previous))
+		if previousHost != "" && previousHost != host {
+			logf.Printf("ディスク%sは前回は別のマシン(%s)から書き込まれています。ディスクIDが重複していないか確認してください。\n",
+				diskInfo.id, previousHost)
+		}
+	}
+
+	err = os.WriteFile(diskInfo.writerFile(), []byte(host+"\n"), 0644)
+	fatalMessageError(ErrIO, err, "書き込み元の記録に失敗しました。: %s\n", diskInfo.writerFile())
+}
+
+// ロックが解除されるのを待つ時間の上限
+const lockWaitTimeout = 10 * time.Minute
+
+// ロックファイルを作成してロックする。
+// 他の書き込み元がロックしていたら、解除されるまで待つ。
+func waitLock(lockFile string) error {
+	err := os.MkdirAll(filepath.Dir(lockFile), 0755)
+	if err != nil {
+		return newError(ErrIO, err, "ロックファイルのディレクトリを作成できませんでした。: %s", filepath.Dir(lockFile))
+	}
+
+	startTime := time.Now()
+	for waiting := false; ; waiting = true {
+		locked, owner, err := createLockFile(lockFile)
+		if err != nil || locked {
+			return err
+		}
+
+		if !waiting {
+			logf.Printf("他の書き込み元がロックしているので、解除されるまで待ちます。: %s (%s)\n", lockFile, owner)
+		}
+		if time.Since(startTime) > lockWaitTimeout {
+			return newError(ErrLocked, nil, "ロックが解除されませんでした。: %s (%s)", lockFile, owner)
+		}
+		if interrupted() {
+			return newError(ErrInterrupted, nil, "ロックが解除されるのを待つのを中断しました。: %s", lockFile)
+		}
+
+		time.Sleep(time.Second)
+	}
+}
+
+// ロックファイルを削除してロックを解除する。
+func unlockFile(lockFile string) {
+	err := os.Remove(lockFile)
+	if err != nil {
+		logf.Printf("ロックファイルの削除に失敗しました。: %s\n", lockFile)
+		logf.Println(err)
+	}
+}
+
+// 統合ハッシュファイルのロックファイルのパスを返す。
+func mergeLockFile(group string) string {
+	return filepath.Join(config.outDir(), group+".merge.lock")
+}
+
+// 統合ハッシュファイルをロックする。
+// 別々に実行したbcbcが同じグループを同時に統合しないように、他の書き込み元が統合中なら終わるまで待つ。
+func lockMerge(group string) error {
+	return waitLock(mergeLockFile(group))
+}
+
+// 統合ハッシュファイルのロックを解除する。
+func unlockMerge(group string) {
+	unlockFile(mergeLockFile(group))
+}
+
+// 他の書き込み元がハッシュ計算中のディスクを探す。
+// ハッシュ計算中のハッシュファイルは書き直す途中かもしれないので統合に使えない。
+// このマシンで異常終了した書き込み元のロックファイルは、ハッシュ計算中ではないので無視する。
+func findLockedDisk(diskIds []string) (string, bool) {
+	for _, diskId := range diskIds {
+		content, err := os.ReadFile((&DiskInfo{id: diskId}).lockFile())
+		if err == nil && !staleLockOwner(strings.TrimSpace(string(content))) {
+			return diskId, true
+		}
+	}
+	return "", false
+}
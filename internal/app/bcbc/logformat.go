@@ -0,0 +1,117 @@
+package bcbc
+
+import (
+	"encoding/json"
+	"fmt"
+	"io"
+	"strings"
+	"sync"
+	"time"
+)
+
+// ログの形式
+const (
+	// LogFormatText 1行のテキスト
+	LogFormatText = "text"
+	// LogFormatJSON 1行ごとにJSONのオブジェクト
+	LogFormatJSON = "json"
+)
+
+// ログのレベル
+const (
+	levelInfo     = "info"
+	levelWarn     = "warn"
+	levelError    = "error"
+	levelProgress = "progress"
+)
+
+// JSON形式のログの1行
+type jsonLogEntry struct {
+	Time  time.Time `json:"time"`
+	Level string    `json:"level"`
+	// 翻訳する前のメッセージの書式。メッセージの種類の識別に使う。
+	Key     string   `json:"key"`
+	Message string   `json:"message"`
+	Args    []string `json:"args,omitempty"`
+	logFields
+}
+
+// JSON形式のログで引数とは別に名前を付けて出力する値。
+// 集計しやすいように、ハッシュ計算とエラーのメッセージでディスクやファイルを特定できる値を出力する。
+type logFields struct {
+	DiskID string  `json:"disk_id,omitempty"`
+	Path   string  `json:"path,omitempty"`
+	Files  *uint64 `json:"files,omitempty"`
+	Bytes  *uint64 `json:"bytes,omitempty"`
+}
+
+// ファイルの数とサイズを0でも出力するためのポインタを返す。
+func logCount(n uint64) *uint64 {
+	return &n
+}
+
+// JSON形式のログの書き込みを1行ずつ行うためのミューテックス
+var jsonLogMutex sync.Mutex
+
+// ログの形式を設定する。
+// 既定値設定ファイルの指定も使うように既定値設定ファイルを読み込んでから、ログを出力する他の初期処理より先に呼ぶ。
+func initLogFormat() {
+	format := *logFormatOption
+	if format == "" {
+		format = LogFormatText
+	}
+	valid := format == LogFormatText || format == LogFormatJSON
+	fatalMessageIf(ErrConfig, !valid, "ログの形式の指定が不正です。: %s\n", format)
+	config.logFormat = format
+}
+
+// JSON形式でログを出力するか判定する。
+func jsonLogs() bool {
+	return config.logFormat == LogFormatJSON
+}
+
+// ログを1行のJSONにして書き込む。
+func writeJSONLog(out io.Writer, level string, key string, message string, args []interface{}, fields logFields) {
+	entry := jsonLogEntry{
+		Time:      time.Now(),
+		Level:     level,
+		Key:       strings.TrimSuffix(key, "\n"),
+		Message:   strings.TrimSuffix(message, "\n"),
+		logFields: fields,
+	}
+	for _, arg := range args {
+		entry.Args = append(entry.Args, fmt.Sprint(arg))
+	}
+
+	line, err := json.Marshal(entry)
+	if err != nil {
+		return
+	}
+
+	jsonLogMutex.Lock()
+	defer jsonLogMutex.Unlock()
+	out.Write(append(line, '\n'))
+}
+
+// 書式からログのレベルを決める。
+func levelOf(format string) string {
+	if strings.HasPrefix(format, "[警告]") {
+		return levelWarn
+	}
+	return levelInfo
+}
+
+// 値からログのレベルを決める。エラーを含んでいればエラーにする。
+func levelOfValues(values []interface{}) string {
+	for _, value := range values {
+		if _, ok := value.(error); ok {
+			return levelError
+		}
+	}
+	if len(values) > 0 {
+		if s, ok := values[0].(string); ok {
+			return levelOf(s)
+		}
+	}
+	return levelInfo
+}
@@ -0,0 +1,175 @@
+package bcbc
+
+import (
+	"container/heap"
+	"os"
+	"sort"
+)
+
+// グループのハッシュファイルを統合ハッシュファイルにまとめる。
+// 全てのディスクの記録を一度にメモリに読み込まないように、ディスクごとに並べ替えて一時ファイルに書き出し、
+// 一時ファイルを先頭から順に読み込みながら併合して書き込む。
+// 同じグループの複数のディスクに同じパスがあれば警告する。
+// 他の書き込み元がハッシュ計算中のディスクがあれば、そちらが終わったときに統合するので統合しない。
+func mergeHashFiles(store HashStore, group string, diskIds []string) {
+	if diskId, locked := findLockedDisk(diskIds); locked {
+		logf.Printf("ディスク%sは他の書き込み元がハッシュ計算中なので、統合ハッシュファイル%sはそちらが終わったときに統合します。\n", diskId, group)
+		return
+	}
+
+	if err := lockMerge(group); err != nil {
+		logf.Println(err)
+		panic(err)
+	}
+	defer unlockMerge(group)
+
+	sortedFiles := make([]*os.File, 0, len(diskIds))
+	defer func() {
+		for _, sortedFile := range sortedFiles {
+			sortedFile.Close()
+			os.Remove(sortedFile.Name())
+		}
+	}()
+
+	for _, diskId := range diskIds {
+		sortedFile, err := writeSortedRecords(store, diskId)
+		fatalMessageError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s\n", diskId)
+		sortedFiles = append(sortedFiles, sortedFile)
+	}
+
+	merger := &recordMerger{diskIds: diskIds}
+	for i, sortedFile := range sortedFiles {
+		err := merger.add(i, newHashRecordReader(sortedFile, sortedFile.Name()))
+		fatalMessageError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s\n", diskIds[i])
+	}
+
+	err := store.writeMerged(group, merger.next)
+	fatalMessageError(ErrIO, err, "統合ハッシュファイルの作成に失敗しました。\n")
+
+	if merger.conflicts > 0 {
+		logf.Printf("統合ハッシュファイル%sで別のディスクとパスが重複した記録が%d件あります。\n", group, merger.conflicts)
+	}
+
+	writeMergeSources(store, group, diskIds)
+}
+
+// ディスクのハッシュファイルをパスの順に並べ替えて一時ファイルに書き出す。
+// 追記で同じパスの記録が複数あれば最後の記録を残す。
+func writeSortedRecords(store HashStore, diskId string) (*os.File, error) {
+	records, err := store.load(diskId)
+	if err != nil {
+		return nil, err
+	}
+
+	sort.SliceStable(records, func(i, j int) bool {
+		return records[i].path < records[j].path
+	})
+
+	deduplicated := records[:0]
+	for i, record := range records {
+		if i+1 < len(records) && records[i+1].path == record.path {
+			continue
+		}
+		deduplicated = append(deduplicated, record)
+	}
+
+	sortedFile, err := os.CreateTemp("", "bcbc-merge-")
+	if err != nil {
+		return nil, err
+	}
+
+	err = writeHashRecords(sortedFile, deduplicated)
+	if err == nil {
+		_, err = sortedFile.Seek(0, 0)
+	}
+	if err != nil {
+		sortedFile.Close()
+		os.Remove(sortedFile.Name())
+		return nil, err
+	}
+
+	return sortedFile, nil
+}
+
+// 並べ替え済みのディスクごとの記録の併合。
+// パス、ハッシュ、ディスクの順に並べるので、同じ内容なら結果はいつも同じになる。
+type recordMerger struct {
+	diskIds []string
+	heads   mergeHeads
+	// 直前に書き込んだ記録とそのディスク
+	last     HashRecord
+	lastDisk int
+	started  bool
+	// 別のディスクの記録とパスが重複した記録の数
+	conflicts int
+}
+
+// 併合中の各ディスクの先頭の記録
+type mergeHead struct {
+	record HashRecord
+	disk   int
+	reader *hashRecordReader
+}
+
+type mergeHeads []mergeHead
+
+func (h mergeHeads) Len() int { return len(h) }
+
+func (h mergeHeads) Less(i, j int) bool {
+	if h[i].record.path != h[j].record.path {
+		return h[i].record.path < h[j].record.path
+	}
+	if h[i].record.hash != h[j].record.hash {
+		return h[i].record.hash < h[j].record.hash
+	}
+	return h[i].disk < h[j].disk
+}
+
+func (h mergeHeads) Swap(i, j int) { h[i], h[j] = h[j], h[i] }
+
+func (h *mergeHeads) Push(x interface{}) { *h = append(*h, x.(mergeHead)) }
+
+func (h *mergeHeads) Pop() interface{} {
+	old := *h
+	head := old[len(old)-1]
+	*h = old[:len(old)-1]
+	return head
+}
+
+// ディスクの記録を併合に加える。
+func (m *recordMerger) add(disk int, reader *hashRecordReader) error {
+	record, found, err := reader.next()
+	if err != nil || !found {
+		return err
+	}
+	heap.Push(&m.heads, mergeHead{record, disk, reader})
+	return nil
+}
+
+// 併合した次の記録を返す。
+func (m *recordMerger) next() (HashRecord, bool, error) {
+	if m.heads.Len() == 0 {
+		return HashRecord{}, false, nil
+	}
+
+	head := heap.Pop(&m.heads).(mergeHead)
+	if err := m.add(head.disk, head.reader); err != nil {
+		return HashRecord{}, false, err
+	}
+
+	if m.started && head.record.path == m.last.path && head.disk != m.lastDisk {
+		m.conflicts++
+		state := "ハッシュは同じ"
+		if head.record.hash != m.last.hash {
+			state = "ハッシュが異なる"
+		}
+		logf.Printf("同じパスが複数のディスクにあります(%s)。: %s (%s, %s)\n",
+			state, head.record.path, m.diskIds[m.lastDisk], m.diskIds[head.disk])
+	}
+
+	m.last = head.record
+	m.lastDisk = head.disk
+	m.started = true
+
+	return head.record, true, nil
+}
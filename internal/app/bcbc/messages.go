@@ -0,0 +1,149 @@
+package bcbc
+
+import (
+	"fmt"
+	"log"
+	"os"
+	"strings"
+)
+
+// メッセージの言語
+const (
+	// LangJapanese 日本語
+	LangJapanese = "ja"
+	// LangEnglish 英語
+	LangEnglish = "en"
+)
+
+// ログとエラーのメッセージの言語
+var messageLanguage = LangJapanese
+
+// メッセージの言語を決める。
+// -langオプション、環境変数LC_ALL、LC_MESSAGES、LANGの順に見て、日本語以外の言語なら英語にする。
+// どれも設定されていないかCやPOSIXなら日本語のままにする。
+func initLanguage() {
+	lang := *langOption
+	if lang == "" {
+		for _, name := range []string{"LC_ALL", "LC_MESSAGES", "LANG"} {
+			if value := os.Getenv(name); value != "" {
+				lang = value
+				break
+			}
+		}
+	}
+
+	switch {
+	case lang == "" || lang == "C" || lang == "POSIX" || strings.HasPrefix(lang, "C."):
+		messageLanguage = LangJapanese
+	case strings.HasPrefix(lang, LangJapanese):
+		messageLanguage = LangJapanese
+	default:
+		messageLanguage = LangEnglish
+	}
+}
+
+// メッセージの書式をメッセージの言語に翻訳する。カタログになければそのまま返す。
+func tr(format string) string {
+	if messageLanguage == LangEnglish {
+		if message, found := englishMessages[format]; found {
+			return message
+		}
+	}
+	return format
+}
+
+// 数える語の単数形と複数形をメッセージの言語に翻訳する。
+func trUnits(one string, other string) (string, string) {
+	if messageLanguage == LangEnglish {
+		if units, found := englishUnits[other]; found {
+			return units[0], units[1]
+		}
+	}
+	return one, other
+}
+
+// 日本語と翻訳の組からメッセージカタログを作成する。
+func newMessageCatalog(pairs [][2]string) map[string]string {
+	catalog := make(map[string]string, len(pairs))
+	for _, pair := range pairs {
+		catalog[pair[0]] = pair[1]
+	}
+	return catalog
+}
+
+// メッセージを翻訳して出力するロガー
+type messageLogger struct {
+	*log.Logger
+	// JSON形式のログに付ける値
+	fields logFields
+}
+
+// ロガーをメッセージを翻訳するロガーで包む。
+func newMessageLogger(logger *log.Logger) *messageLogger {
+	return &messageLogger{Logger: logger}
+}
+
+// JSON形式のログに値を付けて出力するロガーを返す。テキスト形式では値を出力しない。
+func (l *messageLogger) with(fields logFields) *messageLogger {
+	return &messageLogger{Logger: l.Logger, fields: fields}
+}
+
+func (l *messageLogger) Printf(format string, values ...interface{}) {
+	l.printf(levelOf(format), format, values...)
+}
+
+func (l *messageLogger) Println(values ...interface{}) {
+	if jsonLogs() {
+		l.printValues(values, fmt.Sprintln(trValues(values)...))
+		return
+	}
+	l.Logger.Println(trValues(values)...)
+}
+
+func (l *messageLogger) Print(values ...interface{}) {
+	if jsonLogs() {
+		l.printValues(values, fmt.Sprint(trValues(values)...))
+		return
+	}
+	l.Logger.Print(trValues(values)...)
+}
+
+// レベルを指定して書式のメッセージを出力する。
+// JSON形式なら翻訳する前の書式をキーにして、引数も別に出力する。
+func (l *messageLogger) printf(level string, format string, values ...interface{}) {
+	if jsonLogs() {
+		writeJSONLog(l.Writer(), level, format, fmt.Sprintf(tr(format), values...), values, l.fields)
+		return
+	}
+	l.Logger.Printf(tr(format), values...)
+}
+
+// 値を並べたメッセージをJSON形式で出力する。
+// 先頭の値が文字列ならキーにして、残りを引数にする。
+func (l *messageLogger) printValues(values []interface{}, message string) {
+	key := ""
+	args := values
+	if len(values) > 0 {
+		if s, ok := values[0].(string); ok {
+			key = s
+			args = values[1:]
+		}
+	}
+	writeJSONLog(l.Writer(), levelOfValues(values), key, message, args, l.fields)
+}
+
+// 文字列の値を翻訳する。
+func trValues(values []interface{}) []interface{} {
+	if messageLanguage == LangJapanese {
+		return values
+	}
+
+	result := make([]interface{}, len(values))
+	for i, value := range values {
+		if s, ok := value.(string); ok {
+			value = tr(s)
+		}
+		result[i] = value
+	}
+	return result
+}
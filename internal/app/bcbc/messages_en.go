@@ -0,0 +1,678 @@
+package bcbc
+
+// 英語のメッセージカタログ。
+// 日本語のメッセージの書式をキーにして英語の書式を引く。カタログにないメッセージは日本語のまま出力する。
+// 引数の順番が変わるときは%[2]sのように引数の番号を指定する。
+var englishMessages = newMessageCatalog([][2]string{
+	// background.go
+	{"優先度を下げられませんでした。通常の優先度で実行します。", "Could not lower the priority. Running with the normal priority."},
+	{"バックグラウンドモードで実行します。", "Running in background mode."},
+
+	// bench.go
+	{"測定するパスを1つ指定してください。\n", "Specify one path to measure.\n"},
+	{"テストファイルのサイズの指定が不正です。\n", "Invalid test file size.\n"},
+	{"性能の測定を開始します。", "Starting the benchmark."},
+	{"性能の測定を終了しました。", "Finished the benchmark."},
+	{"ハッシュ計算(%s): %s\n", "Hashing (%s): %s\n"},
+	{"測定するパスが見つかりません。: %s\n", "The path to measure was not found.: %s\n"},
+	{"測定するファイルが空です。: %s\n", "The file to measure is empty.: %s\n"},
+	{"テストファイルの読み込みに失敗しました。: %s\n", "Failed to read the test file.: %s\n"},
+	{"読み込み(バッファ%s): %s\n", "Reading (%s buffer): %s\n"},
+	{"%sのテストファイルを書き込みます。: %s\n", "Writing a %s test file.: %s\n"},
+	{"テストファイルを作成できませんでした。: %s\n", "Could not create the test file.: %s\n"},
+	{"テストファイルの書き込みに失敗しました。: %s\n", "Failed to write the test file.: %s\n"},
+	{"最も速い読み込みバッファ: %s (%s)\n", "Fastest read buffer: %s (%s)\n"},
+	{"既定の読み込みバッファ(%s)との差は誤差の範囲です。\n", "The difference from the default read buffer (%s) is within the margin of error.\n"},
+	{"ハッシュ計算が読み込みより遅いので、CPUが律速になります。巨大なファイルが多ければmd5-treeかsha256-treeで複数のコアを使えます。", "Hashing is slower than reading, so the CPU is the bottleneck. If there are many huge files, md5-tree or sha256-tree can use multiple cores."},
+	{"推奨する同時にハッシュ計算するディスクの数: -max-parallel-disks %d (%d個のCPUコア)\n", "Recommended number of disks to hash at once: -max-parallel-disks %d (%d CPU cores)\n"},
+
+	// calc.go
+	{"ハッシュ計算を開始します。", "Starting hash calculation."},
+	{"ハッシュ計算を終了しました。", "Finished hash calculation."},
+	{"diskファイルが見つかりませんでした。\n", "No disk file was found.\n"},
+	{"ディスク(%s)のハッシュ計算中に問題が発生しました。\n", "A problem occurred while hashing disk (%s).\n"},
+	{"進捗の監視中に問題が発生しました。", "A problem occurred while monitoring progress."},
+	{"ハッシュ計算を中断しました。\n", "Hash calculation was interrupted.\n"},
+	{"ハッシュファイルの統合を開始します。", "Starting hash file integration."},
+	{"ハッシュファイルの統合を終了しました。", "Finished hash file integration."},
+	{"出力ファイルの一覧取得に失敗しました。\n", "Failed to list the output files.\n"},
+
+	// command.go
+	{"統合ハッシュファイルが最新か確認する。-auto-mergeで古いものを統合し直す。", "Check whether the merged hash files are up to date. -auto-merge merges stale ones again."},
+	{"進捗ログからディスクごとの処理の経過を再構成して表示する。", "Reconstruct and show the progress of each disk from the progress log."},
+	{"ハッシュファイルの記録とファイルの現在のハッシュを比較する。", "Compare the recorded hashes with the current hashes of the files."},
+	{"BCBCホームディレクトリの構成、設定、ロックファイル、ハッシュファイルを診断する。", "Diagnose the BCBC home directory layout, settings, lock files and hash files."},
+	{"常駐してスケジュール設定ファイルに従ってハッシュ計算と検証を定期的に実行する。", "Stay resident and run hashing and verification on the schedule in the schedule file."},
+	{"ディスクを監視して、追加や変更されたファイルのハッシュをハッシュファイルに追記する。", "Watch disks and append hashes of added or changed files to the hash files."},
+	{"ディスクルートにdiskファイルを作成してディスク登録簿に登録する。", "Create a disk file in a disk root and add the disk to the registry."},
+	{"ディスク登録簿の一覧表示(list)、詳細表示(show)、説明と保管場所の設定(set)を行う。", "List the disk registry (list), show one disk (show), or set its description and location (set)."},
+	{"パスかハッシュが一致する記録を探して、どのディスクにあるか表示する。", "Find records whose path or hash matches and show which disks hold them."},
+	{"ハッシュファイルの形式を検証し、不正な行と重複した記録を削除する。", "Check the hash file format and remove invalid lines and duplicate records."},
+	{"BCBCホームディレクトリと設定ファイルの雛形を作成し、実行できる環境か確認する。", "Create the BCBC home directory and settings templates, and check the environment."},
+	{"フィルター設定ファイルを検証し、指定したパスに一致するフィルターの行を表示する。", "Check the filter settings file and show the filter lines that match the given paths."},
+	{"ディスクのスナップショットの一覧と違いを表示する。", "List the snapshots of a disk and show their differences."},
+	{"ディスクの2つの時点の記録を比べて、追加、削除、ハッシュが変わったファイルを表示する。", "Compare the records of a disk at two points in time and show added, removed and changed files."},
+	{"ハッシュファイルに署名するEd25519の鍵を作成する。", "Create an Ed25519 key for signing hash files."},
+	{"他のマシンのbcbcのハッシュファイルを集める収集サーバーを開始する。", "Start a collection server that gathers hash files from bcbc on other machines."},
+	{"全てのディスクから同じハッシュのファイルを探して、減らせるバイト数が多い順に表示する。", "Find files with the same hash on all disks and show them by the bytes that could be saved."},
+	{"ディスクごとと全体のファイル数、合計サイズ、重複の割合などを表示する。", "Show file counts, total sizes, duplicate ratios and more per disk and overall."},
+	{"記録されたファイルの一部を無作為に抽出して検証する。", "Verify a random sample of the recorded files."},
+	{"しばらく検証していないファイルと一度も検証していないファイルを数える。", "Count files not verified for a while and files never verified."},
+	{"ハッシュを計算しながらファイルをコピーし、コピー先を読み直して確かめる。", "Copy files while hashing them, then read the copies back to check them."},
+	{"2つのディレクトリのファイルを読み込んで比べる。", "Read and compare the files in two directories."},
+	{"ハッシュ計算と読み込みのスループットを測り、推奨する設定を表示する。", "Measure hashing and read throughput and show recommended settings."},
+	{"既知のハッシュと読み込みの経路ごとのハッシュ計算を確かめる。", "Check known hash values and hashing through each read path."},
+
+	// comparetrees.go
+	{"比べる2つのディレクトリを指定してください。\n", "Specify two directories to compare.\n"},
+	{"ディレクトリが見つかりません。: %s\n", "Directory not found.: %s\n"},
+	{"比較を開始します。: %s と %s\n", "Starting comparison.: %s and %s\n"},
+	{"比較を終了しました。", "Finished comparison."},
+	{"%sにだけあります。: %s\n", "Only in %s.: %s\n"},
+	{"比較を中断しました。\n", "Comparison was interrupted.\n"},
+	{"比較結果: 共通%s 内容の相違%s 片方にだけある%s\n", "Comparison result: common %s, content differs %s, only on one side %s\n"},
+
+	// compress.go
+	{"圧縮形式の指定が不正です。: %s\n", "Invalid compression format.: %s\n"},
+
+	// confirm.go
+	{"%sから%d件の記録を削除します。\n", "Removing %[2]d records from %[1]s.\n"},
+	{"  ...他%d件\n", "  ...and %d more\n"},
+	{"%sの記録は削除せずに残します。\n", "Keeping the records of %s.\n"},
+
+	// copy.go
+	{"コピー元のディスクルートとコピー先のディレクトリを指定してください。\n", "Specify the source disk root and the destination directory.\n"},
+	{"コピー先のディレクトリを作成できませんでした。: %s\n", "Could not create the destination directory.: %s\n"},
+	{"コピー元とコピー先が同じディスクIDです。: %s\n", "The source and the destination have the same disk ID.: %s\n"},
+	{"ハッシュファイルをロックできませんでした。: %s\n", "Could not lock the hash file.: %s\n"},
+	{"コピーを開始します。: %s -> %s\n", "Starting copy.: %s -> %s\n"},
+	{"コピーを終了しました。", "Finished copy."},
+	{"コピーを中断しました。\n", "Copy was interrupted.\n"},
+	{"コピーできませんでした。: %s\n", "Could not copy.: %s\n"},
+	{"コピー先のハッシュがコピー元と一致しません。: %s\n", "The hash of the destination does not match the source.: %s\n"},
+	{"%sをコピーしました。(既にあったので%sをコピーせず、%sで問題が発生しました。)\n", "Copied %s. (%s already existed and were not copied, problems occurred with %s.)\n"},
+	{"ハッシュファイルの書き込みに失敗しました。: %s\n", "Failed to write the hash file.: %s\n"},
+
+	// corpusstats.go
+	{"表示する数の指定が不正です。\n", "Invalid number to show.\n"},
+	{"ハッシュファイルの読み込みに失敗しました。: %s\n", "Failed to read the hash file.: %s\n"},
+	{"%-6s %s 最終ハッシュ計算: %s\n", "%-6s %s last hashed: %s\n"},
+	{"全体: ディスク%d台 %s\n", "Total: %d disks %s\n"},
+	{"サイズが大きいファイル:", "Largest files:"},
+	{"拡張子ごとのファイル数:", "Files per extension:"},
+
+	// daemon.go
+	{"スケジュール設定ファイルが見つかりません。: %s\n", "Schedule file not found.: %s\n"},
+	{"スケジュール設定ファイルの形式が不正です。: %d行目: %s\n", "Invalid format in the schedule file.: line %d: %s\n"},
+	{"スケジュール設定ファイルの操作が不正です。: %d行目: %s\n", "Invalid operation in the schedule file.: line %d: %s\n"},
+	{"スケジュール設定ファイルの間隔が不正です。: %d行目: %s\n", "Invalid interval in the schedule file.: line %d: %s\n"},
+	{"スケジュール設定ファイルが読み込めませんでした。: %s\n", "Could not read the schedule file.: %s\n"},
+	{"スケジュール設定ファイルにスケジュールがありません。: %s\n", "The schedule file has no schedules.: %s\n"},
+	{"スケジュールの実行記録が読み込めなかったので、全てのスケジュールを実行します。: %s\n", "Could not read the schedule run record, so all schedules will run.: %s\n"},
+	{"スケジュールの実行記録の書き込みに失敗しました。: %s\n", "Failed to write the schedule run record.: %s\n"},
+	{"確認間隔の指定が不正です。: %s\n", "Invalid check interval.: %s\n"},
+	{"常駐を開始します。スケジュール: %d件\n", "Starting daemon. Schedules: %d\n"},
+	{"常駐を終了しました。", "Daemon stopped."},
+	{"スケジュールを実行します。: %s\n", "Running schedule.: %s\n"},
+	{"スケジュールの実行中に問題が発生しました。: %s\n", "A problem occurred while running the schedule.: %s\n"},
+
+	// defaults.go
+	{"既定値設定ファイルが読み込めませんでした。: %s\n", "Could not read the defaults file.: %s\n"},
+	{"既定値設定ファイルの形式が不正です。: %d行目: %s\n", "Invalid format in the defaults file.: line %d: %s\n"},
+	{"既定値設定ファイルに不明な項目があります。: %d行目: %s\n", "Unknown item in the defaults file.: line %d: %s\n"},
+	{"既定値設定ファイルの値が不正です。: %d行目: %s\n", "Invalid value in the defaults file.: line %d: %s\n"},
+
+	// directio.go
+	{"直接I/Oで読み込めないので、通常の読み込みに切り替えます。: %s\n", "Direct I/O is not available, switching to normal reads.: %s\n"},
+
+	// disconnect.go
+	{"ディスクが取り外されました。: %s", "The disk was disconnected.: %s"},
+	{"読み込みエラーが%d回続いたのでディスクが取り外されたとみなします。: %s", "Read errors occurred %d times in a row, so the disk is assumed to be disconnected.: %s"},
+
+	// disk.go
+	{"カレントディレクトリが取得できませんでした。\n", "Could not get the current directory.\n"},
+	{"走査範囲にはディスクルートの中の相対パスを指定してください。: %s\n", "Specify the directories to scan as relative paths inside the disk root.: %s\n"},
+	{"diskファイルが読み込めませんでした。\n", "Could not read the disk file.\n"},
+	{"diskファイルの内容が不正です。: %s\n", "Invalid content in the disk file.: %s\n"},
+	{"diskファイルのthrottleの指定が不正です。: %s\n", "Invalid throttle in the disk file.: %s\n"},
+	{"[警告] diskファイルに「項目=値」ではない行があるので読み飛ばします。: %s : %d行目\n", "[WARNING] Skipping a line that is not \"key=value\" in the disk file.: %s : line %d\n"},
+	{"diskファイルに不明な項目があります。: %s : %s\n", "Unknown item in the disk file.: %s : %s\n"},
+	{"diskファイルのalgorithmに-algorithmで指定していないアルゴリズムがあります。: %s : %s\n", "The algorithm in the disk file is not one given by -algorithm.: %s : %s\n"},
+	{"diskファイルで指定されたフィルター設定ファイルが読み込めませんでした。: %s\n", "Could not read the filter settings file given in the disk file.: %s\n"},
+	{"フィルター設定ファイルの形式が不正です。: %s : %d行目: %s\n", "Invalid format in the filter settings file.: %s : line %d: %s\n"},
+	{"ディスクID%sが複数のディスクルートにあります。: %s\n", "Disk ID %s is in multiple disk roots.: %s\n"},
+	{"ディスクIDが重複しています。\n", "Duplicate disk IDs.\n"},
+
+	// dupes.go
+	{"重複しているファイルの組が%d組あります。重複を除けば%s減らせます。\n", "There are %d groups of duplicate files. Removing the duplicates would save %s.\n"},
+
+	// doctor.go
+	{"[警告] ", "[WARNING] "},
+	{"問題は見つかりませんでした。", "No problems were found."},
+	{"%s件の問題が見つかりました。\n", "Found %s problems.\n"},
+	{"ディレクトリがありません。: %s\n", "Directory does not exist.: %s\n"},
+	{"ディレクトリではありません。: %s\n", "Not a directory.: %s\n"},
+	{"フィルター設定に対象にするパターンがないので、ハッシュ計算するファイルがありません。\n", "The filter settings have no inclusion patterns, so there are no files to hash.\n"},
+	{"ロックファイルが残っています。実行中でなければ削除してください。: %s (%s)\n", "A lock file remains. Delete it if bcbc is not running.: %s (%s)\n"},
+	{"ハッシュファイル %s (%s): %s件 v%d %s\n", "Hash file %s (%s): %s records v%d %s\n"},
+	{"古い形式のハッシュファイルです。次のハッシュ計算かfsck-hashfileでv%dに書き直されます。: %s (%s)\n", "Old format hash file. It will be rewritten to v%d by the next hash calculation or fsck-hashfile.: %s (%s)\n"},
+	{"読み込めない範囲があった不完全なハッシュがあります。: %s (%s) %s件\n", "Incomplete hashes with unreadable ranges exist.: %s (%s) %s\n"},
+	{"統合ハッシュファイルが古くなっています。statusサブコマンドの-auto-mergeで統合し直してください。: %s (%s)\n", "The integrated hash file is outdated. Re-integrate it with the -auto-merge option of the status subcommand.: %s (%s)\n"},
+	{"実行履歴が読み込めませんでした。: %s\n", "Could not read the run history.: %s\n"},
+	{"ディスク%sは%d日間ハッシュ計算していません。\n", "Disk %s has not been hashed for %d days.\n"},
+
+	// encryption.go
+	{"環境変数%sにパスフレーズを設定してください。\n", "Set the passphrase in the environment variable %s.\n"},
+	{"暗号化の指定が不正です。: %s\n", "Invalid encryption setting.: %s\n"},
+	{"暗号化の鍵が不正です。\n", "Invalid encryption key.\n"},
+	{"暗号化の鍵ファイルの形式が不正です。: %s\n", "Invalid format of the encryption key file.: %s\n"},
+	{"暗号化の鍵ファイルが読み込めませんでした。: %s\n", "Could not read the encryption key file.: %s\n"},
+	{"暗号化の鍵を作成できませんでした。\n", "Could not create the encryption key.\n"},
+	{"設定ディレクトリを作成できませんでした。: %s\n", "Could not create the config directory.: %s\n"},
+	{"暗号化の鍵ファイルを保存できませんでした。: %s\n", "Could not save the encryption key file.: %s\n"},
+	{"暗号化の鍵ファイルを作成しました。なくすとハッシュファイルを読めなくなるので控えておいてください。: %s\n", "Created the encryption key file. Keep a copy, since the hash files cannot be read without it.: %s\n"},
+
+	// environment.go
+	{"実行履歴の書き込みに失敗しました。: %s\n", "Failed to write the run history.: %s\n"},
+
+	// errorreport.go
+	{"エラーレポートを削除できませんでした。: %s\n", "Could not delete the error report.: %s\n"},
+	{"エラーレポートの書き込みに失敗しました。: %s\n", "Failed to write the error report.: %s\n"},
+
+	// explain.go
+	{"フィルターの判定レポートを作成できませんでした。: %s\n", "Could not create the filter decision report.: %s\n"},
+	{"フィルターの判定を記録します。: %s\n", "Recording filter decisions.: %s\n"},
+	{"フィルターの判定レポートの書き込みに失敗しました。: %s\n", "Failed to write the filter decision report.: %s\n"},
+	{"フィルターで%d個のファイルを対象外にしました。: %s\n", "Excluded %d files by filters.: %s\n"},
+
+	// filtercheck.go
+	{"フィルター設定ファイルが見つかりません。\n", "Filter config file not found.\n"},
+	{"フィルター設定ファイルが読み込めませんでした。\n", "Could not read the filter config file.\n"},
+	{"形式が不正です。: %d行目: %s\n", "Invalid format.: line %d: %s\n"},
+	{"%d件のフィルターを読み込みました。形式が不正な行は%d行です。\n", "Loaded %d filters. %d lines have an invalid format.\n"},
+	{"対象外: %s (マッチするフィルターがありません)\n", "Excluded: %s (no matching filter)\n"},
+	{"対象: %s (%s: +%s)\n", "Included: %s (%s: +%s)\n"},
+	{"対象外: %s (%s: -%s)\n", "Excluded: %s (%s: -%s)\n"},
+	{"フィルター設定ファイルに形式が不正な行があります。\n", "The filter config file has lines with an invalid format.\n"},
+
+	// find.go
+	{"パスのパターンか-hashのどちらか1つを指定してください。\n", "Specify either a path pattern or -hash.\n"},
+	{"パターンが不正です。: %s\n", "Invalid pattern.: %s\n"},
+	{"統合ハッシュファイル%s (%s): %s%s\n", "Integrated hash file %s (%s): %s%s\n"},
+	{"%d件見つかりました。\n", "Found %d.\n"},
+
+	// fsck.go
+	{"ディスクIDを1つ指定してください。\n", "Specify one disk ID.\n"},
+	{"ディスクIDの形式が不正です。: %s\n", "Invalid disk ID format.: %s\n"},
+	{"ハッシュファイルの読み込みに失敗しました。: %s (%s)\n", "Failed to read the hash file.: %s (%s)\n"},
+	{"形式が不正な行を削除します。: %s (%s) %d行目\n", "Removing a line with an invalid format.: %s (%s) line %d\n"},
+	{"パスを正規化します。: %s -> %s\n", "Normalizing the path.: %s -> %s\n"},
+	{"%s (%s): 記録%d件 不正な行%d件 重複%d件 正規化%d件\n", "%s (%s): records %d, invalid lines %d, duplicates %d, normalized %d\n"},
+	{"修正する必要はありません。: %s (%s)\n", "No repair needed.: %s (%s)\n"},
+	{"ハッシュファイルの書き直しに失敗しました。: %s (%s)\n", "Failed to rewrite the hash file.: %s (%s)\n"},
+	{"ハッシュファイルを書き直しました。: %s (%s)\n", "Rewrote the hash file.: %s (%s)\n"},
+
+	// group.go
+	{"グループ設定ファイルが読み込めませんでした。: %s\n", "Could not read the group config file.: %s\n"},
+	{"グループ設定ファイルのパターンが不正です。: %d行目: %s\n", "Invalid pattern in the group config file.: line %d: %s\n"},
+	{"グループ設定ファイルのパターンにキャプチャがありません。: %d行目: %s\n", "The pattern in the group config file has no capture group.: line %d: %s\n"},
+	{"グループ設定ファイルのディスクIDが不正です。: %d行目: %s\n", "Invalid disk ID in the group config file.: line %d: %s\n"},
+	{"ディスクIDが複数のグループに指定されています。: %d行目: %s\n", "A disk ID is assigned to multiple groups.: line %d: %s\n"},
+	{"グループ設定ファイルの形式が不正です。: %d行目: %s\n", "Invalid format in the group config file.: line %d: %s\n"},
+	{"グループ名が不正です。英字で始まり英字、_、-だけにしてください。: %s\n", "Invalid group name. It must start with a letter and contain only letters, _ and -.: %s\n"},
+	{"ディスクIDからグループ名を取り出せませんでした。: %s\n", "Could not get the group name from the disk ID.: %s\n"},
+
+	// hash.go
+	{"%sの新しいファイルを計算しました。(全%s中%.1f%%は計算済み%sでした。): %s\n", "Hashed new files: %[1]s. (%[3].1f%% of %[2]s, %[4]s, were already hashed.): %[5]s\n"},
+	{"ディスク(%s)は指定されたディレクトリだけを走査します。: %s\n", "Scanning only the given directories on disk (%s).: %s\n"},
+	{"前回エラーになった%sを再試行します。: %s\n", "Retrying %s that failed last time.: %s\n"},
+	{"[警告] ディスク(%s)でエラーが%d件に達しました。ディスクが故障しかけている可能性があります。\n", "[WARNING] Disk (%s) reached %d errors. The disk may be failing.\n"},
+	{"エラーが多すぎるのでハッシュ計算を打ち切りました。: %s", "Hash calculation was aborted due to too many errors.: %s"},
+	{"ハッシュ計算中にエラーが発生しました。: %s\n", "An error occurred during hash calculation.: %s\n"},
+	{"読み込めない範囲があったので不完全なハッシュを記録します。: %s\n", "Recording an incomplete hash because some ranges could not be read.: %s\n"},
+	{"ハッシュファイルの書き込みに失敗しました。: %s", "Failed to write the hash file.: %s"},
+	{"出力先の空き容量が不足しています。空きができるまで待機します。: %s\n", "The output destination is out of space. Waiting until space is available.: %s\n"},
+	{"出力先に空きができたので書き込みを再開しました。: %s\n", "Space is available in the output destination, resumed writing.: %s\n"},
+	{"ハッシュ対象ファイルの読み込みに失敗しました。:", "Failed to read the file to hash.:"},
+	{"ハッシュ計算を中断しました。: %s", "Hash calculation was interrupted.: %s"},
+	{"読み込めない範囲を読み飛ばします。: %s : %dバイト目から\n", "Skipping an unreadable range.: %s : from byte %d\n"},
+	{"ジャーナルの書き込みに失敗しました。: %s\n", "Failed to write the journal.: %s\n"},
+	{"ジャーナルをハッシュファイルに取り込めませんでした。: %s\n", "Could not fold the journal into the hash file.: %s\n"},
+
+	// help.go
+	{"使い方を表示する。-longでオプション、環境変数、ファイルの形式も表示する。", "Show usage. -long also shows options, environment variables and file formats."},
+	{"BCBCホームディレクトリ。-homeが指定されていなければ使う。", "BCBC home directory, used when -home is not given."},
+	{"-encrypt=passphraseのときの暗号化のパスフレーズ。", "Encryption passphrase for -encrypt=passphrase."},
+	{"serveの収集サーバーとエージェントが認証に使うトークン。", "Token the serve collection server and agents use to authenticate."},
+	{"-store=webdavのときのBasic認証のユーザー名。", "Basic authentication user name for -store=webdav."},
+	{"-store=webdavのときのBasic認証のパスワード。", "Basic authentication password for -store=webdav."},
+	{"-store=s3のときのアクセスキー。", "Access key for -store=s3."},
+	{"-store=s3のときのシークレットキー。", "Secret key for -store=s3."},
+	{"-store=s3のときの一時的な認証情報のセッショントークン。", "Session token of temporary credentials for -store=s3."},
+	{"-langが指定されていなければメッセージの言語を決める。", "Decide the message language when -lang is not given."},
+	{"サブコマンドが見つかりません。: %s\n", "No such subcommand.: %s\n"},
+	{"サブコマンドのオプションはbcbc %s -hで表示します。\n", "Run bcbc %s -h to show the subcommand options.\n"},
+	{"使い方:\n", "Usage:\n"},
+	{"  bcbc [オプション] [ディスクルート[:サブディレクトリ,...]...]\n", "  bcbc [options] [disk-root[:subdir,...]...]\n"},
+	{"  bcbc [オプション] <サブコマンド> [サブコマンドのオプション] [引数...]\n", "  bcbc [options] <subcommand> [subcommand options] [arguments...]\n"},
+	{"\nサブコマンド:\n", "\nSubcommands:\n"},
+	{"\n全てのオプションとファイルの形式はbcbc help -longで表示します。\n", "\nRun bcbc help -long to show all options and file formats.\n"},
+	{"\nオプション:\n", "\nOptions:\n"},
+	{"\n環境変数:\n", "\nEnvironment variables:\n"},
+	{"\nファイル:\n", "\nFiles:\n"},
+	{"        (既定値: %s)\n", "        (default: %s)\n"},
+	{"<ディスクルート>/disk", "<disk root>/disk"},
+	{"<ディスクID>", "<disk ID>"},
+	{"<ディスクID>.journal", "<disk ID>.journal"},
+	{"1行目にディスクIDを書き、2行目以降に「項目=値」でthrottle、label、group、filter、algorithmを書く。全ての行を「項目=値」にしてディスクIDをidの項目に書いてもよい。", "The disk ID on the first line, then throttle, label, group, filter and algorithm as key=value lines. Alternatively every line is key=value with the disk ID in the id item."},
+	{"1行に1つ、+(含める)か-(除外する)に続けてディスクルートからの相対パスの正規表現を書く。上から順に判定して最初に一致した行で決める。", "One rule per line: + (include) or - (exclude) followed by a regular expression for the path relative to the disk root. The first matching line from the top decides."},
+	{"「オプション名=値」でコマンドラインオプションの既定値を書く。", "Default values of command line options as option=value lines."},
+	{"「pattern <正規表現>」でディスクIDからグループ名を取り出し、「group <グループ名> <ディスクID>...」でディスクを明示的にグループに入れる。", "\"pattern <regexp>\" extracts the group name from disk IDs, and \"group <name> <disk ID>...\" puts disks into a group explicitly."},
+	{"ハッシュ計算の結果の通知先を書く。", "Where to send notifications of hashing results."},
+	{"daemonが定期的に実行するハッシュ計算と検証の予定を書く。", "The schedule of hashing and verification that daemon runs."},
+	{"ハッシュファイル。1行目は「# bcbc-hashfile <バージョン>」、続く「# 項目: 値」の行がヘッダーで、記録はエスケープしたパスとハッシュをタブで区切る。", "Hash file. The first line is \"# bcbc-hashfile <version>\", the following \"# key: value\" lines are the header, and each record is an escaped path and a hash separated by a tab."},
+	{"ハッシュ計算中に記録を追記するジャーナル。ハッシュファイルと同じ形式で、ハッシュ計算の最後にハッシュファイルに取り込んで削除する。", "Journal that records are appended to during hash calculation. It has the same format as the hash file and is folded into the hash file and removed at the end of hash calculation."},
+	{"ディスク登録簿。ディスクごとのラベル、グループ、説明、保管場所、最後にハッシュ計算と検証をした日時をJSONで記録する。", "Disk registry. Records each disk's label, group, description, location and last hash and verify times as JSON."},
+
+	// historydiff.go
+	{"ディスクIDと比べる2つの時点を指定してください。\n", "Specify a disk ID and two points in time to compare.\n"},
+	{"スナップショットの一覧取得に失敗しました。\n", "Failed to list the snapshots.\n"},
+	{"ディスク%sのスナップショットがありません。\n", "Disk %s has no snapshots.\n"},
+	{"指定された時点のスナップショットが見つかりません。: %s\n", "No snapshot found for the specified time.: %s\n"},
+	{"スナップショットの読み込みに失敗しました。: %s\n", "Failed to read the snapshot.: %s\n"},
+
+	// ignore.go
+	{"除外設定ファイルが読み込めませんでした。: %s\n", "Could not read the exclusion file.: %s\n"},
+	{"除外設定ファイルの形式が不正な行を無視します。: %s : %d行目\n", "Ignoring a line with an invalid format in the exclusion file.: %s : line %d\n"},
+
+	// index.go
+	{"索引をロックできませんでした。: %s\n", "Could not lock the index.: %s\n"},
+	{"索引が読み込めませんでした。: %s\n", "Could not read the index.: %s\n"},
+	{"索引の形式が不正です。削除すると次のハッシュ計算で作り直します。: %s\n", "Invalid index format. Delete it and it will be rebuilt by the next hash calculation.: %s\n"},
+	{"索引の書き込みに失敗しました。: %s\n", "Failed to write the index.: %s\n"},
+	{"索引を更新しました。(ハッシュファイル%d個中%d個を登録し直しました。)\n", "Updated the index. (Re-registered %[2]d of %[1]d hash files.)\n"},
+
+	// init.go
+	{"環境変数%sが設定されておらず、ユーザーのホームディレクトリも取得できませんでした。\n", "The environment variable %s is not set and the user home directory could not be determined.\n"},
+	{"BCBCホームディレクトリを作成しました。: %s\n", "Created the BCBC home directory.: %s\n"},
+	{"ハッシュ計算の対象は%sで設定してください。\n", "Configure the files to hash in %s.\n"},
+	{"サイズの単位系の指定が不正です。: %s\n", "Invalid size unit system.: %s\n"},
+	{"最大深さの指定が不正です。: %d\n", "Invalid maximum depth.: %d\n"},
+	{"エラーの上限の指定が不正です。: %d\n", "Invalid error limit.: %d\n"},
+	{"報告数の指定が不正です。: %d\n", "Invalid report count.: %d\n"},
+	{"メモリマップで読み込む最小のファイルサイズの指定が不正です。: %d\n", "Invalid minimum file size for memory mapping.: %d\n"},
+	{"スループットの上限の指定が不正です。: %g\n", "Invalid throughput limit.: %g\n"},
+	{"同時にハッシュ計算するディスクの最大数の指定が不正です。: %d\n", "Invalid maximum number of disks to hash in parallel.: %d\n"},
+	{"メトリクスのポートの指定が不正です。: %d\n", "Invalid metrics port.: %d\n"},
+	{"再試行回数の指定が不正です。: %d\n", "Invalid number of retries.: %d\n"},
+	{"再試行の待ち時間の指定が不正です。: %s\n", "Invalid retry wait time.: %s\n"},
+	{"シンボリックリンクの扱いの指定が不正です。: %s\n", "Invalid symbolic link handling.: %s\n"},
+	{"ハッシュファイルの保存先の指定が不正です。: %s\n", "Invalid hash file destination.: %s\n"},
+	{"ハッシュアルゴリズムが指定されていません。\n", "No hash algorithm is specified.\n"},
+	{"ハッシュアルゴリズムの指定が不正です。: %s\n", "Invalid hash algorithm.: %s\n"},
+	{"ハッシュアルゴリズムが重複しています。: %s\n", "Duplicate hash algorithm.: %s\n"},
+	{"ログディレクトリを作成できませんでした。: %s\n", "Could not create the log directory.: %s\n"},
+	{"ログファイルを作成できませんでした。: %s\n", "Could not create the log file.: %s\n"},
+	{"フィルター設定ファイルの形式が不正です。: %d行目: %s\n", "Invalid format in the filter config file.: line %d: %s\n"},
+	{"フィルターのオプションの正規表現が不正です。: %s\n", "Invalid regular expression in the filter option.: %s\n"},
+
+	// initdisk.go
+	{"ディスクルートとディスクIDを指定してください。\n", "Specify the disk root and the disk ID.\n"},
+	{"ディスクルートが見つかりません。: %s\n", "Disk root not found.: %s\n"},
+	{"ディスクルートがディレクトリではありません。: %s\n", "The disk root is not a directory.: %s\n"},
+	{"diskファイルが既にあります。: %s\n", "The disk file already exists.: %s\n"},
+	{"ディスクIDは既に使われています。: %s\n", "The disk ID is already in use.: %s\n"},
+	{"ディスクIDは既にディスク登録簿に登録されています。: %s\n", "The disk ID is already registered in the disk registry.: %s\n"},
+	{"diskファイルを作成できませんでした。: %s\n", "Could not create the disk file.: %s\n"},
+	{"diskファイルを作成しました。: %s\n", "Created the disk file.: %s\n"},
+	{"ディスク登録簿に登録しました。: %s\n", "Registered in the disk registry.: %s\n"},
+
+	// interrupt.go
+	{"中断シグナルを受け取りました。ハッシュ計算を中断します。", "Received an interrupt signal. Interrupting hash calculation."},
+
+	// lastverified.go
+	{"検証日時の記録の書き込みに失敗しました。: %s\n", "Failed to write the verification time record.: %s\n"},
+	{"経過時間の指定が不正です。\n", "Invalid age.\n"},
+	{"%-6s 記録%s 未検証%s 期限切れ%s\n", "%-6s records %s, unverified %s, expired %s\n"},
+	{"  未検証: %s\n", "  Unverified: %s\n"},
+	{"%s中%sが%sより前に検証されていません。\n", "%[2]s of %[1]s have not been verified since %[3]s.\n"},
+
+	// lock.go
+	{"出力ディレクトリを作成できませんでした。: %s", "Could not create the output directory.: %s"},
+	{"[警告] 実行中ではない書き込み元のロックファイルが残っているので削除します。: %s (%s)\n", "[WARNING] Removing a lock file left by a writer that is no longer running.: %s (%s)\n"},
+	{"[警告] -force-unlockが指定されたので、ディスク%sのロックファイルを削除します。: %s\n", "[WARNING] -force-unlock was specified, removing the lock file of disk %s.: %s\n"},
+	{"ディスク%sは他の書き込み元がロックしています。(%s前から) 実行中でなければ-force-unlockを指定してください。: %s", "Disk %s is locked by another writer (for %s). If it is not running, specify -force-unlock.: %s"},
+	{"ロックファイルを作成できませんでした。: %s", "Could not create the lock file.: %s"},
+	{"ロックファイルの書き込みに失敗しました。: %s", "Failed to write the lock file.: %s"},
+	{"ロックファイルの削除に失敗しました。: %s\n", "Failed to delete the lock file.: %s\n"},
+	{"ディスク%sは前回は別のマシン(%s)から書き込まれています。ディスクIDが重複していないか確認してください。\n", "Disk %s was last written from another machine (%s). Check that the disk ID is not duplicated.\n"},
+	{"書き込み元の記録に失敗しました。: %s\n", "Failed to record the writer.: %s\n"},
+	{"ロックファイルのディレクトリを作成できませんでした。: %s", "Could not create the directory for the lock file.: %s"},
+	{"他の書き込み元がロックしているので、解除されるまで待ちます。: %s (%s)\n", "Another writer holds the lock; waiting for it to be released.: %s (%s)\n"},
+	{"ロックが解除されませんでした。: %s (%s)", "The lock was not released.: %s (%s)"},
+	{"ロックが解除されるのを待つのを中断しました。: %s", "Stopped waiting for the lock to be released.: %s"},
+
+	// logformat.go
+	{"ログの形式の指定が不正です。: %s\n", "Invalid log format.: %s\n"},
+
+	// merge.go
+	{"ディスク%sは他の書き込み元がハッシュ計算中なので、統合ハッシュファイル%sはそちらが終わったときに統合します。\n", "Another writer is hashing disk %s, so integrated hash file %s will be merged when it finishes.\n"},
+	{"統合ハッシュファイルの作成に失敗しました。\n", "Failed to create the integrated hash file.\n"},
+	{"統合ハッシュファイル%sで別のディスクとパスが重複した記録が%d件あります。\n", "Integrated hash file %s has %d records whose paths duplicate another disk.\n"},
+	{"同じパスが複数のディスクにあります(%s)。: %s (%s, %s)\n", "The same path exists on multiple disks (%s).: %s (%s, %s)\n"},
+
+	// metrics.go
+	{"メトリクスのポートを開けませんでした。: %d\n", "Could not open the metrics port.: %d\n"},
+	{"メトリクスの提供を終了しました。", "Stopped serving metrics."},
+	{"メトリクスを提供します。: http://%s/metrics\n", "Serving metrics.: http://%s/metrics\n"},
+
+	// missing.go
+	{"[警告] ハッシュファイルに記録があるのにディスクに無いファイルが%d件あります。\n", "[WARNING] %d files are recorded in hash files but missing from the disk.\n"},
+	{"ディスク(%s): %d件\n", "Disk (%s): %d\n"},
+	{"意図して削除したファイルなら-pruneを指定して実行すると記録を削除します。", "If the files were deleted intentionally, run with -prune to remove their records."},
+
+	// mmap.go
+	{"メモリマップできないので通常の読み込みで計算します。: %s\n", "Cannot memory-map the file, hashing with normal reads.: %s\n"},
+
+	// mounts.go
+	{"マウントされているボリュームの一覧が取得できませんでした。\n", "Could not list the mounted volumes.\n"},
+	{"%d個のボリュームから%d台のディスクが見つかりました。\n", "Found %[2]d disks on %[1]d volumes.\n"},
+
+	// moved.go
+	{"ファイルの移動を検出しました。: %s -> %s\n", "Detected a moved file.: %s -> %s\n"},
+	{"%d件のファイルの移動を記録しました。: %s\n", "Recorded %d moved files.: %s\n"},
+
+	// notify.go
+	{"通知設定ファイルが読み込めませんでした。: %s\n", "Could not read the notification config file.: %s\n"},
+	{"通知設定ファイルの形式が不正です。: %d行目: %s\n", "Invalid format in the notification config file.: line %d: %s\n"},
+	{"通知設定ファイルに不明な項目があります。: %d行目: %s\n", "Unknown item in the notification config file.: line %d: %s\n"},
+	{"通知設定ファイルのsmtpにはsmtp-fromとsmtp-toが必要です。\n", "smtp in the notification config file requires smtp-from and smtp-to.\n"},
+	{"通知に失敗しました。: %s\n", "Notification failed.: %s\n"},
+
+	// option.go
+	{"ハッシュアルゴリズム。md5、sha256、変更検知用の高速なcrc32、巨大なファイルを複数のコアで計算するツリーハッシュのmd5-treeとsha256-treeから選び、カンマ区切りで複数指定すると1回の読み込みで全て計算する。md5以外のハッシュファイルはアルゴリズム名のサブディレクトリに出力する。", "Hash algorithm. Choose from md5, sha256, the fast crc32 for change detection, and the tree hashes md5-tree and sha256-tree that hash huge files on multiple cores. Several algorithms separated by commas are all computed in one read. Hash files for algorithms other than md5 are written to a subdirectory named after the algorithm."},
+	{"ハッシュファイルの保存先。fileは出力ディレクトリにテキストファイルで保存する。webdavは-store-urlのWebDAVサーバーに、s3は-store-urlのS3互換のオブジェクトストレージに、sftpは-store-urlのsshで接続できるマシンに、serverは-store-urlのbcbc serveの収集サーバーに保存する。", "Where to store hash files. file stores them as text files in the output directory. webdav stores them on the WebDAV server at -store-url, s3 in the S3 compatible object storage at -store-url, sftp on the machine reachable with ssh at -store-url, and server on the bcbc serve collection server at -store-url."},
+	{"進捗の経過をログディレクトリにJSONL形式で記録する。replayサブコマンドで再構成できる。", "Record the progress in JSONL format in the log directory. The replay subcommand can reconstruct it."},
+	{"シンボリックリンクの扱い。skipはハッシュ計算しない。followはリンク先をたどる(ループするリンクはたどらない)。どちらでもシンボリックリンクは出力ディレクトリの<ディスクID>.symlinksに記録する。", "How to handle symbolic links. skip does not hash them. follow follows the link target (except links that loop). Either way symbolic links are recorded in <disk ID>.symlinks in the output directory."},
+	{"ディスクルートから走査するディレクトリの最大深さ。ディスクルート直下を1とする。0なら制限しない。", "Maximum depth of directories scanned from the disk root. Directly under the disk root is 1. 0 means no limit."},
+	{"ハッシュファイルから記録を削除する前に削除する記録を表示して確認する。", "Show the records to delete and ask for confirmation before deleting them from the hash file."},
+	{"サイズの表示に使う単位系。binaryはGiBなど1024の累乗、decimalはGBなど1000の累乗。", "Units used to show sizes. binary uses powers of 1024 such as GiB, decimal uses powers of 1000 such as GB."},
+	{"一時的な読み込みエラーを再試行する回数。権限がないなどのエラーは再試行しない。", "Number of retries for transient read errors. Errors such as missing permissions are not retried."},
+	{"読み込みエラーを最初に再試行するまでの待ち時間。再試行するたびに倍にする。", "Wait time before the first retry of a read error. Doubled on every retry."},
+	{"読み込めない範囲をゼロとみなして読み飛ばし、不完全な印を付けたハッシュを記録する。読み飛ばした範囲は出力ディレクトリの<ディスクID>.damageに追記する。", "Skip unreadable ranges as zeros and record hashes marked as incomplete. Skipped ranges are appended to <disk ID>.damage in the output directory."},
+	{"実行中にPrometheus形式のメトリクスを/metricsで提供するポート。0なら提供しない。", "Port serving Prometheus metrics at /metrics while running. 0 disables it."},
+	{"同時にハッシュ計算するディスクの最大数。1つ終わるごとに次のディスクを開始する。0なら全てのディスクを同時に計算する。", "Maximum number of disks hashed at the same time. The next disk starts whenever one finishes. 0 hashes all disks at the same time."},
+	{"ディスクごとの読み込みのスループットの上限(MB/s)。0なら制限しない。diskファイルにthrottle=<MB/s>の行を書くとディスクごとに変えられる。", "Read throughput limit per disk (MB/s). 0 means no limit. A throttle=<MB/s> line in the disk file changes it per disk."},
+	{"CPUとI/Oの優先度を下げて、他の作業を妨げないように実行する。", "Run with lower CPU and I/O priority so other work is not disturbed."},
+	{"ページキャッシュを使わずにファイルを読み込む。(LinuxはO_DIRECT、macOSはF_NOCACHE) 対応していないファイルシステムでは通常の読み込みに切り替える。", "Read files without the page cache. (O_DIRECT on Linux, F_NOCACHE on macOS) Falls back to normal reads on file systems that do not support it."},
+	{"このサイズ(MB)以上のファイルはメモリマップして読み込む。0ならメモリマップしない。直接I/Oとサルベージモードでは通常の読み込みを使う。", "Read files of this size (MB) or larger through a memory map. 0 disables memory mapping. Direct I/O and salvage mode use normal reads."},
+	{"ハッシュ計算の最後にスループットが遅かったファイルとディレクトリをこの数まで報告する。0なら報告しない。", "Report up to this many files and directories with slow throughput at the end of hashing. 0 disables the report."},
+	{"BCBCホームディレクトリ。指定しなければ環境変数BCBCHOME、それもなければユーザーのホームディレクトリの.bcbcを使う。", "BCBC home directory. Defaults to the BCBCHOME environment variable, or .bcbc in the user's home directory."},
+	{"フィルターでハッシュ対象外にしたファイルと、対象外にしたフィルターの行をログディレクトリの<日時>.filters.logに記録する。", "Record the files excluded by filters and the filter lines that excluded them in <date time>.filters.log in the log directory."},
+	{"ディスク内にあるBCBCホームディレクトリの出力ディレクトリとログディレクトリ、ディスクルートのdiskファイルを除外せずにハッシュ計算する。", "Hash the output and log directories of a BCBC home directory on the disk and the disk file in the disk root instead of excluding them."},
+	{"同じファイルへのハードリンクは最初のパスだけ読み込み、他のパスには同じハッシュを記録する。-hardlinks=falseなら全てのパスを読み込む。", "Read only the first path of hard links to the same file and record the same hash for the other paths. -hardlinks=false reads every path."},
+	{"ハッシュと一緒にファイルの更新日時、パーミッション、所有者を記録する。verify -metadataで変更を報告できる。", "Record the modification time, permissions and owner of files with the hash. verify -metadata can report changes."},
+	{"ハッシュと一緒に拡張属性(macOSのリソースフォークを含む)のダイジェストを記録する。verifyで記録と異なれば報告する。", "Record a digest of extended attributes (including macOS resource forks) with the hash. verify reports differences from the record."},
+	{"ハッシュ計算が終わったディスクのハッシュファイルを日時の名前のスナップショットとして<ディスクID>.snapshotsに保存する。snapshotsサブコマンドで一覧と違いを表示できる。", "Save the hash file of each hashed disk as a snapshot named by date and time in <disk ID>.snapshots. The snapshots subcommand lists them and shows differences."},
+	{"書き直したハッシュファイルと統合ハッシュファイルに設定ディレクトリの鍵で署名し、.sigファイルを書き込む。鍵はsign-keyサブコマンドで作成する。", "Sign rewritten hash files and merged hash files with the key in the config directory and write .sig files. Create the key with the sign-key subcommand."},
+	{"ハッシュファイルの記録を暗号化する。keyなら設定ディレクトリのencryption.key(なければ作成する)、passphraseなら環境変数BCBC_PASSPHRASEのパスフレーズを鍵にする。", "Encrypt hash file records. key uses encryption.key in the config directory (created if missing), passphrase uses the passphrase in the BCBC_PASSPHRASE environment variable."},
+	{"gzipならハッシュファイルと統合ハッシュファイルを書き直すときに圧縮して拡張子.gzのファイルにする。読み込むときは拡張子で判別して展開する。", "With gzip, hash files and merged hash files are compressed into .gz files when rewritten. They are decompressed on read based on the extension."},
+	{"-store=webdav、s3、sftp、serverのときのハッシュファイルの保存先のURL。(例: https://nas/bcbc/out、https://s3.ap-northeast-1.amazonaws.com/バケット/接頭辞、sftp://nas/bcbc/out) 出力ディレクトリは作業用の複製に使う。", "URL where hash files are stored for -store=webdav, s3, sftp and server. (e.g. https://nas/bcbc/out, https://s3.ap-northeast-1.amazonaws.com/bucket/prefix, sftp://nas/bcbc/out) The output directory holds a working copy."},
+	{"-store=s3のときに署名に使うリージョン。認証情報は環境変数AWS_ACCESS_KEY_ID、AWS_SECRET_ACCESS_KEY、AWS_SESSION_TOKENで指定する。", "Region used for signing with -store=s3. Credentials are given by the AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY and AWS_SESSION_TOKEN environment variables."},
+	{"ハッシュ計算の最後に全てのハッシュファイルの記録をBCBCホームディレクトリのindex.gobにまとめ、findではハッシュファイルの代わりに使う。", "Collect the records of all hash files into index.gob in the BCBC home directory at the end of hashing, and use it instead of the hash files in find."},
+	{"ハッシュ対象に含めるパスの正規表現。フィルター設定ファイルの先頭に+の行として加えたように扱う。複数回指定できる。", "Regular expression of paths to include. Treated as a + line added at the top of the filter file. Can be given more than once."},
+	{"ハッシュ対象から除外するパスの正規表現。フィルター設定ファイルの先頭に-の行として加えたように扱う。複数回指定できる。", "Regular expression of paths to exclude. Treated as a - line added at the top of the filter file. Can be given more than once."},
+	{"ディスクから無くなったファイルの記録をハッシュファイルから削除する。指定しなければ記録を残して、ハッシュ計算の最後に無くなったファイルを報告する。", "Delete records of files gone from the disk from the hash file. Otherwise the records are kept and the missing files are reported at the end of hashing."},
+	{"前回のハッシュ計算で出力ディレクトリの<ディスクID>.errorsに記録されたファイルだけをハッシュ計算し、成功した記録をハッシュファイルに追記する。", "Hash only the files recorded in <disk ID>.errors in the output directory by the previous run, and append the successful records to the hash file."},
+	{"ディスクごとにエラーになったファイルがこの数に達したら、そのディスクのハッシュ計算を打ち切る。0なら打ち切らない。", "Abort hashing a disk when this many of its files have failed. 0 never aborts."},
+	{"ログとエラーのメッセージの言語。jaかen。指定しなければ環境変数LC_ALL、LC_MESSAGES、LANGから決める。", "Language of log and error messages, ja or en. Decided from the LC_ALL, LC_MESSAGES and LANG environment variables if not given."},
+	{"ログの形式。textは1行のテキスト、jsonは日時、レベル、メッセージの種類、メッセージ、引数と、ディスクID、パス、サイズなど名前を付けた値を1行ごとのJSONのオブジェクトで出力する。", "Log format. text writes one line of text, json writes the time, level, message kind, message, arguments and named values such as disk ID, path and size as one JSON object per line."},
+	{"マウントされている全てのボリュームのルートにdiskファイルがあるか調べて、見つかったディスクを全て処理する。ディスクルートの引数と一緒に指定できる。", "Look for a disk file in the root of every mounted volume and process all disks found. Can be combined with disk root arguments."},
+	{"処理するディスクのロックファイルが残っていたら、ロックしている書き込み元が実行中ではないとみなして削除してからロックする。", "If a lock file is left on a disk to process, assume its owner is no longer running, delete it and lock the disk."},
+
+	// pause.go
+	{"ハッシュ計算を一時停止しました。", "Paused hash calculation."},
+	{"ハッシュ計算を再開しました。", "Resumed hash calculation."},
+	{"制御ファイルの指示が不正です。pauseかresumeを書いてください。: %s\n", "Invalid instruction in the control file. Write pause or resume.: %s\n"},
+
+	// preflight.go
+	{"[警告] 出力ディレクトリがハッシュ計算するディスク(%s)にあります。ディスクが壊れるとハッシュファイルも失われます。: %s\n", "[WARNING] The output directory is on a disk being hashed (%s). If the disk fails, the hash files are lost too.: %s\n"},
+	{"[警告] 出力ディレクトリの空き容量が足りない可能性があります。(空き容量%s、必要な容量の見積もり%s): %s\n", "[WARNING] The output directory may not have enough free space. (free %s, estimated requirement %s): %s\n"},
+
+	// progress.go
+	{"全体", "Total"},
+	{" (全%s件中%.1f%%計算済み)", " (%[2].1f%% of %[1]s already hashed)"},
+
+	// progressstate.go
+	{"[警告] 中断したハッシュ計算の進捗が読み込めなかったので、進捗は最初から数えます。: %s\n", "[WARNING] Could not read the progress of the interrupted hashing, so progress is counted from the start.: %s\n"},
+	{"中断したハッシュ計算を再開します。(%sに開始、%s、%sは計算済み): %s\n", "Resuming interrupted hashing. (started at %s, %s and %s already hashed): %s\n"},
+	{"[警告] ハッシュ計算の進捗の記録に失敗しました。: %s\n", "[WARNING] Failed to record the hashing progress.: %s\n"},
+	{"[警告] ハッシュ計算の進捗の記録を削除できませんでした。: %s\n", "[WARNING] Could not remove the hashing progress record.: %s\n"},
+
+	// registry.go
+	{"ディスク登録簿をロックできませんでした。: %s\n", "Could not lock the disk registry.: %s\n"},
+	{"ディスク登録簿が読み込めませんでした。: %s\n", "Could not read the disk registry.: %s\n"},
+	{"ディスク登録簿の形式が不正です。: %s\n", "Invalid disk registry format.: %s\n"},
+	{"ディスク登録簿の書き込みに失敗しました。: %s\n", "Failed to write the disk registry.: %s\n"},
+	{"操作(list、show、set)を指定してください。\n", "Specify an operation (list, show, set).\n"},
+	{"操作の指定が不正です。: %s\n", "Invalid operation.: %s\n"},
+	{"%-6s %10s ハッシュ計算: %s 検証: %s %s %s\n", "%-6s %10s hashed: %s verified: %s %s %s\n"},
+	{"%d台のディスクが登録されています。\n", "%d disks are registered.\n"},
+	{"ディスクが登録されていません。: %s\n", "The disk is not registered.: %s\n"},
+	{"ディスク登録簿を更新しました。: %s\n", "Updated the disk registry.: %s\n"},
+
+	// remote.go
+	{"保存先のURLの指定が不正です。http://かhttps://で始まるURLを指定してください。: %s\n", "Invalid store URL. Specify a URL starting with http:// or https://.: %s\n"},
+	{"保存先のURLの指定が不正です。\n", "Invalid store URL.\n"},
+	{"保存先のURLの指定が不正です。sftp://で始まるURLを指定してください。: %s\n", "Invalid store URL. Specify a URL starting with sftp://.: %s\n"},
+	{"SFTPの保存先にはパスワードを指定できません。sshの鍵で認証できるようにしてください。: %s\n", "A password cannot be specified for an SFTP store. Set up ssh key authentication.: %s\n"},
+	{"[警告] 保存先のURLにパスワードが含まれています。コマンドラインや設定ファイルに残らないように、環境変数%sと%sで指定してください。\n", "[WARNING] The store URL contains a password. To keep it out of command lines and config files, set it with the environment variables %s and %s.\n"},
+	{"保存先のURLにバケット名がありません。: %s\n", "The store URL has no bucket name.: %s\n"},
+	{"ハッシュファイルを保存先から取得できませんでした。: %s", "Could not fetch the hash file from the store.: %s"},
+	{"ハッシュファイルを保存先に送れませんでした。: %s", "Could not send the hash file to the store.: %s"},
+	{"保存先のハッシュファイルの一覧取得に失敗しました。: %s", "Failed to list the hash files in the store.: %s"},
+	{"保存先のハッシュファイルの情報を取得できませんでした。: %s", "Could not get the hash file information from the store.: %s"},
+	{"統合元の記録ファイルを保存先から取得できませんでした。: %s", "Could not fetch the merge source record from the store.: %s"},
+	{"統合元の記録ファイルを保存先に送れませんでした。: %s", "Could not send the merge source record to the store.: %s"},
+	{"スナップショットを保存先に送れませんでした。: %s", "Could not send the snapshot to the store.: %s"},
+	{"保存先のスナップショットの一覧取得に失敗しました。: %s", "Failed to list the snapshots in the store.: %s"},
+	{"スナップショットを保存先から取得できませんでした。: %s", "Could not fetch the snapshot from the store.: %s"},
+
+	// replay.go
+	{"進捗ログを作成できませんでした。: %s\n", "Could not create the progress log.: %s\n"},
+	{"進捗ログを記録します。: %s\n", "Recording the progress log.: %s\n"},
+	{"進捗ログの書き込みに失敗しました。以降は進捗ログを記録しません。", "Failed to write the progress log. The progress log will no longer be recorded."},
+	{"進捗ログの書き込みに失敗しました。", "Failed to write the progress log."},
+	{"進捗ログが読み込めませんでした。: %s\n", "Could not read the progress log.: %s\n"},
+	{"進捗ログの形式が不正です。: %s : %d行目\n", "Invalid progress log format.: %s : line %d\n"},
+	{"進捗ログのパスを1つ指定してください。\n", "Specify one progress log path.\n"},
+	{"集計間隔の指定が不正です。: %s\n", "Invalid aggregation interval.: %s\n"},
+	{"%s: %s - %s (%s) [%s/%s] %s / %s 平均 %s\n", "%s: %s - %s (%s) [%s/%s] %s / %s average %s\n"},
+	{"  最も遅かった区間: 開始から%s後 %s\n", "  Slowest interval: %s after start %s\n"},
+
+	// retry.go
+	{"読み込みに失敗したので%s後に再試行します。(%d/%d): %s\n", "Read failed, retrying in %s. (%d/%d): %s\n"},
+
+	// retryfailed.go
+	{"再試行するファイルが見つかりません。: %s\n", "File to retry not found.: %s\n"},
+
+	// s3.go
+	{"環境変数%sと%sにS3の認証情報を設定してください。\n", "Set the S3 credentials in the environment variables %s and %s.\n"},
+
+	// salvage.go
+	{"損傷レポートの書き込みに失敗しました。: %s\n", "Failed to write the damage report.: %s\n"},
+
+	// scrub.go
+	{"-sampleか-budgetを指定してください。\n", "Specify -sample or -budget.\n"},
+	{"抽出する割合の指定が不正です。\n", "Invalid sample rate.\n"},
+	{"抜き取り検証を開始します。", "Starting scrub."},
+	{"抜き取り検証を終了しました。", "Finished scrub."},
+	{"検証する時間の上限に達しました。: %s\n", "Reached the verification time budget.: %s\n"},
+	{"抜き取り検証を中断しました。\n", "Scrub was interrupted.\n"},
+	{"ディスク%sの抜き取り検証結果: 記録%s中%sを検証し、%sで問題が見つかりました。\n", "Scrub result of disk %s: verified %[3]s of %[2]s records, problems found in %[4]s.\n"},
+
+	// selftest.go
+	{"テストファイルを作成するディレクトリを1つ指定してください。\n", "Specify one directory to create the test files in.\n"},
+	{"自己診断を開始します。", "Starting the self-test."},
+	{"自己診断を終了しました。", "Finished the self-test."},
+	{"[警告] 自己診断で%d件の不一致がありました。\n", "[WARNING] The self-test found %d mismatches.\n"},
+	{"全ての自己診断に合格しました。", "All self-tests passed."},
+	{"既知の値と一致しません。: %s(%q) 期待値: %s 結果: %s\n", "Does not match the known value.: %s(%q) expected: %s actual: %s\n"},
+	{"既知の値を確かめました。: %s\n", "Checked the known values.: %s\n"},
+	{"テストファイルのディレクトリを作成できませんでした。: %s\n", "Could not create the test file directory.: %s\n"},
+	{"テストファイルをハッシュ計算できませんでした。: %s : %s\n", "Could not hash the test file.: %s : %s\n"},
+	{"テストファイルのハッシュが一致しません。: %s : %s : %s 期待値: %s 結果: %s\n", "The test file hash does not match.: %s : %s : %s expected: %s actual: %s\n"},
+	{"テストファイルの読み込みを確かめました。: %s\n", "Checked reading the test files.: %s\n"},
+	{"通常の読み込み", "normal read"},
+	{"メモリマップ", "memory map"},
+	{"直接I/O", "direct I/O"},
+
+	// serve.go
+	{"リクエストのサイズの上限の指定が不正です。\n", "Invalid request size limit.\n"},
+	{"環境変数%sが設定されていません。認証せずに受け付けるなら-insecureを指定してください。\n", "The environment variable %s is not set. Specify -insecure to accept requests without authentication.\n"},
+	{"[警告] 環境変数%sが設定されていないので、認証せずに受け付けます。\n", "[WARNING] The environment variable %s is not set, accepting requests without authentication.\n"},
+	{"収集サーバーのポートを開けませんでした。: %s\n", "Could not open the collector server port.: %s\n"},
+	{"収集サーバーを開始します。: http://%s\n", "Starting the collector server.: http://%s\n"},
+	{"収集サーバーを終了しました。\n", "Stopped the collector server.\n"},
+	{"収集サーバーが停止しました。\n", "The collector server stopped.\n"},
+	{"エージェントから受け取ったファイルを保存できませんでした。: %s\n", "Could not save the file received from the agent.: %s\n"},
+	{"%sがディスク%sのハッシュ計算を開始しました。\n", "%s started hashing disk %s.\n"},
+	{"%sがディスク%sのハッシュ計算を終えました。\n", "%s finished hashing disk %s.\n"},
+
+	// setup.go
+	{"BCBCホームディレクトリをセットアップします。: %s\n", "Setting up the BCBC home directory.: %s\n"},
+	{"ディレクトリを作成できませんでした。: %s\n", "Could not create the directory.: %s\n"},
+	{"既にある設定ファイルを残します。: %s\n", "Keeping the existing config file.: %s\n"},
+	{"設定ファイルを作成できませんでした。: %s\n", "Could not create the config file.: %s\n"},
+	{"設定ファイルを作成しました。: %s\n", "Created the config file.: %s\n"},
+	{"セットアップが完了しました。ハッシュ計算するディスクのルートにはinit-diskサブコマンドでdiskファイルを作成してください。", "Setup completed. Create a disk file at the root of each disk to hash with the init-disk subcommand."},
+	{"出力ディレクトリに書き込めません。: %s\n", "Cannot write to the output directory.: %s\n"},
+	{"フィルター設定を確認しました。: %d件の規則\n", "Checked the filter settings.: %d rules\n"},
+	{"通知設定を確認しました。", "Checked the notification settings."},
+
+	// signing.go
+	{"署名の秘密鍵が読み込めませんでした。sign-keyサブコマンドで作成してください。: %s\n", "Could not read the signing private key. Create it with the sign-key subcommand.: %s\n"},
+	{"署名の秘密鍵の形式が不正です。: %s\n", "Invalid signing private key format.: %s\n"},
+	{"署名の公開鍵が読み込めませんでした。: %s\n", "Could not read the signing public key.: %s\n"},
+	{"署名の公開鍵の形式が不正です。: %s\n", "Invalid signing public key format.: %s\n"},
+	{"署名の鍵は既にあります。作り直すには-forceを指定してください。: %s\n", "The signing key already exists. Specify -force to recreate it.: %s\n"},
+	{"署名の鍵を作成できませんでした。\n", "Could not create the signing key.\n"},
+	{"署名の秘密鍵を保存できませんでした。: %s\n", "Could not save the signing private key.: %s\n"},
+	{"署名の公開鍵を保存できませんでした。: %s\n", "Could not save the signing public key.: %s\n"},
+	{"署名の鍵を作成しました。: %s\n", "Created the signing key.: %s\n"},
+	{"秘密鍵はハッシュファイルとは別の場所にも控えておいてください。", "Keep a copy of the private key in a place separate from the hash files."},
+	{"ハッシュファイルの署名を検証できませんでした。: %s\n", "Could not verify the hash file signature.: %s\n"},
+	{"ハッシュファイルに署名がありません。: %s\n", "The hash file has no signature.: %s\n"},
+	{"ハッシュファイルの署名が一致しません。改ざんされている可能性があります。: %s\n", "The hash file signature does not match. It may have been tampered with.: %s\n"},
+
+	// skeleton.go
+	{"フィルター設定ファイルを作成できませんでした。: %s\n", "Could not create the filter config file.: %s\n"},
+
+	// snapshot.go
+	{"スナップショットのディレクトリを作成できませんでした。: %s", "Could not create the snapshot directory.: %s"},
+	{"スナップショットの保存に失敗しました。: %s", "Failed to save the snapshot.: %s"},
+	{"スナップショットの一覧取得に失敗しました。: %s", "Failed to list the snapshots.: %s"},
+	{"スナップショットの読み込みに失敗しました。: %s", "Failed to read the snapshot.: %s"},
+	{"スナップショットの保存に失敗しました。: %s (%s)\n", "Failed to save the snapshot.: %s (%s)\n"},
+	{"スナップショットを保存しました。: %s (%s) %s\n", "Saved the snapshot.: %s (%s) %s\n"},
+	{"追加%d件、削除%d件、変更%d件\n", "Added %d, removed %d, changed %d\n"},
+	{"スナップショットの名前を2つ指定してください。: %s\n", "Specify two snapshot names.: %s\n"},
+	{"スナップショットの読み込みに失敗しました。\n", "Failed to read the snapshot.\n"},
+	{"%s (%s): %d件のスナップショット\n", "%s (%s): %d snapshots\n"},
+
+	// stats.go
+	{"ハッシュ計算中にサイズが変わったファイル:", "Files whose size changed during hash calculation:"},
+	{"スループットが遅かったファイル:", "Files with slow throughput:"},
+	{"スループットが遅かったディレクトリ:", "Directories with slow throughput:"},
+
+	// status.go
+	{"統合元の記録の書き込みに失敗しました。: %s\n", "Failed to write the merge source record.: %s\n"},
+	{"統合ハッシュファイルが統合元のハッシュファイルより古くなっています。: %s (%s)\n", "The integrated hash file is older than its source hash files.: %s (%s)\n"},
+	{"統合ハッシュファイルはすべて最新です。(%s)\n", "All integrated hash files are up to date. (%s)\n"},
+	{"統合ハッシュファイルを統合し直します。: %s (%s)\n", "Re-integrating the integrated hash file.: %s (%s)\n"},
+	{"統合ハッシュファイルが古くなっています。: %s (%s)\n", "The integrated hash file is outdated.: %s (%s)\n"},
+
+	// statuskey.go
+	{"進捗の詳細:", "Progress details:"},
+	{"  %s: 経過 %s\n", "  %s: elapsed %s\n"},
+	{"    ファイル   %s/%s (エラー %d)\n", "    Files      %s/%s (errors %d)\n"},
+	{"    サイズ     %s/%s %.2f%% %s\n", "    Size       %s/%s %.2f%% %s\n"},
+	{"    残り時間   %s\n", "    Remaining  %s\n"},
+	{"    処理中     %s (%s/%s 残り%s)\n", "    Processing %s (%s/%s remaining %s)\n"},
+
+	// store.go
+	{"ハッシュファイルの最後の行が途中で切れているので読み飛ばします。: %s : %d行目\n", "The last line of the hash file is truncated, skipping it.: %s : line %d\n"},
+	{"ハッシュファイルの読み込みに失敗しました。: %s", "Failed to read the hash file.: %s"},
+	{"対応していないバージョンのハッシュファイルです。: %s : v%d", "Unsupported hash file version.: %s : v%d"},
+	{"ツリーハッシュのチャンクのサイズが異なるハッシュファイルです。: %s : %d", "The hash file uses a different tree hash chunk size.: %s : %d"},
+	{"ハッシュファイルの記録を復号できませんでした。: %s : %d行目", "Could not decrypt a hash file record.: %s : line %d"},
+	{"ハッシュファイルが破損しています。: %s : %d行目", "The hash file is corrupted.: %s : line %d"},
+	{"出力ファイルの一覧取得に失敗しました。", "Failed to list the output files."},
+	{"ハッシュファイルの作成に失敗しました。: %s", "Failed to create the hash file.: %s"},
+	{"ハッシュファイルの署名に失敗しました。: %s", "Failed to sign the hash file.: %s"},
+	{"ジャーナルの読み込みに失敗しました。: %s", "Failed to read the journal.: %s"},
+	{"ジャーナルを削除できませんでした。: %s", "Could not remove the journal.: %s"},
+	{"ジャーナルの書き込みに失敗しました。: %s", "Failed to write the journal.: %s"},
+	{"ジャーナルの修復に失敗しました。: %s", "Failed to repair the journal.: %s"},
+	{"前回のジャーナルをハッシュファイルに取り込みます。: %s\n", "Folding the previous journal into the hash file.: %s\n"},
+	{"ハッシュファイルの途中で切れている最後の行を削除します。: %s\n", "Removing the truncated last line of the hash file.: %s\n"},
+	{"統合元の記録ファイルの読み込みに失敗しました。: %s", "Failed to read the merge source record file.: %s"},
+	{"統合元の記録ファイルの書き込みに失敗しました。: %s", "Failed to write the merge source record file.: %s"},
+
+	// target.go
+	{"拡張属性を取得できませんでした。: %s\n", "Could not get the extended attributes.: %s\n"},
+	{"ファイルサイズの取得に失敗しました。: %s\n", "Failed to get the file size.: %s\n"},
+	{"%d個のハードリンクは読み込まずに同じファイルのハッシュを記録しました。: %s\n", "Recorded the same hash for %d hard links without reading them.: %s\n"},
+	{"ハッシュファイルの作成に失敗しました。\n", "Failed to create the hash file.\n"},
+	{"ハッシュファイルが読み込めませんでした。: %s\n", "Could not read the hash file.: %s\n"},
+
+	// verify.go
+	{"検証を開始します。", "Starting verification."},
+	{"検証を終了しました。", "Finished verification."},
+	{"検証を中断しました。\n", "Verification was interrupted.\n"},
+	{"ディスク%sの検証結果: %s中%sで問題が見つかりました。\n", "Verification result of disk %s: problems found in %[3]s of %[2]s.\n"},
+	{"ハッシュが一致しなかったファイルを記録しました。: %s\n", "Recorded the files whose hash did not match.: %s\n"},
+	{"サイズが記録と異なります。(記録: %s, 現在: %s)\n", "The size differs from the record. (recorded: %s, current: %s)\n"},
+	{"読み込めない範囲がありました。: %s\n", "Some ranges could not be read.: %s\n"},
+	{"メタデータが記録と異なります。: %s\n", "The metadata differs from the record.: %s\n"},
+	{"不一致レポートの書き込みに失敗しました。: %s\n", "Failed to write the mismatch report.: %s\n"},
+	{"検証レポートの書き込みに失敗しました。: %s\n", "Failed to write the verification report.: %s\n"},
+	{"不完全なハッシュが記録されていて照合できなかった%sがあります。ハッシュ計算し直してください。\n", "%s could not be checked because incomplete hashes are recorded. Hash them again.\n"},
+	{"前回の検証で問題があった%d件のファイルを先に検証します。\n", "Verifying first the %d files that had problems in the last verification.\n"},
+
+	// walk.go
+	{"最大深さを超えたので%d個のディレクトリを走査しませんでした。: %s\n", "Did not scan %d directories beyond the maximum depth.: %s\n"},
+	{"%sで%d個のファイルとディレクトリを除外しました。: %s\n", "Excluded %[2]d files and directories by %[1]s.: %[3]s\n"},
+	{"シンボリックリンクがループしているので走査しません。: %s\n", "Not scanning because the symbolic link loops.: %s\n"},
+	{"bcbcの出力先なので走査しません。: %s\n", "Not scanning because it is the bcbc output destination.: %s\n"},
+	{"ディレクトリが読み込めませんでした。: %s\n", "Could not read the directory.: %s\n"},
+	{"シンボリックリンクのリンク先がありません。: %s\n", "The target of the symbolic link does not exist.: %s\n"},
+	{"シンボリックリンクの一覧ファイルの書き込みに失敗しました。: %s\n", "Failed to write the symbolic link list file.: %s\n"},
+
+	// watch.go
+	{"待ち時間の指定が不正です。: %s\n", "Invalid wait time.: %s\n"},
+	{"走査間隔の指定が不正です。: %s\n", "Invalid scan interval.: %s\n"},
+	{"監視を開始します。", "Starting to watch."},
+	{"監視を終了しました。", "Stopped watching."},
+	{"ディスク(%s)の変更されたファイルのハッシュ計算中に問題が発生しました。\n", "A problem occurred while hashing changed files on disk (%s).\n"},
+	{"ディスク(%s)のジャーナルをハッシュファイルに取り込めませんでした。\n", "Could not fold the journal of disk (%s) into the hash file.\n"},
+	{"変更されたファイルのハッシュを記録しました。: %s:%s\n", "Recorded the hash of a changed file.: %s:%s\n"},
+	{"ディスク(%s)を%sごとに走査して変更を監視します。\n", "Watching disk (%s) for changes by scanning every %s.\n"},
+
+	// watch_linux.go
+	{"変更通知が使えません。: %s\n", "Change notification is not available.: %s\n"},
+	{"ディスク(%s)の変更通知を監視します。\n", "Watching change notifications for disk (%s).\n"},
+	{"ディレクトリを監視できませんでした。: %s\n", "Could not watch the directory.: %s\n"},
+	{"ディスク(%s)の変更通知が読み込めなくなりました。\n", "Change notifications for disk (%s) can no longer be read.\n"},
+	{"ディスク(%s)の変更通知が溢れたので一部の変更を取りこぼした可能性があります。\n", "Change notifications for disk (%s) overflowed, some changes may have been missed.\n"},
+
+	// 確認
+	{"削除してよろしいですか? [y/N]: ", "Are you sure you want to delete them? [y/N]: "},
+	{"%sは既にあります。雛形で上書きしますか? [y/N]: ", "%s already exists. Overwrite it with the template? [y/N]: "},
+})
+
+// 英語の数える語の単数形と複数形
+var englishUnits = map[string][2]string{
+	"件":      {" item", " items"},
+	"件のファイル": {" file", " files"},
+}
@@ -0,0 +1,53 @@
+package bcbc
+
+import (
+	"os"
+	"time"
+)
+
+// ファイルのメタデータ
+type fileMetadata struct {
+	modTime time.Time
+	// ls -lと同じ形式のパーミッション
+	mode string
+	// 「UID:GID」の形式の所有者。取得できなければ空。
+	owner string
+}
+
+// ファイルの情報からメタデータを作成する。
+func metadataOf(stat os.FileInfo) *fileMetadata {
+	return &fileMetadata{
+		modTime: stat.ModTime(),
+		mode:    stat.Mode().String(),
+		owner:   fileOwner(stat),
+	}
+}
+
+// メタデータを記録していなければ空のメタデータを設定して返す。
+func (r *HashRecord) ensureMetadata() *fileMetadata {
+	if r.metadata == nil {
+		r.metadata = &fileMetadata{}
+	}
+	return r.metadata
+}
+
+// 記録したメタデータと現在のメタデータの違いを返す。
+// どちらかを記録していなければ比べない。
+func metadataDrift(recorded *fileMetadata, current *fileMetadata) []string {
+	if recorded == nil || current == nil {
+		return nil
+	}
+
+	var drift []string
+	if !recorded.modTime.Equal(current.modTime) {
+		drift = append(drift, "更新日時: "+recorded.modTime.Format(time.RFC3339)+" -> "+current.modTime.Format(time.RFC3339))
+	}
+	if recorded.mode != current.mode {
+		drift = append(drift, "パーミッション: "+recorded.mode+" -> "+current.mode)
+	}
+	if recorded.owner != "" && recorded.owner != current.owner {
+		drift = append(drift, "所有者: "+recorded.owner+" -> "+current.owner)
+	}
+
+	return drift
+}
@@ -0,0 +1,18 @@
+// +build !windows
+
+package bcbc
+
+import (
+	"os"
+	"strconv"
+	"syscall"
+)
+
+// ファイルの所有者を「UID:GID」の形式で返す。
+func fileOwner(stat os.FileInfo) string {
+	sys, ok := stat.Sys().(*syscall.Stat_t)
+	if !ok {
+		return ""
+	}
+	return strconv.FormatUint(uint64(sys.Uid), 10) + ":" + strconv.FormatUint(uint64(sys.Gid), 10)
+}
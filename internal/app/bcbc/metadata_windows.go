@@ -0,0 +1,10 @@
+// +build windows
+
+package bcbc
+
+import "os"
+
+// Windowsでは所有者を記録しない。
+func fileOwner(stat os.FileInfo) string {
+	return ""
+}
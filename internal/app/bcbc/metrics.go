@@ -0,0 +1,109 @@
+package bcbc
+
+import (
+	"fmt"
+	"net"
+	"net/http"
+	"sort"
+	"sync"
+)
+
+// ディスクごとのメトリクス
+type diskMetrics struct {
+	filesDone  uint64
+	filesTotal uint64
+	bytesDone  uint64
+	bytesTotal uint64
+	// 推定した1秒あたりのバイト数
+	throughput float64
+}
+
+// Prometheusで収集するメトリクス
+type metricsRegistry struct {
+	mu         sync.Mutex
+	disks      map[string]diskMetrics
+	fileErrors int
+}
+
+// メトリクス
+var metrics = &metricsRegistry{disks: make(map[string]diskMetrics)}
+
+// 進捗情報をメトリクスに反映する。
+func (m *metricsRegistry) updateProgress(progressInfo ProgressInfo, throughput float64) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	m.disks[progressInfo.diskInfo.id] = diskMetrics{
+		filesDone:  progressInfo.fileCount.processed,
+		filesTotal: progressInfo.fileCount.total,
+		bytesDone:  progressInfo.sizeCount.processed,
+		bytesTotal: progressInfo.sizeCount.total,
+		throughput: throughput,
+	}
+}
+
+// エラーが発生したファイルとディスクの数をメトリクスに反映する。
+func (m *metricsRegistry) updateErrors(fileErrors int) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	m.fileErrors = fileErrors
+}
+
+// Prometheusのテキスト形式でメトリクスを返す。
+func (m *metricsRegistry) ServeHTTP(w http.ResponseWriter, r *http.Request) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	diskIds := make([]string, 0, len(m.disks))
+	for diskId := range m.disks {
+		diskIds = append(diskIds, diskId)
+	}
+	sort.Strings(diskIds)
+
+	w.Header().Set("Content-Type", "text/plain; version=0.0.4; charset=utf-8")
+
+	perDisk := []struct {
+		name  string
+		kind  string
+		help  string
+		value func(diskMetrics) float64
+	}{
+		{"bcbc_bytes_hashed_total", "counter", "Bytes hashed in this run.", func(d diskMetrics) float64 { return float64(d.bytesDone) }},
+		{"bcbc_bytes_target", "gauge", "Bytes to hash in this run.", func(d diskMetrics) float64 { return float64(d.bytesTotal) }},
+		{"bcbc_files_done_total", "counter", "Files hashed in this run.", func(d diskMetrics) float64 { return float64(d.filesDone) }},
+		{"bcbc_files_target", "gauge", "Files to hash in this run.", func(d diskMetrics) float64 { return float64(d.filesTotal) }},
+		{"bcbc_throughput_bytes_per_second", "gauge", "Smoothed read throughput.", func(d diskMetrics) float64 { return d.throughput }},
+	}
+
+	for _, metric := range perDisk {
+		fmt.Fprintf(w, "# HELP %s %s\n# TYPE %s %s\n", metric.name, metric.help, metric.name, metric.kind)
+		for _, diskId := range diskIds {
+			fmt.Fprintf(w, "%s{disk=%q} %g\n", metric.name, diskId, metric.value(m.disks[diskId]))
+		}
+	}
+
+	fmt.Fprintf(w, "# HELP bcbc_errors_total Files and disks that failed in this run.\n# TYPE bcbc_errors_total counter\n")
+	fmt.Fprintf(w, "bcbc_errors_total %d\n", m.fileErrors)
+}
+
+// メトリクスのポートが指定されていればHTTPサーバーを開始する。
+func startMetricsServer() {
+	if config.metricsPort == 0 {
+		return
+	}
+
+	listener, err := net.Listen("tcp", fmt.Sprintf(":%d", config.metricsPort))
+	fatalMessageError(ErrConfig, err, "メトリクスのポートを開けませんでした。: %d\n", config.metricsPort)
+
+	mux := http.NewServeMux()
+	mux.Handle("/metrics", metrics)
+
+	go func() {
+		err := http.Serve(listener, mux)
+		logf.Println("メトリクスの提供を終了しました。")
+		logf.Println(err)
+	}()
+
+	logf.Printf("メトリクスを提供します。: http://%s/metrics\n", listener.Addr())
+}
@@ -0,0 +1,80 @@
+package bcbc
+
+import (
+	"sort"
+	"sync"
+)
+
+// 記録があるのにディスクから無くなったファイル。
+// -pruneが指定されていなければ記録は削除せずに残し、ハッシュ計算の最後に報告する。
+// 意図せずに削除されたファイルを見逃さないようにするため。
+type missingFileReport struct {
+	mu sync.Mutex
+	// ディスクIDごとの無くなったファイルのパス
+	paths map[string]map[string]bool
+}
+
+// ハッシュ計算ごとの無くなったファイルの報告
+var missingFiles = newMissingFileReport()
+
+// 無くなったファイルの報告を初期化する。
+func newMissingFileReport() *missingFileReport {
+	return &missingFileReport{paths: make(map[string]map[string]bool)}
+}
+
+// 無くなったファイルを追加する。
+// アルゴリズムごとに呼ばれるので、同じパスは1つにまとめる。
+func (r *missingFileReport) add(diskId string, normPaths []string) {
+	if len(normPaths) == 0 {
+		return
+	}
+
+	r.mu.Lock()
+	defer r.mu.Unlock()
+
+	paths, found := r.paths[diskId]
+	if !found {
+		paths = make(map[string]bool)
+		r.paths[diskId] = paths
+	}
+	for _, normPath := range normPaths {
+		paths[normPath] = true
+	}
+}
+
+// 無くなったファイルをディスクごとにログに出力する。
+func (r *missingFileReport) report() {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+
+	if len(r.paths) == 0 {
+		return
+	}
+
+	diskIds := make([]string, 0, len(r.paths))
+	total := 0
+	for diskId, paths := range r.paths {
+		diskIds = append(diskIds, diskId)
+		total += len(paths)
+	}
+	sort.Strings(diskIds)
+
+	logf.Printf("[警告] ハッシュファイルに記録があるのにディスクに無いファイルが%d件あります。\n", total)
+	for _, diskId := range diskIds {
+		paths := make([]string, 0, len(r.paths[diskId]))
+		for normPath := range r.paths[diskId] {
+			paths = append(paths, normPath)
+		}
+		sort.Strings(paths)
+
+		logf.Printf("ディスク(%s): %d件\n", diskId, len(paths))
+		for i, normPath := range paths {
+			if i >= confirmPreviewLimit {
+				logf.Printf("  ...他%d件\n", len(paths)-i)
+				break
+			}
+			logf.Printf("  %s\n", normPath)
+		}
+	}
+	logf.Println("意図して削除したファイルなら-pruneを指定して実行すると記録を削除します。")
+}
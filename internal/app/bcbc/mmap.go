@@ -0,0 +1,70 @@
+package bcbc
+
+import (
+	"fmt"
+	"hash"
+	"os"
+	"runtime/debug"
+)
+
+// メモリマップで読み込む最小のファイルサイズの単位(MB)のバイト数
+const mmapMinSizeUnit = 1000 * 1000
+
+// メモリマップで読み込むファイルか判定する。
+// 直接I/Oとサルベージモードではページキャッシュを使わない、読み込めない範囲を読み飛ばすために通常の読み込みを使う。
+func useMmap(size uint64) bool {
+	if config.mmapMinSize <= 0 || config.directIO || config.salvage {
+		return false
+	}
+	return size > 0 && size >= uint64(config.mmapMinSize)*mmapMinSizeUnit
+}
+
+// ハッシュ対象ファイルをメモリマップする。
+// マップできなければ通常の読み込みに切り替えるためにnilを返す。
+func mapTarget(fileIn targetFile, file string, size uint64) []byte {
+	osFile, ok := fileIn.(*os.File)
+	if !ok {
+		return nil
+	}
+
+	mapped, err := mmapFile(osFile, size)
+	if err != nil {
+		logf.Printf("メモリマップできないので通常の読み込みで計算します。: %s\n", file)
+		logf.Println(err)
+		return nil
+	}
+
+	return mapped
+}
+
+// マップしたメモリから指定された位置のチャンクを切り出す。
+// 進捗を更新できるように読み込みバッファと同じ大きさずつ切り出す。
+func mappedChunk(mapped []byte, offset int64, chunkSize int) []byte {
+	if offset >= int64(len(mapped)) {
+		return nil
+	}
+
+	end := offset + int64(chunkSize)
+	if end > int64(len(mapped)) {
+		end = int64(len(mapped))
+	}
+
+	return mapped[offset:end]
+}
+
+// マップしたメモリのチャンクのハッシュを計算する。
+// マップしたメモリの読み込みに失敗するとSIGBUSになるので、パニックにして回復しエラーとして返す。
+func hashMappedChunk(hashers []hash.Hash, chunk []byte, file string, offset int64) (err error) {
+	defer debug.SetPanicOnFault(debug.SetPanicOnFault(true))
+	defer func() {
+		if r := recover(); r != nil {
+			err = fmt.Errorf("メモリマップしたファイルを読み込めませんでした。: %s : %dバイト目から: %v", file, offset, r)
+		}
+	}()
+
+	for _, hasher := range hashers {
+		hasher.Write(chunk)
+	}
+
+	return nil
+}
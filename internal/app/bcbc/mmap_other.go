@@ -0,0 +1,17 @@
+// +build !linux,!darwin,!freebsd,!netbsd,!openbsd
+
+package bcbc
+
+import (
+	"errors"
+	"os"
+)
+
+// その他のOSではメモリマップに対応していない。
+func mmapFile(fileIn *os.File, size uint64) ([]byte, error) {
+	return nil, errors.New("このOSではメモリマップに対応していません。")
+}
+
+// メモリマップを解除する。
+func munmapFile(mapped []byte) {
+}
@@ -0,0 +1,26 @@
+// +build linux darwin freebsd netbsd openbsd
+
+package bcbc
+
+import (
+	"os"
+	"syscall"
+)
+
+// ファイル全体を読み込み専用でメモリマップする。
+// 先頭から順に読むので先読みを促す。
+func mmapFile(fileIn *os.File, size uint64) ([]byte, error) {
+	mapped, err := syscall.Mmap(int(fileIn.Fd()), 0, int(size), syscall.PROT_READ, syscall.MAP_SHARED)
+	if err != nil {
+		return nil, err
+	}
+
+	syscall.Madvise(mapped, syscall.MADV_SEQUENTIAL)
+
+	return mapped, nil
+}
+
+// メモリマップを解除する。
+func munmapFile(mapped []byte) {
+	syscall.Munmap(mapped)
+}
@@ -0,0 +1,34 @@
+package bcbc
+
+import (
+	"os"
+	"path/filepath"
+)
+
+// マウントされているボリュームのルートにあるdiskファイルを一覧に加える。
+// 引数で指定されたディスクと同じdiskファイルは加えない。
+func appendMountedDiskFiles(diskFiles []string) []string {
+	mountPoints, err := listMountPoints()
+	fatalMessageError(ErrIO, err, "マウントされているボリュームの一覧が取得できませんでした。\n")
+
+	given := make(map[string]bool)
+	for _, diskFile := range diskFiles {
+		given[diskFile] = true
+	}
+
+	found := 0
+	for _, mountPoint := range mountPoints {
+		diskFile := filepath.Join(normalizeRootPath(mountPoint), "disk")
+		if stat, err := os.Stat(diskFile); err != nil || !stat.Mode().IsRegular() {
+			continue
+		}
+		found++
+		if !given[diskFile] {
+			diskFiles = append(diskFiles, diskFile)
+		}
+	}
+
+	logf.Printf("%d個のボリュームから%d台のディスクが見つかりました。\n", len(mountPoints), found)
+
+	return diskFiles
+}
@@ -0,0 +1,33 @@
+// +build darwin
+
+package bcbc
+
+import "syscall"
+
+// マウントポイントの一覧をgetfsstatで取得する。
+func listMountPoints() ([]string, error) {
+	count, err := syscall.Getfsstat(nil, syscall.MNT_NOWAIT)
+	if err != nil {
+		return nil, err
+	}
+
+	stats := make([]syscall.Statfs_t, count)
+	count, err = syscall.Getfsstat(stats, syscall.MNT_NOWAIT)
+	if err != nil {
+		return nil, err
+	}
+
+	mountPoints := make([]string, 0, count)
+	for _, stat := range stats[:count] {
+		name := make([]byte, 0, len(stat.Mntonname))
+		for _, c := range stat.Mntonname {
+			if c == 0 {
+				break
+			}
+			name = append(name, byte(c))
+		}
+		mountPoints = append(mountPoints, string(name))
+	}
+
+	return mountPoints, nil
+}
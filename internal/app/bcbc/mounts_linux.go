@@ -0,0 +1,58 @@
+// +build linux
+
+package bcbc
+
+import (
+	"bufio"
+	"os"
+	"strings"
+)
+
+// ディスクを置くことがない仮想的なファイルシステム
+var virtualFilesystems = map[string]bool{
+	"autofs":      true,
+	"binfmt_misc": true,
+	"bpf":         true,
+	"cgroup":      true,
+	"cgroup2":     true,
+	"configfs":    true,
+	"debugfs":     true,
+	"devpts":      true,
+	"devtmpfs":    true,
+	"fusectl":     true,
+	"hugetlbfs":   true,
+	"mqueue":      true,
+	"proc":        true,
+	"pstore":      true,
+	"securityfs":  true,
+	"sysfs":       true,
+	"tracefs":     true,
+}
+
+// マウントポイントの一覧を/proc/self/mountsから取得する。
+func listMountPoints() ([]string, error) {
+	mountsIn, err := os.Open("/proc/self/mounts")
+	if err != nil {
+		return nil, err
+	}
+	defer mountsIn.Close()
+
+	var mountPoints []string
+	seen := make(map[string]bool)
+
+	scanner := bufio.NewScanner(mountsIn)
+	for scanner.Scan() {
+		fields := strings.Fields(scanner.Text())
+		if len(fields) < 3 || virtualFilesystems[fields[2]] {
+			continue
+		}
+
+		mountPoint := unescapeMountInfo(fields[1])
+		if !seen[mountPoint] {
+			seen[mountPoint] = true
+			mountPoints = append(mountPoints, mountPoint)
+		}
+	}
+
+	return mountPoints, scanner.Err()
+}
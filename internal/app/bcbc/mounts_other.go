@@ -0,0 +1,10 @@
+// +build !linux,!darwin,!windows
+
+package bcbc
+
+import "errors"
+
+// この環境ではマウントポイントの一覧を取得できない。
+func listMountPoints() ([]string, error) {
+	return nil, errors.New("この環境ではマウントされているボリュームを列挙できません。")
+}
@@ -0,0 +1,17 @@
+// +build windows
+
+package bcbc
+
+import "os"
+
+// 接続されているドライブのルートの一覧を取得する。
+func listMountPoints() ([]string, error) {
+	var mountPoints []string
+	for letter := 'A'; letter <= 'Z'; letter++ {
+		root := string(letter) + `:\`
+		if _, err := os.Stat(root); err == nil {
+			mountPoints = append(mountPoints, root)
+		}
+	}
+	return mountPoints, nil
+}
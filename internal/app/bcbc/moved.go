@@ -0,0 +1,116 @@
+package bcbc
+
+import (
+	"fmt"
+	"sort"
+)
+
+// 記録されたパスから無くなったファイルと同じサイズとハッシュで新しく見つかったファイル
+type fileMove struct {
+	from string
+	to   string
+}
+
+// 無くなったファイルの記録と今回計算した記録を突き合わせて、移動や名前の変更を見つける。
+// 全てのアルゴリズムのハッシュとサイズが一致すれば移動とみなす。
+// 同じ内容の無くなったファイルが複数あれば、パスの順に1つずつ対応させる。
+func (d *targetDiscovery) detectMoves(newRecords [][]HashRecord) []fileMove {
+	if len(d.hashMaps) == 0 || len(newRecords[0]) == 0 {
+		return nil
+	}
+
+	candidates := make(map[string][]string)
+	for _, normPath := range sortedRecordPaths(d.hashMaps[0]) {
+		record := d.hashMaps[0][normPath]
+		if d.existingPaths[normPath] || !d.diskInfo.covers(normPath) || record.size < 0 || isIncompleteHash(record.hash) {
+			continue
+		}
+		key := moveKey(record)
+		candidates[key] = append(candidates[key], normPath)
+	}
+	if len(candidates) == 0 {
+		return nil
+	}
+
+	newHashMaps := make([]map[string]HashRecord, len(newRecords))
+	for i, records := range newRecords {
+		newHashMaps[i] = make(map[string]HashRecord, len(records))
+		for _, record := range records {
+			newHashMaps[i][record.path] = record
+		}
+	}
+
+	var moves []fileMove
+	for _, record := range newRecords[0] {
+		key := moveKey(record)
+		for j, from := range candidates[key] {
+			if !sameHashes(d.hashMaps, from, newHashMaps, record.path) {
+				continue
+			}
+			moves = append(moves, fileMove{from, record.path})
+			candidates[key] = append(candidates[key][:j], candidates[key][j+1:]...)
+			break
+		}
+	}
+
+	return moves
+}
+
+// 移動を見つけるために記録をサイズとハッシュで分類するキーを返す。
+func moveKey(record HashRecord) string {
+	return fmt.Sprintf("%d:%s", record.size, record.hash)
+}
+
+// 2つのパスの記録の全てのアルゴリズムのハッシュが一致するか判定する。
+func sameHashes(aMaps []map[string]HashRecord, aPath string, bMaps []map[string]HashRecord, bPath string) bool {
+	for i := range aMaps {
+		a, foundA := aMaps[i][aPath]
+		b, foundB := bMaps[i][bPath]
+		if !foundA || !foundB || a.hash != b.hash {
+			return false
+		}
+	}
+	return true
+}
+
+// 移動したファイルの元のパスの記録を削除して、無くなったファイルとして扱わないようにする。
+// 元のパスの検証日時は移動先のパスに引き継ぐ。
+func (d *targetDiscovery) applyMoves(moves []fileMove, newRecords [][]HashRecord) {
+	if len(moves) == 0 {
+		return
+	}
+
+	checkedTimes := loadCheckedTimes(d.diskInfo)
+	for _, move := range moves {
+		logf.Printf("ファイルの移動を検出しました。: %s -> %s\n", move.from, move.to)
+		for _, hashMap := range d.hashMaps {
+			delete(hashMap, move.from)
+		}
+		if checked, found := checkedTimes[move.from]; found {
+			checkedTimes[move.to] = checked
+			delete(checkedTimes, move.from)
+		}
+	}
+	logf.Printf("%d件のファイルの移動を記録しました。: %s\n", len(moves), d.diskInfo.id)
+
+	recorded := make(map[string]HashRecord, len(d.hashMaps[0])+len(newRecords[0]))
+	for normPath, record := range d.hashMaps[0] {
+		recorded[normPath] = record
+	}
+	for _, record := range newRecords[0] {
+		recorded[record.path] = record
+	}
+	if len(checkedTimes) > 0 {
+		saveCheckedTimes(d.diskInfo, checkedTimes, recorded)
+	}
+}
+
+// 記録のパスを昇順に並べて返す。
+func sortedRecordPaths(hashMap map[string]HashRecord) []string {
+	keys := make([]string, 0, len(hashMap))
+	for key := range hashMap {
+		keys = append(keys, key)
+	}
+	sort.Strings(keys)
+	return keys
+}
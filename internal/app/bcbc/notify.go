@@ -0,0 +1,301 @@
+package bcbc
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"mime"
+	"net/http"
+	"net/smtp"
+	"os"
+	"os/exec"
+	"path/filepath"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+)
+
+// 通知のイベント
+const (
+	// NotifyCompleted ハッシュ計算が完了した
+	NotifyCompleted = "completed"
+	// NotifyFailed 致命的なエラーで処理を続行できなかった
+	NotifyFailed = "failed"
+	// NotifyInterrupted 中断された
+	NotifyInterrupted = "interrupted"
+	// NotifyErrorThreshold エラーが発生したファイルの数が閾値に達した
+	NotifyErrorThreshold = "error-threshold"
+)
+
+// Notification 通知の内容
+type Notification struct {
+	Event   string    `json:"event"`
+	Host    string    `json:"host"`
+	Time    time.Time `json:"time"`
+	Errors  int       `json:"errors"`
+	Message string    `json:"message"`
+}
+
+// 通知設定
+type notifyConfig struct {
+	webhook      string
+	smtpAddr     string
+	smtpFrom     string
+	smtpTo       []string
+	smtpUser     string
+	smtpPassword string
+	command      string
+	// この数のファイルでエラーが発生したら完了を待たずに通知する。0なら通知しない。
+	errorThreshold int
+}
+
+// 通知設定ファイルを読み込む。
+// 通知設定ファイルがなければ通知しない。
+func initNotify() {
+	config.notify = nil
+
+	notifyConfigFile := filepath.Join(config.configDir(), "notify.conf")
+	notifyFileIn, err := os.Open(notifyConfigFile)
+	if os.IsNotExist(err) {
+		return
+	}
+	fatalMessageError(ErrConfig, err, "通知設定ファイルが読み込めませんでした。: %s\n", notifyConfigFile)
+	defer notifyFileIn.Close()
+
+	notify := &notifyConfig{}
+
+	scanner := bufio.NewScanner(notifyFileIn)
+	for i := 1; scanner.Scan(); i++ {
+		line := strings.TrimSpace(scanner.Text())
+		if line == "" || line[0] == '#' {
+			continue
+		}
+
+		separator := strings.Index(line, "=")
+		fatalMessageIf(ErrConfig, separator < 0, "通知設定ファイルの形式が不正です。: %d行目: %s\n", i, line)
+		key := strings.TrimSpace(line[:separator])
+		value := strings.TrimSpace(line[separator+1:])
+
+		switch key {
+		case "webhook":
+			notify.webhook = value
+		case "smtp":
+			notify.smtpAddr = value
+		case "smtp-from":
+			notify.smtpFrom = value
+		case "smtp-to":
+			for _, to := range strings.Split(value, ",") {
+				notify.smtpTo = append(notify.smtpTo, strings.TrimSpace(to))
+			}
+		case "smtp-user":
+			notify.smtpUser = value
+		case "smtp-password":
+			notify.smtpPassword = value
+		case "command":
+			notify.command = value
+		case "error-threshold":
+			notify.errorThreshold, err = strconv.Atoi(value)
+			fatalMessageIf(ErrConfig, err != nil || notify.errorThreshold < 0, "通知設定ファイルの形式が不正です。: %d行目: %s\n", i, line)
+		default:
+			fatalMessageIf(ErrConfig, true, "通知設定ファイルに不明な項目があります。: %d行目: %s\n", i, line)
+		}
+	}
+	fatalMessageError(ErrConfig, scanner.Err(), "通知設定ファイルが読み込めませんでした。: %s\n", notifyConfigFile)
+
+	smtpIncomplete := notify.smtpAddr != "" && (notify.smtpFrom == "" || len(notify.smtpTo) == 0)
+	fatalMessageIf(ErrConfig, smtpIncomplete, "通知設定ファイルのsmtpにはsmtp-fromとsmtp-toが必要です。\n")
+
+	config.notify = notify
+}
+
+// エラーの数の閾値の通知。
+// 長時間かかるディスクでも間に合うように、ファイルのエラーは進捗監視ルーチンが進捗から更新し、
+// ディスクのエラーはメインルーチンが更新して、合計が閾値に達したら全ディスクの完了を待たずに一度だけ通知する。
+type errorThresholdNotifier struct {
+	mu sync.Mutex
+	// ディスクごとのエラーが発生したファイルの数
+	fileErrors map[string]int
+	// 問題が発生したディスクの数
+	diskErrors int
+	notified   bool
+}
+
+// ハッシュ計算中のエラーの数の閾値の通知。ハッシュ計算中でなければnil。
+var errorNotifier *errorThresholdNotifier
+
+func newErrorThresholdNotifier() *errorThresholdNotifier {
+	return &errorThresholdNotifier{fileErrors: make(map[string]int)}
+}
+
+// ディスクのエラーが発生したファイルの数を更新する。
+func (e *errorThresholdNotifier) updateFileErrors(diskId string, fileErrors int) {
+	if e == nil {
+		return
+	}
+	e.mu.Lock()
+	if fileErrors > e.fileErrors[diskId] {
+		e.fileErrors[diskId] = fileErrors
+	}
+	total, crossed := e.crossed()
+	e.mu.Unlock()
+
+	if crossed {
+		e.send(total)
+	}
+}
+
+// 問題が発生したディスクを数える。
+func (e *errorThresholdNotifier) addDiskError() {
+	if e == nil {
+		return
+	}
+	e.mu.Lock()
+	e.diskErrors++
+	total, crossed := e.crossed()
+	e.mu.Unlock()
+
+	if crossed {
+		e.send(total)
+	}
+}
+
+// エラーの合計を返し、初めて閾値に達したらtrueを返す。ロックしてから呼ぶこと。
+func (e *errorThresholdNotifier) crossed() (int, bool) {
+	total := e.diskErrors
+	for _, n := range e.fileErrors {
+		total += n
+	}
+	if e.notified || config.notify == nil || config.notify.errorThreshold == 0 || total < config.notify.errorThreshold {
+		return total, false
+	}
+	e.notified = true
+	return total, true
+}
+
+func (e *errorThresholdNotifier) send(total int) {
+	message := fmt.Sprintf("エラーが発生したファイルとディスクの数が%dに達しました。", total)
+	config.notify.send(NotifyErrorThreshold, total, message)
+}
+
+// 設定された全ての通知先に通知する。
+// 通知に失敗してもログに出力するだけで処理は続ける。
+func (n *notifyConfig) send(event string, errors int, message string) {
+	if n == nil {
+		return
+	}
+
+	notification := Notification{
+		Event:   event,
+		Host:    writerHost(),
+		Time:    time.Now(),
+		Errors:  errors,
+		Message: message,
+	}
+
+	senders := []struct {
+		name    string
+		enabled bool
+		send    func(Notification) error
+	}{
+		{"webhook", n.webhook != "", n.sendWebhook},
+		{"smtp", n.smtpAddr != "", n.sendMail},
+		{"command", n.command != "", n.runCommand},
+	}
+
+	for _, sender := range senders {
+		if !sender.enabled {
+			continue
+		}
+		if err := sender.send(notification); err != nil {
+			logf.Printf("通知に失敗しました。: %s\n", sender.name)
+			logf.Println(err)
+		}
+	}
+}
+
+// 通知の送信のタイムアウト
+const notifyTimeout = 30 * time.Second
+
+// WebhookのURLに通知の内容をJSONでPOSTする。
+func (n *notifyConfig) sendWebhook(notification Notification) error {
+	body, err := json.Marshal(notification)
+	if err != nil {
+		return err
+	}
+
+	client := http.Client{Timeout: notifyTimeout}
+	response, err := client.Post(n.webhook, "application/json", bytes.NewReader(body))
+	if err != nil {
+		return err
+	}
+	defer response.Body.Close()
+
+	if response.StatusCode >= 300 {
+		return fmt.Errorf("webhook: %s", response.Status)
+	}
+	return nil
+}
+
+// SMTPでメールを送信する。
+func (n *notifyConfig) sendMail(notification Notification) error {
+	var auth smtp.Auth
+	if n.smtpUser != "" {
+		host := n.smtpAddr
+		if i := strings.LastIndex(host, ":"); i >= 0 {
+			host = host[:i]
+		}
+		auth = smtp.PlainAuth("", n.smtpUser, n.smtpPassword, host)
+	}
+
+	subject := fmt.Sprintf("bcbc: %s (%s)", notification.Event, notification.Host)
+
+	var message strings.Builder
+	fmt.Fprintf(&message, "From: %s\r\n", n.smtpFrom)
+	fmt.Fprintf(&message, "To: %s\r\n", strings.Join(n.smtpTo, ", "))
+	fmt.Fprintf(&message, "Subject: %s\r\n", mime.BEncoding.Encode("UTF-8", subject))
+	fmt.Fprintf(&message, "MIME-Version: 1.0\r\n")
+	fmt.Fprintf(&message, "Content-Type: text/plain; charset=UTF-8\r\n")
+	fmt.Fprintf(&message, "\r\n")
+	fmt.Fprintf(&message, "%s\r\n", notification.Message)
+	fmt.Fprintf(&message, "エラー: %d\r\n", notification.Errors)
+	fmt.Fprintf(&message, "日時: %s\r\n", notification.Time.Format(time.RFC3339))
+
+	return smtp.SendMail(n.smtpAddr, auth, n.smtpFrom, n.smtpTo, []byte(message.String()))
+}
+
+// 通知用のコマンドを実行する。
+// 通知の内容は環境変数で渡す。
+func (n *notifyConfig) runCommand(notification Notification) error {
+	command := exec.Command(n.command)
+	command.Env = append(os.Environ(),
+		"BCBC_EVENT="+notification.Event,
+		"BCBC_HOST="+notification.Host,
+		"BCBC_ERRORS="+strconv.Itoa(notification.Errors),
+		"BCBC_MESSAGE="+notification.Message,
+	)
+
+	output, err := command.CombinedOutput()
+	if err != nil && len(output) > 0 {
+		return fmt.Errorf("%w: %s", err, strings.TrimSpace(string(output)))
+	}
+	return err
+}
+
+// ハッシュ計算の結果を通知する。
+// 致命的なエラーで終了するときは、通知してからエラーをそのまま伝える。
+func notifyResult(errorCount *int) {
+	if r := recover(); r != nil {
+		e, ok := r.(*Error)
+		switch {
+		case ok && e.Kind == ErrInterrupted:
+			config.notify.send(NotifyInterrupted, *errorCount, strings.TrimSpace(e.Error()))
+		case ok:
+			config.notify.send(NotifyFailed, *errorCount, strings.TrimSpace(e.Error()))
+		}
+		panic(r)
+	}
+
+	config.notify.send(NotifyCompleted, *errorCount, fmt.Sprintf("ハッシュ計算を終了しました。エラー: %d", *errorCount))
+}
@@ -0,0 +1,187 @@
+package bcbc
+
+import (
+	"flag"
+	"os"
+	"time"
+)
+
+// コマンドラインオプションをFlagSetに登録する関数。
+// ライブラリとして組み込んだプログラムのフラグと衝突しないように、パッケージの初期化ではflag.CommandLineに登録せず、
+// CLIとして実行するときだけ作成するFlagSetに登録する。ライブラリとして使うときは既定値のまま使う。
+var optionDefinitions []func(flagSet *flag.FlagSet)
+
+// コマンドラインオプションを登録したFlagSet。CLIとして実行するときだけ作成する。
+var commandLine *flag.FlagSet
+
+// 全てのコマンドラインオプションを登録したFlagSetを作成する。
+func newCommandLine() *flag.FlagSet {
+	flagSet := flag.NewFlagSet(os.Args[0], flag.ExitOnError)
+	for _, define := range optionDefinitions {
+		define(flagSet)
+	}
+	return flagSet
+}
+
+func stringOption(name string, value string, usage string) *string {
+	p := new(string)
+	*p = value
+	optionDefinitions = append(optionDefinitions, func(flagSet *flag.FlagSet) {
+		flagSet.StringVar(p, name, value, usage)
+	})
+	return p
+}
+
+func boolOption(name string, value bool, usage string) *bool {
+	p := new(bool)
+	*p = value
+	optionDefinitions = append(optionDefinitions, func(flagSet *flag.FlagSet) {
+		flagSet.BoolVar(p, name, value, usage)
+	})
+	return p
+}
+
+func intOption(name string, value int, usage string) *int {
+	p := new(int)
+	*p = value
+	optionDefinitions = append(optionDefinitions, func(flagSet *flag.FlagSet) {
+		flagSet.IntVar(p, name, value, usage)
+	})
+	return p
+}
+
+func float64Option(name string, value float64, usage string) *float64 {
+	p := new(float64)
+	*p = value
+	optionDefinitions = append(optionDefinitions, func(flagSet *flag.FlagSet) {
+		flagSet.Float64Var(p, name, value, usage)
+	})
+	return p
+}
+
+func durationOption(name string, value time.Duration, usage string) *time.Duration {
+	p := new(time.Duration)
+	*p = value
+	optionDefinitions = append(optionDefinitions, func(flagSet *flag.FlagSet) {
+		flagSet.DurationVar(p, name, value, usage)
+	})
+	return p
+}
+
+// コマンドラインオプション: ハッシュアルゴリズム
+var algorithmOption = stringOption("algorithm", AlgorithmMD5,
+	"ハッシュアルゴリズム。md5、sha256、変更検知用の高速なcrc32、巨大なファイルを複数のコアで計算するツリーハッシュのmd5-treeとsha256-treeから選び、カンマ区切りで複数指定すると1回の読み込みで全て計算する。md5以外のハッシュファイルはアルゴリズム名のサブディレクトリに出力する。")
+
+// コマンドラインオプション: ハッシュファイルの保存先
+var storeOption = stringOption("store", StoreFile, "ハッシュファイルの保存先。fileは出力ディレクトリにテキストファイルで保存する。webdavは-store-urlのWebDAVサーバーに、s3は-store-urlのS3互換のオブジェクトストレージに、sftpは-store-urlのsshで接続できるマシンに、serverは-store-urlのbcbc serveの収集サーバーに保存する。")
+
+// コマンドラインオプション: 進捗ログの記録
+var progressLogOption = boolOption("progress-log", false, "進捗の経過をログディレクトリにJSONL形式で記録する。replayサブコマンドで再構成できる。")
+
+// コマンドラインオプション: シンボリックリンクの扱い
+var symlinksOption = stringOption("symlinks", SymlinksFollow,
+	"シンボリックリンクの扱い。skipはハッシュ計算しない。followはリンク先をたどる(ループするリンクはたどらない)。どちらでもシンボリックリンクは出力ディレクトリの<ディスクID>.symlinksに記録する。")
+
+// コマンドラインオプション: ディレクトリを走査する最大深さ
+var maxDepthOption = intOption("max-depth", 0, "ディスクルートから走査するディレクトリの最大深さ。ディスクルート直下を1とする。0なら制限しない。")
+
+// コマンドラインオプション: 記録の削除の確認
+var confirmOption = boolOption("confirm", false, "ハッシュファイルから記録を削除する前に削除する記録を表示して確認する。")
+
+// コマンドラインオプション: サイズの単位系
+var unitsOption = stringOption("units", UnitsBinary, "サイズの表示に使う単位系。binaryはGiBなど1024の累乗、decimalはGBなど1000の累乗。")
+
+// コマンドラインオプション: 読み込みエラーの再試行回数
+var retriesOption = intOption("retries", 2, "一時的な読み込みエラーを再試行する回数。権限がないなどのエラーは再試行しない。")
+
+// コマンドラインオプション: 読み込みエラーを再試行するまでの待ち時間
+var retryBackoffOption = durationOption("retry-backoff", time.Second, "読み込みエラーを最初に再試行するまでの待ち時間。再試行するたびに倍にする。")
+
+// コマンドラインオプション: サルベージモード
+var salvageOption = boolOption("salvage", false, "読み込めない範囲をゼロとみなして読み飛ばし、不完全な印を付けたハッシュを記録する。読み飛ばした範囲は出力ディレクトリの<ディスクID>.damageに追記する。")
+
+// コマンドラインオプション: メトリクスのポート
+var metricsPortOption = intOption("metrics-port", 0, "実行中にPrometheus形式のメトリクスを/metricsで提供するポート。0なら提供しない。")
+
+// コマンドラインオプション: 同時にハッシュ計算するディスクの最大数
+var maxParallelDisksOption = intOption("max-parallel-disks", 0, "同時にハッシュ計算するディスクの最大数。1つ終わるごとに次のディスクを開始する。0なら全てのディスクを同時に計算する。")
+
+// コマンドラインオプション: 読み込みのスループットの制限
+var throttleOption = float64Option("throttle", 0, "ディスクごとの読み込みのスループットの上限(MB/s)。0なら制限しない。diskファイルにthrottle=<MB/s>の行を書くとディスクごとに変えられる。")
+
+// コマンドラインオプション: バックグラウンドモード
+var backgroundOption = boolOption("background", false, "CPUとI/Oの優先度を下げて、他の作業を妨げないように実行する。")
+
+// コマンドラインオプション: 直接I/O
+var directIOOption = boolOption("direct-io", false, "ページキャッシュを使わずにファイルを読み込む。(LinuxはO_DIRECT、macOSはF_NOCACHE) 対応していないファイルシステムでは通常の読み込みに切り替える。")
+
+// コマンドラインオプション: メモリマップで読み込む最小のファイルサイズ
+var mmapMinSizeOption = intOption("mmap-min-size", 0, "このサイズ(MB)以上のファイルはメモリマップして読み込む。0ならメモリマップしない。直接I/Oとサルベージモードでは通常の読み込みを使う。")
+
+// コマンドラインオプション: スループットが遅かったファイルとディレクトリの報告数
+var slowReportOption = intOption("slow-report", 10, "ハッシュ計算の最後にスループットが遅かったファイルとディレクトリをこの数まで報告する。0なら報告しない。")
+
+// コマンドラインオプション: BCBCホームディレクトリ
+var homeOption = stringOption("home", "", "BCBCホームディレクトリ。指定しなければ環境変数BCBCHOME、それもなければユーザーのホームディレクトリの.bcbcを使う。")
+
+// コマンドラインオプション: フィルターの判定の記録
+var explainFiltersOption = boolOption("explain-filters", false, "フィルターでハッシュ対象外にしたファイルと、対象外にしたフィルターの行をログディレクトリの<日時>.filters.logに記録する。")
+
+// コマンドラインオプション: bcbcのメタデータのハッシュ計算
+var hashMetadataOption = boolOption("hash-metadata", false, "ディスク内にあるBCBCホームディレクトリの出力ディレクトリとログディレクトリ、ディスクルートのdiskファイルを除外せずにハッシュ計算する。")
+
+// コマンドラインオプション: ハードリンクのハッシュの使い回し
+var hardLinksOption = boolOption("hardlinks", true, "同じファイルへのハードリンクは最初のパスだけ読み込み、他のパスには同じハッシュを記録する。-hardlinks=falseなら全てのパスを読み込む。")
+
+// コマンドラインオプション: メタデータモード
+var metadataOption = boolOption("metadata", false, "ハッシュと一緒にファイルの更新日時、パーミッション、所有者を記録する。verify -metadataで変更を報告できる。")
+
+// コマンドラインオプション: 拡張属性モード
+var xattrsOption = boolOption("xattrs", false, "ハッシュと一緒に拡張属性(macOSのリソースフォークを含む)のダイジェストを記録する。verifyで記録と異なれば報告する。")
+
+// コマンドラインオプション: スナップショットモード
+var snapshotOption = boolOption("snapshot", false, "ハッシュ計算が終わったディスクのハッシュファイルを日時の名前のスナップショットとして<ディスクID>.snapshotsに保存する。snapshotsサブコマンドで一覧と違いを表示できる。")
+
+// コマンドラインオプション: 署名モード
+var signOption = boolOption("sign", false, "書き直したハッシュファイルと統合ハッシュファイルに設定ディレクトリの鍵で署名し、.sigファイルを書き込む。鍵はsign-keyサブコマンドで作成する。")
+
+// コマンドラインオプション: 暗号化モード
+var encryptOption = stringOption("encrypt", "", "ハッシュファイルの記録を暗号化する。keyなら設定ディレクトリのencryption.key(なければ作成する)、passphraseなら環境変数BCBC_PASSPHRASEのパスフレーズを鍵にする。")
+
+// コマンドラインオプション: 圧縮モード
+var compressOption = stringOption("compress", "", "gzipならハッシュファイルと統合ハッシュファイルを書き直すときに圧縮して拡張子.gzのファイルにする。読み込むときは拡張子で判別して展開する。")
+
+// コマンドラインオプション: WebDAVの保存先のURL
+var storeURLOption = stringOption("store-url", "", "-store=webdav、s3、sftp、serverのときのハッシュファイルの保存先のURL。(例: https://nas/bcbc/out、https://s3.ap-northeast-1.amazonaws.com/バケット/接頭辞、sftp://nas/bcbc/out) 出力ディレクトリは作業用の複製に使う。")
+
+// コマンドラインオプション: S3のリージョン
+var s3RegionOption = stringOption("s3-region", "us-east-1", "-store=s3のときに署名に使うリージョン。認証情報は環境変数AWS_ACCESS_KEY_ID、AWS_SECRET_ACCESS_KEY、AWS_SESSION_TOKENで指定する。")
+
+// コマンドラインオプション: 索引
+var indexOption = boolOption("index", false, "ハッシュ計算の最後に全てのハッシュファイルの記録をBCBCホームディレクトリのindex.gobにまとめ、findではハッシュファイルの代わりに使う。")
+
+// コマンドラインオプション: フィルター設定ファイルより優先するフィルター
+var filterOptions = newFilterOptions(
+	"ハッシュ対象に含めるパスの正規表現。フィルター設定ファイルの先頭に+の行として加えたように扱う。複数回指定できる。",
+	"ハッシュ対象から除外するパスの正規表現。フィルター設定ファイルの先頭に-の行として加えたように扱う。複数回指定できる。")
+
+// コマンドラインオプション: 無くなったファイルの記録の削除
+var pruneOption = boolOption("prune", false, "ディスクから無くなったファイルの記録をハッシュファイルから削除する。指定しなければ記録を残して、ハッシュ計算の最後に無くなったファイルを報告する。")
+
+// コマンドラインオプション: 前回エラーになったファイルの再試行
+var retryFailedOption = boolOption("retry-failed", false, "前回のハッシュ計算で出力ディレクトリの<ディスクID>.errorsに記録されたファイルだけをハッシュ計算し、成功した記録をハッシュファイルに追記する。")
+
+// コマンドラインオプション: ディスクのハッシュ計算を打ち切るエラーの数
+var maxErrorsOption = intOption("max-errors", 0, "ディスクごとにエラーになったファイルがこの数に達したら、そのディスクのハッシュ計算を打ち切る。0なら打ち切らない。")
+
+// コマンドラインオプション: メッセージの言語
+var langOption = stringOption("lang", "", "ログとエラーのメッセージの言語。jaかen。指定しなければ環境変数LC_ALL、LC_MESSAGES、LANGから決める。")
+
+// コマンドラインオプション: ログの形式
+var logFormatOption = stringOption("log-format", LogFormatText, "ログの形式。textは1行のテキスト、jsonは日時、レベル、メッセージの種類、メッセージ、引数と、ディスクID、パス、サイズなど名前を付けた値を1行ごとのJSONのオブジェクトで出力する。")
+
+// コマンドラインオプション: マウントされたディスクの自動検出
+var scanMountsOption = boolOption("scan-mounts", false, "マウントされている全てのボリュームのルートにdiskファイルがあるか調べて、見つかったディスクを全て処理する。ディスクルートの引数と一緒に指定できる。")
+
+// コマンドラインオプション: 残っているロックファイルの削除
+var forceUnlockOption = boolOption("force-unlock", false, "処理するディスクのロックファイルが残っていたら、ロックしている書き込み元が実行中ではないとみなして削除してからロックする。")
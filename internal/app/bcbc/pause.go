@@ -0,0 +1,109 @@
+package bcbc
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+	"sync"
+	"time"
+)
+
+// 制御ファイルを確認する間隔
+const controlFilePollInterval = time.Second
+
+// ハッシュ計算の一時停止。
+// 一時停止中はハッシュルーチンがファイルの読み込みを止めて、再開されるか中断されるまで待つ。
+type pauseControl struct {
+	mu     sync.Mutex
+	paused bool
+	// 再開すると閉じるチャネル
+	resumed chan struct{}
+}
+
+// ハッシュ計算の一時停止の状態
+var pauseState = &pauseControl{}
+
+// 一時停止する。
+func (p *pauseControl) pause() {
+	p.mu.Lock()
+	defer p.mu.Unlock()
+
+	if p.paused {
+		return
+	}
+	p.paused = true
+	p.resumed = make(chan struct{})
+	logf.Println("ハッシュ計算を一時停止しました。")
+}
+
+// 一時停止から再開する。
+func (p *pauseControl) resume() {
+	p.mu.Lock()
+	defer p.mu.Unlock()
+
+	if !p.paused {
+		return
+	}
+	p.paused = false
+	close(p.resumed)
+	logf.Println("ハッシュ計算を再開しました。")
+}
+
+// 一時停止中なら再開されるか中断されるまで待つ。
+func (p *pauseControl) wait() {
+	p.mu.Lock()
+	if !p.paused {
+		p.mu.Unlock()
+		return
+	}
+	resumed := p.resumed
+	p.mu.Unlock()
+
+	select {
+	case <-resumed:
+	case <-interruptChannel:
+	case <-abortChannel:
+	}
+}
+
+// 制御ファイルのパスを返す。
+func controlFile() string {
+	return filepath.Join(config.homeDir, "control")
+}
+
+// 一時停止と再開の指示を監視する。
+// シグナル(SIGUSR1で一時停止、SIGUSR2で再開)と、BCBCホームディレクトリの制御ファイルに
+// pauseかresumeを書き込む指示を受け付ける。制御ファイルは内容が変わったときだけ反映する。
+func watchPause() {
+	watchPauseSignals()
+
+	go func() {
+		lastCommand := readControlFile()
+		for range time.Tick(controlFilePollInterval) {
+			command := readControlFile()
+			if command == lastCommand {
+				continue
+			}
+			lastCommand = command
+
+			switch command {
+			case "pause":
+				pauseState.pause()
+			case "resume":
+				pauseState.resume()
+			case "":
+			default:
+				logf.Printf("制御ファイルの指示が不正です。pauseかresumeを書いてください。: %s\n", command)
+			}
+		}
+	}()
+}
+
+// 制御ファイルの指示を読み込む。なければ空文字列を返す。
+func readControlFile() string {
+	content, err := os.ReadFile(controlFile())
+	if err != nil {
+		return ""
+	}
+	return strings.TrimSpace(string(content))
+}
@@ -0,0 +1,25 @@
+// +build !windows
+
+package bcbc
+
+import (
+	"os"
+	"os/signal"
+	"syscall"
+)
+
+// SIGUSR1で一時停止し、SIGUSR2で再開する。
+func watchPauseSignals() {
+	signalChannel := make(chan os.Signal, 1)
+	signal.Notify(signalChannel, syscall.SIGUSR1, syscall.SIGUSR2)
+
+	go func() {
+		for sig := range signalChannel {
+			if sig == syscall.SIGUSR1 {
+				pauseState.pause()
+			} else {
+				pauseState.resume()
+			}
+		}
+	}()
+}
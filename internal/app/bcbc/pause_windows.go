@@ -0,0 +1,7 @@
+// +build windows
+
+package bcbc
+
+// Windowsには一時停止に使えるシグナルがないので、制御ファイルだけで受け付ける。
+func watchPauseSignals() {
+}
@@ -0,0 +1,61 @@
+package bcbc
+
+import (
+	"os"
+	"path/filepath"
+)
+
+// 空き容量の見積もりに使う平均的なファイルサイズ
+const assumedAverageFileSize = 1024 * 1024
+
+// 空き容量の見積もりに使うハッシュファイルの1行の平均的な長さ
+const assumedRecordLength = 128
+
+// ハッシュ計算を始める前に出力ディレクトリの空き容量を確かめる。
+// ディスクの使用量から記録の数を見積もって、ハッシュファイルの書き直しと統合ハッシュファイルの
+// 書き込みに足りなさそうなら警告する。
+// 平均的なファイルサイズからの大まかな見積もりなので中止はしない。実際に空きがなくなったら空きができるまで待機する。
+// 出力ディレクトリがハッシュ計算するディスクにあれば警告する。
+func checkOutputSpace(diskInfoList []DiskInfo) {
+	outDir := config.outDir()
+	if err := os.MkdirAll(outDir, 0755); err != nil {
+		return
+	}
+
+	for _, diskInfo := range diskInfoList {
+		if sameDevice(outDir, diskInfo.rootPath) {
+			logf.Printf("[警告] 出力ディレクトリがハッシュ計算するディスク(%s)にあります。ディスクが壊れるとハッシュファイルも失われます。: %s\n", diskInfo.id, outDir)
+		}
+	}
+
+	var outEnvironment DiskEnvironment
+	inspectDiskEnvironment(outDir, &outEnvironment)
+	if outEnvironment.TotalBytes == 0 {
+		// 空き容量を調べられない環境では確かめない
+		return
+	}
+
+	var required uint64
+	for _, diskInfo := range diskInfoList {
+		var diskEnvironment DiskEnvironment
+		inspectDiskEnvironment(diskInfo.rootPath, &diskEnvironment)
+		estimated := (diskEnvironment.TotalBytes - diskEnvironment.FreeBytes) / assumedAverageFileSize * assumedRecordLength
+
+		for _, algorithm := range diskInfo.hashAlgorithms() {
+			var current uint64
+			if stat, err := os.Stat(existingHashFile(filepath.Join(config.hashDir(algorithm), diskInfo.id))); err == nil {
+				current = uint64(stat.Size())
+			}
+			if estimated < current {
+				estimated = current
+			}
+			// ハッシュファイルと統合ハッシュファイルの増加分と、書き直すときの一時ファイル
+			required += (estimated-current)*2 + estimated
+		}
+	}
+
+	if required > outEnvironment.FreeBytes {
+		logf.Printf("[警告] 出力ディレクトリの空き容量が足りない可能性があります。(空き容量%s、必要な容量の見積もり%s): %s\n",
+			formatSize(float64(outEnvironment.FreeBytes)), formatSize(float64(required)), outDir)
+	}
+}
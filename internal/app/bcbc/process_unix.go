@@ -0,0 +1,14 @@
+// +build !windows
+
+package bcbc
+
+import (
+	"syscall"
+)
+
+// 同じマシンで指定されたプロセスIDのプロセスが実行中か返す。
+// シグナルを送る権限がないだけならプロセスはあるので実行中とみなす。
+func processExists(pid int) bool {
+	err := syscall.Kill(pid, 0)
+	return err == nil || err == syscall.EPERM
+}
@@ -0,0 +1,24 @@
+// +build windows
+
+package bcbc
+
+import (
+	"syscall"
+)
+
+// OpenProcessに渡すプロセスの情報を問い合わせる権限
+const processQueryLimitedInformation = 0x1000
+
+// 存在しないプロセスを開こうとしたときのエラー(ERROR_INVALID_PARAMETER)
+const errorInvalidParameter = syscall.Errno(87)
+
+// 同じマシンで指定されたプロセスIDのプロセスが実行中か返す。
+// 権限がないなど、存在しないこと以外の理由で開けなければ実行中とみなす。
+func processExists(pid int) bool {
+	handle, err := syscall.OpenProcess(processQueryLimitedInformation, false, uint32(pid))
+	if err != nil {
+		return err != errorInvalidParameter
+	}
+	syscall.CloseHandle(handle)
+	return true
+}
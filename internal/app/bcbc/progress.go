@@ -3,17 +3,41 @@ package bcbc
 import (
 	"fmt"
 	"log"
+	"math"
+	"os"
 	"strings"
 	"time"
 )
 
+// 進捗の1行を出力するロガー。ライブラリとして実行するときは呼び出し元のロガーにする。
+var progressLogger = log.New(os.Stderr, "", log.LstdFlags)
+
 // ProgressInfo 進捗情報
 type ProgressInfo struct {
 	diskInfo       *DiskInfo
 	sizeCount      ProgressCount
 	fileCount      ProgressCount
 	processingFile string
-	startTime      time.Time
+	// 処理中のファイルのサイズと読み込んだバイト数
+	fileBytes ProgressCount
+	startTime time.Time
+	// ここまでにエラーが発生したファイルの数
+	fileErrors int
+	// 計算済みなので読み込まないファイルの数とサイズの合計
+	skippedFiles uint64
+	skippedBytes uint64
+	// 中断する前に計算したファイルの数とサイズの合計。計算したファイルに含める。
+	resumedFiles uint64
+	resumedBytes uint64
+}
+
+// 計算済みのファイルを含めた全てのファイルのうち計算済みのファイルの割合を返す。
+func (pi *ProgressInfo) skippedRate() float64 {
+	all := pi.skippedFiles + pi.fileCount.total
+	if all == 0 {
+		return 0
+	}
+	return float64(pi.skippedFiles) / float64(all)
 }
 
 type ProgressCount struct {
@@ -22,7 +46,7 @@ type ProgressCount struct {
 }
 
 func (pc *ProgressCount) ProgressRate() float64 {
-	if pc.total == 0 {
+	if pc.total == 0 || pc.processed >= pc.total {
 		return 1.0
 	}
 	return float64(pc.processed) / float64(pc.total)
@@ -36,63 +60,195 @@ func (pc *ProgressCount) Increment(n uint64) {
 	pc.processed += n
 }
 
+// 進捗監視ルーチンを開始する。
+// 返された関数は進捗チャネルを閉じて進捗監視ルーチンの終了を待ち、進捗監視中に発生したエラーを返す。
+// 進捗チャネルに送るゴルーチンが全て終了してから呼ぶこと。
+func startProgressMonitor(numberOfDisks int) (chan ProgressInfo, func() error) {
+	progressChannel := make(chan ProgressInfo)
+	doneChannel := make(chan error, 1)
+
+	watchStatusKey()
+	go watchProgress(numberOfDisks, progressChannel, doneChannel)
+
+	shutdown := func() error {
+		close(progressChannel)
+		return <-doneChannel
+	}
+
+	return progressChannel, shutdown
+}
+
 // 進捗監視ルーチン。
-func watchProgress(numberOfDisks int, progressChannel chan ProgressInfo) {
+// 進捗チャネルが閉じられたら最後の進捗を表示して終了し、発生したエラーを完了チャネルで返す。
+func watchProgress(numberOfDisks int, progressChannel chan ProgressInfo, doneChannel chan error) {
+	var err error
+	defer func() {
+		if r := recover(); r != nil {
+			e, ok := r.(*Error)
+			if !ok {
+				panic(r)
+			}
+			err = e
+		}
+		doneChannel <- err
+	}()
+
 	progressInfoList := make([]ProgressInfo, numberOfDisks)
+	estimators := make([]throughputEstimator, numberOfDisks)
 
 	lastPrintTime := time.Now()
 
-	for {
-		progressInfo := <-progressChannel
-		progressInfoList[progressInfo.diskInfo.index] = progressInfo
+	recorder := newProgressRecorder()
+	reporter := newProgressReporter()
 
-		if time.Now().Sub(lastPrintTime) >= time.Second {
-			if numberOfDisks == 1 {
-				printProgress(progressInfoList[0])
-			} else {
-				printProgressSummary(progressInfoList)
+	printAll := func() {
+		if statusArea != nil {
+			renderProgress(progressInfoList, estimators)
+		} else if numberOfDisks == 1 {
+			printProgress(progressInfoList[0], &estimators[0])
+		} else {
+			printProgressSummary(progressInfoList, estimators)
+		}
+		reporter.report(progressInfoList, estimators)
+		lastPrintTime = time.Now()
+	}
+
+	for running := true; running; {
+		select {
+		case progressInfo, ok := <-progressChannel:
+			if !ok {
+				running = false
+				break
 			}
-			lastPrintTime = time.Now()
+
+			index := progressInfo.diskInfo.index
+			progressInfoList[index] = progressInfo
+			estimators[index].update(progressInfo.sizeCount.processed)
+			errorNotifier.updateFileErrors(progressInfo.diskInfo.id, progressInfo.fileErrors)
+			metrics.updateProgress(progressInfo, estimators[index].rate)
+			recorder.record(progressInfo)
+
+			if time.Now().Sub(lastPrintTime) >= time.Second {
+				printAll()
+			}
+		case <-statusRequests:
+			printStatusDump(progressInfoList, estimators)
 		}
 	}
+
+	printAll()
+
+	err = recorder.close()
 }
 
 // 1つのディスク処理について進捗情報を表示する。
-func printProgress(progressInfo ProgressInfo) {
+func printProgress(progressInfo ProgressInfo, estimator *throughputEstimator) {
 
 	if progressInfo.diskInfo == nil {
 		return
 	}
 
+	printProgressLine(formatProgress(progressInfo, estimator))
+}
+
+// 端末の進捗表示領域にディスクごとに1行ずつ進捗情報を表示する。
+// 複数のディスクを処理していれば最後に全体の進捗を表示する。
+func renderProgress(progressInfoList []ProgressInfo, estimators []throughputEstimator) {
+	rows := make([]string, 0, len(progressInfoList))
+	for i, pi := range progressInfoList {
+		if pi.diskInfo != nil {
+			rows = append(rows, formatProgress(pi, &estimators[i]))
+		}
+	}
+	if len(rows) > 1 {
+		rows = append(rows, formatOverallProgress(progressInfoList, estimators))
+	}
+
+	statusArea.render(rows)
+}
+
+// 1つのディスク処理の進捗情報を1行にフォーマットする。
+// 残り時間はディスク全体と処理中のファイルの両方を表示する。
+func formatProgress(progressInfo ProgressInfo, estimator *throughputEstimator) string {
 	fc := progressInfo.fileCount
 	sc := progressInfo.sizeCount
+	fb := progressInfo.fileBytes
 	rate := sc.ProgressRate()
-	remainTime := calcRemainTime(progressInfo.startTime, rate)
-	formattedRemainTime := formatRemainTime(remainTime)
 
-	log.Printf("%s [%5d/%5d] %6.2f%% %s %s\n",
-		progressInfo.diskInfo.id, fc.processed, fc.total, rate*100, formattedRemainTime, progressInfo.processingFile)
+	// 計算済みのファイルがあれば、新しいファイルの数だけでなく全体のうち計算済みの割合も表示する
+	skipped := ""
+	if progressInfo.skippedFiles > 0 {
+		skipped = fmt.Sprintf(tr(" (全%s件中%.1f%%計算済み)"), formatCount(progressInfo.skippedFiles+fc.total), progressInfo.skippedRate()*100)
+	}
+
+	return fmt.Sprintf("%s [%7s/%7s]%s %10s/%10s %6.2f%% %s (%s) %s",
+		progressInfo.diskInfo.id, formatCount(fc.processed), formatCount(fc.total), skipped,
+		formatSize(float64(sc.processed)), formatSize(float64(sc.total)),
+		rate*100, formatRemainTime(estimator.remainTime(sc)), formatRemainTime(estimator.remainTime(fb)),
+		progressInfo.processingFile)
 }
 
 // 複数のディスク処理について進捗情報の概要を表示する。
-func printProgressSummary(progressInfoList []ProgressInfo) {
+// ディスクごとの進捗率と処理済みのファイル数を桁をそろえて並べ、最後に全体の進捗を表示する。
+func printProgressSummary(progressInfoList []ProgressInfo, estimators []throughputEstimator) {
 	summaries := make([]string, 0, len(progressInfoList))
 
-	maxRemainTime := int64(0)
+	idWidth := 0
+	for _, pi := range progressInfoList {
+		if pi.diskInfo != nil && len(pi.diskInfo.id) > idWidth {
+			idWidth = len(pi.diskInfo.id)
+		}
+	}
 
 	for _, pi := range progressInfoList {
 		if pi.diskInfo != nil {
 			rate := pi.sizeCount.ProgressRate()
-			summaries = append(summaries, fmt.Sprintf("%s %6.2f%%", pi.diskInfo.id, rate*100))
+			summaries = append(summaries, fmt.Sprintf("%-*s %6.2f%% [%7s/%7s]", idWidth, pi.diskInfo.id, rate*100,
+				formatCount(pi.fileCount.processed), formatCount(pi.fileCount.total)))
+		}
+	}
 
-			remainTime := calcRemainTime(pi.startTime, rate)
-			if remainTime > maxRemainTime {
-				maxRemainTime = remainTime
-			}
+	printProgressLine(strings.Join(summaries, " / ") + " - " + formatOverallProgress(progressInfoList, estimators))
+}
+
+// 全ディスクの進捗を合計して1行にフォーマットする。
+// 残り時間は最も時間がかかるディスクの残り時間にする。推定できていないディスクがあれば表示しない。
+func formatOverallProgress(progressInfoList []ProgressInfo, estimators []throughputEstimator) string {
+	var sizeCount, fileCount ProgressCount
+	maxRemainTime := int64(0)
+
+	for i, pi := range progressInfoList {
+		if pi.diskInfo == nil {
+			continue
+		}
+
+		sizeCount.total += pi.sizeCount.total
+		sizeCount.processed += pi.sizeCount.processed
+		fileCount.total += pi.fileCount.total
+		fileCount.processed += pi.fileCount.processed
+
+		remainTime := estimators[i].remainTime(pi.sizeCount)
+		if remainTime == -1 {
+			maxRemainTime = -1
+		}
+		if maxRemainTime != -1 && remainTime > maxRemainTime {
+			maxRemainTime = remainTime
 		}
 	}
 
-	log.Println(strings.Join(summaries, " / "), "-", formatRemainTime(maxRemainTime))
+	return fmt.Sprintf("%s [%7s/%7s] %10s/%10s %6.2f%% %s", tr("全体"),
+		formatCount(fileCount.processed), formatCount(fileCount.total),
+		formatSize(float64(sizeCount.processed)), formatSize(float64(sizeCount.total)),
+		sizeCount.ProgressRate()*100, formatRemainTime(maxRemainTime))
+}
+
+// 進捗の1行をコンソールに出力する。進捗はログファイルには記録しない。
+func printProgressLine(line string) {
+	if jsonLogs() {
+		writeJSONLog(consoleOut, levelProgress, "", line, nil, logFields{})
+		return
+	}
+	progressLogger.Println(line)
 }
 
 // 残り時間を計算してhhh:mm:ss形式の文字列にフォーマットする。
@@ -110,11 +266,54 @@ func formatRemainTime(remainTime int64) string {
 	return fmt.Sprintf("%3d:%02d:%02d", hours, minutes, seconds)
 }
 
-// 残り時間を計算する。
-func calcRemainTime(startTime time.Time, rate float64) int64 {
-	if rate == 0 {
+// スループットを平滑化する時定数
+const throughputTimeConstant = 30 * time.Second
+
+// スループットの推定。
+// 開始からの平均では序盤やファイルサイズがばらつくときに残り時間が大きく揺れるので、
+// 直近の読み込み速度ほど重く扱う指数加重移動平均で推定する。
+type throughputEstimator struct {
+	lastTime  time.Time
+	lastBytes uint64
+	// 1秒あたりのバイト数。まだ推定できていなければ0。
+	rate float64
+}
+
+// 読み込んだバイト数の合計で推定を更新する。
+func (e *throughputEstimator) update(processed uint64) {
+	now := time.Now()
+
+	if e.lastTime.IsZero() || processed < e.lastBytes {
+		e.lastTime = now
+		e.lastBytes = processed
+		return
+	}
+
+	elapsed := now.Sub(e.lastTime)
+	if elapsed <= 0 {
+		return
+	}
+
+	current := float64(processed-e.lastBytes) / elapsed.Seconds()
+	if e.rate == 0 {
+		e.rate = current
+	} else {
+		alpha := 1 - math.Exp(-float64(elapsed)/float64(throughputTimeConstant))
+		e.rate += alpha * (current - e.rate)
+	}
+
+	e.lastTime = now
+	e.lastBytes = processed
+}
+
+// 推定したスループットで残りのバイト数を読み込む時間を計算する。
+// 推定できていなければ-1を返す。
+func (e *throughputEstimator) remainTime(count ProgressCount) int64 {
+	if count.Completed() {
+		return 0
+	}
+	if e.rate <= 0 {
 		return -1
 	}
-	elapsedTime := float64(time.Now().Sub(startTime))
-	return int64(elapsedTime/rate - elapsedTime)
+	return int64(float64(count.total-count.processed) / e.rate * float64(time.Second))
 }
@@ -0,0 +1,88 @@
+package bcbc
+
+import (
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"time"
+)
+
+// 中断したハッシュ計算の進捗を記録する間隔
+const progressStateInterval = 30 * time.Second
+
+// 中断したハッシュ計算の進捗。
+// 再開したときに前回までに計算したファイルを全体の進捗に含めるために使う。
+type progressState struct {
+	// 最初に開始した日時
+	StartTime time.Time `json:"startTime"`
+	// これまでに計算したファイルの数とサイズの合計
+	Files uint64 `json:"files"`
+	Bytes uint64 `json:"bytes"`
+}
+
+// 中断したハッシュ計算の進捗の記録のパスを返す。
+func (di *DiskInfo) progressStateFile() string {
+	return filepath.Join(config.outDir(), di.id+".progress")
+}
+
+// 前回中断したハッシュ計算の進捗を進捗情報に引き継ぐ。
+// 前回までに計算したファイルは今回は計算済みとして数えられるので、計算済みから除いて計算したファイルに含める。
+func resumeProgress(diskInfo *DiskInfo, progressInfo *ProgressInfo) {
+	data, err := os.ReadFile(diskInfo.progressStateFile())
+	if os.IsNotExist(err) {
+		return
+	}
+
+	var state progressState
+	if err == nil {
+		err = json.Unmarshal(data, &state)
+	}
+	if err != nil {
+		logf.Printf("[警告] 中断したハッシュ計算の進捗が読み込めなかったので、進捗は最初から数えます。: %s\n", diskInfo.progressStateFile())
+		logf.Println(err)
+		return
+	}
+
+	progressInfo.startTime = state.StartTime
+	progressInfo.resumedFiles = state.Files
+	progressInfo.resumedBytes = state.Bytes
+	progressInfo.fileCount.processed = state.Files
+	progressInfo.sizeCount.processed = state.Bytes
+
+	logf.Printf("中断したハッシュ計算を再開します。(%sに開始、%s、%sは計算済み): %s\n",
+		state.StartTime.Local().Format("2006-01-02 15:04:05"), formatQuantity(state.Files, "件のファイル", "件のファイル"),
+		formatSize(float64(state.Bytes)), diskInfo.id)
+}
+
+// ハッシュ計算の進捗を記録する。
+// 書き込み中に中断されても前回の記録が壊れないように、一時ファイルに書き込んでから置き換える。
+func saveProgressState(diskInfo *DiskInfo, progressInfo ProgressInfo) {
+	state := progressState{
+		StartTime: progressInfo.startTime,
+		Files:     progressInfo.fileCount.processed,
+		Bytes:     progressInfo.sizeCount.processed,
+	}
+
+	stateFile := diskInfo.progressStateFile()
+	tempFile := stateFile + ".tmp"
+	data, err := json.MarshalIndent(state, "", "  ")
+	if err == nil {
+		err = os.WriteFile(tempFile, data, 0644)
+	}
+	if err == nil {
+		err = os.Rename(tempFile, stateFile)
+	}
+	if err != nil {
+		logf.Printf("[警告] ハッシュ計算の進捗の記録に失敗しました。: %s\n", stateFile)
+		logf.Println(err)
+	}
+}
+
+// ハッシュ計算が最後まで終わったので進捗の記録を削除する。
+func removeProgressState(diskInfo *DiskInfo) {
+	err := os.Remove(diskInfo.progressStateFile())
+	if err != nil && !os.IsNotExist(err) {
+		logf.Printf("[警告] ハッシュ計算の進捗の記録を削除できませんでした。: %s\n", diskInfo.progressStateFile())
+		logf.Println(err)
+	}
+}
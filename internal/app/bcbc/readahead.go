@@ -0,0 +1,106 @@
+package bcbc
+
+// 先読みするバッファの数。
+// 1つをハッシュ計算している間にもう1つに読み込む。
+const readAheadBuffers = 2
+
+// 先読みで読み込んだチャンク
+type readResult struct {
+	chunk   []byte
+	damaged []byteRange
+	err     error
+}
+
+// ファイルの先読み。
+// 読み込みとハッシュ計算を別のゴルーチンで行い、ディスクの読み込みとハッシュ計算を重ねる。
+type readAhead struct {
+	results chan readResult
+	// ハッシュ計算が終わって読み込みに使えるバッファ
+	free chan []byte
+	// ハッシュ計算をやめたときに閉じて読み込みを打ち切らせる
+	done chan struct{}
+	// 読み込みのゴルーチンが終了したら閉じる
+	finished chan struct{}
+	buffers  [][]byte
+}
+
+// 先読みを開始する。
+// 読み込みのスループットの制限は読み込みのゴルーチンで行う。
+func startReadAhead(fileIn targetFile, file string, size uint64, bufferSize int, limiter *rateLimiter) *readAhead {
+	r := &readAhead{
+		results:  make(chan readResult, readAheadBuffers),
+		free:     make(chan []byte, readAheadBuffers),
+		done:     make(chan struct{}),
+		finished: make(chan struct{}),
+	}
+
+	for i := 0; i < readAheadBuffers; i++ {
+		buffer := getBuffer(bufferSize)
+		r.buffers = append(r.buffers, buffer)
+		r.free <- buffer
+	}
+
+	go r.run(fileIn, file, size, limiter)
+
+	return r
+}
+
+// 空いたバッファに順に読み込んで送る。
+// ファイルの末尾まで読み込むか、エラーが発生したら終了する。
+func (r *readAhead) run(fileIn targetFile, file string, size uint64, limiter *rateLimiter) {
+	defer close(r.finished)
+	defer close(r.results)
+
+	var offset int64
+
+	for {
+		var buffer []byte
+		select {
+		case buffer = <-r.free:
+		case <-r.done:
+			return
+		}
+
+		ret, damaged, err := readChunk(fileIn, file, buffer, offset, size)
+		if err == nil {
+			limiter.wait(ret)
+		}
+
+		select {
+		case r.results <- readResult{buffer[:ret], damaged, err}:
+		case <-r.done:
+			return
+		}
+
+		if err != nil || ret == 0 {
+			return
+		}
+		offset += int64(ret)
+	}
+}
+
+// 次に読み込んだチャンクを受け取る。
+// ファイルの末尾まで読み込んだら空のチャンクを返す。
+func (r *readAhead) next() readResult {
+	result, ok := <-r.results
+	if !ok {
+		return readResult{}
+	}
+	return result
+}
+
+// ハッシュ計算が終わったチャンクのバッファを読み込みに戻す。
+func (r *readAhead) release(chunk []byte) {
+	r.free <- chunk[:cap(chunk)]
+}
+
+// 先読みを終了してバッファをプールに戻す。
+// 読み込み中なら読み込みが終わるまで待つ。
+func (r *readAhead) stop() {
+	close(r.done)
+	<-r.finished
+
+	for _, buffer := range r.buffers {
+		putBuffer(buffer)
+	}
+}
@@ -0,0 +1,203 @@
+package bcbc
+
+import (
+	"encoding/json"
+	"flag"
+	"os"
+	"path/filepath"
+	"sort"
+	"time"
+)
+
+// DiskRecord ディスク登録簿のディスク1台分の記録
+type DiskRecord struct {
+	ID            string    `json:"id"`
+	Label         string    `json:"label,omitempty"`
+	Group         string    `json:"group,omitempty"`
+	Description   string    `json:"description,omitempty"`
+	Location      string    `json:"location,omitempty"`
+	Serial        string    `json:"serial,omitempty"`
+	CapacityBytes uint64    `json:"capacity_bytes,omitempty"`
+	RegisteredAt  time.Time `json:"registered_at"`
+	LastHashAt    time.Time `json:"last_hash_at,omitempty"`
+	LastVerifyAt  time.Time `json:"last_verify_at,omitempty"`
+}
+
+// ディスク登録簿のパスを返す。
+func registryFile() string {
+	return filepath.Join(config.homeDir, "registry.json")
+}
+
+// ディスク登録簿のロックファイルのパスを返す。
+// 出力ディレクトリを共有する別々に実行したbcbcが、同時に読み込んで書き込んだ更新を失わないようにする。
+func registryLockFile() string {
+	return registryFile() + ".lock"
+}
+
+// ディスク登録簿をロックする。他の書き込み元がロックしていたら解除されるまで待つ。
+func lockRegistry() {
+	err := waitLock(registryLockFile())
+	fatalMessageError(ErrLocked, err, "ディスク登録簿をロックできませんでした。: %s\n", registryFile())
+}
+
+// ディスク登録簿のロックを解除する。
+func unlockRegistry() {
+	unlockFile(registryLockFile())
+}
+
+// ディスク登録簿を読み込む。
+// ディスク登録簿がなければ空を返す。
+func loadRegistry() map[string]*DiskRecord {
+	registry := make(map[string]*DiskRecord)
+
+	data, err := os.ReadFile(registryFile())
+	if os.IsNotExist(err) {
+		return registry
+	}
+	fatalMessageError(ErrIO, err, "ディスク登録簿が読み込めませんでした。: %s\n", registryFile())
+
+	err = json.Unmarshal(data, &registry)
+	fatalMessageError(ErrConfig, err, "ディスク登録簿の形式が不正です。: %s\n", registryFile())
+
+	return registry
+}
+
+// ディスク登録簿を書き込む。
+// 書き込み中に中断されても壊れないように、一時ファイルに書き込んでから置き換える。
+// 読み込んでから書き込むまではlockRegistryでロックしておくこと。
+func saveRegistry(registry map[string]*DiskRecord) {
+	data, err := json.MarshalIndent(registry, "", "  ")
+	fatalMessageError(ErrIO, err, "ディスク登録簿の書き込みに失敗しました。: %s\n", registryFile())
+
+	tempFile := registryFile() + ".tmp"
+	err = os.WriteFile(tempFile, data, 0644)
+	if err == nil {
+		err = os.Rename(tempFile, registryFile())
+	}
+	fatalMessageError(ErrIO, err, "ディスク登録簿の書き込みに失敗しました。: %s\n", registryFile())
+}
+
+// ディスク登録簿のディスクの記録を更新する。
+// 登録されていないディスクは登録してから更新する。
+func updateRegistry(diskIds []string, update func(record *DiskRecord)) {
+	if len(diskIds) == 0 {
+		return
+	}
+
+	lockRegistry()
+	defer unlockRegistry()
+
+	registry := loadRegistry()
+	for _, diskId := range diskIds {
+		record, found := registry[diskId]
+		if !found {
+			record = &DiskRecord{ID: diskId, RegisteredAt: time.Now()}
+			registry[diskId] = record
+		}
+		update(record)
+	}
+	saveRegistry(registry)
+}
+
+// disksサブコマンド。
+// ディスク登録簿の一覧表示(list)、1台の詳細表示(show)、説明と保管場所の設定(set)を行う。
+func executeDisks(args []string) {
+	fatalMessageIf(ErrConfig, len(args) == 0, "操作(list、show、set)を指定してください。\n")
+
+	switch args[0] {
+	case "list":
+		listDisks()
+	case "show":
+		fatalMessageIf(ErrConfig, len(args) != 2, "ディスクIDを1つ指定してください。\n")
+		showDisk(args[1])
+	case "set":
+		setDisk(args[1:])
+	default:
+		fatalMessageIf(ErrConfig, true, "操作の指定が不正です。: %s\n", args[0])
+	}
+}
+
+// 登録されている全てのディスクを一覧表示する。
+func listDisks() {
+	registry := loadRegistry()
+
+	diskIds := make([]string, 0, len(registry))
+	for diskId := range registry {
+		diskIds = append(diskIds, diskId)
+	}
+	sort.Strings(diskIds)
+
+	for _, diskId := range diskIds {
+		record := registry[diskId]
+		logf.Printf("%-6s %10s ハッシュ計算: %s 検証: %s %s %s\n",
+			record.ID, formatSize(float64(record.CapacityBytes)),
+			formatDate(record.LastHashAt), formatDate(record.LastVerifyAt), record.Label, record.Location)
+	}
+
+	logf.Printf("%d台のディスクが登録されています。\n", len(diskIds))
+}
+
+// 1台のディスクの記録を表示する。
+func showDisk(diskId string) {
+	record, found := loadRegistry()[diskId]
+	fatalMessageIf(ErrConfig, !found, "ディスクが登録されていません。: %s\n", diskId)
+
+	items := []struct {
+		name  string
+		value string
+	}{
+		{"ディスクID", record.ID},
+		{"ラベル", record.Label},
+		{"グループ", record.Group},
+		{"説明", record.Description},
+		{"保管場所", record.Location},
+		{"シリアル番号", record.Serial},
+		{"容量", formatSize(float64(record.CapacityBytes))},
+		{"登録日", formatDate(record.RegisteredAt)},
+		{"最終ハッシュ計算日", formatDate(record.LastHashAt)},
+		{"最終検証日", formatDate(record.LastVerifyAt)},
+	}
+
+	for _, item := range items {
+		logf.Printf("%s: %s\n", item.name, item.value)
+	}
+}
+
+// ディスクの説明などを設定する。
+func setDisk(args []string) {
+	flagSet := flag.NewFlagSet("disks set", flag.ExitOnError)
+	label := flagSet.String("label", "", "ラベル。")
+	description := flagSet.String("description", "", "説明。")
+	location := flagSet.String("location", "", "保管場所。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, flagSet.NArg() != 1, "ディスクIDを1つ指定してください。\n")
+	diskId := flagSet.Arg(0)
+
+	_, found := loadRegistry()[diskId]
+	fatalMessageIf(ErrConfig, !found, "ディスクが登録されていません。: %s\n", diskId)
+
+	// 指定された項目だけ更新する
+	updateRegistry([]string{diskId}, func(record *DiskRecord) {
+		flagSet.Visit(func(f *flag.Flag) {
+			switch f.Name {
+			case "label":
+				record.Label = *label
+			case "description":
+				record.Description = *description
+			case "location":
+				record.Location = *location
+			}
+		})
+	})
+
+	logf.Printf("ディスク登録簿を更新しました。: %s\n", diskId)
+}
+
+// 日付を表示用にフォーマットする。日時がなければ「-」にする。
+func formatDate(t time.Time) string {
+	if t.IsZero() {
+		return "-"
+	}
+	return t.Format("2006-01-02")
+}
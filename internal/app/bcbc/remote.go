@@ -0,0 +1,336 @@
+package bcbc
+
+import (
+	"io"
+	"net/http"
+	"net/url"
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// remoteStorage ハッシュファイルを置くリモートの保存先。
+// 名前は保存先のURLからの「/」で区切った相対パス。
+type remoteStorage interface {
+	// ファイルを取得する。なければnilを返す。
+	get(name string) (io.ReadCloser, error)
+	// ファイルを取得せずに、変わったか判定するための目印を返す。なければmissingStampを返す。
+	stamp(name string) (string, error)
+	// ファイルを置く。
+	put(name string, body io.ReadSeeker, size int64) error
+	// ファイルを削除する。なければ何もしない。
+	remove(name string) error
+	// ディレクトリにあるファイル名の一覧を返す。ディレクトリがなければ空を返す。
+	list(dir string) ([]string, error)
+	// ログに表示する名前の場所を返す。
+	location(name string) string
+}
+
+// リモートのハッシュファイルの保存先。
+// 出力ディレクトリを作業用の複製にして、読み込む前にリモートから取得し、書き直したらリモートに送る。
+// 追記中のハッシュファイルは手元の複製が最新なので、書き直してリモートに送るまで取得しない。
+type remoteHashStore struct {
+	*fileHashStore
+	remote remoteStorage
+}
+
+// リモートの保存先でのアルゴリズムのハッシュファイルのディレクトリを返す。
+// 出力ディレクトリと同じく、MD5以外はアルゴリズム名のサブディレクトリにする。
+func remoteHashDir(algorithm string) string {
+	if algorithm == AlgorithmMD5 {
+		return ""
+	}
+	return algorithm
+}
+
+// 保存先のURLの指定を検証する。
+// パスワードがログやエラーメッセージに出ないように、URLからユーザー名とパスワードを取り除いて別に返す。
+func checkStoreURL(store string, storeURL string) (string, *url.Userinfo) {
+	if store == StoreFile {
+		return storeURL, nil
+	}
+	parsed, err := url.Parse(storeURL)
+	fatalMessageIf(ErrConfig, err != nil, "保存先のURLの指定が不正です。\n")
+	userinfo := parsed.User
+	parsed.User = nil
+
+	// SFTPはsshコマンドで接続するので、sshのオプションと間違えるマシン名とパスワードは受け付けない
+	if store == StoreSFTP {
+		valid := parsed.Scheme == "sftp" && parsed.Hostname() != "" && !strings.HasPrefix(parsed.Hostname(), "-")
+		fatalMessageIf(ErrConfig, !valid, "保存先のURLの指定が不正です。sftp://で始まるURLを指定してください。: %s\n", parsed)
+		_, found := userinfo.Password()
+		fatalMessageIf(ErrConfig, found, "SFTPの保存先にはパスワードを指定できません。sshの鍵で認証できるようにしてください。: %s\n", parsed)
+		return parsed.String(), userinfo
+	}
+
+	valid := (parsed.Scheme == "http" || parsed.Scheme == "https") && parsed.Host != ""
+	fatalMessageIf(ErrConfig, !valid, "保存先のURLの指定が不正です。http://かhttps://で始まるURLを指定してください。: %s\n", parsed)
+	if store == StoreS3 {
+		fatalMessageIf(ErrConfig, strings.Trim(parsed.Path, "/") == "", "保存先のURLにバケット名がありません。: %s\n", parsed)
+		checkS3Credentials()
+	}
+	if _, found := userinfo.Password(); found {
+		logf.Printf("[警告] 保存先のURLにパスワードが含まれています。コマンドラインや設定ファイルに残らないように、環境変数%sと%sで指定してください。\n", EnvWebDAVUser, EnvWebDAVPassword)
+	}
+
+	return parsed.String(), userinfo
+}
+
+// ファイルがないことを表す目印
+const missingStamp = "-"
+
+// レスポンスのヘッダーからファイルが変わったか判定するための目印を作る。
+// ETagがなければサイズと更新日時を使う。
+func responseStamp(response *http.Response) string {
+	if etag := response.Header.Get("ETag"); etag != "" {
+		return etag
+	}
+	return response.Header.Get("Content-Length") + " " + response.Header.Get("Last-Modified")
+}
+
+// 追記中であることを表すファイルのパスを返す。
+func (s *remoteHashStore) appendingMarker(diskId string) string {
+	return s.file(diskId + ".appending")
+}
+
+// リモートのファイルを手元に取得する。リモートになければ手元のファイルも削除する。
+func (s *remoteHashStore) download(name string) error {
+	in, err := s.remote.get(name)
+	if err != nil {
+		return err
+	}
+
+	localFile := s.file(filepath.FromSlash(name))
+	if in == nil {
+		if err := os.Remove(localFile); err != nil && !os.IsNotExist(err) {
+			return err
+		}
+		return nil
+	}
+	defer in.Close()
+
+	if err := os.MkdirAll(filepath.Dir(localFile), 0755); err != nil {
+		return err
+	}
+	tempFile := localFile + ".tmp"
+	out, err := os.OpenFile(tempFile, os.O_CREATE|os.O_WRONLY|os.O_TRUNC, 0644)
+	if err != nil {
+		return err
+	}
+	_, err = io.Copy(out, in)
+	if closeErr := out.Close(); err == nil {
+		err = closeErr
+	}
+	if err == nil {
+		os.Remove(localFile)
+		err = os.Rename(tempFile, localFile)
+	}
+	if err != nil {
+		os.Remove(tempFile)
+	}
+	return err
+}
+
+// 手元のファイルをリモートに送る。手元になければリモートのファイルも削除する。
+func (s *remoteHashStore) upload(name string) error {
+	in, err := os.Open(s.file(filepath.FromSlash(name)))
+	if os.IsNotExist(err) {
+		return s.remote.remove(name)
+	}
+	if err != nil {
+		return err
+	}
+	defer in.Close()
+
+	stat, err := in.Stat()
+	if err != nil {
+		return err
+	}
+	return s.remote.put(name, in, stat.Size())
+}
+
+// ハッシュファイルの圧縮形式と署名の違いを含めたファイル名
+func hashFileVariants(name string) []string {
+	return []string{
+		name,
+		name + signatureExtension,
+		name + compressedExtension,
+		name + compressedExtension + signatureExtension,
+	}
+}
+
+// ハッシュファイルをリモートから取得する。追記中なら手元の複製を使う。
+func (s *remoteHashStore) fetch(name string) error {
+	if _, err := os.Stat(s.appendingMarker(name)); err == nil {
+		return nil
+	}
+	if err := os.MkdirAll(s.dir, 0755); err != nil {
+		return newError(ErrIO, err, "出力ディレクトリを作成できませんでした。: %s", s.dir)
+	}
+
+	for _, variant := range hashFileVariants(name) {
+		if err := s.download(variant); err != nil {
+			return newError(ErrIO, err, "ハッシュファイルを保存先から取得できませんでした。: %s", s.remote.location(variant))
+		}
+	}
+	return nil
+}
+
+// 書き直したハッシュファイルをリモートに送る。
+func (s *remoteHashStore) push(name string) error {
+	for _, variant := range hashFileVariants(name) {
+		if err := s.upload(variant); err != nil {
+			return newError(ErrIO, err, "ハッシュファイルを保存先に送れませんでした。: %s", s.remote.location(variant))
+		}
+	}
+	return nil
+}
+
+func (s *remoteHashStore) diskIds() ([]string, error) {
+	fileNames, err := s.remote.list("")
+	if err != nil {
+		return nil, newError(ErrIO, err, "保存先のハッシュファイルの一覧取得に失敗しました。: %s", s.remote.location(""))
+	}
+
+	// 追記中でまだリモートに送っていないハッシュファイルも含める
+	markers, _ := filepath.Glob(s.file("*.appending"))
+	for _, marker := range markers {
+		fileNames = append(fileNames, strings.TrimSuffix(filepath.Base(marker), ".appending"))
+	}
+
+	return hashFileDiskIds(fileNames), nil
+}
+
+func (s *remoteHashStore) load(diskId string) ([]HashRecord, error) {
+	if err := s.fetch(diskId); err != nil {
+		return nil, err
+	}
+	return s.fileHashStore.load(diskId)
+}
+
+func (s *remoteHashStore) loadLenient(diskId string) ([]HashRecord, []int, error) {
+	if err := s.fetch(diskId); err != nil {
+		return nil, nil, err
+	}
+	return s.fileHashStore.loadLenient(diskId)
+}
+
+func (s *remoteHashStore) loadInfo(name string) (HashFileInfo, error) {
+	if err := s.fetch(name); err != nil {
+		return HashFileInfo{}, err
+	}
+	return s.fileHashStore.loadInfo(name)
+}
+
+func (s *remoteHashStore) digest(diskId string) (string, error) {
+	if err := s.fetch(diskId); err != nil {
+		return "", err
+	}
+	return s.fileHashStore.digest(diskId)
+}
+
+// 追記中なら手元の複製の目印を返す。
+func (s *remoteHashStore) stamp(diskId string) (string, error) {
+	if _, err := os.Stat(s.appendingMarker(diskId)); err == nil {
+		return s.fileHashStore.stamp(diskId)
+	}
+
+	stamps := make([]string, 0, 2)
+	for _, name := range []string{diskId, diskId + compressedExtension} {
+		stamp, err := s.remote.stamp(name)
+		if err != nil {
+			return "", newError(ErrIO, err, "保存先のハッシュファイルの情報を取得できませんでした。: %s", s.remote.location(name))
+		}
+		stamps = append(stamps, stamp)
+	}
+	return strings.Join(stamps, " "), nil
+}
+
+func (s *remoteHashStore) rewrite(diskId string, records []HashRecord) error {
+	if err := s.fileHashStore.rewrite(diskId, records); err != nil {
+		return err
+	}
+	if err := s.push(diskId); err != nil {
+		return err
+	}
+	os.Remove(s.appendingMarker(diskId))
+	return nil
+}
+
+func (s *remoteHashStore) openAppender(diskId string) (HashAppender, error) {
+	if err := s.fetch(diskId); err != nil {
+		return nil, err
+	}
+	if err := os.WriteFile(s.appendingMarker(diskId), nil, 0644); err != nil {
+		return nil, newError(ErrIO, err, "ハッシュファイルの書き込みに失敗しました。: %s", s.appendingMarker(diskId))
+	}
+	return s.fileHashStore.openAppenderWith(s, diskId)
+}
+
+func (s *remoteHashStore) foldJournal(diskId string) error {
+	return s.fileHashStore.foldJournalWith(s, diskId)
+}
+
+func (s *remoteHashStore) writeMerged(group string, next func() (HashRecord, bool, error)) error {
+	// 作成日時を引き継ぐために今の統合ハッシュファイルを取得しておく
+	if err := s.fetch(group); err != nil {
+		return err
+	}
+	if err := s.fileHashStore.writeMerged(group, next); err != nil {
+		return err
+	}
+	return s.push(group)
+}
+
+func (s *remoteHashStore) loadMergeSources(group string) (string, error) {
+	if err := s.download(group + ".sources"); err != nil {
+		return "", newError(ErrIO, err, "統合元の記録ファイルを保存先から取得できませんでした。: %s", s.remote.location(group+".sources"))
+	}
+	return s.fileHashStore.loadMergeSources(group)
+}
+
+func (s *remoteHashStore) saveMergeSources(group string, sources string) error {
+	if err := s.fileHashStore.saveMergeSources(group, sources); err != nil {
+		return err
+	}
+	if err := s.upload(group + ".sources"); err != nil {
+		return newError(ErrIO, err, "統合元の記録ファイルを保存先に送れませんでした。: %s", s.remote.location(group+".sources"))
+	}
+	return nil
+}
+
+func (s *remoteHashStore) snapshot(diskId string) (string, error) {
+	if err := s.fetch(diskId); err != nil {
+		return "", err
+	}
+	name, err := s.fileHashStore.snapshot(diskId)
+	if err != nil {
+		return "", err
+	}
+
+	dir := diskId + snapshotDirExtension
+	for _, fileName := range []string{name, name + compressedExtension, latestSnapshotFile} {
+		if err := s.upload(dir + "/" + fileName); err != nil {
+			return "", newError(ErrIO, err, "スナップショットを保存先に送れませんでした。: %s", s.remote.location(dir+"/"+fileName))
+		}
+	}
+	return name, nil
+}
+
+func (s *remoteHashStore) snapshots(diskId string) ([]string, error) {
+	fileNames, err := s.remote.list(diskId + snapshotDirExtension)
+	if err != nil {
+		return nil, newError(ErrIO, err, "保存先のスナップショットの一覧取得に失敗しました。: %s", s.remote.location(diskId+snapshotDirExtension))
+	}
+	return snapshotNames(fileNames), nil
+}
+
+func (s *remoteHashStore) loadSnapshot(diskId string, name string) ([]HashRecord, error) {
+	dir := diskId + snapshotDirExtension
+	for _, fileName := range []string{name, name + compressedExtension} {
+		if err := s.download(dir + "/" + fileName); err != nil {
+			return nil, newError(ErrIO, err, "スナップショットを保存先から取得できませんでした。: %s", s.remote.location(dir+"/"+fileName))
+		}
+	}
+	return s.fileHashStore.loadSnapshot(diskId, name)
+}
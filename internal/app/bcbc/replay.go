@@ -0,0 +1,197 @@
+package bcbc
+
+import (
+	"bufio"
+	"encoding/json"
+	"flag"
+	"os"
+	"path/filepath"
+	"sort"
+	"time"
+)
+
+// ProgressEvent 進捗ログに記録する進捗イベント
+type ProgressEvent struct {
+	Time       time.Time `json:"time"`
+	DiskId     string    `json:"disk"`
+	FilesDone  uint64    `json:"files_done"`
+	FilesTotal uint64    `json:"files_total"`
+	BytesDone  uint64    `json:"bytes_done"`
+	BytesTotal uint64    `json:"bytes_total"`
+	File       string    `json:"file,omitempty"`
+}
+
+// 進捗ログの記録間隔
+const progressLogInterval = time.Second
+
+// 進捗ログの記録
+type progressRecorder struct {
+	out         *os.File
+	encoder     *json.Encoder
+	lastRecords map[string]ProgressEvent
+	// 書き込みに失敗したら以降は記録しない
+	err error
+}
+
+// 進捗ログの記録を開始する。
+// 進捗ログが無効か作成できなければnilを返す。
+func newProgressRecorder() *progressRecorder {
+	if !*progressLogOption {
+		return nil
+	}
+
+	progressLogPath := filepath.Join(config.logDir(), time.Now().Format("20060102150405.progress.jsonl"))
+	progressLogOut, err := os.OpenFile(progressLogPath, os.O_CREATE|os.O_WRONLY|os.O_APPEND, 0644)
+	if err != nil {
+		logf.Printf("進捗ログを作成できませんでした。: %s\n", progressLogPath)
+		logf.Println(err)
+		return nil
+	}
+
+	logf.Printf("進捗ログを記録します。: %s\n", progressLogPath)
+
+	return &progressRecorder{out: progressLogOut, encoder: json.NewEncoder(progressLogOut), lastRecords: make(map[string]ProgressEvent)}
+}
+
+// 進捗情報を進捗ログに記録する。
+// ディスクごとに処理中のファイルが変わったときか記録間隔が経過したときだけ記録する。
+func (r *progressRecorder) record(progressInfo ProgressInfo) {
+	if r == nil || r.err != nil || progressInfo.diskInfo == nil {
+		return
+	}
+
+	event := ProgressEvent{
+		Time:       time.Now(),
+		DiskId:     progressInfo.diskInfo.id,
+		FilesDone:  progressInfo.fileCount.processed,
+		FilesTotal: progressInfo.fileCount.total,
+		BytesDone:  progressInfo.sizeCount.processed,
+		BytesTotal: progressInfo.sizeCount.total,
+		File:       progressInfo.processingFile,
+	}
+
+	last, found := r.lastRecords[event.DiskId]
+	if found && last.File == event.File && event.Time.Sub(last.Time) < progressLogInterval && !progressInfo.sizeCount.Completed() {
+		return
+	}
+
+	if err := r.encoder.Encode(event); err != nil {
+		logf.Println("進捗ログの書き込みに失敗しました。以降は進捗ログを記録しません。")
+		logf.Println(err)
+		r.err = newError(ErrIO, err, "進捗ログの書き込みに失敗しました。")
+		return
+	}
+
+	r.lastRecords[event.DiskId] = event
+}
+
+// 進捗ログを閉じる。
+// 記録中に書き込みに失敗していればそのエラーを返す。
+func (r *progressRecorder) close() error {
+	if r == nil {
+		return nil
+	}
+
+	if err := r.out.Close(); err != nil && r.err == nil {
+		r.err = newError(ErrIO, err, "進捗ログの書き込みに失敗しました。")
+	}
+
+	return r.err
+}
+
+// 進捗ログを読み込む。
+func readProgressEvents(progressLogPath string) []ProgressEvent {
+	progressLogIn, err := os.Open(progressLogPath)
+	fatalMessageError(ErrIO, err, "進捗ログが読み込めませんでした。: %s\n", progressLogPath)
+	defer progressLogIn.Close()
+
+	var events []ProgressEvent
+
+	scanner := bufio.NewScanner(progressLogIn)
+	scanner.Buffer(make([]byte, 64<<10), 1<<20)
+	for i := 1; scanner.Scan(); i++ {
+		var event ProgressEvent
+		err := json.Unmarshal(scanner.Bytes(), &event)
+		fatalMessageError(ErrConfig, err, "進捗ログの形式が不正です。: %s : %d行目\n", progressLogPath, i)
+		events = append(events, event)
+	}
+	fatalMessageError(ErrIO, scanner.Err(), "進捗ログが読み込めませんでした。: %s\n", progressLogPath)
+
+	return events
+}
+
+// replayサブコマンド。
+// 進捗ログからディスクごとの処理の経過を再構成して表示する。
+func executeReplay(args []string) {
+	flagSet := flag.NewFlagSet("replay", flag.ExitOnError)
+	interval := flagSet.Duration("interval", time.Minute, "経過を集計する間隔。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, flagSet.NArg() != 1, "進捗ログのパスを1つ指定してください。\n")
+	fatalMessageIf(ErrConfig, *interval <= 0, "集計間隔の指定が不正です。: %s\n", *interval)
+
+	eventsByDisk := make(map[string][]ProgressEvent)
+	for _, event := range readProgressEvents(flagSet.Arg(0)) {
+		eventsByDisk[event.DiskId] = append(eventsByDisk[event.DiskId], event)
+	}
+
+	diskIds := make([]string, 0, len(eventsByDisk))
+	for diskId := range eventsByDisk {
+		diskIds = append(diskIds, diskId)
+	}
+	sort.Strings(diskIds)
+
+	for _, diskId := range diskIds {
+		replayDisk(diskId, eventsByDisk[diskId], *interval)
+	}
+}
+
+// 1つのディスクの経過を表示する。
+func replayDisk(diskId string, events []ProgressEvent, interval time.Duration) {
+	sort.SliceStable(events, func(i, j int) bool {
+		return events[i].Time.Before(events[j].Time)
+	})
+
+	first := events[0]
+	last := events[len(events)-1]
+	elapsed := last.Time.Sub(first.Time)
+
+	logf.Printf("%s: %s - %s (%s) [%s/%s] %s / %s 平均 %s\n",
+		diskId, first.Time.Format(time.RFC3339), last.Time.Format(time.RFC3339), elapsed.Round(time.Second),
+		formatCount(last.FilesDone), formatCount(last.FilesTotal),
+		formatSize(float64(last.BytesDone)), formatSize(float64(last.BytesTotal)),
+		formatThroughput(throughput(last.BytesDone-first.BytesDone, elapsed)))
+
+	// 集計間隔ごとのスループット
+	bucketStart := first
+	slowest := time.Duration(0)
+	slowestThroughput := -1.0
+	for i, event := range events[1:] {
+		span := event.Time.Sub(bucketStart.Time)
+		if span < interval && i < len(events)-2 {
+			continue
+		}
+
+		rate := throughput(event.BytesDone-bucketStart.BytesDone, span)
+		logf.Printf("  %s %s %s\n", bucketStart.Time.Format("15:04:05"), formatThroughput(rate), event.File)
+
+		if slowestThroughput < 0 || rate < slowestThroughput {
+			slowestThroughput = rate
+			slowest = bucketStart.Time.Sub(first.Time)
+		}
+
+		bucketStart = event
+	}
+
+	if slowestThroughput >= 0 {
+		logf.Printf("  最も遅かった区間: 開始から%s後 %s\n", slowest.Round(time.Second), formatThroughput(slowestThroughput))
+	}
+}
+
+// 1秒あたりのバイト数を計算する。
+func throughput(bytes uint64, elapsed time.Duration) float64 {
+	if elapsed <= 0 {
+		return 0
+	}
+	return float64(bytes) / elapsed.Seconds()
+}
@@ -0,0 +1,31 @@
+package bcbc
+
+import (
+	"errors"
+	"io/fs"
+	"time"
+)
+
+// 読み込みを再試行する。
+// 権限がない、ファイルがないなど再試行しても変わらないエラーは再試行しない。
+// 再試行するたびに待ち時間を倍にする。
+func retryRead(file string, read func() error) error {
+	backoff := config.retryBackoff
+
+	for attempt := 1; ; attempt++ {
+		err := read()
+		if err == nil || attempt > config.retries || !isTransientError(err) || interrupted() {
+			return err
+		}
+
+		logf.Printf("読み込みに失敗したので%s後に再試行します。(%d/%d): %s\n", backoff, attempt, config.retries, file)
+		logf.Println(err)
+		time.Sleep(backoff)
+		backoff *= 2
+	}
+}
+
+// 再試行すれば成功する可能性があるエラーか判定する。
+func isTransientError(err error) bool {
+	return !errors.Is(err, fs.ErrPermission) && !errors.Is(err, fs.ErrNotExist)
+}
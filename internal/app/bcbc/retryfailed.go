@@ -0,0 +1,73 @@
+package bcbc
+
+import (
+	"bufio"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"sync/atomic"
+)
+
+// 前回のハッシュ計算のエラーレポートからハッシュ計算できなかったファイルのパスを読み込む。
+// エラーレポートがなければ空を返す。
+func loadErrorReport(diskInfo *DiskInfo) []string {
+	reportIn, err := os.Open(diskInfo.errorReportFile())
+	if err != nil {
+		return nil
+	}
+	defer reportIn.Close()
+
+	found := make(map[string]bool)
+	for scanner := bufio.NewScanner(reportIn); scanner.Scan(); {
+		line := scanner.Text()
+		if strings.HasPrefix(line, "#") {
+			continue
+		}
+		tokens := strings.SplitN(line, "\t", 3)
+		if len(tokens) < 2 {
+			continue
+		}
+		if normPath, ok := unescapePath(tokens[1]); ok {
+			found[normPath] = true
+		}
+	}
+
+	normPaths := make([]string, 0, len(found))
+	for normPath := range found {
+		normPaths = append(normPaths, normPath)
+	}
+	sort.Strings(normPaths)
+
+	return normPaths
+}
+
+// 指定されたパスのファイルだけをハッシュ対象としてtargetsに送る。
+// ディスク内を探索しないので、-retry-failedではハッシュファイルを書き直さずに追記するだけにする。
+// 探索が終わったらtargetsを閉じる。別のゴルーチンで実行する。
+func (d *targetDiscovery) runPaths(normPaths []string) {
+	defer close(d.targets)
+
+	var fileInfo FileInfo
+	for _, normPath := range normPaths {
+		(&fileInfo).init(d.diskInfo, filepath.Join(d.diskInfo.rootPath, filepath.FromSlash(normPath)))
+
+		if fileInfo.stat() == nil {
+			logf.Printf("再試行するファイルが見つかりません。: %s\n", fileInfo.realPath)
+			continue
+		}
+		if _, found := lookupHashes(d.hashMaps, fileInfo.normPath); found || !d.diskInfo.filterFile(fileInfo.normPath) {
+			continue
+		}
+
+		size, _ := fileInfo.size()
+		atomic.AddUint64(&d.fileCount, 1)
+		atomic.AddUint64(&d.totalSize, size)
+
+		select {
+		case d.targets <- fileInfo:
+		case <-d.stop:
+			return
+		}
+	}
+}
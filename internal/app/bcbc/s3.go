@@ -0,0 +1,325 @@
+package bcbc
+
+import (
+	"crypto/hmac"
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/xml"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"os"
+	"path"
+	"sort"
+	"strings"
+	"time"
+)
+
+// S3の認証情報の環境変数名
+const (
+	// EnvS3AccessKey 環境変数名: アクセスキー
+	EnvS3AccessKey = "AWS_ACCESS_KEY_ID"
+	// EnvS3SecretKey 環境変数名: シークレットキー
+	EnvS3SecretKey = "AWS_SECRET_ACCESS_KEY"
+	// EnvS3SessionToken 環境変数名: 一時的な認証情報のセッショントークン
+	EnvS3SessionToken = "AWS_SESSION_TOKEN"
+)
+
+// S3への1回のリクエストのタイムアウト
+const s3Timeout = 5 * time.Minute
+
+// 空のリクエストボディのSHA-256
+const emptyPayloadHash = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+
+// S3互換のオブジェクトストレージの保存先。
+// 保存先のURLは「https://エンドポイント/バケット/接頭辞」のパス形式で指定する。
+type s3Storage struct {
+	scheme string
+	host   string
+	bucket string
+	// アルゴリズムのハッシュファイルのキーの接頭辞。空でなければ「/」で終わる。
+	prefix       string
+	region       string
+	accessKey    string
+	secretKey    string
+	sessionToken string
+	client       *http.Client
+}
+
+func newS3HashStore(algorithm string) HashStore {
+	storeURL, _ := url.Parse(config.storeURL)
+	parts := strings.SplitN(strings.Trim(storeURL.Path, "/"), "/", 2)
+
+	prefix := ""
+	if len(parts) == 2 && parts[1] != "" {
+		prefix = strings.TrimSuffix(parts[1], "/") + "/"
+	}
+	if dir := remoteHashDir(algorithm); dir != "" {
+		prefix += dir + "/"
+	}
+
+	return &remoteHashStore{
+		&fileHashStore{config.hashDir(algorithm), algorithm},
+		&s3Storage{
+			scheme:       storeURL.Scheme,
+			host:         storeURL.Host,
+			bucket:       parts[0],
+			prefix:       prefix,
+			region:       config.s3Region,
+			accessKey:    os.Getenv(EnvS3AccessKey),
+			secretKey:    os.Getenv(EnvS3SecretKey),
+			sessionToken: os.Getenv(EnvS3SessionToken),
+			client:       &http.Client{Timeout: s3Timeout},
+		},
+	}
+}
+
+// S3の認証情報が環境変数に設定されているか検証する。
+func checkS3Credentials() {
+	missing := os.Getenv(EnvS3AccessKey) == "" || os.Getenv(EnvS3SecretKey) == ""
+	fatalMessageIf(ErrConfig, missing, "環境変数%sと%sにS3の認証情報を設定してください。\n", EnvS3AccessKey, EnvS3SecretKey)
+}
+
+func (s *s3Storage) location(name string) string {
+	return "s3://" + s.bucket + "/" + s.prefix + name
+}
+
+// SigV4で署名したリクエストを送る。
+func (s *s3Storage) request(method string, key string, query url.Values, body io.ReadSeeker, size int64) (*http.Response, error) {
+	payloadHash := emptyPayloadHash
+	if body != nil {
+		hasher := sha256.New()
+		if _, err := io.Copy(hasher, body); err != nil {
+			return nil, err
+		}
+		if _, err := body.Seek(0, io.SeekStart); err != nil {
+			return nil, err
+		}
+		payloadHash = hex.EncodeToString(hasher.Sum(nil))
+	}
+
+	canonicalURI := "/" + awsEscape(s.bucket, false)
+	if key != "" {
+		canonicalURI += "/" + awsEscape(key, true)
+	}
+	canonicalQuery := awsCanonicalQuery(query)
+
+	target := s.scheme + "://" + s.host + canonicalURI
+	if canonicalQuery != "" {
+		target += "?" + canonicalQuery
+	}
+
+	var request *http.Request
+	var err error
+	if body != nil {
+		request, err = http.NewRequest(method, target, body)
+		request.ContentLength = size
+	} else {
+		request, err = http.NewRequest(method, target, nil)
+	}
+	if err != nil {
+		return nil, err
+	}
+
+	now := time.Now().UTC()
+	amzDate := now.Format("20060102T150405Z")
+	headers := map[string]string{
+		"host":                 s.host,
+		"x-amz-content-sha256": payloadHash,
+		"x-amz-date":           amzDate,
+	}
+	if s.sessionToken != "" {
+		headers["x-amz-security-token"] = s.sessionToken
+	}
+
+	headerNames := make([]string, 0, len(headers))
+	for name := range headers {
+		headerNames = append(headerNames, name)
+	}
+	sort.Strings(headerNames)
+
+	var canonicalHeaders strings.Builder
+	for _, name := range headerNames {
+		canonicalHeaders.WriteString(name + ":" + headers[name] + "\n")
+		if name != "host" {
+			request.Header.Set(name, headers[name])
+		}
+	}
+	signedHeaders := strings.Join(headerNames, ";")
+
+	canonicalRequest := strings.Join([]string{
+		method, canonicalURI, canonicalQuery, canonicalHeaders.String(), signedHeaders, payloadHash,
+	}, "\n")
+	requestHash := sha256.Sum256([]byte(canonicalRequest))
+
+	scope := now.Format("20060102") + "/" + s.region + "/s3/aws4_request"
+	stringToSign := "AWS4-HMAC-SHA256\n" + amzDate + "\n" + scope + "\n" + hex.EncodeToString(requestHash[:])
+
+	signingKey := hmacSHA256([]byte("AWS4"+s.secretKey), now.Format("20060102"))
+	for _, part := range []string{s.region, "s3", "aws4_request"} {
+		signingKey = hmacSHA256(signingKey, part)
+	}
+	signature := hex.EncodeToString(hmacSHA256(signingKey, stringToSign))
+
+	request.Header.Set("Authorization", fmt.Sprintf("AWS4-HMAC-SHA256 Credential=%s/%s, SignedHeaders=%s, Signature=%s",
+		s.accessKey, scope, signedHeaders, signature))
+
+	return s.client.Do(request)
+}
+
+func hmacSHA256(key []byte, data string) []byte {
+	mac := hmac.New(sha256.New, key)
+	mac.Write([]byte(data))
+	return mac.Sum(nil)
+}
+
+// SigV4の規則でURIエンコードする。
+// 英数字と「-_.~」以外をエンコードし、keepSlashなら「/」はそのままにする。
+func awsEscape(s string, keepSlash bool) string {
+	var builder strings.Builder
+	for i := 0; i < len(s); i++ {
+		c := s[i]
+		switch {
+		case 'A' <= c && c <= 'Z', 'a' <= c && c <= 'z', '0' <= c && c <= '9', c == '-', c == '_', c == '.', c == '~':
+			builder.WriteByte(c)
+		case c == '/' && keepSlash:
+			builder.WriteByte(c)
+		default:
+			fmt.Fprintf(&builder, "%%%02X", c)
+		}
+	}
+	return builder.String()
+}
+
+// 名前の順に並べてエンコードしたクエリ文字列を返す。
+func awsCanonicalQuery(query url.Values) string {
+	keys := make([]string, 0, len(query))
+	for key := range query {
+		keys = append(keys, key)
+	}
+	sort.Strings(keys)
+
+	pairs := make([]string, 0, len(keys))
+	for _, key := range keys {
+		for _, value := range query[key] {
+			pairs = append(pairs, awsEscape(key, false)+"="+awsEscape(value, false))
+		}
+	}
+	return strings.Join(pairs, "&")
+}
+
+// エラーのレスポンスをエラーにする。
+func (s *s3Storage) responseError(name string, response *http.Response) error {
+	message, _ := io.ReadAll(io.LimitReader(response.Body, 1024))
+	return fmt.Errorf("%s: %s %s", s.location(name), response.Status, strings.TrimSpace(string(message)))
+}
+
+func (s *s3Storage) get(name string) (io.ReadCloser, error) {
+	response, err := s.request(http.MethodGet, s.prefix+name, nil, nil, 0)
+	if err != nil {
+		return nil, err
+	}
+
+	switch response.StatusCode {
+	case http.StatusOK:
+		return response.Body, nil
+	case http.StatusNotFound:
+		response.Body.Close()
+		return nil, nil
+	default:
+		defer response.Body.Close()
+		return nil, s.responseError(name, response)
+	}
+}
+
+func (s *s3Storage) stamp(name string) (string, error) {
+	response, err := s.request(http.MethodHead, s.prefix+name, nil, nil, 0)
+	if err != nil {
+		return "", err
+	}
+	response.Body.Close()
+
+	switch response.StatusCode {
+	case http.StatusOK:
+		return responseStamp(response), nil
+	case http.StatusNotFound:
+		return missingStamp, nil
+	default:
+		return "", fmt.Errorf("%s: %s", s.location(name), response.Status)
+	}
+}
+
+func (s *s3Storage) put(name string, body io.ReadSeeker, size int64) error {
+	response, err := s.request(http.MethodPut, s.prefix+name, nil, body, size)
+	if err != nil {
+		return err
+	}
+	defer response.Body.Close()
+
+	if response.StatusCode/100 != 2 {
+		return s.responseError(name, response)
+	}
+	return nil
+}
+
+func (s *s3Storage) remove(name string) error {
+	response, err := s.request(http.MethodDelete, s.prefix+name, nil, nil, 0)
+	if err != nil {
+		return err
+	}
+	defer response.Body.Close()
+
+	if response.StatusCode/100 != 2 && response.StatusCode != http.StatusNotFound {
+		return s.responseError(name, response)
+	}
+	return nil
+}
+
+func (s *s3Storage) list(dir string) ([]string, error) {
+	prefix := s.prefix
+	if dir != "" {
+		prefix += dir + "/"
+	}
+
+	var names []string
+	continuationToken := ""
+	for {
+		query := url.Values{"list-type": {"2"}, "prefix": {prefix}, "delimiter": {"/"}}
+		if continuationToken != "" {
+			query.Set("continuation-token", continuationToken)
+		}
+
+		response, err := s.request(http.MethodGet, "", query, nil, 0)
+		if err != nil {
+			return nil, err
+		}
+		if response.StatusCode != http.StatusOK {
+			err := s.responseError(dir, response)
+			response.Body.Close()
+			return nil, err
+		}
+
+		var result struct {
+			Contents []struct {
+				Key string `xml:"Key"`
+			} `xml:"Contents"`
+			IsTruncated           bool   `xml:"IsTruncated"`
+			NextContinuationToken string `xml:"NextContinuationToken"`
+		}
+		err = xml.NewDecoder(response.Body).Decode(&result)
+		response.Body.Close()
+		if err != nil {
+			return nil, err
+		}
+
+		for _, content := range result.Contents {
+			names = append(names, path.Base(content.Key))
+		}
+
+		if !result.IsTruncated || result.NextContinuationToken == "" {
+			return names, nil
+		}
+		continuationToken = result.NextContinuationToken
+	}
+}
@@ -0,0 +1,94 @@
+package bcbc
+
+import (
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"strings"
+	"time"
+)
+
+// 読み込めない範囲を探すときに読み直す単位
+const salvageBlockSize = 4096
+
+// 読み込めない範囲があったファイルのハッシュに付ける印
+const incompleteHashSuffix = "!incomplete"
+
+// ファイル内のバイト範囲。endは含まない。
+type byteRange struct {
+	start int64
+	end   int64
+}
+
+func (r byteRange) String() string {
+	return fmt.Sprintf("%d-%d", r.start, r.end)
+}
+
+// 読み込めなかったバッファ1つ分の範囲をブロックごとに読み直す。
+// 読み込めなかったブロックはゼロで埋めて読み飛ばし、読み込んだバイト数と読み込めなかった範囲を返す。
+// 計算中にファイルが小さくなって位置がファイルサイズを超えていたら、末尾まで読み込んだとみなして0を返す。
+func salvageRead(fileIn io.ReaderAt, buffer []byte, offset int64, size uint64) (int, []byteRange) {
+	limit := len(buffer)
+	if remaining := int64(size) - offset; remaining < int64(limit) {
+		limit = int(remaining)
+	}
+	if limit <= 0 {
+		return 0, nil
+	}
+
+	var damaged []byteRange
+
+	for pos := 0; pos < limit; pos += salvageBlockSize {
+		blockEnd := pos + salvageBlockSize
+		if blockEnd > limit {
+			blockEnd = limit
+		}
+		block := buffer[pos:blockEnd]
+
+		n, err := fileIn.ReadAt(block, offset+int64(pos))
+		if err == nil || (err == io.EOF && n == len(block)) {
+			continue
+		}
+
+		for i := range block {
+			block[i] = 0
+		}
+
+		start := offset + int64(pos)
+		end := offset + int64(blockEnd)
+		if len(damaged) > 0 && damaged[len(damaged)-1].end == start {
+			damaged[len(damaged)-1].end = end
+		} else {
+			damaged = append(damaged, byteRange{start, end})
+		}
+	}
+
+	return limit, damaged
+}
+
+// 損傷レポートのパスを返す。
+func (di *DiskInfo) damageReportFile() string {
+	return filepath.Join(config.outDir(), di.id+".damage")
+}
+
+// 読み込めなかった範囲を損傷レポートに追記する。
+// 実行のたびに追記するので、サルベージの経過を追跡できる。
+func recordDamage(diskInfo *DiskInfo, normPath string, damaged []byteRange) {
+	ranges := make([]string, 0, len(damaged))
+	for _, r := range damaged {
+		ranges = append(ranges, r.String())
+	}
+
+	reportOut, err := os.OpenFile(diskInfo.damageReportFile(), os.O_CREATE|os.O_WRONLY|os.O_APPEND, 0644)
+	fatalMessageError(ErrIO, err, "損傷レポートの書き込みに失敗しました。: %s\n", diskInfo.damageReportFile())
+	defer reportOut.Close()
+
+	_, err = fmt.Fprintf(reportOut, "%s\t%s\t%s\n", time.Now().Format(time.RFC3339), normPath, strings.Join(ranges, ","))
+	fatalMessageError(ErrIO, err, "損傷レポートの書き込みに失敗しました。: %s\n", diskInfo.damageReportFile())
+}
+
+// 読み込めない範囲があったファイルのハッシュか判定する。
+func isIncompleteHash(hash string) bool {
+	return strings.HasSuffix(hash, incompleteHashSuffix)
+}
@@ -0,0 +1,175 @@
+package bcbc
+
+import (
+	"flag"
+	"fmt"
+	"math"
+	"math/rand"
+	"path/filepath"
+	"sort"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// 抽出する割合の指定を解析する。「5%」か「5」なら5%にする。
+func parseSampleRate(sample string) (float64, error) {
+	rate, err := strconv.ParseFloat(strings.TrimSuffix(strings.TrimSpace(sample), "%"), 64)
+	if err != nil || rate <= 0 || rate > 100 {
+		return 0, fmt.Errorf("0より大きく100以下の割合を指定してください。: %s", sample)
+	}
+	return rate / 100, nil
+}
+
+// scrubサブコマンド。
+// 記録されたファイルの一部を無作為に抽出して検証する。
+// 最後に検証してから長いファイルほど抽出されやすくし、検証日時を記録するので、繰り返すと全てのファイルを順に検証できる。
+func executeScrub(args []string) {
+	flagSet := flag.NewFlagSet("scrub", flag.ExitOnError)
+	sample := flagSet.String("sample", "", "ディスクごとに検証するファイルの割合。(例: 5%)")
+	budget := flagSet.Duration("budget", 0, "ディスクごとに検証する時間の上限。(例: 1h)")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, *sample == "" && *budget <= 0, "-sampleか-budgetを指定してください。\n")
+	rate := 1.0
+	if *sample != "" {
+		var err error
+		rate, err = parseSampleRate(*sample)
+		fatalMessageError(ErrConfig, err, "抽出する割合の指定が不正です。\n")
+	}
+
+	logf.Println("抜き取り検証を開始します。")
+	defer logf.Println("抜き取り検証を終了しました。")
+
+	diskFiles := findDiskFiles(flagSet.Args())
+	diskInfoList := makeDiskInfoList(diskFiles)
+
+	progressChannel, stopProgressMonitor := startProgressMonitor(len(diskInfoList))
+
+	for i := range diskInfoList {
+		scrubDisk(&diskInfoList[i], rate, *budget, progressChannel)
+	}
+
+	if err := stopProgressMonitor(); err != nil {
+		logf.Println("進捗の監視中に問題が発生しました。")
+		logf.Println(err)
+	}
+
+	statusArea.finish()
+}
+
+// 1つのディスクのファイルを抽出して検証する。
+func scrubDisk(diskInfo *DiskInfo, rate float64, budget time.Duration, progressChannel chan ProgressInfo) {
+	algorithms := diskInfo.hashAlgorithms()
+	hashMaps := make([]map[string]HashRecord, 0, len(algorithms))
+	for _, algorithm := range algorithms {
+		hashMaps = append(hashMaps, makeHashMap(diskInfo, hashStoreFor(algorithm)))
+	}
+
+	checkedTimes := loadCheckedTimes(diskInfo)
+	normPaths := sampleByAge(hashMaps[0], checkedTimes, rate, time.Now())
+
+	fileInfoList := make([]FileInfo, len(normPaths))
+	var totalSize uint64
+	for i, normPath := range normPaths {
+		fileInfoList[i].init(diskInfo, filepath.Join(diskInfo.rootPath, filepath.FromSlash(normPath)))
+		size, _ := fileInfoList[i].size()
+		totalSize += size
+	}
+
+	progressInfo := ProgressInfo{
+		diskInfo:  diskInfo,
+		fileCount: ProgressCount{uint64(len(fileInfoList)), 0},
+		sizeCount: ProgressCount{totalSize, 0},
+		startTime: time.Now(),
+	}
+	progressChannel <- progressInfo
+
+	problems := 0
+	verified := 0
+
+	for i := range fileInfoList {
+		if budget > 0 && time.Since(progressInfo.startTime) >= budget {
+			logf.Printf("検証する時間の上限に達しました。: %s\n", diskInfo.id)
+			break
+		}
+
+		fi := &fileInfoList[i]
+		status, _, err := verifyFile(fi, hashMaps, progressInfo, progressChannel)
+		if err != nil {
+			saveCheckedTimes(diskInfo, checkedTimes, hashMaps[0])
+			fatalMessageError(ErrInterrupted, err, "抜き取り検証を中断しました。\n")
+		}
+		verified++
+
+		size, _ := fi.size()
+		progressInfo.fileCount.Increment(1)
+		progressInfo.sizeCount.Increment(size)
+
+		if status != "" {
+			logf.Printf("%s: %s\n", status, fi.realPath)
+			problems++
+			continue
+		}
+		checkedTimes[fi.normPath] = time.Now()
+	}
+
+	progressChannel <- progressInfo
+
+	saveCheckedTimes(diskInfo, checkedTimes, hashMaps[0])
+
+	logf.Printf("ディスク%sの抜き取り検証結果: 記録%s中%sを検証し、%sで問題が見つかりました。\n", diskInfo.id,
+		formatQuantity(uint64(len(hashMaps[0])), "件", "件"), formatQuantity(uint64(verified), "件", "件"),
+		formatQuantity(uint64(problems), "件", "件"))
+}
+
+// 最後に検証してからの時間で重み付けして、ファイルを無作為に割合の数だけ抽出する。
+// 抽出したファイルは検証する順に返す。時間の上限で途中までしか検証できなくても古いものが優先されやすい。
+// 一度も検証していないファイルは最も古いファイルよりさらに古いものとして扱う。
+func sampleByAge(recorded map[string]HashRecord, checkedTimes map[string]time.Time, rate float64, now time.Time) []string {
+	var oldest time.Duration
+	for normPath := range recorded {
+		if checked, found := checkedTimes[normPath]; found && now.Sub(checked) > oldest {
+			oldest = now.Sub(checked)
+		}
+	}
+	unchecked := oldest + 24*time.Hour
+
+	// 実行するたびに違うファイルを抽出する
+	random := rand.New(rand.NewSource(now.UnixNano()))
+
+	type candidate struct {
+		normPath string
+		key      float64
+	}
+	candidates := make([]candidate, 0, len(recorded))
+	for normPath := range recorded {
+		age := unchecked
+		if checked, found := checkedTimes[normPath]; found {
+			age = now.Sub(checked)
+		}
+		weight := age.Hours() + 1
+
+		// 重み付きの非復元抽出(Efraimidis-Spirakis): log(u)/wが大きい順に選ぶ
+		u := random.Float64()
+		for u == 0 {
+			u = random.Float64()
+		}
+		candidates = append(candidates, candidate{normPath, math.Log(u) / weight})
+	}
+
+	sort.Slice(candidates, func(i, j int) bool {
+		return candidates[i].key > candidates[j].key
+	})
+
+	count := int(math.Ceil(float64(len(candidates)) * rate))
+	if count > len(candidates) {
+		count = len(candidates)
+	}
+
+	normPaths := make([]string, count)
+	for i := range normPaths {
+		normPaths[i] = candidates[i].normPath
+	}
+	return normPaths
+}
@@ -0,0 +1,202 @@
+package bcbc
+
+import (
+	"crypto/md5"
+	"crypto/sha256"
+	"encoding/hex"
+	"flag"
+	"hash"
+	"math/rand"
+	"os"
+	"path/filepath"
+	"sort"
+)
+
+// 既知の入力とハッシュの組
+type knownAnswer struct {
+	algorithm string
+	input     string
+	expected  string
+}
+
+// 既知の入力とハッシュの組。MD5はRFC 1321の付録A.5のテストスイート。
+var knownAnswers = []knownAnswer{
+	{AlgorithmMD5, "", "d41d8cd98f00b204e9800998ecf8427e"},
+	{AlgorithmMD5, "a", "0cc175b9c0f1b6a831c399e269772661"},
+	{AlgorithmMD5, "abc", "900150983cd24fb0d6963f7d28e17f72"},
+	{AlgorithmMD5, "message digest", "f96b697d7cb7938d525a2f31aaf161d0"},
+	{AlgorithmMD5, "abcdefghijklmnopqrstuvwxyz", "c3fcd3d76192e4007dfb496cca67e13b"},
+	{AlgorithmMD5, "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789", "d174ab98d277d9f5a5611c2c9f419d9f"},
+	{AlgorithmMD5, "12345678901234567890123456789012345678901234567890123456789012345678901234567890", "57edf4a22be3c955ac49da2e2107b67a"},
+	{AlgorithmSHA256, "", "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"},
+	{AlgorithmSHA256, "abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"},
+	{AlgorithmSHA256, "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq", "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"},
+	{AlgorithmCRC32, "123456789", "cbf43926"},
+	{AlgorithmMD5Tree, "", "d41d8cd98f00b204e9800998ecf8427e"},
+	{AlgorithmMD5Tree, "abc", "af5da9f45af7a300e3aded972f8ff687"},
+	{AlgorithmSHA256Tree, "abc", "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358"},
+}
+
+// 読み込みの経路を確かめるテストファイルのサイズ。
+// 空のファイル、バッファ1つに収まるファイル、バッファとツリーハッシュのチャンクの境界をまたいで端数が残るファイル。
+var selftestFileSizes = []int{0, 1000, 2*BufferSize + TreeChunkSize/3 + 123}
+
+// selftestサブコマンド。
+// 既知の入力とハッシュの組でハッシュ関数を確かめ、テストファイルを通常の読み込み、メモリマップ、直接I/Oでハッシュ計算して、
+// メモリ上で計算したハッシュと一致するか確かめる。
+// テストファイルは指定されたディレクトリ(指定しなければ一時ディレクトリ)に作成して、終わったら削除する。
+func executeSelftest(args []string) {
+	flagSet := flag.NewFlagSet("selftest", flag.ExitOnError)
+	flagSet.Parse(args)
+	fatalMessageIf(ErrConfig, flagSet.NArg() > 1, "テストファイルを作成するディレクトリを1つ指定してください。\n")
+
+	logf.Println("自己診断を開始します。")
+	defer logf.Println("自己診断を終了しました。")
+
+	failures := checkKnownAnswers()
+
+	dir := os.TempDir()
+	if flagSet.NArg() == 1 {
+		dir = flagSet.Arg(0)
+	}
+	failures += checkReadPaths(dir)
+
+	if failures > 0 {
+		logf.Printf("[警告] 自己診断で%d件の不一致がありました。\n", failures)
+	} else {
+		logf.Println("全ての自己診断に合格しました。")
+	}
+	commandErrors = failures
+}
+
+// 既知の入力とハッシュの組で、ハッシュ関数を一度に書き込んだときと1バイトずつ書き込んだときを確かめる。
+// 不一致の数を返す。
+func checkKnownAnswers() int {
+	failures := 0
+
+	for _, answer := range knownAnswers {
+		whole := hasherFactories[answer.algorithm]()
+		whole.Write([]byte(answer.input))
+
+		bytewise := hasherFactories[answer.algorithm]()
+		for i := 0; i < len(answer.input); i++ {
+			bytewise.Write([]byte{answer.input[i]})
+		}
+
+		for _, actual := range []string{hex.EncodeToString(whole.Sum(nil)), hex.EncodeToString(bytewise.Sum(nil))} {
+			if actual != answer.expected {
+				logf.Printf("既知の値と一致しません。: %s(%q) 期待値: %s 結果: %s\n", answer.algorithm, answer.input, answer.expected, actual)
+				failures++
+			}
+		}
+	}
+
+	logf.Printf("既知の値を確かめました。: %s\n", formatQuantity(uint64(len(knownAnswers)), "件", "件"))
+
+	return failures
+}
+
+// テストファイルを読み込みの経路ごとにハッシュ計算して、メモリ上で計算したハッシュと比べる。
+// 不一致の数を返す。
+func checkReadPaths(dir string) int {
+	algorithms := make([]string, 0, len(hasherFactories))
+	for algorithm := range hasherFactories {
+		algorithms = append(algorithms, algorithm)
+	}
+	sort.Strings(algorithms)
+
+	tempDir, err := os.MkdirTemp(dir, "bcbc-selftest")
+	fatalMessageError(ErrIO, err, "テストファイルのディレクトリを作成できませんでした。: %s\n", dir)
+	defer os.RemoveAll(tempDir)
+
+	// 進捗は表示しないので読み捨てる
+	progressChannel := make(chan ProgressInfo)
+	defer close(progressChannel)
+	go func() {
+		for range progressChannel {
+		}
+	}()
+
+	// 読み込みの経路を切り替える設定は終わったら戻す
+	savedMmapMinSize, savedDirectIO := config.mmapMinSize, config.directIO
+	defer func() {
+		config.mmapMinSize, config.directIO = savedMmapMinSize, savedDirectIO
+	}()
+	readPaths := []struct {
+		name        string
+		mmapMinSize int
+		directIO    bool
+	}{
+		{"通常の読み込み", 0, false},
+		{"メモリマップ", 1, false},
+		{"直接I/O", 0, true},
+	}
+
+	random := rand.New(rand.NewSource(1))
+	failures := 0
+
+	for _, size := range selftestFileSizes {
+		data := make([]byte, size)
+		random.Read(data)
+		file := filepath.Join(tempDir, "data")
+		err := os.WriteFile(file, data, 0644)
+		fatalMessageError(ErrIO, err, "テストファイルの書き込みに失敗しました。: %s\n", file)
+
+		expected := make([]string, len(algorithms))
+		for i, algorithm := range algorithms {
+			expected[i] = hex.EncodeToString(referenceHash(algorithm, data))
+		}
+
+		for _, readPath := range readPaths {
+			config.mmapMinSize, config.directIO = readPath.mmapMinSize, readPath.directIO
+
+			hashes, _, err := calcHash(file, uint64(size), algorithms, ProgressInfo{}, progressChannel)
+			if err != nil {
+				logf.Printf("テストファイルをハッシュ計算できませんでした。: %s : %s\n", tr(readPath.name), formatSize(float64(size)))
+				logf.Println(err)
+				failures++
+				continue
+			}
+
+			for i, algorithm := range algorithms {
+				if actual := hex.EncodeToString(hashes[i]); actual != expected[i] {
+					logf.Printf("テストファイルのハッシュが一致しません。: %s : %s : %s 期待値: %s 結果: %s\n",
+						tr(readPath.name), formatSize(float64(size)), algorithm, expected[i], actual)
+					failures++
+				}
+			}
+		}
+	}
+
+	logf.Printf("テストファイルの読み込みを確かめました。: %s\n", formatQuantity(uint64(len(selftestFileSizes)*len(readPaths)), "件", "件"))
+
+	return failures
+}
+
+// メモリ上のデータのハッシュを計算する。
+// ツリーハッシュは並行して計算せずに、チャンクごとのハッシュを順に計算する。
+func referenceHash(algorithm string, data []byte) []byte {
+	var newHash func() hash.Hash
+	switch algorithm {
+	case AlgorithmMD5Tree:
+		newHash = md5.New
+	case AlgorithmSHA256Tree:
+		newHash = sha256.New
+	default:
+		hasher := hasherFactories[algorithm]()
+		hasher.Write(data)
+		return hasher.Sum(nil)
+	}
+
+	root := newHash()
+	for offset := 0; offset < len(data); offset += TreeChunkSize {
+		end := offset + TreeChunkSize
+		if end > len(data) {
+			end = len(data)
+		}
+		chunk := newHash()
+		chunk.Write(data[offset:end])
+		root.Write(chunk.Sum(nil))
+	}
+	return root.Sum(nil)
+}
@@ -0,0 +1,447 @@
+package bcbc
+
+import (
+	"bytes"
+	"crypto/subtle"
+	"encoding/json"
+	"flag"
+	"fmt"
+	"io"
+	"net"
+	"net/http"
+	"net/url"
+	"os"
+	"path"
+	"path/filepath"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+)
+
+// EnvServerToken 環境変数名: 収集サーバーの認証トークン
+const EnvServerToken = "BCBC_SERVER_TOKEN"
+
+// 収集サーバーのパス
+const (
+	serverFilesPath    = "/files/"
+	serverListPath     = "/list/"
+	serverProgressPath = "/progress"
+)
+
+// 収集サーバーへの1回のリクエストのタイムアウト
+const serverTimeout = 5 * time.Minute
+
+// エージェントが報告する進捗
+type agentProgress struct {
+	Agent      string    `json:"agent"`
+	Disk       string    `json:"disk"`
+	FilesDone  uint64    `json:"filesDone"`
+	FilesTotal uint64    `json:"filesTotal"`
+	BytesDone  uint64    `json:"bytesDone"`
+	BytesTotal uint64    `json:"bytesTotal"`
+	Throughput float64   `json:"throughput"`
+	Reported   time.Time `json:"reported"`
+}
+
+// serveサブコマンド。
+// 他のマシンのbcbcのハッシュファイルを出力ディレクトリに集める収集サーバーを開始する。
+// エージェントは-store=server -store-url=http://サーバー:ポートで実行すると、ハッシュファイルをこのサーバーに保存し、進捗を報告する。
+// 集めたハッシュファイルはこのマシンでfindなどのサブコマンドで検索できる。
+func executeServe(args []string) {
+	flagSet := flag.NewFlagSet("serve", flag.ExitOnError)
+	listen := flagSet.String("listen", ":8420", "待ち受けるアドレスとポート。")
+	insecure := flagSet.Bool("insecure", false, "環境変数BCBC_SERVER_TOKENが設定されていなくても、認証せずに受け付けて開始する。")
+	maxRequestSizeText := flagSet.String("max-request-size", "4G", "受け付けるリクエストの本文のサイズの上限。(例: 16G)")
+	flagSet.Parse(args)
+
+	maxRequestSize, err := parseByteSize(*maxRequestSizeText)
+	fatalMessageError(ErrConfig, err, "リクエストのサイズの上限の指定が不正です。\n")
+
+	// 誰でも出力ディレクトリに書き込めてしまうので、認証しないときは明示的に指定させる
+	token := os.Getenv(EnvServerToken)
+	if token == "" {
+		fatalMessageIf(ErrConfig, !*insecure, "環境変数%sが設定されていません。認証せずに受け付けるなら-insecureを指定してください。\n", EnvServerToken)
+		logf.Printf("[警告] 環境変数%sが設定されていないので、認証せずに受け付けます。\n", EnvServerToken)
+	}
+
+	server := &collectorServer{token: token, maxRequestSize: int64(maxRequestSize), progress: make(map[string]agentProgress)}
+
+	mux := http.NewServeMux()
+	mux.HandleFunc(serverFilesPath, server.authorized(server.serveFile))
+	mux.HandleFunc(serverListPath, server.authorized(server.serveList))
+	mux.HandleFunc(serverProgressPath, server.authorized(server.serveProgress))
+
+	listener, err := net.Listen("tcp", *listen)
+	fatalMessageError(ErrConfig, err, "収集サーバーのポートを開けませんでした。: %s\n", *listen)
+
+	logf.Printf("収集サーバーを開始します。: http://%s\n", listener.Addr())
+
+	go func() {
+		<-interruptChannel
+		listener.Close()
+	}()
+
+	err = http.Serve(listener, mux)
+	fatalMessageIf(ErrInterrupted, interrupted(), "収集サーバーを終了しました。\n")
+	fatalMessageError(ErrIO, err, "収集サーバーが停止しました。\n")
+}
+
+// 収集サーバー
+type collectorServer struct {
+	token string
+	// リクエストの本文のサイズの上限
+	maxRequestSize int64
+	mu             sync.Mutex
+	progress       map[string]agentProgress
+}
+
+// トークンが一致するリクエストだけを処理する。
+// トークンの比較にかかる時間から推測されないように、一致する長さによらない時間で比較する。
+func (c *collectorServer) authorized(handler http.HandlerFunc) http.HandlerFunc {
+	return func(w http.ResponseWriter, r *http.Request) {
+		expected := []byte("Bearer " + c.token)
+		if c.token != "" && subtle.ConstantTimeCompare([]byte(r.Header.Get("Authorization")), expected) != 1 {
+			http.Error(w, "unauthorized", http.StatusUnauthorized)
+			return
+		}
+		r.Body = http.MaxBytesReader(w, r.Body, c.maxRequestSize)
+		handler(w, r)
+	}
+}
+
+// リクエストのパスを出力ディレクトリのパスにする。出力ディレクトリの外を指していれば空文字列を返す。
+func serverLocalPath(prefix string, requestPath string) string {
+	name := path.Clean("/" + strings.TrimPrefix(requestPath, prefix))
+	if name == "/" || strings.Contains(name, "\\") {
+		return config.outDir()
+	}
+	return filepath.Join(config.outDir(), filepath.FromSlash(name[1:]))
+}
+
+// ファイルの取得、保存、削除
+func (c *collectorServer) serveFile(w http.ResponseWriter, r *http.Request) {
+	file := serverLocalPath(serverFilesPath, r.URL.Path)
+	if file == config.outDir() {
+		http.Error(w, "bad path", http.StatusBadRequest)
+		return
+	}
+
+	switch r.Method {
+	case http.MethodGet, http.MethodHead:
+		http.ServeFile(w, r, file)
+
+	case http.MethodPut:
+		if err := writeFileAtomically(file, r.Body); err != nil {
+			logf.Printf("エージェントから受け取ったファイルを保存できませんでした。: %s\n", file)
+			logf.Println(err)
+			http.Error(w, err.Error(), http.StatusInternalServerError)
+			return
+		}
+		w.WriteHeader(http.StatusNoContent)
+
+	case http.MethodDelete:
+		if err := os.Remove(file); err != nil && !os.IsNotExist(err) {
+			http.Error(w, err.Error(), http.StatusInternalServerError)
+			return
+		}
+		w.WriteHeader(http.StatusNoContent)
+
+	default:
+		http.Error(w, "method not allowed", http.StatusMethodNotAllowed)
+	}
+}
+
+// 一時ファイルに書き込んでから置き換える。
+// 同じファイルへのアップロードが同時に来ても混ざらないように、一時ファイルは毎回別の名前で作成する。
+func writeFileAtomically(file string, in io.Reader) error {
+	if err := os.MkdirAll(filepath.Dir(file), 0755); err != nil {
+		return err
+	}
+
+	out, err := os.CreateTemp(filepath.Dir(file), "."+filepath.Base(file)+".*.tmp")
+	if err != nil {
+		return err
+	}
+	tempFile := out.Name()
+	_, err = io.Copy(out, in)
+	if closeErr := out.Close(); err == nil {
+		err = closeErr
+	}
+	if err == nil {
+		// CreateTempは所有者だけが読めるファイルを作成する
+		err = os.Chmod(tempFile, 0644)
+	}
+	if err == nil {
+		os.Remove(file)
+		err = os.Rename(tempFile, file)
+	}
+	if err != nil {
+		os.Remove(tempFile)
+	}
+	return err
+}
+
+// ディレクトリのファイル名の一覧を1行に1つずつ返す。
+func (c *collectorServer) serveList(w http.ResponseWriter, r *http.Request) {
+	entries, err := os.ReadDir(serverLocalPath(serverListPath, r.URL.Path))
+	if os.IsNotExist(err) {
+		http.NotFound(w, r)
+		return
+	}
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusInternalServerError)
+		return
+	}
+
+	w.Header().Set("Content-Type", "text/plain; charset=utf-8")
+	for _, entry := range entries {
+		// 書き込み中の一時ファイルは返さない
+		if strings.HasPrefix(entry.Name(), ".") {
+			continue
+		}
+		fmt.Fprintln(w, entry.Name())
+	}
+}
+
+// エージェントの進捗の報告を受け取り、GETなら全エージェントの進捗を返す。
+func (c *collectorServer) serveProgress(w http.ResponseWriter, r *http.Request) {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	switch r.Method {
+	case http.MethodPost:
+		var progress agentProgress
+		if err := json.NewDecoder(r.Body).Decode(&progress); err != nil {
+			http.Error(w, err.Error(), http.StatusBadRequest)
+			return
+		}
+		progress.Reported = time.Now()
+
+		key := progress.Agent + "/" + progress.Disk
+		previous, found := c.progress[key]
+		if !found || previous.FilesDone > progress.FilesDone {
+			logf.Printf("%sがディスク%sのハッシュ計算を開始しました。\n", progress.Agent, progress.Disk)
+		}
+		if progress.FilesTotal > 0 && progress.FilesDone == progress.FilesTotal && (!found || previous.FilesDone != previous.FilesTotal) {
+			logf.Printf("%sがディスク%sのハッシュ計算を終えました。\n", progress.Agent, progress.Disk)
+		}
+		c.progress[key] = progress
+		w.WriteHeader(http.StatusNoContent)
+
+	case http.MethodGet:
+		keys := make([]string, 0, len(c.progress))
+		for key := range c.progress {
+			keys = append(keys, key)
+		}
+		sort.Strings(keys)
+
+		w.Header().Set("Content-Type", "text/plain; charset=utf-8")
+		for _, key := range keys {
+			p := c.progress[key]
+			fmt.Fprintf(w, "%s\t%s\t%d/%d files\t%s/%s\t%s/s\t%s\n", p.Agent, p.Disk, p.FilesDone, p.FilesTotal,
+				formatSize(float64(p.BytesDone)), formatSize(float64(p.BytesTotal)), formatSize(p.Throughput), p.Reported.Format(time.RFC3339))
+		}
+
+	default:
+		http.Error(w, "method not allowed", http.StatusMethodNotAllowed)
+	}
+}
+
+// 収集サーバーの保存先
+type serverStorage struct {
+	baseURL string
+	// アルゴリズムのハッシュファイルのディレクトリ
+	dir    string
+	token  string
+	client *http.Client
+}
+
+func newServerHashStore(algorithm string) HashStore {
+	return &remoteHashStore{
+		&fileHashStore{config.hashDir(algorithm), algorithm},
+		newServerStorage(remoteHashDir(algorithm)),
+	}
+}
+
+func newServerStorage(dir string) *serverStorage {
+	return &serverStorage{strings.TrimSuffix(config.storeURL, "/"), dir, os.Getenv(EnvServerToken), &http.Client{Timeout: serverTimeout}}
+}
+
+// 名前の出力ディレクトリからの相対パスをURLのパスにする。
+func (s *serverStorage) escapedPath(name string) string {
+	if s.dir != "" {
+		name = path.Join(s.dir, name)
+	}
+	segments := strings.Split(name, "/")
+	for i, segment := range segments {
+		segments[i] = url.PathEscape(segment)
+	}
+	return strings.Join(segments, "/")
+}
+
+func (s *serverStorage) location(name string) string {
+	return s.baseURL + serverFilesPath + s.escapedPath(name)
+}
+
+// 収集サーバーにリクエストを送る。
+func (s *serverStorage) request(method string, target string, body io.Reader, size int64) (*http.Response, error) {
+	request, err := http.NewRequest(method, target, body)
+	if err != nil {
+		return nil, err
+	}
+	if body != nil {
+		request.ContentLength = size
+	}
+	if s.token != "" {
+		request.Header.Set("Authorization", "Bearer "+s.token)
+	}
+	return s.client.Do(request)
+}
+
+func (s *serverStorage) get(name string) (io.ReadCloser, error) {
+	response, err := s.request(http.MethodGet, s.location(name), nil, 0)
+	if err != nil {
+		return nil, err
+	}
+
+	switch response.StatusCode {
+	case http.StatusOK:
+		return response.Body, nil
+	case http.StatusNotFound:
+		response.Body.Close()
+		return nil, nil
+	default:
+		response.Body.Close()
+		return nil, fmt.Errorf("%s: %s", s.location(name), response.Status)
+	}
+}
+
+func (s *serverStorage) stamp(name string) (string, error) {
+	response, err := s.request(http.MethodHead, s.location(name), nil, 0)
+	if err != nil {
+		return "", err
+	}
+	response.Body.Close()
+
+	switch response.StatusCode {
+	case http.StatusOK:
+		return responseStamp(response), nil
+	case http.StatusNotFound:
+		return missingStamp, nil
+	default:
+		return "", fmt.Errorf("%s: %s", s.location(name), response.Status)
+	}
+}
+
+func (s *serverStorage) put(name string, body io.ReadSeeker, size int64) error {
+	return s.send(http.MethodPut, name, body, size)
+}
+
+func (s *serverStorage) remove(name string) error {
+	return s.send(http.MethodDelete, name, nil, 0)
+}
+
+func (s *serverStorage) send(method string, name string, body io.Reader, size int64) error {
+	response, err := s.request(method, s.location(name), body, size)
+	if err != nil {
+		return err
+	}
+	response.Body.Close()
+
+	if response.StatusCode/100 != 2 {
+		return fmt.Errorf("%s: %s", s.location(name), response.Status)
+	}
+	return nil
+}
+
+func (s *serverStorage) list(dir string) ([]string, error) {
+	target := s.baseURL + serverListPath + s.escapedPath(dir)
+	response, err := s.request(http.MethodGet, target, nil, 0)
+	if err != nil {
+		return nil, err
+	}
+	defer response.Body.Close()
+
+	if response.StatusCode == http.StatusNotFound {
+		return nil, nil
+	}
+	if response.StatusCode != http.StatusOK {
+		return nil, fmt.Errorf("%s: %s", target, response.Status)
+	}
+
+	content, err := io.ReadAll(response.Body)
+	if err != nil {
+		return nil, err
+	}
+	return strings.Fields(string(content)), nil
+}
+
+// 収集サーバーへの進捗の報告。
+// 報告が遅れても進捗の表示を止めないように、前の報告が終わっていなければ今回の報告は送らない。
+type progressReporter struct {
+	storage *serverStorage
+	agent   string
+	mu      sync.Mutex
+	busy    bool
+}
+
+// 収集サーバーに保存するときだけ進捗の報告を作成する。
+func newProgressReporter() *progressReporter {
+	if config.store != StoreServer {
+		return nil
+	}
+	agent, err := os.Hostname()
+	if err != nil {
+		agent = "unknown"
+	}
+	return &progressReporter{storage: newServerStorage(""), agent: agent}
+}
+
+// 全ディスクの進捗を報告する。
+func (r *progressReporter) report(progressInfoList []ProgressInfo, estimators []throughputEstimator) {
+	if r == nil {
+		return
+	}
+
+	r.mu.Lock()
+	if r.busy {
+		r.mu.Unlock()
+		return
+	}
+	r.busy = true
+	r.mu.Unlock()
+
+	var reports []agentProgress
+	for i, progressInfo := range progressInfoList {
+		if progressInfo.diskInfo == nil {
+			continue
+		}
+		reports = append(reports, agentProgress{
+			Agent:      r.agent,
+			Disk:       progressInfo.diskInfo.id,
+			FilesDone:  progressInfo.fileCount.processed,
+			FilesTotal: progressInfo.fileCount.total,
+			BytesDone:  progressInfo.sizeCount.processed,
+			BytesTotal: progressInfo.sizeCount.total,
+			Throughput: estimators[i].rate,
+		})
+	}
+
+	go func() {
+		defer func() {
+			r.mu.Lock()
+			r.busy = false
+			r.mu.Unlock()
+		}()
+
+		for _, report := range reports {
+			body, _ := json.Marshal(report)
+			response, err := r.storage.request(http.MethodPost, r.storage.baseURL+serverProgressPath, bytes.NewReader(body), int64(len(body)))
+			if err != nil {
+				return
+			}
+			response.Body.Close()
+		}
+	}()
+}
@@ -0,0 +1,75 @@
+package bcbc
+
+import (
+	"flag"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// setupサブコマンド。
+// BCBCホームディレクトリの構成と設定ファイルの雛形を作成し、実行できる環境か確認する。
+// 既にある設定ファイルは上書きしてよいか確認し、-yesなら確認せずに残し、-forceなら確認せずに上書きする。
+func executeSetup(args []string) {
+	flagSet := flag.NewFlagSet("setup", flag.ExitOnError)
+	yes := flagSet.Bool("yes", false, "確認せずに、既にある設定ファイルは残す。")
+	force := flagSet.Bool("force", false, "確認せずに、既にある設定ファイルを雛形で上書きする。")
+	flagSet.Parse(args)
+
+	logf.Printf("BCBCホームディレクトリをセットアップします。: %s\n", config.homeDir)
+
+	for _, dir := range []string{config.configDir(), config.outDir(), config.logDir()} {
+		err := os.MkdirAll(dir, 0755)
+		fatalMessageError(ErrIO, err, "ディレクトリを作成できませんでした。: %s\n", dir)
+	}
+
+	templates := []struct {
+		name    string
+		content string
+	}{
+		{"filter.conf", filterConfTemplate},
+		{"bcbc.conf", defaultsConfTemplate},
+	}
+	for _, template := range templates {
+		file := filepath.Join(config.configDir(), template.name)
+		if _, err := os.Stat(file); err == nil && !*force && (*yes || !confirmOverwrite(file)) {
+			logf.Printf("既にある設定ファイルを残します。: %s\n", file)
+			continue
+		}
+
+		err := os.WriteFile(file, []byte(template.content), 0644)
+		fatalMessageError(ErrIO, err, "設定ファイルを作成できませんでした。: %s\n", file)
+		logf.Printf("設定ファイルを作成しました。: %s\n", file)
+	}
+
+	checkSetup()
+
+	logf.Println("セットアップが完了しました。ハッシュ計算するディスクのルートにはinit-diskサブコマンドでdiskファイルを作成してください。")
+}
+
+// 設定ファイルを雛形で上書きしてよいか確認する。
+func confirmOverwrite(file string) bool {
+	fmt.Printf(tr("%sは既にあります。雛形で上書きしますか? [y/N]: "), file)
+	answer, _ := confirmReader.ReadString('\n')
+	answer = strings.ToLower(strings.TrimSpace(answer))
+
+	return answer == "y" || answer == "yes"
+}
+
+// 実行できる環境か確認する。
+// 出力ディレクトリに書き込めるか、設定ファイルを読み込めるかを確認し、問題があれば致命的なエラーにする。
+func checkSetup() {
+	probe, err := os.CreateTemp(config.outDir(), "setup")
+	fatalMessageError(ErrIO, err, "出力ディレクトリに書き込めません。: %s\n", config.outDir())
+	probe.Close()
+	os.Remove(probe.Name())
+
+	initFilters()
+	initNotify()
+
+	logf.Printf("フィルター設定を確認しました。: %d件の規則\n", len(config.filters))
+	if config.notify != nil {
+		logf.Println("通知設定を確認しました。")
+	}
+}
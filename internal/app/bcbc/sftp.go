@@ -0,0 +1,162 @@
+package bcbc
+
+import (
+	"bytes"
+	"context"
+	"fmt"
+	"io"
+	"net/url"
+	"os/exec"
+	"path"
+	"strings"
+	"time"
+)
+
+// SFTPの保存先への1回のコマンドのタイムアウト
+const sftpTimeout = 5 * time.Minute
+
+// リモートのファイルがないことを表すコマンドの終了コード。sshは自身のエラーで255を返すので区別できる。
+const sftpMissingStatus = 3
+
+// SFTPの保存先。
+// 依存するライブラリを増やさないように、sshコマンドでリモートのマシンのshを実行してファイルを読み書きする。
+// 認証はsshの設定に任せるので、鍵やエージェントでパスワードなしで接続できるようにしておく。
+type sftpStorage struct {
+	// アルゴリズムのハッシュファイルのディレクトリのURL。ログに表示する。
+	baseURL string
+	// 接続先。ユーザー名があれば「ユーザー名@マシン名」
+	host string
+	// 接続先のポート。空ならsshの設定に任せる。
+	port string
+	// アルゴリズムのハッシュファイルのディレクトリのリモートのパス
+	baseDir string
+}
+
+func newSFTPHashStore(algorithm string) HashStore {
+	parsed, _ := url.Parse(config.storeURL)
+	host := parsed.Hostname()
+	if user := config.storeUser.Username(); user != "" {
+		host = user + "@" + host
+	}
+
+	// URLのパスがなければログインしたユーザーのホームディレクトリに置く
+	baseDir := "."
+	if parsed.Path != "" && parsed.Path != "/" {
+		baseDir = path.Clean(parsed.Path)
+	}
+	baseURL := strings.TrimSuffix(config.storeURL, "/")
+	if dir := remoteHashDir(algorithm); dir != "" {
+		baseDir = path.Join(baseDir, dir)
+		baseURL += "/" + dir
+	}
+
+	return &remoteHashStore{
+		&fileHashStore{config.hashDir(algorithm), algorithm},
+		&sftpStorage{baseURL, host, parsed.Port(), baseDir},
+	}
+}
+
+func (s *sftpStorage) location(name string) string {
+	if name == "" {
+		return s.baseURL + "/"
+	}
+	return s.baseURL + "/" + name
+}
+
+// 名前のリモートのパスを返す。
+func (s *sftpStorage) remotePath(name string) string {
+	return path.Join(s.baseDir, name)
+}
+
+// シェルの引数として1つの文字列になるように引用符で囲む。
+func shellQuote(s string) string {
+	return "'" + strings.ReplaceAll(s, "'", `'\''`) + "'"
+}
+
+// リモートのマシンでスクリプトをshで実行して標準出力を返す。
+// スクリプトがsftpMissingStatusで終了したら、ファイルがないのでtrueを返す。
+func (s *sftpStorage) run(script string, stdin io.Reader) ([]byte, bool, error) {
+	ctx, cancel := context.WithTimeout(context.Background(), sftpTimeout)
+	defer cancel()
+
+	// パスワードを尋ねて止まらないようにBatchModeにする
+	args := []string{"-o", "BatchMode=yes"}
+	if s.port != "" {
+		args = append(args, "-p", s.port)
+	}
+	// ログインシェルがshでなくても同じように動くように、sh -cで実行する
+	args = append(args, "--", s.host, "sh -c "+shellQuote(script))
+
+	command := exec.CommandContext(ctx, "ssh", args...)
+	command.Stdin = stdin
+	var stderr bytes.Buffer
+	command.Stderr = &stderr
+
+	output, err := command.Output()
+	if exitErr, ok := err.(*exec.ExitError); ok {
+		if exitErr.ExitCode() == sftpMissingStatus {
+			return nil, true, nil
+		}
+		return nil, false, fmt.Errorf("%s: %v: %s", s.host, err, strings.TrimSpace(stderr.String()))
+	}
+	if err != nil {
+		return nil, false, err
+	}
+	return output, false, nil
+}
+
+func (s *sftpStorage) get(name string) (io.ReadCloser, error) {
+	file := shellQuote(s.remotePath(name))
+	output, missing, err := s.run(fmt.Sprintf("if [ -f %s ]; then cat %s; else exit %d; fi", file, file, sftpMissingStatus), nil)
+	if err != nil || missing {
+		return nil, err
+	}
+	return io.NopCloser(bytes.NewReader(output)), nil
+}
+
+// リモートのマシンで計算したチェックサムとサイズを目印にする。
+// ファイルを転送しないので、取得し直すより軽い。
+func (s *sftpStorage) stamp(name string) (string, error) {
+	file := shellQuote(s.remotePath(name))
+	output, missing, err := s.run(fmt.Sprintf("if [ -f %s ]; then cksum < %s; else exit %d; fi", file, file, sftpMissingStatus), nil)
+	if err != nil {
+		return "", err
+	}
+	if missing {
+		return missingStamp, nil
+	}
+	return strings.TrimSpace(string(output)), nil
+}
+
+// 同じディレクトリの一時ファイルに書き込んでから置き換える。
+// 一時ファイルは「.」で始めて、一覧に含まれないようにする。
+func (s *sftpStorage) put(name string, body io.ReadSeeker, size int64) error {
+	file := s.remotePath(name)
+	dir := shellQuote(path.Dir(file))
+	tempFile := shellQuote(path.Join(path.Dir(file), "."+path.Base(file))) + ".$$.tmp"
+	script := fmt.Sprintf("mkdir -p %s && cat > %s && mv -f %s %s || { rm -f %s; exit 1; }",
+		dir, tempFile, tempFile, shellQuote(file), tempFile)
+	_, _, err := s.run(script, body)
+	return err
+}
+
+func (s *sftpStorage) remove(name string) error {
+	_, _, err := s.run("rm -f "+shellQuote(s.remotePath(name)), nil)
+	return err
+}
+
+func (s *sftpStorage) list(dir string) ([]string, error) {
+	target := shellQuote(s.remotePath(dir))
+	output, _, err := s.run(fmt.Sprintf("if [ -d %s ]; then ls -1 %s; fi", target, target), nil)
+	if err != nil {
+		return nil, err
+	}
+
+	var names []string
+	for _, name := range strings.Split(string(output), "\n") {
+		if name != "" {
+			names = append(names, name)
+		}
+	}
+	return names, nil
+}
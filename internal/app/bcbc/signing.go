@@ -0,0 +1,164 @@
+package bcbc
+
+import (
+	"crypto/ed25519"
+	"crypto/rand"
+	"encoding/base64"
+	"flag"
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// 署名の鍵ファイルの名前
+const (
+	signingKeyFile   = "signing.key"
+	signingPublicKey = "signing.pub"
+)
+
+// 署名ファイルの拡張子
+const signatureExtension = ".sig"
+
+// 署名の秘密鍵のパスを返す。
+func (c *Config) signingKeyPath() string {
+	return filepath.Join(c.configDir(), signingKeyFile)
+}
+
+// 署名の公開鍵のパスを返す。
+func (c *Config) signingPublicKeyPath() string {
+	return filepath.Join(c.configDir(), signingPublicKey)
+}
+
+// -signが指定されていれば署名の秘密鍵を読み込む。
+func initSigning() {
+	config.signingKey = nil
+	if !*signOption {
+		return
+	}
+
+	key, err := readBase64File(config.signingKeyPath())
+	fatalMessageError(ErrConfig, err, "署名の秘密鍵が読み込めませんでした。sign-keyサブコマンドで作成してください。: %s\n", config.signingKeyPath())
+	fatalMessageIf(ErrConfig, len(key) != ed25519.PrivateKeySize, "署名の秘密鍵の形式が不正です。: %s\n", config.signingKeyPath())
+
+	config.signingKey = ed25519.PrivateKey(key)
+}
+
+// base64で書かれたファイルを読み込む。
+func readBase64File(file string) ([]byte, error) {
+	data, err := os.ReadFile(file)
+	if err != nil {
+		return nil, err
+	}
+	return base64.StdEncoding.DecodeString(strings.TrimSpace(string(data)))
+}
+
+// ファイルに署名して署名ファイルを書き込む。
+// 署名する設定でなければ何もしない。
+func signFile(file string) error {
+	if config.signingKey == nil {
+		return nil
+	}
+
+	data, err := os.ReadFile(file)
+	if err != nil {
+		return err
+	}
+
+	signature := ed25519.Sign(config.signingKey, data)
+	return os.WriteFile(file+signatureExtension, []byte(base64.StdEncoding.EncodeToString(signature)+"\n"), 0644)
+}
+
+// 追記などで内容が変わるファイルの署名ファイルを削除する。
+// 古い署名が残っていると改ざんと区別できないので、書き直して署名し直すまで署名なしにする。
+func removeSignature(file string) {
+	os.Remove(file + signatureExtension)
+}
+
+// 署名の検証結果
+const (
+	signatureValid   = "valid"
+	signatureInvalid = "invalid"
+	signatureMissing = "missing"
+)
+
+// ファイルの署名を公開鍵で検証する。
+func verifySignature(file string, publicKey ed25519.PublicKey) (string, error) {
+	signature, err := readBase64File(file + signatureExtension)
+	if os.IsNotExist(err) {
+		return signatureMissing, nil
+	}
+	if err != nil {
+		return "", err
+	}
+
+	data, err := os.ReadFile(file)
+	if err != nil {
+		return "", err
+	}
+
+	if !ed25519.Verify(publicKey, data, signature) {
+		return signatureInvalid, nil
+	}
+	return signatureValid, nil
+}
+
+// 署名の公開鍵を読み込む。公開鍵がなければnilを返す。
+func loadSigningPublicKey() ed25519.PublicKey {
+	key, err := readBase64File(config.signingPublicKeyPath())
+	if os.IsNotExist(err) {
+		return nil
+	}
+	fatalMessageError(ErrConfig, err, "署名の公開鍵が読み込めませんでした。: %s\n", config.signingPublicKeyPath())
+	fatalMessageIf(ErrConfig, len(key) != ed25519.PublicKeySize, "署名の公開鍵の形式が不正です。: %s\n", config.signingPublicKeyPath())
+
+	return ed25519.PublicKey(key)
+}
+
+// sign-keyサブコマンド。
+// ハッシュファイルに署名するEd25519の鍵を作成して設定ディレクトリに保存する。
+// 既に鍵があれば-forceを指定しない限り作り直さない。
+func executeSignKey(args []string) {
+	flagSet := flag.NewFlagSet("sign-key", flag.ExitOnError)
+	force := flagSet.Bool("force", false, "既にある鍵を作り直す。作り直すと今までの署名は検証できなくなる。")
+	flagSet.Parse(args)
+
+	if _, err := os.Stat(config.signingKeyPath()); err == nil && !*force {
+		fatalMessageIf(ErrConfig, true, "署名の鍵は既にあります。作り直すには-forceを指定してください。: %s\n", config.signingKeyPath())
+	}
+
+	publicKey, privateKey, err := ed25519.GenerateKey(rand.Reader)
+	fatalMessageError(ErrIO, err, "署名の鍵を作成できませんでした。\n")
+
+	err = os.MkdirAll(config.configDir(), 0755)
+	fatalMessageError(ErrIO, err, "設定ディレクトリを作成できませんでした。: %s\n", config.configDir())
+	err = os.WriteFile(config.signingKeyPath(), []byte(base64.StdEncoding.EncodeToString(privateKey)+"\n"), 0600)
+	fatalMessageError(ErrIO, err, "署名の秘密鍵を保存できませんでした。: %s\n", config.signingKeyPath())
+	err = os.WriteFile(config.signingPublicKeyPath(), []byte(base64.StdEncoding.EncodeToString(publicKey)+"\n"), 0644)
+	fatalMessageError(ErrIO, err, "署名の公開鍵を保存できませんでした。: %s\n", config.signingPublicKeyPath())
+
+	logf.Printf("署名の鍵を作成しました。: %s\n", config.signingKeyPath())
+	logf.Println("秘密鍵はハッシュファイルとは別の場所にも控えておいてください。")
+}
+
+// ディスクのハッシュファイルの署名を検証する。
+// 署名が一致しなければハッシュファイルが改ざんされている可能性があるので致命的なエラーにする。
+func verifyHashFileSignatures(diskInfo *DiskInfo) {
+	publicKey := loadSigningPublicKey()
+	if publicKey == nil {
+		return
+	}
+
+	for _, algorithm := range diskInfo.hashAlgorithms() {
+		hashFile := existingHashFile(filepath.Join(config.hashDir(algorithm), diskInfo.id))
+		result, err := verifySignature(hashFile, publicKey)
+		fatalMessageError(ErrIO, err, "ハッシュファイルの署名を検証できませんでした。: %s\n", hashFile)
+
+		switch result {
+		case signatureMissing:
+			logf.Printf("ハッシュファイルに署名がありません。: %s\n", hashFile)
+		case signatureInvalid:
+			fatalMessageIf(ErrHashFileFormat, true, "ハッシュファイルの署名が一致しません。改ざんされている可能性があります。: %s\n", hashFile)
+		}
+	}
+}
+
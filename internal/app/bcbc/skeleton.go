@@ -0,0 +1,73 @@
+package bcbc
+
+import (
+	"os"
+	"path/filepath"
+)
+
+// 初めて実行したときに作成するフィルター設定ファイルの内容
+const filterConfTemplate = `# フィルター設定
+#
+# 書式:
+# 空白行と#から始まるコメント行は無視する。
+# それ以外の行は'+'か'-'で始め、2文字目以降は正規表現の式にする。
+# 対象ファイルの判定:
+# 正規表現を上から順番に試し、ディスクルートからの相対パスがマッチする式を探す。
+# マッチする式が見つかったら行頭の記号を確認し、'+'ならそのファイルのハッシュ計算を行う。
+# '-'なら行わない。
+# 複数の式が相対パスとマッチしても、最初の式の行頭記号で判定は確定する。
+# マッチする式が見つからなければハッシュ計算の対象にしない。
+# Windowsで実行してもパスはスラッシュ区切りになる。
+#
+# 例: 行頭の#を外すと有効になる。
+# ごみ箱とWindowsのシステムフォルダを対象にしない
+#-^\$RECYCLE\.BIN/
+#-^System Volume Information/
+# 一時ファイルを対象にしない
+#-\.tmp$
+# photosフォルダ以下だけを対象にするなら最後の+.*の代わりに書く
+#+^photos/
+
+-/desktop\.ini$
+-/Thumbs\.db$
+-/\.DS_Store$
++.*
+`
+
+// セットアップで作成する既定値設定ファイルの内容
+const defaultsConfTemplate = `# 既定値設定
+#
+# 書式:
+# 空白行と#から始まるコメント行は無視する。
+# それ以外の行は「項目名=値」にする。
+# 項目名はコマンドラインオプションの名前(先頭の'-'は付けない)で、値はそのオプションの既定値になる。
+# コマンドラインで指定したオプションはこのファイルの値より優先される。
+# 通知の設定はnotify.confに書く。
+# homeを書くとそのBCBCホームディレクトリに切り替えて、そちらの既定値設定ファイルで残りの既定値を補う。
+
+#algorithm=md5,sha256
+#max-parallel-disks=2
+#throttle=100
+#retries=2
+#encrypt=key
+#compress=gzip
+`
+
+// BCBCホームディレクトリがなければ、設定ディレクトリ、フィルター設定ファイル、出力ディレクトリを作成する。
+// 作成したらtrueを返す。
+func createHomeSkeleton() bool {
+	if _, err := os.Stat(config.homeDir); err == nil {
+		return false
+	}
+
+	for _, dir := range []string{config.configDir(), config.outDir()} {
+		err := os.MkdirAll(dir, 0755)
+		fatalMessageError(ErrIO, err, "ディレクトリを作成できませんでした。: %s\n", dir)
+	}
+
+	filterConfigFile := filepath.Join(config.configDir(), "filter.conf")
+	err := os.WriteFile(filterConfigFile, []byte(filterConfTemplate), 0644)
+	fatalMessageError(ErrIO, err, "フィルター設定ファイルを作成できませんでした。: %s\n", filterConfigFile)
+
+	return true
+}
@@ -0,0 +1,231 @@
+package bcbc
+
+import (
+	"flag"
+	"io"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"time"
+)
+
+// スナップショットの名前の形式
+const snapshotNameFormat = "20060102-150405"
+
+// 最新のスナップショットの名前を記録するファイルの名前
+const latestSnapshotFile = "latest"
+
+// スナップショットのディレクトリの拡張子
+const snapshotDirExtension = ".snapshots"
+
+// ディスクのスナップショットのディレクトリを返す。
+// ハッシュファイルと同じ名前のディレクトリは作れないので、拡張子を付けたディレクトリにする。
+func (s *fileHashStore) snapshotDir(diskId string) string {
+	return s.file(diskId + snapshotDirExtension)
+}
+
+// ハッシュファイルを読み取り専用のファイルにコピーしてスナップショットにする。
+// 同じ秒に2回保存したら後から保存したほうで置き換える。
+func (s *fileHashStore) snapshot(diskId string) (string, error) {
+	dir := s.snapshotDir(diskId)
+	if err := os.MkdirAll(dir, 0755); err != nil {
+		return "", newError(ErrIO, err, "スナップショットのディレクトリを作成できませんでした。: %s", dir)
+	}
+
+	name := time.Now().Format(snapshotNameFormat)
+	// 圧縮したハッシュファイルは圧縮したままコピーする
+	hashFile := existingHashFile(s.file(diskId))
+	snapshotFile := filepath.Join(dir, name)
+	if strings.HasSuffix(hashFile, compressedExtension) {
+		snapshotFile += compressedExtension
+	}
+
+	err := copyFileReadOnly(hashFile, snapshotFile)
+	if err == nil {
+		err = os.WriteFile(filepath.Join(dir, latestSnapshotFile), []byte(name+"\n"), 0644)
+	}
+	if err != nil {
+		return "", newError(ErrIO, err, "スナップショットの保存に失敗しました。: %s", snapshotFile)
+	}
+
+	return name, nil
+}
+
+// ファイルを読み取り専用のファイルにコピーする。
+// 一時ファイルに書き込んでから置き換えるので、途中で中断されても壊れたスナップショットは残らない。
+func copyFileReadOnly(source string, destination string) error {
+	in, err := os.Open(source)
+	if err != nil {
+		return err
+	}
+	defer in.Close()
+
+	tempFile := destination + ".tmp"
+	out, err := os.OpenFile(tempFile, os.O_CREATE|os.O_WRONLY|os.O_TRUNC, 0444)
+	if err != nil {
+		return err
+	}
+
+	_, err = io.Copy(out, in)
+	if closeErr := out.Close(); err == nil {
+		err = closeErr
+	}
+	if err == nil {
+		os.Remove(destination)
+		err = os.Rename(tempFile, destination)
+	}
+	if err != nil {
+		os.Remove(tempFile)
+	}
+
+	return err
+}
+
+func (s *fileHashStore) snapshots(diskId string) ([]string, error) {
+	entries, err := os.ReadDir(s.snapshotDir(diskId))
+	if os.IsNotExist(err) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, newError(ErrIO, err, "スナップショットの一覧取得に失敗しました。: %s", s.snapshotDir(diskId))
+	}
+
+	fileNames := make([]string, 0, len(entries))
+	for _, entry := range entries {
+		fileNames = append(fileNames, entry.Name())
+	}
+
+	return snapshotNames(fileNames), nil
+}
+
+// スナップショットのディレクトリのファイル名からスナップショットの名前を古い順に取り出す。
+func snapshotNames(fileNames []string) []string {
+	var names []string
+	for _, fileName := range fileNames {
+		name := strings.TrimSuffix(fileName, compressedExtension)
+		if _, err := time.Parse(snapshotNameFormat, name); err == nil {
+			names = append(names, name)
+		}
+	}
+	sort.Strings(names)
+
+	return names
+}
+
+func (s *fileHashStore) loadSnapshot(diskId string, name string) ([]HashRecord, error) {
+	snapshotFile := existingHashFile(filepath.Join(s.snapshotDir(diskId), name))
+	snapshotIn, err := openHashFile(snapshotFile)
+	if err != nil {
+		return nil, newError(ErrIO, err, "スナップショットの読み込みに失敗しました。: %s", snapshotFile)
+	}
+	defer snapshotIn.Close()
+
+	return readHashRecords(snapshotIn, snapshotFile)
+}
+
+// ハッシュ計算が終わったディスクのスナップショットを保存する。
+func saveSnapshots(diskInfo *DiskInfo) {
+	for _, algorithm := range diskInfo.hashAlgorithms() {
+		name, err := hashStoreFor(algorithm).snapshot(diskInfo.id)
+		fatalMessageError(ErrIO, err, "スナップショットの保存に失敗しました。: %s (%s)\n", diskInfo.id, algorithm)
+		logf.Printf("スナップショットを保存しました。: %s (%s) %s\n", diskInfo.id, algorithm, name)
+	}
+}
+
+// 2つの記録の一覧の違い
+type recordDiff struct {
+	added   []HashRecord
+	removed []HashRecord
+	// 変更後の記録
+	changed []HashRecord
+}
+
+// 2つの記録の一覧を比べて、追加、削除、ハッシュが変わった記録を返す。
+// 追記で同じパスの記録が複数あれば最後の記録を使う。
+func diffRecords(before []HashRecord, after []HashRecord) recordDiff {
+	beforeMap := make(map[string]HashRecord, len(before))
+	for _, record := range before {
+		beforeMap[record.path] = record
+	}
+	afterMap := make(map[string]HashRecord, len(after))
+	for _, record := range after {
+		afterMap[record.path] = record
+	}
+
+	var diff recordDiff
+	for path, record := range afterMap {
+		previous, found := beforeMap[path]
+		switch {
+		case !found:
+			diff.added = append(diff.added, record)
+		case previous.hash != record.hash:
+			diff.changed = append(diff.changed, record)
+		}
+	}
+	for path, record := range beforeMap {
+		if _, found := afterMap[path]; !found {
+			diff.removed = append(diff.removed, record)
+		}
+	}
+
+	for _, records := range [][]HashRecord{diff.added, diff.removed, diff.changed} {
+		sort.Slice(records, func(i, j int) bool {
+			return records[i].path < records[j].path
+		})
+	}
+
+	return diff
+}
+
+// 記録の違いを表示する。
+func printRecordDiff(diff recordDiff) {
+	for _, record := range diff.added {
+		logf.Printf("+ %s\n", record.path)
+	}
+	for _, record := range diff.removed {
+		logf.Printf("- %s\n", record.path)
+	}
+	for _, record := range diff.changed {
+		logf.Printf("* %s\n", record.path)
+	}
+	logf.Printf("追加%d件、削除%d件、変更%d件\n", len(diff.added), len(diff.removed), len(diff.changed))
+}
+
+// snapshotsサブコマンド。
+// ディスクのスナップショットの一覧を表示する。-diffで2つのスナップショットの違いを表示する。
+func executeSnapshots(args []string) {
+	flagSet := flag.NewFlagSet("snapshots", flag.ExitOnError)
+	diff := flagSet.String("diff", "", "違いを表示する2つのスナップショットの名前をカンマ区切りで指定する。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, flagSet.NArg() != 1, "ディスクIDを1つ指定してください。\n")
+	diskId := flagSet.Arg(0)
+	fatalMessageIf(ErrConfig, !diskIdPattern.MatchString(diskId), "ディスクIDの形式が不正です。: %s\n", diskId)
+
+	for _, algorithm := range config.algorithms {
+		store := hashStoreFor(algorithm)
+
+		if *diff != "" {
+			names := strings.Split(*diff, ",")
+			fatalMessageIf(ErrConfig, len(names) != 2, "スナップショットの名前を2つ指定してください。: %s\n", *diff)
+
+			before, err := store.loadSnapshot(diskId, strings.TrimSpace(names[0]))
+			fatalMessageError(ErrIO, err, "スナップショットの読み込みに失敗しました。\n")
+			after, err := store.loadSnapshot(diskId, strings.TrimSpace(names[1]))
+			fatalMessageError(ErrIO, err, "スナップショットの読み込みに失敗しました。\n")
+
+			logf.Printf("%s (%s): %s -> %s\n", diskId, algorithm, names[0], names[1])
+			printRecordDiff(diffRecords(before, after))
+			continue
+		}
+
+		names, err := store.snapshots(diskId)
+		fatalMessageError(ErrIO, err, "スナップショットの一覧取得に失敗しました。\n")
+
+		logf.Printf("%s (%s): %d件のスナップショット\n", diskId, algorithm, len(names))
+		for _, name := range names {
+			logf.Printf("  %s\n", name)
+		}
+	}
+}
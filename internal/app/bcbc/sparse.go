@@ -0,0 +1,116 @@
+package bcbc
+
+import (
+	"io"
+	"os"
+	"sort"
+)
+
+// 疎なファイルの穴を調べるためのファイル
+type seekableFile interface {
+	Stat() (os.FileInfo, error)
+	Seek(offset int64, whence int) (int64, error)
+}
+
+// 疎なファイルならデータがある範囲だけを読み込むようにする。
+// 穴はゼロとしてハッシュ計算するので、ディスクから読み込まずにゼロで埋める。
+// 疎なファイルでないか、データがある範囲を調べられなければそのまま返す。
+func detectSparse(fileIn targetFile, size uint64) targetFile {
+	seekable, ok := fileIn.(seekableFile)
+	if !ok || size == 0 {
+		return fileIn
+	}
+
+	stat, err := seekable.Stat()
+	if err != nil {
+		return fileIn
+	}
+	if allocated, found := allocatedSize(stat); !found || allocated >= stat.Size() {
+		return fileIn
+	}
+
+	data, err := dataRanges(seekable, stat.Size())
+	if err != nil {
+		return fileIn
+	}
+
+	return &sparseFile{targetFile: fileIn, data: data, size: stat.Size()}
+}
+
+// SEEK_DATAとSEEK_HOLEでデータがある範囲を調べる。
+func dataRanges(seekable seekableFile, size int64) ([]byteRange, error) {
+	var data []byteRange
+
+	for offset := int64(0); offset < size; {
+		start, err := seekable.Seek(offset, seekData)
+		if isNoMoreData(err) {
+			break
+		}
+		if err != nil {
+			return nil, err
+		}
+
+		end, err := seekable.Seek(start, seekHole)
+		if err != nil {
+			return nil, err
+		}
+
+		data = append(data, byteRange{start, end})
+		offset = end
+	}
+
+	return data, nil
+}
+
+// 疎なファイル。
+// 穴の範囲は読み込まずにゼロで埋める。
+type sparseFile struct {
+	targetFile
+	// データがある範囲。開始位置の順に並んでいる。
+	data []byteRange
+	size int64
+}
+
+func (f *sparseFile) ReadAt(p []byte, off int64) (int, error) {
+	if off >= f.size {
+		return 0, io.EOF
+	}
+
+	n := len(p)
+	if remaining := f.size - off; remaining < int64(n) {
+		n = int(remaining)
+	}
+	end := off + int64(n)
+
+	for i := range p[:n] {
+		p[i] = 0
+	}
+
+	// 読み込む範囲と重なるデータがある範囲だけを読み込む
+	first := sort.Search(len(f.data), func(i int) bool {
+		return f.data[i].end > off
+	})
+	for _, r := range f.data[first:] {
+		if r.start >= end {
+			break
+		}
+
+		start := r.start
+		if start < off {
+			start = off
+		}
+		stop := r.end
+		if stop > end {
+			stop = end
+		}
+
+		if _, err := f.targetFile.ReadAt(p[start-off:stop-off], start); err != nil && err != io.EOF {
+			return 0, err
+		}
+	}
+
+	if n < len(p) {
+		return n, io.EOF
+	}
+	return n, nil
+}
@@ -0,0 +1,29 @@
+// +build darwin
+
+package bcbc
+
+import (
+	"errors"
+	"os"
+	"syscall"
+)
+
+// lseekのwhence。macOSではLinuxと値が逆になっている。
+const (
+	seekHole = 3
+	seekData = 4
+)
+
+// ディスク上の割り当てサイズを返す。
+func allocatedSize(stat os.FileInfo) (int64, bool) {
+	sys, ok := stat.Sys().(*syscall.Stat_t)
+	if !ok {
+		return 0, false
+	}
+	return sys.Blocks * 512, true
+}
+
+// SEEK_DATAでファイルの末尾までデータがなかったか判定する。
+func isNoMoreData(err error) bool {
+	return errors.Is(err, syscall.ENXIO)
+}
@@ -0,0 +1,29 @@
+// +build linux
+
+package bcbc
+
+import (
+	"errors"
+	"os"
+	"syscall"
+)
+
+// lseekのwhence
+const (
+	seekData = 3
+	seekHole = 4
+)
+
+// ディスク上の割り当てサイズを返す。
+func allocatedSize(stat os.FileInfo) (int64, bool) {
+	sys, ok := stat.Sys().(*syscall.Stat_t)
+	if !ok {
+		return 0, false
+	}
+	return sys.Blocks * 512, true
+}
+
+// SEEK_DATAでファイルの末尾までデータがなかったか判定する。
+func isNoMoreData(err error) bool {
+	return errors.Is(err, syscall.ENXIO)
+}
@@ -0,0 +1,21 @@
+// +build !linux,!darwin
+
+package bcbc
+
+import "os"
+
+// lseekのwhence。使わない。
+const (
+	seekData = 3
+	seekHole = 4
+)
+
+// その他のOSでは割り当てサイズを調べないので、疎なファイルを検出しない。
+func allocatedSize(stat os.FileInfo) (int64, bool) {
+	return 0, false
+}
+
+// SEEK_DATAでファイルの末尾までデータがなかったか判定する。
+func isNoMoreData(err error) bool {
+	return false
+}
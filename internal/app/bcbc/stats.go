@@ -0,0 +1,148 @@
+package bcbc
+
+import (
+	"path"
+	"sort"
+	"sync"
+	"time"
+)
+
+// スループットを比べるファイルとディレクトリの最小のサイズ。
+// 小さいファイルは開く時間などが大半を占めてスループットが当てにならないので比べない。
+const slowStatsMinSize = 1 << 20
+
+// 1つのファイルまたはディレクトリのハッシュ計算にかかった時間
+type hashTiming struct {
+	diskId  string
+	path    string
+	bytes   uint64
+	elapsed time.Duration
+}
+
+// 1秒あたりのバイト数
+func (t hashTiming) throughput() float64 {
+	return throughput(t.bytes, t.elapsed)
+}
+
+// ファイルごとのハッシュ計算の時間の統計。
+// 全ファイルの時間は保持せず、遅いファイルは報告する数だけ、ディレクトリは合計を保持する。
+type hashStats struct {
+	mu    sync.Mutex
+	limit int
+	// スループットが遅い順のファイル
+	slowFiles []hashTiming
+	// ディスクIDとディレクトリごとの合計
+	dirs map[[2]string]*hashTiming
+	// ハッシュ計算中にサイズが変わったファイル
+	sizeChanges []sizeChange
+}
+
+// ハッシュ計算中にサイズが変わったファイル
+type sizeChange struct {
+	diskId string
+	path   string
+	before uint64
+	after  uint64
+}
+
+// ハッシュ計算の時間の統計
+var stats = newHashStats(0)
+
+// 遅い順に指定された数まで報告する統計を作成する。0なら報告しない。
+func newHashStats(limit int) *hashStats {
+	return &hashStats{limit: limit, dirs: make(map[[2]string]*hashTiming)}
+}
+
+// 1つのファイルのハッシュ計算にかかった時間を記録する。
+func (s *hashStats) recordFile(diskId string, normPath string, size uint64, elapsed time.Duration) {
+	if s.limit == 0 {
+		return
+	}
+
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	key := [2]string{diskId, path.Dir(normPath)}
+	dir, found := s.dirs[key]
+	if !found {
+		dir = &hashTiming{diskId: diskId, path: key[1]}
+		s.dirs[key] = dir
+	}
+	dir.bytes += size
+	dir.elapsed += elapsed
+
+	if size < slowStatsMinSize {
+		return
+	}
+
+	timing := hashTiming{diskId, normPath, size, elapsed}
+	i := sort.Search(len(s.slowFiles), func(i int) bool {
+		return s.slowFiles[i].throughput() > timing.throughput()
+	})
+	if i >= s.limit {
+		return
+	}
+	s.slowFiles = append(s.slowFiles, hashTiming{})
+	copy(s.slowFiles[i+1:], s.slowFiles[i:])
+	s.slowFiles[i] = timing
+	if len(s.slowFiles) > s.limit {
+		s.slowFiles = s.slowFiles[:s.limit]
+	}
+}
+
+// ハッシュ計算中にサイズが変わったファイルを記録する。
+// 書き込み中のログファイルやコピー中のファイルは記録したハッシュが最終的な内容と異なる。
+func (s *hashStats) recordSizeChange(diskId string, normPath string, before uint64, after uint64) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	s.sizeChanges = append(s.sizeChanges, sizeChange{diskId, normPath, before, after})
+}
+
+// ハッシュ計算中にサイズが変わったファイルと、スループットが遅いファイルとディレクトリを報告する。
+// 不良セクターや不安定なネットワークマウントを見つける手掛かりにする。
+func (s *hashStats) report() {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if len(s.sizeChanges) > 0 {
+		logf.Println("ハッシュ計算中にサイズが変わったファイル:")
+		for _, c := range s.sizeChanges {
+			logf.Printf("  %10s -> %10s %s: %s\n", formatSize(float64(c.before)), formatSize(float64(c.after)), c.diskId, c.path)
+		}
+	}
+
+	if s.limit == 0 {
+		return
+	}
+
+	var slowDirs []hashTiming
+	for _, dir := range s.dirs {
+		if dir.bytes >= slowStatsMinSize {
+			slowDirs = append(slowDirs, *dir)
+		}
+	}
+	sort.Slice(slowDirs, func(i, j int) bool {
+		return slowDirs[i].throughput() < slowDirs[j].throughput()
+	})
+	if len(slowDirs) > s.limit {
+		slowDirs = slowDirs[:s.limit]
+	}
+
+	if len(s.slowFiles) > 0 {
+		logf.Println("スループットが遅かったファイル:")
+		printTimings(s.slowFiles)
+	}
+	if len(slowDirs) > 0 {
+		logf.Println("スループットが遅かったディレクトリ:")
+		printTimings(slowDirs)
+	}
+}
+
+// ハッシュ計算にかかった時間を1行ずつ表示する。
+func printTimings(timings []hashTiming) {
+	for _, t := range timings {
+		logf.Printf("  %s %10s %10s %s: %s\n", formatThroughput(t.throughput()), formatSize(float64(t.bytes)),
+			t.elapsed.Round(time.Millisecond), t.diskId, t.path)
+	}
+}
@@ -0,0 +1,90 @@
+package bcbc
+
+import (
+	"flag"
+	"sort"
+	"strings"
+)
+
+// 統合元のハッシュファイルごとに「ディスクID:ダイジェスト」の一覧を作成する。
+func digestHashFiles(store HashStore, diskIds []string) string {
+	digests := make([]string, 0, len(diskIds))
+
+	for _, diskId := range diskIds {
+		digest, err := store.digest(diskId)
+		fatalMessageError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s\n", diskId)
+		digests = append(digests, diskId+":"+digest)
+	}
+
+	sort.Strings(digests)
+
+	return strings.Join(digests, "\n") + "\n"
+}
+
+// 統合元のハッシュファイルのダイジェストを記録する。
+func writeMergeSources(store HashStore, group string, diskIds []string) {
+	err := store.saveMergeSources(group, digestHashFiles(store, diskIds))
+	fatalMessageError(ErrIO, err, "統合元の記録の書き込みに失敗しました。: %s\n", group)
+}
+
+// 統合ハッシュファイルが統合元のハッシュファイルより古くなっているか判定する。
+func isStaleGroup(store HashStore, group string, diskIds []string) bool {
+	recorded, err := store.loadMergeSources(group)
+	if err != nil || recorded == "" {
+		return true
+	}
+
+	return recorded != digestHashFiles(store, diskIds)
+}
+
+// 統合ハッシュファイルが古くなっているグループの一覧を作成する。
+func findStaleGroups(store HashStore) []string {
+	var staleGroups []string
+
+	for group, diskIds := range groupDiskIds(store) {
+		if isStaleGroup(store, group, diskIds) {
+			staleGroups = append(staleGroups, group)
+		}
+	}
+
+	sort.Strings(staleGroups)
+
+	return staleGroups
+}
+
+// 古くなっている統合ハッシュファイルがあれば警告する。
+func warnStaleGroups() {
+	for _, algorithm := range config.algorithms {
+		for _, group := range findStaleGroups(hashStoreFor(algorithm)) {
+			logf.Printf("統合ハッシュファイルが統合元のハッシュファイルより古くなっています。: %s (%s)\n", group, algorithm)
+		}
+	}
+}
+
+// statusサブコマンド。
+// 統合ハッシュファイルが最新か確認し、オプションが指定されていれば古いものを統合し直す。
+func executeStatus(args []string) {
+	flagSet := flag.NewFlagSet("status", flag.ExitOnError)
+	autoMerge := flagSet.Bool("auto-merge", false, "古くなっている統合ハッシュファイルを統合し直す。")
+	flagSet.Parse(args)
+
+	for _, algorithm := range config.algorithms {
+		store := hashStoreFor(algorithm)
+		groups := groupDiskIds(store)
+
+		staleGroups := findStaleGroups(store)
+		if len(staleGroups) == 0 {
+			logf.Printf("統合ハッシュファイルはすべて最新です。(%s)\n", algorithm)
+			continue
+		}
+
+		for _, group := range staleGroups {
+			if *autoMerge {
+				logf.Printf("統合ハッシュファイルを統合し直します。: %s (%s)\n", group, algorithm)
+				mergeHashFiles(store, group, groups[group])
+			} else {
+				logf.Printf("統合ハッシュファイルが古くなっています。: %s (%s)\n", group, algorithm)
+			}
+		}
+	}
+}
@@ -0,0 +1,71 @@
+package bcbc
+
+import (
+	"os"
+	"strings"
+	"sync"
+	"time"
+)
+
+// 詳しい進捗の表示の要求。進捗監視ルーチンが受け取って表示する。
+var statusRequests = make(chan struct{}, 1)
+
+// 詳しい進捗の表示の要求の監視を1回だけ開始するため
+var statusKeyOnce sync.Once
+
+// 詳しい進捗の表示を要求する。既に要求されていれば何もしない。
+func requestStatus() {
+	select {
+	case statusRequests <- struct{}{}:
+	default:
+	}
+}
+
+// 詳しい進捗の表示の要求を監視する。
+// 標準入力が端末ならsとEnterキーで、BSDとmacOSならSIGINFO(Ctrl+T)でも要求できる。
+// -confirmのときは確認の回答と混ざらないように標準入力は読まない。
+func watchStatusKey() {
+	statusKeyOnce.Do(func() {
+		watchStatusSignal()
+
+		stat, err := os.Stdin.Stat()
+		if err != nil || stat.Mode()&os.ModeCharDevice == 0 || *confirmOption {
+			return
+		}
+
+		go func() {
+			for {
+				line, err := confirmReader.ReadString('\n')
+				if strings.TrimSpace(line) == "s" {
+					requestStatus()
+				}
+				if err != nil {
+					return
+				}
+			}
+		}()
+	})
+}
+
+// ディスクごとに処理中のファイル、読み込んだバイト数、残り時間、エラーの数を表示する。
+func printStatusDump(progressInfoList []ProgressInfo, estimators []throughputEstimator) {
+	logf.Println("進捗の詳細:")
+	for i, pi := range progressInfoList {
+		if pi.diskInfo == nil {
+			continue
+		}
+
+		fc := pi.fileCount
+		sc := pi.sizeCount
+		fb := pi.fileBytes
+		logf.Printf("  %s: 経過 %s\n", pi.diskInfo.id, time.Since(pi.startTime).Round(time.Second))
+		logf.Printf("    ファイル   %s/%s (エラー %d)\n", formatCount(fc.processed), formatCount(fc.total), pi.fileErrors)
+		logf.Printf("    サイズ     %s/%s %.2f%% %s\n", formatSize(float64(sc.processed)), formatSize(float64(sc.total)),
+			sc.ProgressRate()*100, formatThroughput(estimators[i].rate))
+		logf.Printf("    残り時間   %s\n", strings.TrimSpace(formatRemainTime(estimators[i].remainTime(sc))))
+		if pi.processingFile != "" {
+			logf.Printf("    処理中     %s (%s/%s 残り%s)\n", pi.processingFile, formatSize(float64(fb.processed)), formatSize(float64(fb.total)),
+				strings.TrimSpace(formatRemainTime(estimators[i].remainTime(fb))))
+		}
+	}
+}
@@ -0,0 +1,21 @@
+// +build darwin freebsd netbsd openbsd dragonfly
+
+package bcbc
+
+import (
+	"os"
+	"os/signal"
+	"syscall"
+)
+
+// SIGINFO(Ctrl+T)で詳しい進捗の表示を要求する。
+func watchStatusSignal() {
+	signalChannel := make(chan os.Signal, 1)
+	signal.Notify(signalChannel, syscall.SIGINFO)
+
+	go func() {
+		for range signalChannel {
+			requestStatus()
+		}
+	}()
+}
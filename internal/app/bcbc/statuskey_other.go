@@ -0,0 +1,7 @@
+// +build !darwin,!freebsd,!netbsd,!openbsd,!dragonfly
+
+package bcbc
+
+// SIGINFOがない環境ではキー入力だけで要求する。
+func watchStatusSignal() {
+}
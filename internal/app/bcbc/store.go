@@ -0,0 +1,691 @@
+package bcbc
+
+import (
+	"bufio"
+	"bytes"
+	"compress/gzip"
+	"crypto/md5"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"regexp"
+	"sort"
+	"strings"
+	"time"
+)
+
+// HashRecord ハッシュファイルの1件分の記録
+type HashRecord struct {
+	path string
+	hash string
+	// ファイルサイズ。記録していなければ-1。
+	size int64
+	// 疎なファイルのディスク上の割り当てサイズ。疎なファイルでなければ-1。
+	allocated int64
+	// ファイルのメタデータ。記録していなければnil。
+	metadata *fileMetadata
+	// 拡張属性のダイジェスト。記録していなければ空。
+	xattrs string
+}
+
+// サイズを記録していない記録を作成する。
+func newHashRecord(path string, hash string) HashRecord {
+	return HashRecord{path, hash, -1, -1, nil, ""}
+}
+
+// 「パス:ハッシュ」の形式にする。
+// v1のハッシュファイルの1行の形式で、ログの表示や記録の比較に使う。
+func (r HashRecord) String() string {
+	return r.path + ":" + r.hash
+}
+
+// HashStore ハッシュファイルの保存先
+// アルゴリズムごとに1つ作成する。
+type HashStore interface {
+	// ハッシュファイルがあるディスクIDの一覧を返す。
+	diskIds() ([]string, error)
+	// ディスクのハッシュファイルをジャーナルの記録を反映して読み込む。ハッシュファイルがなければ空を返す。
+	load(diskId string) ([]HashRecord, error)
+	// ディスクのハッシュファイルを形式が不正な行を読み飛ばして読み込み、読み飛ばした行番号も返す。
+	loadLenient(diskId string) ([]HashRecord, []int, error)
+	// ハッシュファイルか統合ハッシュファイルのヘッダーの情報を読み込む。
+	loadInfo(name string) (HashFileInfo, error)
+	// ディスクのハッシュファイルをパスの順に書き直す。記録は全て取り込まれたのでジャーナルは削除する。
+	rewrite(diskId string, records []HashRecord) error
+	// ディスクのジャーナルに追記する準備をする。
+	openAppender(diskId string) (HashAppender, error)
+	// ディスクのジャーナルの記録をハッシュファイルに取り込む。ジャーナルがなければ何もしない。
+	foldJournal(diskId string) error
+	// 統合ハッシュファイルを書き込む。
+	// nextが返す記録を順に書き込み、全て書き込めたら統合ハッシュファイルを置き換える。
+	writeMerged(group string, next func() (HashRecord, bool, error)) error
+	// ディスクのハッシュファイルの内容のダイジェストを返す。
+	digest(diskId string) (string, error)
+	// ディスクのハッシュファイルを読み込まずに、変わったか判定するための目印を返す。
+	// 目印が変わっていなければ内容も変わっていないとみなせる。目印が変わっても内容は同じことがある。
+	stamp(diskId string) (string, error)
+	// 統合ハッシュファイルの統合元の記録を読み込む。記録がなければ空文字列を返す。
+	loadMergeSources(group string) (string, error)
+	// 統合ハッシュファイルの統合元の記録を書き込む。
+	saveMergeSources(group string, sources string) error
+	// ディスクのハッシュファイルの現在の状態を日時の名前のスナップショットとして保存し、その名前を返す。
+	snapshot(diskId string) (string, error)
+	// ディスクのスナップショットの名前を古い順に返す。
+	snapshots(diskId string) ([]string, error)
+	// ディスクのスナップショットを読み込む。
+	loadSnapshot(diskId string, name string) ([]HashRecord, error)
+}
+
+// HashAppender ハッシュファイルへの追記
+type HashAppender interface {
+	append(record HashRecord) error
+	close() error
+}
+
+// StoreFile ハッシュファイルの保存先: 出力ディレクトリのテキストファイル
+const StoreFile = "file"
+
+// StoreWebDAV ハッシュファイルの保存先: WebDAVサーバー
+const StoreWebDAV = "webdav"
+
+// StoreS3 ハッシュファイルの保存先: S3互換のオブジェクトストレージ
+const StoreS3 = "s3"
+
+// StoreSFTP ハッシュファイルの保存先: sshで接続できるマシン
+const StoreSFTP = "sftp"
+
+// StoreServer ハッシュファイルの保存先: bcbcの収集サーバー
+const StoreServer = "server"
+
+// 保存先の種類ごとの作成関数
+var hashStoreFactories = map[string]func(algorithm string) HashStore{
+	StoreFile:   newFileHashStore,
+	StoreWebDAV: newWebDAVHashStore,
+	StoreS3:     newS3HashStore,
+	StoreSFTP:   newSFTPHashStore,
+	StoreServer: newServerHashStore,
+}
+
+// 設定された保存先のアルゴリズムのハッシュファイルの保存先を返す。
+func hashStoreFor(algorithm string) HashStore {
+	return hashStoreFactories[config.store](algorithm)
+}
+
+// ディスクIDのパターン
+var diskIdPattern = regexp.MustCompile("^[A-Z]+\\d+$")
+
+// ハッシュファイルの内容を読み込む。
+func readHashRecords(in io.Reader, name string) ([]HashRecord, error) {
+	records := make([]HashRecord, 0, 1024)
+
+	reader := newHashRecordReader(in, name)
+	for {
+		record, found, err := reader.next()
+		if err != nil {
+			return nil, err
+		}
+		if !found {
+			break
+		}
+		records = append(records, record)
+	}
+
+	return records, nil
+}
+
+// ハッシュファイルを1件ずつ読み込む。
+type hashRecordReader struct {
+	reader *bufio.Reader
+	name   string
+	// 読み込んだ行数
+	line int
+	// ヘッダーの情報。ヘッダー行がなければv1。
+	info HashFileInfo
+	// 形式が不正な行をエラーにせずに読み飛ばすか
+	lenient bool
+	// 読み飛ばした形式が不正な行の行番号
+	malformed []int
+}
+
+func newHashRecordReader(in io.Reader, name string) *hashRecordReader {
+	return &hashRecordReader{reader: bufio.NewReader(in), name: name, info: HashFileInfo{Version: 1}}
+}
+
+// 次の記録を読み込む。最後まで読み込んだらfalseを返す。
+// 追記中に中断されて改行で終わっていない最後の行は、途中までしか書き込まれていないので読み飛ばす。
+func (r *hashRecordReader) next() (HashRecord, bool, error) {
+	for {
+		line, err := r.reader.ReadString('\n')
+		r.line++
+		if err == io.EOF {
+			if line != "" {
+				logf.Printf("ハッシュファイルの最後の行が途中で切れているので読み飛ばします。: %s : %d行目\n", r.name, r.line)
+			}
+			return HashRecord{}, false, nil
+		}
+		if err != nil {
+			return HashRecord{}, false, newError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s", r.name)
+		}
+
+		line = strings.TrimSuffix(line, "\n")
+		if line == "" {
+			continue
+		}
+
+		if r.line == 1 {
+			if version, found := parseHashFileHeader(line); found {
+				if version > hashFileVersion {
+					return HashRecord{}, false, newError(ErrHashFileFormat, nil, "対応していないバージョンのハッシュファイルです。: %s : v%d", r.name, version)
+				}
+				r.info.Version = version
+				continue
+			}
+		}
+
+		// v2以降の「#」で始まる行はヘッダー
+		if r.info.Version >= 2 && line[0] == '#' {
+			parseHeaderLine(line, &r.info)
+			// チャンクのサイズが異なるツリーハッシュは同じ内容でも値が異なるので比べられない
+			if r.info.TreeChunkSize != 0 && r.info.TreeChunkSize != TreeChunkSize {
+				return HashRecord{}, false, newError(ErrHashFileFormat, nil, "ツリーハッシュのチャンクのサイズが異なるハッシュファイルです。: %s : %d", r.name, r.info.TreeChunkSize)
+			}
+			continue
+		}
+
+		// 暗号化したハッシュファイルは記録の行を復号してから解析する
+		if r.info.Encryption != "" {
+			plain, err := openHashLine(line)
+			if err != nil && r.lenient {
+				r.malformed = append(r.malformed, r.line)
+				continue
+			}
+			if err != nil {
+				return HashRecord{}, false, newError(ErrHashFileFormat, err, "ハッシュファイルの記録を復号できませんでした。: %s : %d行目", r.name, r.line)
+			}
+			line = strings.TrimSuffix(plain, "\n")
+		}
+
+		record, ok := parseHashLine(line, r.info.Version)
+		if !ok && r.lenient {
+			r.malformed = append(r.malformed, r.line)
+			continue
+		}
+		if !ok {
+			return HashRecord{}, false, newError(ErrHashFileFormat, nil, "ハッシュファイルが破損しています。: %s : %d行目", r.name, r.line)
+		}
+
+		return record, true, nil
+	}
+}
+
+// 記録を順に取り出しながらハッシュファイルに書き込む。
+// ヘッダーに暗号方式があれば記録の行を暗号化する。
+func writeRecordStream(out io.Writer, info HashFileInfo, next func() (HashRecord, bool, error)) error {
+	writer := bufio.NewWriter(out)
+	if _, err := writer.WriteString(hashFileHeader(info)); err != nil {
+		return err
+	}
+	for {
+		record, found, err := next()
+		if err != nil {
+			return err
+		}
+		if !found {
+			break
+		}
+		line := formatHashLine(record)
+		if info.Encryption != "" {
+			if line, err = sealHashLine(line); err != nil {
+				return err
+			}
+		}
+		if _, err := writer.WriteString(line); err != nil {
+			return err
+		}
+	}
+	return writer.Flush()
+}
+
+// スライスの記録を順に取り出す関数を返す。
+func recordIterator(records []HashRecord) func() (HashRecord, bool, error) {
+	i := 0
+	return func() (HashRecord, bool, error) {
+		if i >= len(records) {
+			return HashRecord{}, false, nil
+		}
+		i++
+		return records[i-1], true, nil
+	}
+}
+
+// ハッシュファイルの内容を書き込む。
+// 一時ファイルにも平文が残らないように、暗号化する設定なら暗号化する。
+func writeHashRecords(out io.Writer, records []HashRecord) error {
+	return writeRecordStream(out, HashFileInfo{Encryption: currentEncryption()}, recordIterator(records))
+}
+
+// テキストファイルのハッシュファイルの保存先
+type fileHashStore struct {
+	dir       string
+	algorithm string
+}
+
+func newFileHashStore(algorithm string) HashStore {
+	return &fileHashStore{config.hashDir(algorithm), algorithm}
+}
+
+func (s *fileHashStore) file(name string) string {
+	return filepath.Join(s.dir, name)
+}
+
+func (s *fileHashStore) diskIds() ([]string, error) {
+	files, err := filepath.Glob(filepath.Join(s.dir, "*"))
+	if err != nil {
+		return nil, newError(ErrIO, err, "出力ファイルの一覧取得に失敗しました。")
+	}
+
+	fileNames := make([]string, 0, len(files))
+	for _, file := range files {
+		fileNames = append(fileNames, filepath.Base(file))
+	}
+
+	return hashFileDiskIds(fileNames), nil
+}
+
+// 保存先のファイル名からハッシュファイルがあるディスクIDを取り出して並べる。
+func hashFileDiskIds(fileNames []string) []string {
+	found := make(map[string]bool, len(fileNames))
+	ids := make([]string, 0, len(fileNames))
+	for _, fileName := range fileNames {
+		// 圧縮したハッシュファイルとジャーナルは同じディスクのものなので、重複させない
+		name := strings.TrimSuffix(strings.TrimSuffix(fileName, compressedExtension), journalExtension)
+		if diskIdPattern.MatchString(name) && !found[name] {
+			found[name] = true
+			ids = append(ids, name)
+		}
+	}
+
+	sort.Strings(ids)
+
+	return ids
+}
+
+func (s *fileHashStore) load(diskId string) ([]HashRecord, error) {
+	records, err := loadHashFile(existingHashFile(s.file(diskId)))
+	if err != nil {
+		return nil, err
+	}
+	journal, err := loadHashFile(s.journalFile(diskId))
+	if err != nil {
+		return nil, err
+	}
+	return applyJournal(records, journal), nil
+}
+
+// ハッシュファイルを読み込む。ファイルがなければ空を返す。
+func loadHashFile(file string) ([]HashRecord, error) {
+	hashFileIn, err := openHashFile(file)
+	if os.IsNotExist(err) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, newError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s", file)
+	}
+	defer hashFileIn.Close()
+
+	return readHashRecords(hashFileIn, file)
+}
+
+// 不正な行の行番号はハッシュファイルのものを返す。ジャーナルの不正な行は読み飛ばすだけにする。
+func (s *fileHashStore) loadLenient(diskId string) ([]HashRecord, []int, error) {
+	file := existingHashFile(s.file(diskId))
+	records, malformed, err := loadHashFileLenient(file)
+	if os.IsNotExist(err) && s.hasJournal(diskId) {
+		err = nil
+	}
+	if err != nil {
+		return nil, nil, newError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s", file)
+	}
+
+	journal, _, err := loadHashFileLenient(s.journalFile(diskId))
+	if err != nil && !os.IsNotExist(err) {
+		return nil, nil, newError(ErrIO, err, "ジャーナルの読み込みに失敗しました。: %s", s.journalFile(diskId))
+	}
+
+	return applyJournal(records, journal), malformed, nil
+}
+
+// ハッシュファイルを形式が不正な行を読み飛ばして読み込み、読み飛ばした行番号も返す。
+func loadHashFileLenient(file string) ([]HashRecord, []int, error) {
+	hashFileIn, err := openHashFile(file)
+	if err != nil {
+		return nil, nil, err
+	}
+	defer hashFileIn.Close()
+
+	reader := newHashRecordReader(hashFileIn, file)
+	reader.lenient = true
+
+	var records []HashRecord
+	for {
+		record, found, err := reader.next()
+		if err != nil {
+			return nil, nil, err
+		}
+		if !found {
+			break
+		}
+		records = append(records, record)
+	}
+
+	return records, reader.malformed, nil
+}
+
+func (s *fileHashStore) loadInfo(name string) (HashFileInfo, error) {
+	file := existingHashFile(s.file(name))
+	// 最初のハッシュ計算が中断されていればジャーナルしかない
+	if _, err := os.Stat(file); os.IsNotExist(err) && s.hasJournal(name) {
+		file = s.journalFile(name)
+	}
+	hashFileIn, err := openHashFile(file)
+	if err != nil {
+		return HashFileInfo{}, newError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s", file)
+	}
+	defer hashFileIn.Close()
+
+	// ヘッダーは先頭にあるので最初の記録まで読めばよい
+	reader := newHashRecordReader(hashFileIn, file)
+	reader.lenient = true
+	if _, _, err := reader.next(); err != nil {
+		return HashFileInfo{}, err
+	}
+
+	return reader.info, nil
+}
+
+// 書き直すハッシュファイルのヘッダーの情報を作成する。
+// 作成日時は元のハッシュファイルから引き継ぎ、書き込み元はこのプロセスにする。
+func (s *fileHashStore) newInfo(name string) HashFileInfo {
+	now := time.Now()
+	info := HashFileInfo{
+		Version:    hashFileVersion,
+		Algorithm:  s.algorithm,
+		Created:    now,
+		Updated:    now,
+		Tool:       "bcbc " + Version,
+		Encryption: currentEncryption(),
+		Writer:     writerId(),
+	}
+	if isTreeAlgorithm(s.algorithm) {
+		info.TreeChunkSize = TreeChunkSize
+	}
+
+	if previous, err := s.loadInfo(name); err == nil && !previous.Created.IsZero() {
+		info.Created = previous.Created
+	}
+
+	return info
+}
+
+func (s *fileHashStore) rewrite(diskId string, records []HashRecord) error {
+	sorted := make([]HashRecord, len(records))
+	copy(sorted, records)
+	sort.SliceStable(sorted, func(i, j int) bool {
+		return sorted[i].path < sorted[j].path
+	})
+
+	info := s.newInfo(diskId)
+	info.DiskId = diskId
+	if err := s.replaceFile(diskId, info, recordIterator(sorted)); err != nil {
+		return err
+	}
+
+	if err := os.Remove(s.journalFile(diskId)); err != nil && !os.IsNotExist(err) {
+		return newError(ErrIO, err, "ジャーナルを削除できませんでした。: %s", s.journalFile(diskId))
+	}
+	return nil
+}
+
+func (s *fileHashStore) writeMerged(group string, next func() (HashRecord, bool, error)) error {
+	info := s.newInfo(group)
+	info.Group = group
+	return s.replaceFile(group, info, next)
+}
+
+// ハッシュファイルを書き直す。
+// 書き込み中に中断されても元のファイルが残るように、一時ファイルに書き込んでから置き換える。
+// 圧縮する設定なら拡張子.gzのファイルに圧縮して書き込み、もう一方の形式のファイルは削除する。
+func (s *fileHashStore) replaceFile(name string, info HashFileInfo, next func() (HashRecord, bool, error)) error {
+	if err := os.MkdirAll(s.dir, 0755); err != nil {
+		return newError(ErrIO, err, "出力ディレクトリを作成できませんでした。: %s", s.dir)
+	}
+
+	file := s.file(name) + compressedSuffix()
+	tempFile := s.file(name + ".tmp")
+	out, err := os.OpenFile(tempFile, os.O_CREATE|os.O_WRONLY|os.O_TRUNC, 0644)
+	if err != nil {
+		return newError(ErrIO, err, "ハッシュファイルの作成に失敗しました。: %s", tempFile)
+	}
+
+	if config.compress == CompressGzip {
+		gzipOut := gzip.NewWriter(out)
+		err = writeRecordStream(gzipOut, info, next)
+		if closeErr := gzipOut.Close(); err == nil {
+			err = closeErr
+		}
+	} else {
+		err = writeRecordStream(out, info, next)
+	}
+	if closeErr := out.Close(); err == nil {
+		err = closeErr
+	}
+	if err == nil {
+		err = os.Rename(tempFile, file)
+	}
+	if err != nil {
+		os.Remove(tempFile)
+		return newError(ErrIO, err, "ハッシュファイルの書き込みに失敗しました。: %s", file)
+	}
+
+	if file == s.file(name) {
+		removeHashFile(s.file(name) + compressedExtension)
+	} else {
+		removeHashFile(s.file(name))
+	}
+
+	if err := signFile(file); err != nil {
+		return newError(ErrIO, err, "ハッシュファイルの署名に失敗しました。: %s", file)
+	}
+
+	return nil
+}
+
+// ハッシュファイルは書き直すときまで変更せずに、記録はジャーナルに追記する。
+func (s *fileHashStore) openAppender(diskId string) (HashAppender, error) {
+	return s.openAppenderWith(s, diskId)
+}
+
+// ジャーナルに追記する準備をする。
+// 前回のジャーナルを取り込むときは、保存先に送るストアでも取り込んだハッシュファイルを送るようにouterで取り込んで開き直す。
+func (s *fileHashStore) openAppenderWith(outer HashStore, diskId string) (HashAppender, error) {
+	if err := os.MkdirAll(s.dir, 0755); err != nil {
+		return nil, newError(ErrIO, err, "出力ディレクトリを作成できませんでした。: %s", s.dir)
+	}
+
+	journal := s.journalFile(diskId)
+	out, err := os.OpenFile(journal, os.O_CREATE|os.O_APPEND|os.O_RDWR, 0644)
+	if err != nil {
+		return nil, newError(ErrIO, err, "ジャーナルの書き込みに失敗しました。: %s", journal)
+	}
+
+	if err := truncateTornTail(out); err != nil {
+		out.Close()
+		return nil, newError(ErrIO, err, "ジャーナルの修復に失敗しました。: %s", journal)
+	}
+
+	version, err := hashFileVersionOf(out)
+	if err != nil {
+		out.Close()
+		return nil, newError(ErrIO, err, "ジャーナルの読み込みに失敗しました。: %s", journal)
+	}
+
+	if version == 0 {
+		// 新しいジャーナルにはハッシュファイルと同じヘッダー行を書いてから追記する
+		info := s.newInfo(diskId)
+		info.DiskId = diskId
+		if err := writeHashLine(out, hashFileHeader(info)); err != nil {
+			out.Close()
+			return nil, newError(ErrIO, err, "ジャーナルの書き込みに失敗しました。: %s", journal)
+		}
+		return &fileHashAppender{out, currentEncryption() != ""}, nil
+	}
+
+	// 前回中断したジャーナルの形式か暗号化の設定が違えば、混在しないようにハッシュファイルに取り込んでから開き直す
+	info, err := s.loadInfo(diskId + journalExtension)
+	if err != nil {
+		out.Close()
+		return nil, err
+	}
+	if version < hashFileVersion || info.Encryption != currentEncryption() {
+		out.Close()
+		logf.Printf("前回のジャーナルをハッシュファイルに取り込みます。: %s\n", journal)
+		if err := outer.foldJournal(diskId); err != nil {
+			return nil, err
+		}
+		return outer.openAppender(diskId)
+	}
+
+	return &fileHashAppender{out, currentEncryption() != ""}, nil
+}
+
+// 前回の追記が中断されて途中で切れている最後の行を削除する。
+// 改行は1行ごとに最後に書き込むので、最後の改行までが正しく書き込めた範囲になる。
+func truncateTornTail(file *os.File) error {
+	stat, err := file.Stat()
+	if err != nil {
+		return err
+	}
+
+	buffer := make([]byte, 64<<10)
+
+	// 後ろから最後の改行を探す
+	end := stat.Size()
+	for end > 0 {
+		start := end - int64(len(buffer))
+		if start < 0 {
+			start = 0
+		}
+		chunk := buffer[:end-start]
+		if _, err := file.ReadAt(chunk, start); err != nil {
+			return err
+		}
+
+		if i := bytes.LastIndexByte(chunk, '\n'); i >= 0 {
+			end = start + int64(i) + 1
+			break
+		}
+		end = start
+	}
+
+	if end == stat.Size() {
+		return nil
+	}
+
+	logf.Printf("ハッシュファイルの途中で切れている最後の行を削除します。: %s\n", file.Name())
+	return file.Truncate(end)
+}
+
+// ハッシュファイルとジャーナルのサイズと更新日時を目印にする。
+func (s *fileHashStore) stamp(diskId string) (string, error) {
+	stamps := make([]string, 0, 2)
+	for _, file := range []string{existingHashFile(s.file(diskId)), s.journalFile(diskId)} {
+		stat, err := os.Stat(file)
+		if os.IsNotExist(err) {
+			stamps = append(stamps, missingStamp)
+			continue
+		}
+		if err != nil {
+			return "", newError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s", file)
+		}
+		stamps = append(stamps, fmt.Sprintf("%d %d", stat.Size(), stat.ModTime().UnixNano()))
+	}
+	return strings.Join(stamps, " "), nil
+}
+
+// 圧縮したかどうかで変わらないように、展開した内容のダイジェストを返す。
+func (s *fileHashStore) digest(diskId string) (string, error) {
+	hasher := md5.New()
+
+	// 最初のハッシュ計算が中断されていればジャーナルしかない
+	file := existingHashFile(s.file(diskId))
+	if _, err := os.Stat(file); err == nil || !s.hasJournal(diskId) {
+		hashFileIn, err := openHashFile(file)
+		if err != nil {
+			return "", newError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s", file)
+		}
+		defer hashFileIn.Close()
+
+		if _, err := io.Copy(hasher, hashFileIn); err != nil {
+			return "", newError(ErrIO, err, "ハッシュファイルの読み込みに失敗しました。: %s", file)
+		}
+	}
+
+	// ジャーナルに追記されても変わるように、ジャーナルの内容も含める
+	journalIn, err := os.Open(s.journalFile(diskId))
+	if err == nil {
+		defer journalIn.Close()
+		if _, err := io.Copy(hasher, journalIn); err != nil {
+			return "", newError(ErrIO, err, "ジャーナルの読み込みに失敗しました。: %s", s.journalFile(diskId))
+		}
+	}
+
+	return fmt.Sprintf("%x", hasher.Sum(nil)), nil
+}
+
+func (s *fileHashStore) loadMergeSources(group string) (string, error) {
+	data, err := os.ReadFile(s.file(group + ".sources"))
+	if os.IsNotExist(err) {
+		return "", nil
+	}
+	if err != nil {
+		return "", newError(ErrIO, err, "統合元の記録ファイルの読み込みに失敗しました。: %s", s.file(group+".sources"))
+	}
+	return string(data), nil
+}
+
+func (s *fileHashStore) saveMergeSources(group string, sources string) error {
+	err := os.WriteFile(s.file(group+".sources"), []byte(sources), 0644)
+	if err != nil {
+		return newError(ErrIO, err, "統合元の記録ファイルの書き込みに失敗しました。: %s", s.file(group+".sources"))
+	}
+	return nil
+}
+
+// テキストファイルのハッシュファイルへの追記
+type fileHashAppender struct {
+	out *os.File
+	// 記録の行を暗号化するか
+	encrypted bool
+}
+
+func (a *fileHashAppender) append(record HashRecord) error {
+	line := formatHashLine(record)
+	if a.encrypted {
+		var err error
+		if line, err = sealHashLine(line); err != nil {
+			return err
+		}
+	}
+	return writeHashLine(a.out, line)
+}
+
+// 書き直す前に閉じても、後で閉じ直せるように2回目は何もしない。
+func (a *fileHashAppender) close() error {
+	if a.out == nil {
+		return nil
+	}
+	err := a.out.Close()
+	a.out = nil
+	return err
+}
@@ -0,0 +1,158 @@
+package bcbc
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+	"time"
+)
+
+func TestHashRecordRoundTrip(t *testing.T) {
+	modTime := time.Date(2021, 3, 4, 5, 6, 7, 890000000, time.UTC)
+	withSize := newHashRecord("photos/a.jpg", "d41d8cd98f00b204e9800998ecf8427e")
+	withSize.size = 1024
+	sparse := newHashRecord("vm/disk.img", "9e107d9d372bb6826bd81d3542a419d6")
+	sparse.size = 1 << 30
+	sparse.allocated = 4096
+	withMetadata := newHashRecord("docs/b.txt", "e4d909c290d0fb1ca068ffaddf22cbd0")
+	withMetadata.size = 12
+	withMetadata.metadata = &fileMetadata{modTime: modTime, mode: "-rw-r--r--", owner: "1000:1000"}
+	withXattrs := newHashRecord("docs/c.txt", "0cc175b9c0f1b6a831c399e269772661")
+	withXattrs.metadata = &fileMetadata{modTime: modTime, mode: "-rw-------"}
+	withXattrs.xattrs = "5d41402abc4b2a76b9719d911017c592"
+
+	tests := []struct {
+		name   string
+		record HashRecord
+	}{
+		{"plain", newHashRecord("a.txt", "d41d8cd98f00b204e9800998ecf8427e")},
+		{"tab", newHashRecord("a\tb.txt", "d41d8cd98f00b204e9800998ecf8427e")},
+		{"newline", newHashRecord("a\nb\r.txt", "d41d8cd98f00b204e9800998ecf8427e")},
+		{"backslash", newHashRecord(`dir\a\tb.txt`, "d41d8cd98f00b204e9800998ecf8427e")},
+		{"header-like", newHashRecord("# bcbc-hashfile 3", "d41d8cd98f00b204e9800998ecf8427e")},
+		{"colon", newHashRecord("a:b.txt", "d41d8cd98f00b204e9800998ecf8427e")},
+		{"size", withSize},
+		{"allocated", sparse},
+		{"metadata", withMetadata},
+		{"xattrs", withXattrs},
+		{"incomplete", newHashRecord("bad.bin", "d41d8cd98f00b204e9800998ecf8427e"+incompleteHashSuffix)},
+	}
+
+	for _, test := range tests {
+		t.Run(test.name, func(t *testing.T) {
+			info := HashFileInfo{Algorithm: AlgorithmMD5, DiskId: "A1", Tool: "bcbc test", Writer: "host pid=1"}
+			var out bytes.Buffer
+			if err := writeRecordStream(&out, info, recordIterator([]HashRecord{test.record})); err != nil {
+				t.Fatalf("writeRecordStream: %v", err)
+			}
+
+			reader := newHashRecordReader(&out, test.name)
+			got, found, err := reader.next()
+			if err != nil || !found {
+				t.Fatalf("next = %v, %v, want a record\n%s", found, err, out.String())
+			}
+			if !sameHashRecord(got, test.record) {
+				t.Errorf("read %+v, want %+v", got, test.record)
+			}
+			if _, found, err := reader.next(); found || err != nil {
+				t.Errorf("next after the last record = %v, %v, want false, nil", found, err)
+			}
+
+			if reader.info.Version != hashFileVersion || reader.info.Algorithm != info.Algorithm ||
+				reader.info.DiskId != info.DiskId || reader.info.Tool != info.Tool || reader.info.Writer != info.Writer {
+				t.Errorf("header = %+v, want %+v", reader.info, info)
+			}
+		})
+	}
+}
+
+func TestHashRecordReaderTornTail(t *testing.T) {
+	header := hashFileHeader(HashFileInfo{Algorithm: AlgorithmMD5, DiskId: "A1"})
+
+	tests := []struct {
+		name    string
+		content string
+		want    []string
+	}{
+		{"complete", header + "a.txt\td41d\tsize=1\nb.txt\t0cc1\n", []string{"a.txt", "b.txt"}},
+		{"torn record", header + "a.txt\td41d\tsize=1\nb.txt\t0c", []string{"a.txt"}},
+		{"torn attribute", header + "a.txt\td41d\nb.txt\t0cc1\tsize=", []string{"a.txt"}},
+		{"torn header", "# bcbc-hash", nil},
+		{"v1 torn", "a.txt:d41d\nb.txt:0c", []string{"a.txt"}},
+		{"v1 complete", "a.txt:d41d\nb.txt:0cc1\n", []string{"a.txt", "b.txt"}},
+	}
+
+	for _, test := range tests {
+		t.Run(test.name, func(t *testing.T) {
+			reader := newHashRecordReader(strings.NewReader(test.content), test.name)
+			var got []string
+			for {
+				record, found, err := reader.next()
+				if err != nil {
+					t.Fatalf("next: %v", err)
+				}
+				if !found {
+					break
+				}
+				got = append(got, record.path)
+			}
+			if strings.Join(got, ",") != strings.Join(test.want, ",") {
+				t.Errorf("read %q, want %q", got, test.want)
+			}
+		})
+	}
+}
+
+func TestTruncateTornTail(t *testing.T) {
+	header := hashFileHeader(HashFileInfo{Algorithm: AlgorithmMD5, DiskId: "A1"})
+
+	tests := []struct {
+		name    string
+		content string
+		want    string
+	}{
+		{"complete", header + "a.txt\td41d\n", header + "a.txt\td41d\n"},
+		{"torn", header + "a.txt\td41d\nb.txt\t0c", header + "a.txt\td41d\n"},
+		{"only torn", "a.txt\td4", ""},
+		{"empty", "", ""},
+	}
+
+	for _, test := range tests {
+		t.Run(test.name, func(t *testing.T) {
+			journal := filepath.Join(t.TempDir(), "A1.journal")
+			if err := os.WriteFile(journal, []byte(test.content), 0644); err != nil {
+				t.Fatal(err)
+			}
+			file, err := os.OpenFile(journal, os.O_RDWR, 0644)
+			if err != nil {
+				t.Fatal(err)
+			}
+			err = truncateTornTail(file)
+			file.Close()
+			if err != nil {
+				t.Fatalf("truncateTornTail: %v", err)
+			}
+
+			got, err := os.ReadFile(journal)
+			if err != nil {
+				t.Fatal(err)
+			}
+			if string(got) != test.want {
+				t.Errorf("content = %q, want %q", got, test.want)
+			}
+		})
+	}
+}
+
+// 記録が同じか比べる。更新日時はタイムゾーンの表し方が異なっても同じ時刻なら同じとする。
+func sameHashRecord(a HashRecord, b HashRecord) bool {
+	if a.path != b.path || a.hash != b.hash || a.size != b.size || a.allocated != b.allocated || a.xattrs != b.xattrs {
+		return false
+	}
+	if a.metadata == nil || b.metadata == nil {
+		return a.metadata == b.metadata
+	}
+	return a.metadata.modTime.Equal(b.metadata.modTime) && a.metadata.mode == b.metadata.mode && a.metadata.owner == b.metadata.owner
+}
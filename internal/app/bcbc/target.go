@@ -1,13 +1,12 @@
 package bcbc
 
 import (
-	"bufio"
+	"fmt"
 	"golang.org/x/text/unicode/norm"
-	"io/fs"
-	"io/ioutil"
 	"os"
 	"path/filepath"
-	"strings"
+	"sort"
+	"sync/atomic"
 )
 
 // FileInfo ファイル情報
@@ -15,7 +14,11 @@ type FileInfo struct {
 	diskInfo *DiskInfo
 	realPath string
 	normPath string
-	_size    int64
+	// statメソッドで遅延初期化する
+	_stat os.FileInfo
+	// xattrsメソッドで遅延初期化する
+	_xattrs       []xattr
+	_xattrsLoaded bool
 }
 
 // ファイル情報を初期化する。
@@ -24,120 +27,372 @@ func (fi *FileInfo) init(diskInfo *DiskInfo, realPath string) {
 
 	fi.realPath = realPath
 
+	// Windowsでもハッシュファイルにはスラッシュ区切りで記録する
 	normPath, _ := filepath.Rel(diskInfo.rootPath, realPath)
 	normPath = filepath.ToSlash(normPath)
 	normPath = norm.NFC.String(normPath)
 	fi.normPath = normPath
 
-	// sizeメソッドで遅延初期化する
-	fi._size = -1
+	fi._stat = nil
+	fi._xattrs = nil
+	fi._xattrsLoaded = false
+}
+
+// ファイルの情報を取得する。取得できなければnilを返す。
+func (fi *FileInfo) stat() os.FileInfo {
+	if fi._stat == nil {
+		fi._stat, _ = os.Stat(fi.realPath)
+	}
+	return fi._stat
 }
 
 func (fi *FileInfo) size() (uint64, error) {
-	if fi._size != -1 {
-		return uint64(fi._size), nil
+	if stat := fi.stat(); stat != nil {
+		return uint64(stat.Size()), nil
 	}
+	return 0, nil
+}
 
-	stat, err := os.Stat(fi.realPath)
-	if err == nil {
-		fi._size = stat.Size()
-		return uint64(fi._size), nil
-	} else {
-		return 0, nil
+// ハードリンクならファイルの識別子を返す。
+func (fi *FileInfo) hardLinkId() (fileId, bool) {
+	stat := fi.stat()
+	if stat == nil {
+		return fileId{}, false
 	}
+	return hardLinkIdOf(stat)
 }
 
-// ハッシュ対象ファイルの一覧を作成する。
-func listFileInfo(diskInfo *DiskInfo) ([]FileInfo, uint64) {
+// 拡張属性を取得する。取得できなければ警告して空とする。
+func (fi *FileInfo) xattrs() []xattr {
+	if !fi._xattrsLoaded {
+		var err error
+		fi._xattrs, err = listXattrs(fi.realPath)
+		if err != nil {
+			logf.Printf("拡張属性を取得できませんでした。: %s\n", fi.realPath)
+			logf.Println(err)
+		}
+		fi._xattrsLoaded = true
+	}
+	return fi._xattrs
+}
+
+// 計算したアルゴリズムのハッシュの記録を作成する。
+// ファイルサイズも記録し、疎なファイルならディスク上の割り当てサイズも記録する。
+// メタデータモードなら更新日時、パーミッション、所有者も記録する。
+// 拡張属性モードなら拡張属性のダイジェストも記録する。
+func (fi *FileInfo) newRecord(algorithm string, hash string) HashRecord {
+	record := newHashRecord(fi.normPath, hash)
+
+	if stat := fi.stat(); stat != nil {
+		record.size = stat.Size()
+		if allocated, found := allocatedSize(stat); found && allocated < stat.Size() {
+			record.allocated = allocated
+		}
+		if config.metadata {
+			record.metadata = metadataOf(stat)
+		}
+	}
+
+	if config.xattrs {
+		record.xattrs = xattrDigest(algorithm, fi.xattrs())
+	}
 
-	hashMap := makeHashMap(diskInfo)
+	return record
+}
 
-	trimmedHashs := strings.Builder{}
+// 同じファイルへのハードリンク
+type hardLink struct {
+	normPath string
+	// 最初に見つけたハードリンクのパス
+	firstPath string
+}
 
-	files := listFiles(diskInfo.rootPath)
+// 探索したハッシュ対象ファイルを計算待ちにしておける数
+const targetQueueSize = 1024
 
-	capacity := len(files) - len(hashMap)
-	if capacity < 0 {
-		capacity = 0
+// ハッシュ対象ファイルの探索。
+// 探索しながら見つけたファイルをtargetsに送り、ハッシュルーチンは探索の終了を待たずに計算を始める。
+type targetDiscovery struct {
+	// 見つけたハッシュ対象ファイルの数とサイズの合計。探索中に増えていくのでatomicで読み書きする。
+	fileCount uint64
+	totalSize uint64
+	// 計算済みなので読み込まないファイルの数とサイズの合計。atomicで読み書きする。
+	skippedCount uint64
+	skippedSize  uint64
+
+	diskInfo *DiskInfo
+	hashMaps []map[string]HashRecord
+	targets  chan FileInfo
+	// ハッシュルーチンが計算をやめたときに閉じて探索を打ち切らせる
+	stop chan struct{}
+
+	// 以下はtargetsが閉じられてから読むこと
+	// ハッシュ計算済みなのでハッシュファイルに残す記録
+	keptRecords [][]HashRecord
+	// ディスク内に存在するファイル
+	existingPaths map[string]bool
+	// ハードリンクのファイルの識別子ごとの最初に見つけたパスと、計算済みならハッシュ
+	linkFirstPaths map[fileId]string
+	linkHashes     map[fileId][]HashRecord
+	// 最初に見つけたハードリンクのハッシュ計算が終わってから同じハッシュを記録するハードリンク
+	pendingLinks []hardLink
+	// ハッシュを使い回したハードリンクの数
+	reusedLinks int
+	// 探索中に発生した致命的なエラー
+	err error
+}
+
+// ハッシュ対象ファイルの探索を準備する。
+func newTargetDiscovery(diskInfo *DiskInfo) *targetDiscovery {
+	algorithms := diskInfo.hashAlgorithms()
+	hashMaps := make([]map[string]HashRecord, 0, len(algorithms))
+	for _, algorithm := range algorithms {
+		hashMaps = append(hashMaps, makeHashMap(diskInfo, hashStoreFor(algorithm)))
 	}
 
-	fileInfoList := make([]FileInfo, 0, capacity)
+	return &targetDiscovery{
+		diskInfo:       diskInfo,
+		hashMaps:       hashMaps,
+		targets:        make(chan FileInfo, targetQueueSize),
+		stop:           make(chan struct{}),
+		keptRecords:    make([][]HashRecord, len(algorithms)),
+		existingPaths:  make(map[string]bool),
+		linkFirstPaths: make(map[fileId]string),
+		linkHashes:     make(map[fileId][]HashRecord),
+	}
+}
 
-	var totalSize uint64
+// ディスク内を探索してハッシュ対象ファイルをtargetsに送る。
+// 探索が終わったらtargetsを閉じる。別のゴルーチンで実行する。
+func (d *targetDiscovery) run() {
+	defer close(d.targets)
+
+	// 致命的なエラーはハッシュルーチンに引き継ぐ
+	defer func() {
+		if r := recover(); r != nil {
+			e, ok := r.(*Error)
+			if !ok {
+				panic(r)
+			}
+			d.err = e
+		}
+	}()
 
 	var fileInfo FileInfo
-	for _, file := range files {
+	symlinks := listDiskFiles(d.diskInfo, func(file string) bool {
 
-		(&fileInfo).init(diskInfo, file)
+		(&fileInfo).init(d.diskInfo, file)
+		d.existingPaths[fileInfo.normPath] = true
 
-		hash, found := hashMap[fileInfo.normPath]
+		// ハードリンクは最初に見つけたパスだけ読み込んで、他のパスにはそのハッシュを記録する
+		var linkId fileId
+		linked := false
+		if *hardLinksOption {
+			linkId, linked = fileInfo.hardLinkId()
+		}
+
+		records, found := lookupHashes(d.hashMaps, fileInfo.normPath)
 		if found {
-			_, err := trimmedHashs.WriteString(fileInfo.normPath + ":" + hash + "\n")
-			fatalMessageError(err, "ハッシュファイルの書き込みに失敗しました。\n")
-			continue
+			d.keepRecords(fileInfo.normPath, records)
+			atomic.AddUint64(&d.skippedCount, 1)
+			if records[0].size > 0 {
+				atomic.AddUint64(&d.skippedSize, uint64(records[0].size))
+			}
+			if linked {
+				if _, seen := d.linkFirstPaths[linkId]; !seen {
+					d.linkFirstPaths[linkId] = fileInfo.normPath
+					d.linkHashes[linkId] = records
+				}
+			}
+			return true
+		}
+
+		filter, found := matchFilter(d.diskInfo.filterList(), fileInfo.normPath)
+		if !found || !filter.inclusion {
+			filterExplain.exclude(d.diskInfo.id, fileInfo.normPath, filter, found)
+			return true
 		}
 
-		if filterFile(fileInfo.normPath) {
-			fileInfoList = append(fileInfoList, fileInfo)
-			size, err := fileInfo.size()
-			fatalMessageError(err, "ファイルサイズの取得に失敗しました。: %s\n", fileInfo.realPath)
-			totalSize += size
+		if linked {
+			if firstPath, seen := d.linkFirstPaths[linkId]; seen {
+				d.reusedLinks++
+				if records, hashed := d.linkHashes[linkId]; hashed {
+					d.keepRecords(fileInfo.normPath, records)
+				} else {
+					d.pendingLinks = append(d.pendingLinks, hardLink{fileInfo.normPath, firstPath})
+				}
+				return true
+			}
+			d.linkFirstPaths[linkId] = fileInfo.normPath
 		}
+
+		size, err := fileInfo.size()
+		fatalMessageError(ErrIO, err, "ファイルサイズの取得に失敗しました。: %s\n", fileInfo.realPath)
+		atomic.AddUint64(&d.fileCount, 1)
+		atomic.AddUint64(&d.totalSize, size)
+
+		select {
+		case d.targets <- fileInfo:
+			return true
+		case <-d.stop:
+			return false
+		}
+	})
+	recordSymlinks(d.diskInfo, symlinks)
+}
+
+// ハッシュ計算済みの記録を指定されたパスの記録としてハッシュファイルに残す。
+func (d *targetDiscovery) keepRecords(normPath string, records []HashRecord) {
+	for i, record := range records {
+		record.path = normPath
+		d.keptRecords[i] = append(d.keptRecords[i], record)
 	}
+}
 
-	err := ioutil.WriteFile(diskInfo.hashFile(), []byte(trimmedHashs.String()), 0644)
-	fatalMessageError(err, "ハッシュファイルの作成に失敗しました。\n")
+// ここまでに見つけたハッシュ対象ファイルと計算済みのファイルの数とサイズの合計を進捗情報に反映する。
+func (d *targetDiscovery) updateTotals(progressInfo *ProgressInfo) {
+	progressInfo.fileCount.total = atomic.LoadUint64(&d.fileCount) + progressInfo.resumedFiles
+	progressInfo.sizeCount.total = atomic.LoadUint64(&d.totalSize) + progressInfo.resumedBytes
+	progressInfo.skippedFiles = subtractResumed(atomic.LoadUint64(&d.skippedCount), progressInfo.resumedFiles)
+	progressInfo.skippedBytes = subtractResumed(atomic.LoadUint64(&d.skippedSize), progressInfo.resumedBytes)
+}
 
-	return fileInfoList, totalSize
+// 計算済みの数から中断する前に計算した分を除く。
+// 探索の途中ではまだ見つかっていない分があるので、0より小さくはしない。
+func subtractResumed(skipped uint64, resumed uint64) uint64 {
+	if skipped < resumed {
+		return 0
+	}
+	return skipped - resumed
 }
 
-// ハッシュファイルからハッシュ計算済みのファイルセットを作成する。
-func makeHashMap(diskInfo *DiskInfo) map[string]string {
+// ハッシュ計算中にサイズが変わったファイルの分だけサイズの合計を補正する。
+func (d *targetDiscovery) adjustTotalSize(before uint64, after uint64) {
+	atomic.AddUint64(&d.totalSize, after-before)
+}
 
-	hashFileIn, err := os.Open(diskInfo.hashFile())
-	if err != nil {
-		return map[string]string{}
+// ハッシュファイルを計算済みの記録と今回計算した記録で書き直す。
+// 計算中は追記しているだけなので、無くなったファイルの記録は探索と計算がすべて終わってから削除する。
+// 無くなったファイルと同じ内容のファイルを今回計算していれば、移動とみなして元のパスの記録を削除する。
+func (d *targetDiscovery) rewriteHashFiles(newRecords [][]HashRecord) {
+	if d.reusedLinks > 0 {
+		logf.Printf("%d個のハードリンクは読み込まずに同じファイルのハッシュを記録しました。: %s\n", d.reusedLinks, d.diskInfo.id)
 	}
-	defer hashFileIn.Close()
 
-	result := make(map[string]string, 1024)
+	d.applyMoves(d.detectMoves(newRecords), newRecords)
 
-	hashFileScanner := bufio.NewScanner(hashFileIn)
-	for i := 1; hashFileScanner.Scan(); i++ {
-		line := hashFileScanner.Text()
+	for i, algorithm := range d.diskInfo.hashAlgorithms() {
+		records := keepRecordsIfNotConfirmed(d.diskInfo, algorithm, d.hashMaps[i], d.existingPaths, d.keptRecords[i])
+		records = append(records, newRecords[i]...)
+		records = append(records, linkedRecords(d.pendingLinks, newRecords[i])...)
 
-		tokens := strings.Split(line, ":")
-		fatalMessageIf(len(tokens) != 2, "ハッシュファイルが破損しています。: %s : %d行目:\n", diskInfo.hashFile(), i)
+		err := hashStoreFor(algorithm).rewrite(d.diskInfo.id, records)
+		fatalMessageError(ErrIO, err, "ハッシュファイルの作成に失敗しました。\n")
+	}
+}
 
-		result[tokens[0]] = tokens[1]
+// 最初に見つけたハードリンクの今回計算したハッシュを他のハードリンクの記録にする。
+// 最初のハードリンクがエラーで計算できなかったら記録しない。
+func linkedRecords(links []hardLink, newRecords []HashRecord) []HashRecord {
+	if len(links) == 0 {
+		return nil
 	}
 
-	return result
+	recordsByPath := make(map[string]HashRecord, len(newRecords))
+	for _, record := range newRecords {
+		recordsByPath[record.path] = record
+	}
+
+	var records []HashRecord
+	for _, link := range links {
+		if record, found := recordsByPath[link.firstPath]; found {
+			record.path = link.normPath
+			records = append(records, record)
+		}
+	}
+
+	return records
 }
 
-// ディスク内のファイル一覧を作成する。
-func listFiles(rootPath string) []string {
+// ディスクから無くなったファイルの記録をハッシュファイルから削除してよいか確認する。
+// -pruneが指定されていないか削除が確認されなければ、無くなったファイルの記録を書き直す記録に加えて返す。
+// -pruneが指定されていなければ無くなったファイルを報告に加える。
+func keepRecordsIfNotConfirmed(diskInfo *DiskInfo, algorithm string, hashMap map[string]HashRecord, existingPaths map[string]bool, records []HashRecord) []HashRecord {
+	var removedPaths []string
+	var outsidePaths []string
+	for normPath := range hashMap {
+		switch {
+		case existingPaths[normPath]:
+		case !diskInfo.covers(normPath):
+			outsidePaths = append(outsidePaths, normPath)
+		default:
+			removedPaths = append(removedPaths, normPath)
+		}
+	}
+	sort.Strings(removedPaths)
+	sort.Strings(outsidePaths)
+
+	// 走査範囲外のファイルは確かめていないので記録をそのまま残す
+	for _, outsidePath := range outsidePaths {
+		records = append(records, hashMap[outsidePath])
+	}
+
+	if !config.prune {
+		missingFiles.add(diskInfo.id, removedPaths)
+	} else if confirmRemoval(fmt.Sprintf("ハッシュファイル(%s, %s)", diskInfo.id, algorithm), removedPaths) {
+		return records
+	}
+
+	for _, removedPath := range removedPaths {
+		records = append(records, hashMap[removedPath])
+	}
+
+	return records
+}
 
-	result := make([]string, 0)
+// 全アルゴリズムのハッシュが計算済みならアルゴリズムの順番で記録を返す。
+// 1つでも未計算か不完全なアルゴリズムがあればファイルを読み直すので見つからなかったことにする。
+func lookupHashes(hashMaps []map[string]HashRecord, normPath string) ([]HashRecord, bool) {
+	records := make([]HashRecord, 0, len(hashMaps))
 
-	err := filepath.WalkDir(rootPath, func(path string, dirEntry fs.DirEntry, err error) error {
-		if !dirEntry.IsDir() {
-			result = append(result, path)
+	for _, hashMap := range hashMaps {
+		record, found := hashMap[normPath]
+		if !found || isIncompleteHash(record.hash) {
+			return nil, false
 		}
-		return nil
-	})
-	fatalMessageError(err, "ファイル一覧の作成中にエラーが発生しました。\n")
+		records = append(records, record)
+	}
+
+	return records, true
+}
+
+// ハッシュファイルからハッシュ計算済みのファイルセットを作成する。
+func makeHashMap(diskInfo *DiskInfo, store HashStore) map[string]HashRecord {
+
+	records, err := store.load(diskInfo.id)
+	fatalMessageError(ErrHashFileFormat, err, "ハッシュファイルが読み込めませんでした。: %s\n", diskInfo.id)
+
+	result := make(map[string]HashRecord, len(records))
+	for _, record := range records {
+		result[record.path] = record
+	}
 
 	return result
 }
 
 // 指定されたファイルがハッシュ対象であるかフィルター設定から判定する。
 func filterFile(normPath string) bool {
-	for _, filter := range config.filters {
+	filter, found := matchFilter(config.filters, normPath)
+	return found && filter.inclusion
+}
+
+// 指定されたパスに最初にマッチするフィルターを返す。
+func matchFilter(filters []Filter, normPath string) (Filter, bool) {
+	for _, filter := range filters {
 		if filter.pattern.MatchString(normPath) {
-			return filter.inclusion
+			return filter, true
 		}
 	}
 
-	return false
+	return Filter{}, false
 }
@@ -0,0 +1,104 @@
+package bcbc
+
+import (
+	"fmt"
+	"os"
+	"runtime"
+	"strings"
+	"sync"
+)
+
+// 進捗を同じ場所で書き換えて表示する端末の表示領域。
+// 進捗の出力先が端末でなければnilで、1秒ごとに進捗を1行ずつ出力する。
+var statusArea = newTerminalStatusArea(os.Stderr)
+
+// 端末の進捗表示領域
+type terminalStatusArea struct {
+	mu  sync.Mutex
+	out *os.File
+	// 表示している行
+	rows []string
+	// 端末に表示している行数
+	shown int
+}
+
+// 出力先が端末なら進捗表示領域を作成する。
+// Windowsのコンソールは制御シーケンスを有効にしないと解釈しないので端末として扱わない。
+func newTerminalStatusArea(out *os.File) *terminalStatusArea {
+	if runtime.GOOS == "windows" {
+		return nil
+	}
+	stat, err := out.Stat()
+	if err != nil || stat.Mode()&os.ModeCharDevice == 0 {
+		return nil
+	}
+	return &terminalStatusArea{out: out}
+}
+
+// 進捗表示領域を書き換える。
+func (a *terminalStatusArea) render(rows []string) {
+	a.mu.Lock()
+	defer a.mu.Unlock()
+
+	a.erase()
+	a.rows = rows
+	a.draw()
+}
+
+// 進捗表示領域を今の表示のまま残して、以降は書き換えないようにする。
+func (a *terminalStatusArea) finish() {
+	if a == nil {
+		return
+	}
+
+	a.mu.Lock()
+	defer a.mu.Unlock()
+
+	a.rows = nil
+	a.shown = 0
+}
+
+// ログを出力する。
+// 進捗表示領域を消してからログを出力し、その下に進捗表示領域を描き直す。
+func (a *terminalStatusArea) print(out *os.File, p []byte) (int, error) {
+	a.mu.Lock()
+	defer a.mu.Unlock()
+
+	a.erase()
+	n, err := out.Write(p)
+	a.draw()
+
+	return n, err
+}
+
+func (a *terminalStatusArea) erase() {
+	if a.shown == 0 {
+		return
+	}
+	fmt.Fprintf(a.out, "\x1b[%dA\x1b[J", a.shown)
+	a.shown = 0
+}
+
+func (a *terminalStatusArea) draw() {
+	if len(a.rows) == 0 {
+		return
+	}
+	fmt.Fprint(a.out, strings.Join(a.rows, "\n")+"\n")
+	a.shown = len(a.rows)
+}
+
+// コンソールへのログ出力。
+// 進捗表示領域があれば表示を崩さないように進捗表示領域を経由して出力する。
+type consoleWriter struct {
+	out *os.File
+}
+
+func (w consoleWriter) Write(p []byte) (int, error) {
+	if statusArea == nil {
+		return w.out.Write(p)
+	}
+	return statusArea.print(w.out, p)
+}
+
+// コンソール(標準出力)
+var consoleOut = consoleWriter{os.Stdout}
@@ -0,0 +1,40 @@
+package bcbc
+
+import "time"
+
+// スループットの制限の単位(MB/s)のバイト数
+const throttleUnit = 1000 * 1000
+
+// 読み込みのスループットの制限。
+// ディスクごとに作成して、そのディスクのファイルの読み込みで共有する。
+type rateLimiter struct {
+	bytesPerSecond float64
+	// 次の読み込みを始めてよい時刻
+	next time.Time
+}
+
+// MB/sで指定されたスループットの制限を作成する。0以下なら制限しないのでnilを返す。
+func newRateLimiter(megabytesPerSecond float64) *rateLimiter {
+	if megabytesPerSecond <= 0 {
+		return nil
+	}
+	return &rateLimiter{bytesPerSecond: megabytesPerSecond * throttleUnit}
+}
+
+// 読み込んだバイト数に応じて、制限を超えないように待つ。
+// しばらく読み込んでいなかった分をまとめて読み込まないように、待たなくてよい時間は持ち越さない。
+func (l *rateLimiter) wait(n int) {
+	if l == nil {
+		return
+	}
+
+	now := time.Now()
+	if l.next.Before(now) {
+		l.next = now
+	}
+	l.next = l.next.Add(time.Duration(float64(n) / l.bytesPerSecond * float64(time.Second)))
+
+	if delay := time.Until(l.next); delay > 0 {
+		time.Sleep(delay)
+	}
+}
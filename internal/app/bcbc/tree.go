@@ -0,0 +1,123 @@
+package bcbc
+
+import (
+	"hash"
+	"runtime"
+	"sync"
+)
+
+// TreeChunkSize ツリーハッシュでファイルを分割するチャンクのサイズ
+const TreeChunkSize = 4 << 20
+
+// ツリーハッシュのチャンクを並行して計算するゴルーチン数の上限。
+// 全てのディスクのハッシュルーチンで共有する。
+var treeWorkers = make(chan struct{}, runtime.NumCPU())
+
+// ツリーハッシュ。
+// ファイルを固定サイズのチャンクに分けてチャンクごとのハッシュを複数のコアで並行して計算し、
+// チャンクのハッシュを順番に連結したもののハッシュをファイルのハッシュとする。
+// 通常のハッシュとは値が異なるのでmd5sumなどとの互換性はない。
+type treeHash struct {
+	newHash func() hash.Hash
+	// 書き込み途中のチャンク
+	chunk []byte
+	// 計算を開始したチャンク
+	chunks []*treeChunk
+	wg     sync.WaitGroup
+}
+
+// ツリーハッシュのチャンク
+type treeChunk struct {
+	digest []byte
+}
+
+// ツリーハッシュのアルゴリズムか判定する。
+// ツリーハッシュのハッシュファイルにはチャンクのサイズを記録する。
+func isTreeAlgorithm(algorithm string) bool {
+	return algorithm == AlgorithmMD5Tree || algorithm == AlgorithmSHA256Tree
+}
+
+// 指定されたハッシュ関数でチャンクを計算するツリーハッシュを生成する。
+func newTreeHash(newHash func() hash.Hash) hash.Hash {
+	return &treeHash{newHash: newHash}
+}
+
+func (t *treeHash) Write(p []byte) (int, error) {
+	n := len(p)
+
+	for len(p) > 0 {
+		if t.chunk == nil {
+			t.chunk = getBuffer(TreeChunkSize)[:0]
+		}
+
+		m := TreeChunkSize - len(t.chunk)
+		if m > len(p) {
+			m = len(p)
+		}
+		t.chunk = append(t.chunk, p[:m]...)
+		p = p[m:]
+
+		if len(t.chunk) == TreeChunkSize {
+			t.startChunk()
+		}
+	}
+
+	return n, nil
+}
+
+// 書き込みが終わったチャンクのハッシュ計算を別のゴルーチンで開始する。
+// 計算中のチャンクが上限に達していたら空くまで待つ。
+func (t *treeHash) startChunk() {
+	chunk := &treeChunk{}
+	t.chunks = append(t.chunks, chunk)
+
+	data := t.chunk
+	t.chunk = nil
+
+	treeWorkers <- struct{}{}
+	t.wg.Add(1)
+	go func() {
+		defer t.wg.Done()
+		defer func() { <-treeWorkers }()
+
+		hasher := t.newHash()
+		hasher.Write(data)
+		chunk.digest = hasher.Sum(nil)
+		putBuffer(data)
+	}()
+}
+
+func (t *treeHash) Sum(b []byte) []byte {
+	t.wg.Wait()
+
+	root := t.newHash()
+	for _, chunk := range t.chunks {
+		root.Write(chunk.digest)
+	}
+
+	// 端数のチャンク
+	if len(t.chunk) > 0 {
+		hasher := t.newHash()
+		hasher.Write(t.chunk)
+		root.Write(hasher.Sum(nil))
+	}
+
+	return root.Sum(b)
+}
+
+func (t *treeHash) Reset() {
+	t.wg.Wait()
+	if t.chunk != nil {
+		putBuffer(t.chunk[:TreeChunkSize])
+	}
+	t.chunk = nil
+	t.chunks = nil
+}
+
+func (t *treeHash) Size() int {
+	return t.newHash().Size()
+}
+
+func (t *treeHash) BlockSize() int {
+	return t.newHash().BlockSize()
+}
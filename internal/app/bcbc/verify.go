@@ -0,0 +1,438 @@
+package bcbc
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/hex"
+	"flag"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"time"
+)
+
+// 検証結果の状態
+const (
+	// VerifyMismatch ハッシュが一致しない
+	VerifyMismatch = "mismatch"
+	// VerifyMissing ファイルがない
+	VerifyMissing = "missing"
+	// VerifyError 読み込みでエラーが発生した
+	VerifyError = "error"
+	// VerifySizeChanged 記録したサイズと異なる。読み込まずに判定する。
+	VerifySizeChanged = "size-changed"
+	// VerifyMetadataChanged ハッシュは一致するが、記録したメタデータと異なる
+	VerifyMetadataChanged = "metadata-changed"
+	// VerifyXattrsChanged ハッシュは一致するが、記録した拡張属性と異なる
+	VerifyXattrsChanged = "xattrs-changed"
+	// VerifyIncomplete 読み込めない範囲を読み飛ばした不完全なハッシュが記録されているので照合できない。
+	// 破損とは限らないので問題の数には含めない。
+	VerifyIncomplete = "incomplete"
+)
+
+// VerifyResult 問題があったファイルの検証結果
+type VerifyResult struct {
+	status   string
+	normPath string
+}
+
+// 検証でメタデータも比べるか
+var verifyMetadata bool
+
+// 不一致レポートのパスを返す。
+// ハッシュが一致しなかったファイルを、別のコピーから復元できるように記録したハッシュと計算したハッシュと一緒に記録する。
+func (di *DiskInfo) mismatchReportFile() string {
+	return filepath.Join(config.outDir(), di.id+".mismatch")
+}
+
+// ハッシュが一致しなかったファイルの記録
+type mismatchEntry struct {
+	normPath  string
+	algorithm string
+	expected  string
+	actual    string
+	// サイズが記録と異なるときは「記録したサイズ/現在のサイズ」
+	size    string
+	modTime time.Time
+}
+
+// 検証レポートのパスを返す。
+// 検証で問題があったファイルを記録し、次回の検証ではそれらのファイルを先に検証する。
+func (di *DiskInfo) verifyReportFile() string {
+	return filepath.Join(config.outDir(), di.id+".verify")
+}
+
+// verifyサブコマンド。
+// ハッシュファイルに記録されたハッシュとファイルの現在のハッシュを比較する。
+// ハッシュ計算と同じようにディスクごとに並行して検証し、問題が見つかれば終了コードで知らせる。
+func executeVerify(args []string) {
+	flagSet := flag.NewFlagSet("verify", flag.ExitOnError)
+	metadata := flagSet.Bool("metadata", false, "ハッシュが一致しても、記録した更新日時、パーミッション、所有者と異なれば報告する。")
+	flagSet.Parse(args)
+	verifyMetadata = *metadata
+
+	logf.Println("検証を開始します。")
+	defer logf.Println("検証を終了しました。")
+
+	diskFiles := findDiskFiles(flagSet.Args())
+	diskInfoList := makeDiskInfoList(diskFiles)
+
+	progressChannel, stopProgressMonitor := startProgressMonitor(len(diskInfoList))
+	completionChannel := make(chan CompletionMessage)
+
+	// 同時に検証するディスクの数を制限して、1つ終わるごとに次のディスクを開始する
+	started := 0
+	startNextDisk := func() {
+		if started < len(diskInfoList) && !interrupted() {
+			go verifyRoutine(&diskInfoList[started], progressChannel, completionChannel)
+			started++
+		}
+	}
+
+	parallelDisks := config.maxParallelDisks
+	if parallelDisks == 0 || parallelDisks > len(diskInfoList) {
+		parallelDisks = len(diskInfoList)
+	}
+	for i := 0; i < parallelDisks; i++ {
+		startNextDisk()
+	}
+
+	// ディスク登録簿は並行して書き込まないように、全ての検証が終わってからまとめて更新する
+	var fatalErr error
+	var verifiedDiskIds []string
+	for completed := 0; completed < started; completed++ {
+		completion := <-completionChannel
+		commandErrors += completion.fileErrors
+		if completion.err == nil {
+			verifiedDiskIds = append(verifiedDiskIds, completion.diskId)
+		}
+		if completion.err != nil && (fatalErr == nil || IsKind(fatalErr, ErrInterrupted)) {
+			fatalErr = completion.err
+		}
+		if fatalErr == nil {
+			startNextDisk()
+		}
+	}
+
+	if err := stopProgressMonitor(); err != nil {
+		logf.Println("進捗の監視中に問題が発生しました。")
+		logf.Println(err)
+	}
+
+	statusArea.finish()
+
+	updateRegistry(verifiedDiskIds, func(record *DiskRecord) {
+		record.LastVerifyAt = time.Now()
+	})
+
+	if fatalErr != nil {
+		panic(fatalErr)
+	}
+	fatalMessageIf(ErrInterrupted, started < len(diskInfoList), "検証を中断しました。\n")
+}
+
+// 検証ルーチン。
+// 致命的なエラーと問題が見つかったファイルの数を完了メッセージでメインルーチンに伝える。
+func verifyRoutine(diskInfo *DiskInfo, progressChannel chan ProgressInfo, completionChannel chan CompletionMessage) {
+	defer func() {
+		if r := recover(); r != nil {
+			e, ok := r.(*Error)
+			if !ok {
+				panic(r)
+			}
+			completionChannel <- CompletionMessage{diskId: diskInfo.id, err: e, fatal: true}
+		}
+	}()
+
+	problems := verifyDisk(diskInfo, progressChannel)
+	completionChannel <- CompletionMessage{diskId: diskInfo.id, fileErrors: problems}
+}
+
+// 1つのディスクを検証して、問題が見つかったファイルの数を返す。
+// 署名の公開鍵があれば、ハッシュファイルの署名も検証する。
+func verifyDisk(diskInfo *DiskInfo, progressChannel chan ProgressInfo) int {
+	algorithms := diskInfo.hashAlgorithms()
+	hashMaps := make([]map[string]HashRecord, 0, len(algorithms))
+	for _, algorithm := range algorithms {
+		hashMaps = append(hashMaps, makeHashMap(diskInfo, hashStoreFor(algorithm)))
+	}
+
+	// 保存先から取得したハッシュファイルの署名を検証する
+	verifyHashFileSignatures(diskInfo)
+
+	// アルゴリズムを後から追加したときなど、一部のアルゴリズムにしか記録がないファイルも検証する
+	recorded := make(map[string]HashRecord)
+	for _, hashMap := range hashMaps {
+		for normPath, record := range hashMap {
+			if _, found := recorded[normPath]; !found {
+				recorded[normPath] = record
+			}
+		}
+	}
+
+	normPaths := make([]string, 0, len(recorded))
+	for normPath := range recorded {
+		if diskInfo.covers(normPath) {
+			normPaths = append(normPaths, normPath)
+		}
+	}
+	sort.Strings(normPaths)
+	normPaths = prioritizePaths(normPaths, loadVerifyReport(diskInfo))
+
+	fileInfoList := make([]FileInfo, len(normPaths))
+	var totalSize uint64
+	for i, normPath := range normPaths {
+		fileInfoList[i].init(diskInfo, filepath.Join(diskInfo.rootPath, filepath.FromSlash(normPath)))
+		size, _ := fileInfoList[i].size()
+		totalSize += size
+	}
+
+	progressInfo := ProgressInfo{
+		diskInfo:  diskInfo,
+		fileCount: ProgressCount{uint64(len(fileInfoList)), 0},
+		sizeCount: ProgressCount{totalSize, 0},
+		startTime: time.Now(),
+	}
+	progressChannel <- progressInfo
+
+	var results []VerifyResult
+	var mismatches []mismatchEntry
+	incomplete := 0
+	checkedTimes := loadCheckedTimes(diskInfo)
+
+	for i := range fileInfoList {
+		fi := &fileInfoList[i]
+
+		status, hashes, err := verifyFile(fi, hashMaps, progressInfo, progressChannel)
+		if err != nil {
+			saveVerifyReport(diskInfo, results)
+			saveMismatchReport(diskInfo, mismatches)
+			saveCheckedTimes(diskInfo, checkedTimes, recorded)
+			fatalMessageError(ErrInterrupted, err, "検証を中断しました。\n")
+		}
+
+		size, _ := fi.size()
+		progressInfo.fileCount.Increment(1)
+		progressInfo.sizeCount.Increment(size)
+
+		if status != "" {
+			logf.Printf("%s: %s\n", status, fi.realPath)
+			results = append(results, VerifyResult{status, fi.normPath})
+			if status == VerifyIncomplete {
+				incomplete++
+			}
+			if status == VerifyMismatch || status == VerifySizeChanged {
+				mismatches = append(mismatches, newMismatchEntries(fi, hashMaps, hashes)...)
+			}
+		} else {
+			checkedTimes[fi.normPath] = time.Now()
+		}
+	}
+
+	progressChannel <- progressInfo
+
+	saveVerifyReport(diskInfo, results)
+	saveMismatchReport(diskInfo, mismatches)
+	saveCheckedTimes(diskInfo, checkedTimes, recorded)
+
+	problems := len(results) - incomplete
+	logf.Printf("ディスク%sの検証結果: %s中%sで問題が見つかりました。\n", diskInfo.id,
+		formatQuantity(uint64(len(fileInfoList)), "件", "件"), formatQuantity(uint64(problems), "件", "件"))
+	if incomplete > 0 {
+		logf.Printf("不完全なハッシュが記録されていて照合できなかった%sがあります。ハッシュ計算し直してください。\n",
+			formatQuantity(uint64(incomplete), "件のファイル", "件のファイル"))
+	}
+	if len(mismatches) > 0 {
+		logf.Printf("ハッシュが一致しなかったファイルを記録しました。: %s\n", diskInfo.mismatchReportFile())
+	}
+
+	return problems
+}
+
+// 1つのファイルを検証して問題があれば検証結果の状態を返す。
+// 問題がなければ空文字列を返す。読み込んだときは計算したハッシュも返す。中断されたときだけエラーを返す。
+func verifyFile(fi *FileInfo, hashMaps []map[string]HashRecord, progressInfo ProgressInfo, progressChannel chan ProgressInfo) (string, [][]byte, error) {
+	if _, err := os.Stat(fi.realPath); os.IsNotExist(err) {
+		return VerifyMissing, nil, nil
+	}
+
+	// サイズが記録と異なれば切り詰められたなどで内容も異なるので読み込まない
+	size, _ := fi.size()
+	for _, hashMap := range hashMaps {
+		if recorded, found := hashMap[fi.normPath]; found && recorded.size >= 0 && uint64(recorded.size) != size {
+			logf.Printf("サイズが記録と異なります。(記録: %s, 現在: %s)\n", formatSize(float64(recorded.size)), formatSize(float64(size)))
+			return VerifySizeChanged, nil, nil
+		}
+	}
+
+	hashes, damaged, err := calcHash(fi.realPath, size, fi.diskInfo.hashAlgorithms(), progressInfo, progressChannel)
+	if IsKind(err, ErrInterrupted) {
+		return "", nil, err
+	}
+	if err != nil {
+		logf.Println(err)
+		return VerifyError, nil, nil
+	}
+	if len(damaged) > 0 {
+		logf.Printf("読み込めない範囲がありました。: %s\n", fi.realPath)
+		return VerifyError, hashes, nil
+	}
+
+	// 不完全なハッシュは読み飛ばした範囲をゼロとみなして計算したものなので照合しない
+	incomplete := false
+	for i, hashMap := range hashMaps {
+		recorded, found := hashMap[fi.normPath]
+		if !found {
+			continue
+		}
+		if isIncompleteHash(recorded.hash) {
+			incomplete = true
+			continue
+		}
+		expected, err := hex.DecodeString(recorded.hash)
+		if err != nil || !bytes.Equal(expected, hashes[i]) {
+			return VerifyMismatch, hashes, nil
+		}
+	}
+	if incomplete {
+		return VerifyIncomplete, hashes, nil
+	}
+
+	// 拡張属性はコピーで失われやすいので、記録していれば比べる
+	for i, hashMap := range hashMaps {
+		recorded := hashMap[fi.normPath]
+		if recorded.xattrs != "" && recorded.xattrs != xattrDigest(fi.diskInfo.hashAlgorithms()[i], fi.xattrs()) {
+			return VerifyXattrsChanged, hashes, nil
+		}
+	}
+
+	if verifyMetadata {
+		if stat := fi.stat(); stat != nil {
+			var metadata *fileMetadata
+			for _, hashMap := range hashMaps {
+				if recorded, found := hashMap[fi.normPath]; found && recorded.metadata != nil {
+					metadata = recorded.metadata
+					break
+				}
+			}
+			drift := metadataDrift(metadata, metadataOf(stat))
+			if len(drift) > 0 {
+				logf.Printf("メタデータが記録と異なります。: %s\n", strings.Join(drift, ", "))
+				return VerifyMetadataChanged, hashes, nil
+			}
+		}
+	}
+
+	return "", hashes, nil
+}
+
+// ハッシュが一致しなかったアルゴリズムごとに不一致レポートの記録を作成する。
+// サイズが記録と異なって読み込まなかったときは、計算したハッシュを空にして記録したサイズと現在のサイズを記録する。
+func newMismatchEntries(fi *FileInfo, hashMaps []map[string]HashRecord, hashes [][]byte) []mismatchEntry {
+	size, _ := fi.size()
+	var modTime time.Time
+	if stat := fi.stat(); stat != nil {
+		modTime = stat.ModTime()
+	}
+
+	var entries []mismatchEntry
+	for i, hashMap := range hashMaps {
+		recorded, found := hashMap[fi.normPath]
+		if !found {
+			continue
+		}
+		algorithm := fi.diskInfo.hashAlgorithms()[i]
+		if hashes == nil {
+			sizeColumn := fmt.Sprintf("%d/%d", recorded.size, size)
+			entries = append(entries, mismatchEntry{fi.normPath, algorithm, recorded.hash, "", sizeColumn, modTime})
+			continue
+		}
+		actual := hex.EncodeToString(hashes[i])
+		if recorded.hash != actual {
+			entries = append(entries, mismatchEntry{fi.normPath, algorithm, recorded.hash, actual, fmt.Sprint(size), modTime})
+		}
+	}
+	return entries
+}
+
+// 不一致レポートを書き込む。一致しなかったファイルがなければ前回のレポートを削除する。
+// 1行に1ファイルで、パス、アルゴリズム、記録したハッシュ、計算したハッシュ、サイズ、更新日時をタブで区切る。
+// サイズが記録と異なるファイルは計算したハッシュを空にして、サイズを「記録したサイズ/現在のサイズ」とする。
+func saveMismatchReport(diskInfo *DiskInfo, mismatches []mismatchEntry) {
+	if len(mismatches) == 0 {
+		os.Remove(diskInfo.mismatchReportFile())
+		return
+	}
+
+	content := strings.Builder{}
+	content.WriteString("# path\talgorithm\texpected\tactual\tsize\tmtime\n")
+	for _, m := range mismatches {
+		fmt.Fprintf(&content, "%s\t%s\t%s\t%s\t%s\t%s\n", pathEscaper.Replace(m.normPath), m.algorithm,
+			m.expected, m.actual, m.size, m.modTime.UTC().Format(time.RFC3339))
+	}
+
+	err := os.WriteFile(diskInfo.mismatchReportFile(), []byte(content.String()), 0644)
+	fatalMessageError(ErrIO, err, "不一致レポートの書き込みに失敗しました。: %s\n", diskInfo.mismatchReportFile())
+}
+
+// 前回の検証レポートを読み込んで問題があったファイルのセットを返す。
+func loadVerifyReport(diskInfo *DiskInfo) map[string]bool {
+	result := make(map[string]bool)
+
+	reportIn, err := os.Open(diskInfo.verifyReportFile())
+	if err != nil {
+		return result
+	}
+	defer reportIn.Close()
+
+	for scanner := bufio.NewScanner(reportIn); scanner.Scan(); {
+		tokens := strings.SplitN(scanner.Text(), "\t", 2)
+		if len(tokens) != 2 {
+			continue
+		}
+		if normPath, ok := unescapePath(tokens[1]); ok {
+			result[normPath] = true
+		}
+	}
+
+	return result
+}
+
+// 検証レポートを書き込む。
+// 1行に1ファイルで、状態とハッシュファイルと同じようにエスケープしたパスをタブで区切る。
+func saveVerifyReport(diskInfo *DiskInfo, results []VerifyResult) {
+	content := strings.Builder{}
+	for _, result := range results {
+		fmt.Fprintf(&content, "%s\t%s\n", result.status, pathEscaper.Replace(result.normPath))
+	}
+
+	err := os.WriteFile(diskInfo.verifyReportFile(), []byte(content.String()), 0644)
+	fatalMessageError(ErrIO, err, "検証レポートの書き込みに失敗しました。: %s\n", diskInfo.verifyReportFile())
+}
+
+// 優先するパスが先頭になるように並べ替える。
+// それ以外の順番は変えない。
+func prioritizePaths(normPaths []string, priorities map[string]bool) []string {
+	if len(priorities) == 0 {
+		return normPaths
+	}
+
+	result := make([]string, 0, len(normPaths))
+	for _, normPath := range normPaths {
+		if priorities[normPath] {
+			result = append(result, normPath)
+		}
+	}
+	if len(result) > 0 {
+		logf.Printf("前回の検証で問題があった%d件のファイルを先に検証します。\n", len(result))
+	}
+	for _, normPath := range normPaths {
+		if !priorities[normPath] {
+			result = append(result, normPath)
+		}
+	}
+
+	return result
+}
@@ -0,0 +1,292 @@
+package bcbc
+
+import (
+	"fmt"
+	"golang.org/x/text/unicode/norm"
+	"io/fs"
+	"os"
+	"path"
+	"path/filepath"
+	"strings"
+)
+
+// SymlinksSkip シンボリックリンクの扱い: ハッシュ計算の対象にしない
+const SymlinksSkip = "skip"
+
+// SymlinksFollow シンボリックリンクの扱い: リンク先をたどってハッシュ計算する
+const SymlinksFollow = "follow"
+
+// ディスク内のファイルを走査して、見つけたファイルのパスを引数visitに渡す。
+// ディスク内で見つけたシンボリックリンクの一覧を返す。
+// 一覧を作ってから処理するのではなく見つけた順に渡すので、ファイル数が多くてもパスの一覧を保持しない。
+// visitがfalseを返したら走査を打ち切る。
+func listFiles(rootPath string, visit func(file string) bool) []string {
+	return listFilesUnder(rootPath, nil, visit)
+}
+
+// ディスクの走査範囲のファイルを走査する。走査範囲が指定されていなければディスク全体を走査する。
+func listDiskFiles(diskInfo *DiskInfo, visit func(file string) bool) []string {
+	return listFilesUnder(diskInfo.rootPath, diskInfo.subpaths, visit)
+}
+
+// ディスクルートから指定されたスラッシュ区切りの相対パスのディレクトリだけを走査する。
+// 相対パスがなければディスク全体を走査する。
+func listFilesUnder(rootPath string, subpaths []string, visit func(file string) bool) []string {
+	walker := fileWalker{
+		rootPath:     rootPath,
+		visit:        visit,
+		symlinks:     make([]string, 0),
+		visited:      make(map[string]bool),
+		excludedDirs: metadataDirs(),
+	}
+
+	if len(subpaths) == 0 {
+		walker.walk(rootPath, 1, nil)
+	}
+	for _, subpath := range subpaths {
+		if walker.stopped {
+			break
+		}
+		// ディスク全体を走査したときと同じ規則で除外するように、親ディレクトリの除外設定ファイルの規則を引き継ぐ
+		ignores, included := walker.ancestorIgnores(subpath)
+		if !included {
+			walker.ignored++
+			continue
+		}
+		walker.walk(filepath.Join(rootPath, filepath.FromSlash(subpath)), strings.Count(subpath, "/")+2, ignores)
+	}
+
+	if walker.depthLimited > 0 {
+		logf.Printf("最大深さを超えたので%d個のディレクトリを走査しませんでした。: %s\n", walker.depthLimited, rootPath)
+	}
+	if walker.ignored > 0 {
+		logf.Printf("%sで%d個のファイルとディレクトリを除外しました。: %s\n", ignoreFileName, walker.ignored, rootPath)
+	}
+
+	return walker.symlinks
+}
+
+// ディレクトリの走査
+type fileWalker struct {
+	rootPath string
+	visit    func(file string) bool
+	symlinks []string
+	// 走査したディレクトリの実パス。シンボリックリンクのループを検出するために使う。
+	visited map[string]bool
+	// 未走査のディレクトリのスタック
+	stack []walkEntry
+	// 最大深さを超えたので走査しなかったディレクトリの数
+	depthLimited int
+	// 走査が打ち切られたか
+	stopped bool
+	// 除外設定ファイルで除外したファイルとディレクトリの数
+	ignored int
+	// bcbcが書き込むので走査しないディレクトリの実パス
+	excludedDirs map[string]bool
+}
+
+// 未走査のディレクトリ
+type walkEntry struct {
+	dir string
+	// ディスクルートの直下を1とする深さ
+	depth int
+	// 親ディレクトリまでの除外設定ファイルの規則
+	ignores []ignoreRule
+}
+
+// 指定された深さのディレクトリから、親ディレクトリまでの除外設定ファイルの規則で走査する。
+// 深い階層でもスタックが溢れないように、再帰呼び出しではなく未走査のディレクトリのスタックで走査する。
+func (w *fileWalker) walk(dir string, depth int, ignores []ignoreRule) {
+	w.stack = append(w.stack, walkEntry{dir, depth, ignores})
+
+	for len(w.stack) > 0 && !w.stopped {
+		entry := w.stack[len(w.stack)-1]
+		w.stack = w.stack[:len(w.stack)-1]
+		w.walkDir(entry)
+	}
+}
+
+// ディスクルートから走査範囲のディレクトリの親ディレクトリまでにある除外設定ファイルの規則を集める。
+// 途中のディレクトリか走査範囲のディレクトリ自体が除外されていればfalseを返す。
+func (w *fileWalker) ancestorIgnores(subpath string) ([]ignoreRule, bool) {
+	var ignores []ignoreRule
+	relDir := ""
+	for _, name := range strings.Split(subpath, "/") {
+		ignoreFile := filepath.Join(w.rootPath, filepath.FromSlash(relDir), ignoreFileName)
+		if stat, err := os.Lstat(ignoreFile); err == nil && stat.Mode().IsRegular() {
+			ignores = append(ignores, readIgnoreFile(ignoreFile, relDir)...)
+		}
+
+		relDir = path.Join(relDir, name)
+		if len(ignores) > 0 && ignoredByRules(ignores, relDir, true) {
+			return nil, false
+		}
+	}
+	return ignores, true
+}
+
+// ディレクトリを1つ走査して、ファイルを渡してサブディレクトリをスタックに積む。
+func (w *fileWalker) walkDir(entry walkEntry) {
+	realDir, err := filepath.EvalSymlinks(entry.dir)
+	if err == nil {
+		if w.visited[realDir] {
+			logf.Printf("シンボリックリンクがループしているので走査しません。: %s\n", entry.dir)
+			return
+		}
+		w.visited[realDir] = true
+
+		if absDir, err := filepath.Abs(realDir); err == nil && w.excludedDirs[absDir] {
+			logf.Printf("bcbcの出力先なので走査しません。: %s\n", entry.dir)
+			return
+		}
+	}
+
+	dirEntries, err := os.ReadDir(entry.dir)
+	if err != nil {
+		logf.Printf("ディレクトリが読み込めませんでした。: %s\n", entry.dir)
+		logf.Println(err)
+		return
+	}
+
+	// 除外設定ファイルがあれば親ディレクトリまでの規則に加える
+	relDir := w.relPath(entry.dir)
+	ignores := entry.ignores
+	for _, dirEntry := range dirEntries {
+		if dirEntry.Name() == ignoreFileName && dirEntry.Type().IsRegular() {
+			rules := readIgnoreFile(filepath.Join(entry.dir, ignoreFileName), relDir)
+			ignores = append(append([]ignoreRule(nil), entry.ignores...), rules...)
+			break
+		}
+	}
+
+	var subDirs []string
+
+	for _, dirEntry := range dirEntries {
+		entryPath := filepath.Join(entry.dir, dirEntry.Name())
+
+		// ディスクルートのdiskファイルはbcbcが書き換えるのでハッシュ計算しない
+		if relDir == "" && dirEntry.Name() == "disk" && !*hashMetadataOption {
+			continue
+		}
+
+		if len(ignores) > 0 && ignoredByRules(ignores, path.Join(relDir, norm.NFC.String(dirEntry.Name())), dirEntry.IsDir()) {
+			w.ignored++
+			continue
+		}
+
+		if dirEntry.Type()&fs.ModeSymlink != 0 {
+			w.symlinks = append(w.symlinks, entryPath)
+			if config.symlinks == SymlinksFollow {
+				if w.followSymlink(entryPath) {
+					subDirs = append(subDirs, entryPath)
+				}
+			}
+			continue
+		}
+
+		if dirEntry.IsDir() {
+			subDirs = append(subDirs, entryPath)
+			continue
+		}
+
+		if dirEntry.Type().IsRegular() {
+			w.visitFile(entryPath)
+		}
+
+		if w.stopped {
+			return
+		}
+	}
+
+	if config.maxDepth > 0 && entry.depth >= config.maxDepth {
+		w.depthLimited += len(subDirs)
+		return
+	}
+
+	// 名前順に走査するように逆順に積む
+	for i := len(subDirs) - 1; i >= 0; i-- {
+		w.stack = append(w.stack, walkEntry{subDirs[i], entry.depth + 1, ignores})
+	}
+}
+
+// ディスクルートからのスラッシュ区切りの相対パスを返す。ディスクルートなら空を返す。
+func (w *fileWalker) relPath(dir string) string {
+	relPath, err := filepath.Rel(w.rootPath, dir)
+	if err != nil || relPath == "." {
+		return ""
+	}
+	return norm.NFC.String(filepath.ToSlash(relPath))
+}
+
+// ディスク内にあっても走査しない、bcbcが書き込むディレクトリの実パスを返す。
+// システムディスクをハッシュ計算すると、出力ディレクトリのハッシュファイルやログを計算し続けてしまうので除外する。
+// -hash-metadataが指定されていれば除外しない。
+func metadataDirs() map[string]bool {
+	dirs := make(map[string]bool)
+	if *hashMetadataOption || config.homeDir == "" {
+		return dirs
+	}
+
+	for _, dir := range []string{config.outDir(), config.logDir()} {
+		if realDir, err := filepath.EvalSymlinks(dir); err == nil {
+			if absDir, err := filepath.Abs(realDir); err == nil {
+				dirs[absDir] = true
+			}
+		}
+	}
+
+	return dirs
+}
+
+// シンボリックリンクのリンク先をたどる。
+// リンク先がファイルなら渡して、ディレクトリならtrueを返す。
+func (w *fileWalker) followSymlink(linkPath string) bool {
+	stat, err := os.Stat(linkPath)
+	if err != nil {
+		logf.Printf("シンボリックリンクのリンク先がありません。: %s\n", linkPath)
+		return false
+	}
+
+	if stat.Mode().IsRegular() {
+		w.visitFile(linkPath)
+	}
+
+	return stat.IsDir()
+}
+
+// ファイルを渡す。打ち切りを指示されたら以降は走査しない。
+func (w *fileWalker) visitFile(file string) {
+	if !w.visit(file) {
+		w.stopped = true
+	}
+}
+
+// シンボリックリンクの一覧ファイルのパスを返す。
+func (di *DiskInfo) symlinksFile() string {
+	return filepath.Join(config.outDir(), di.id+".symlinks")
+}
+
+// ディスク内のシンボリックリンクとリンク先を一覧ファイルに記録する。
+// シンボリックリンクがなければ一覧ファイルを削除する。
+func recordSymlinks(diskInfo *DiskInfo, symlinks []string) {
+	if len(symlinks) == 0 {
+		os.Remove(diskInfo.symlinksFile())
+		return
+	}
+
+	content := strings.Builder{}
+	for _, symlink := range symlinks {
+		target, err := os.Readlink(symlink)
+		if err != nil {
+			target = "?"
+		}
+
+		normPath, _ := filepath.Rel(diskInfo.rootPath, symlink)
+		normPath = norm.NFC.String(filepath.ToSlash(normPath))
+
+		fmt.Fprintf(&content, "%s -> %s\n", normPath, target)
+	}
+
+	err := os.WriteFile(diskInfo.symlinksFile(), []byte(content.String()), 0644)
+	fatalMessageError(ErrIO, err, "シンボリックリンクの一覧ファイルの書き込みに失敗しました。: %s\n", diskInfo.symlinksFile())
+}
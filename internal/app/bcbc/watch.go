@@ -0,0 +1,222 @@
+package bcbc
+
+import (
+	"flag"
+	"fmt"
+	"os"
+	"sort"
+	"strings"
+	"time"
+)
+
+// 監視で見つけた変更
+type watchedChange struct {
+	diskInfo *DiskInfo
+	file     string
+}
+
+// 変更を待っているファイル
+type pendingChange struct {
+	diskInfo    *DiskInfo
+	lastChanged time.Time
+}
+
+// watchサブコマンド。
+// ディスクルート以下を監視して、追加や変更されたファイルのハッシュを計算してハッシュファイルに追記する。
+// 変更通知が使えればそれを使い、使えなければ定期的に走査して変更を見つける。
+// 中断シグナルを受け取るまで終了しない。
+func executeWatch(args []string) {
+	flagSet := flag.NewFlagSet("watch", flag.ExitOnError)
+	settle := flagSet.Duration("settle", 5*time.Second, "ファイルが変更されてからハッシュ計算するまでの待ち時間。書き込み中のファイルを計算しないように、この間変更がなくなるまで待つ。")
+	pollInterval := flagSet.Duration("poll-interval", 30*time.Second, "変更通知が使えないときにディスクを走査する間隔。")
+	flagSet.Parse(args)
+
+	fatalMessageIf(ErrConfig, *settle < 0, "待ち時間の指定が不正です。: %s\n", *settle)
+	fatalMessageIf(ErrConfig, *pollInterval <= 0, "走査間隔の指定が不正です。: %s\n", *pollInterval)
+
+	initFilters()
+
+	diskFiles := findDiskFiles(flagSet.Args())
+	fatalMessageIf(ErrDiskFile, len(diskFiles) == 0, "diskファイルが見つかりませんでした。\n")
+	diskInfoList := makeDiskInfoList(diskFiles)
+
+	changes := make(chan watchedChange, 1024)
+	for i := range diskInfoList {
+		startWatcher(&diskInfoList[i], *pollInterval, changes)
+	}
+
+	// 監視中は進捗を表示しない
+	progressChannel := make(chan ProgressInfo)
+	go func() {
+		for range progressChannel {
+		}
+	}()
+
+	logf.Println("監視を開始します。")
+	defer logf.Println("監視を終了しました。")
+
+	pending := make(map[string]pendingChange)
+
+	ticker := time.NewTicker(time.Second)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-interruptChannel:
+			for i := range diskInfoList {
+				foldWatchedJournals(&diskInfoList[i])
+			}
+			return
+
+		case change := <-changes:
+			pending[change.file] = pendingChange{change.diskInfo, time.Now()}
+
+		case <-ticker.C:
+			settled := make(map[*DiskInfo][]string)
+			for file, change := range pending {
+				if time.Since(change.lastChanged) >= *settle {
+					settled[change.diskInfo] = append(settled[change.diskInfo], file)
+					delete(pending, file)
+				}
+			}
+
+			for diskInfo, files := range settled {
+				sort.Strings(files)
+				hashChangedFiles(diskInfo, files, progressChannel)
+			}
+		}
+	}
+}
+
+// 監視中に追記したジャーナルをハッシュファイルに取り込む。
+// 他の書き込み元がハッシュ計算中なら、そちらが最後に取り込むので何もしない。
+func foldWatchedJournals(diskInfo *DiskInfo) {
+	if err := lockDisk(diskInfo); err != nil {
+		if !IsKind(err, ErrLocked) {
+			logf.Println(err)
+		}
+		return
+	}
+	defer unlockDisk(diskInfo)
+
+	if err := foldJournals(diskInfo); err != nil {
+		logf.Printf("ディスク(%s)のジャーナルをハッシュファイルに取り込めませんでした。\n", diskInfo.id)
+		logf.Println(err)
+	}
+}
+
+// 変更されたファイルのハッシュを計算してハッシュファイルに追記する。
+// 追記するたびにディスクをロックするので、監視中でも他の書き込み元がハッシュ計算できる。
+func hashChangedFiles(diskInfo *DiskInfo, files []string, progressChannel chan ProgressInfo) {
+	var err error
+	func() {
+		defer recoverFatal(&err)
+		err = appendChangedFiles(diskInfo, files, progressChannel)
+	}()
+
+	if err != nil && !IsKind(err, ErrInterrupted) {
+		logf.Printf("ディスク(%s)の変更されたファイルのハッシュ計算中に問題が発生しました。\n", diskInfo.id)
+		logf.Println(err)
+	}
+}
+
+func appendChangedFiles(diskInfo *DiskInfo, files []string, progressChannel chan ProgressInfo) error {
+	if err := lockDisk(diskInfo); err != nil {
+		return err
+	}
+	defer unlockDisk(diskInfo)
+
+	recordWriter(diskInfo)
+
+	algorithms := diskInfo.hashAlgorithms()
+	appenders := make([]HashAppender, 0, len(algorithms))
+	for _, algorithm := range algorithms {
+		appender, err := hashStoreFor(algorithm).openAppender(diskInfo.id)
+		fatalMessageError(ErrIO, err, "ハッシュファイルの書き込みに失敗しました。: %s\n", diskInfo.id)
+		defer appender.close()
+
+		appenders = append(appenders, appender)
+	}
+
+	for _, file := range files {
+		stat, err := os.Stat(file)
+		if err != nil || !stat.Mode().IsRegular() {
+			continue
+		}
+
+		var fi FileInfo
+		fi.init(diskInfo, file)
+		if !diskInfo.covers(fi.normPath) || !diskInfo.filterFile(fi.normPath) {
+			continue
+		}
+
+		progressInfo := ProgressInfo{diskInfo: diskInfo, startTime: time.Now()}
+		size, _ := fi.size()
+		hashes, damaged, err := calcHash(fi.realPath, size, algorithms, progressInfo, progressChannel)
+		if IsKind(err, ErrInterrupted) {
+			return err
+		}
+		if err != nil {
+			logf.Printf("ハッシュ計算中にエラーが発生しました。: %s\n", fi.realPath)
+			logf.Println(err)
+			continue
+		}
+
+		hashSuffix := ""
+		if len(damaged) > 0 {
+			recordDamage(diskInfo, fi.normPath, damaged)
+			hashSuffix = incompleteHashSuffix
+		}
+
+		hashStrings := make([]string, 0, len(appenders))
+		for i, appender := range appenders {
+			record := fi.newRecord(algorithms[i], fmt.Sprintf("%x", hashes[i])+hashSuffix)
+			if err := appender.append(record); err != nil {
+				return newError(ErrIO, err, "ハッシュファイルの書き込みに失敗しました。: %s", diskInfo.id)
+			}
+			hashStrings = append(hashStrings, record.hash)
+		}
+
+		logf.Printf("変更されたファイルのハッシュを記録しました。: %s:%s\n", fi.normPath, strings.Join(hashStrings, ","))
+	}
+
+	return nil
+}
+
+// ファイルの更新日時とサイズ
+type fileStamp struct {
+	modTime time.Time
+	size    int64
+}
+
+// 定期的にディスクを走査して、前回の走査から追加や変更されたファイルを通知する。
+func startPollingWatcher(diskInfo *DiskInfo, pollInterval time.Duration, changes chan<- watchedChange) {
+	logf.Printf("ディスク(%s)を%sごとに走査して変更を監視します。\n", diskInfo.id, pollInterval)
+
+	scan := func() map[string]fileStamp {
+		stamps := make(map[string]fileStamp)
+		listDiskFiles(diskInfo, func(file string) bool {
+			if stat, err := os.Stat(file); err == nil {
+				stamps[file] = fileStamp{stat.ModTime(), stat.Size()}
+			}
+			return !interrupted()
+		})
+		return stamps
+	}
+
+	go func() {
+		previous := scan()
+
+		for !interrupted() {
+			time.Sleep(pollInterval)
+
+			current := scan()
+			for file, stamp := range current {
+				if last, found := previous[file]; !found || last != stamp {
+					changes <- watchedChange{diskInfo, file}
+				}
+			}
+			previous = current
+		}
+	}()
+}
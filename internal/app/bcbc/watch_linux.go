@@ -0,0 +1,123 @@
+// +build linux
+
+package bcbc
+
+import (
+	"io/fs"
+	"path/filepath"
+	"strings"
+	"syscall"
+	"time"
+	"unsafe"
+)
+
+// 監視する変更
+const inotifyMask = syscall.IN_CLOSE_WRITE | syscall.IN_MOVED_TO | syscall.IN_CREATE
+
+// inotifyによるディスクの監視
+type inotifyWatcher struct {
+	fd       int
+	diskInfo *DiskInfo
+	// 監視記述子ごとのディレクトリ
+	dirs    map[int32]string
+	changes chan<- watchedChange
+}
+
+// inotifyでディスクの監視を開始する。
+// inotifyが使えなければ定期的な走査で監視する。
+func startWatcher(diskInfo *DiskInfo, pollInterval time.Duration, changes chan<- watchedChange) {
+	fd, err := syscall.InotifyInit1(syscall.IN_CLOEXEC)
+	if err != nil {
+		logf.Printf("変更通知が使えません。: %s\n", err)
+		startPollingWatcher(diskInfo, pollInterval, changes)
+		return
+	}
+
+	w := &inotifyWatcher{fd, diskInfo, make(map[int32]string), changes}
+	if err := w.addTree(diskInfo.rootPath, false); err != nil {
+		logf.Printf("変更通知が使えません。: %s\n", err)
+		syscall.Close(fd)
+		startPollingWatcher(diskInfo, pollInterval, changes)
+		return
+	}
+
+	logf.Printf("ディスク(%s)の変更通知を監視します。\n", diskInfo.id)
+
+	go w.run()
+}
+
+// ディレクトリ以下の全てのディレクトリを監視する。
+// 監視を始める前に作成されたファイルを取りこぼさないように、notifyならディレクトリ内のファイルも通知する。
+func (w *inotifyWatcher) addTree(root string, notify bool) error {
+	return filepath.WalkDir(root, func(path string, entry fs.DirEntry, err error) error {
+		if err != nil {
+			return nil
+		}
+
+		if !entry.IsDir() {
+			if notify && entry.Type().IsRegular() {
+				w.changes <- watchedChange{w.diskInfo, path}
+			}
+			return nil
+		}
+
+		wd, err := syscall.InotifyAddWatch(w.fd, path, inotifyMask)
+		if err != nil {
+			// 監視数の上限に達したときは全体を諦める
+			if err == syscall.ENOSPC {
+				return err
+			}
+			logf.Printf("ディレクトリを監視できませんでした。: %s\n", path)
+			return nil
+		}
+		w.dirs[int32(wd)] = path
+
+		return nil
+	})
+}
+
+// 変更通知を読み込んで変更されたファイルを通知する。
+func (w *inotifyWatcher) run() {
+	defer syscall.Close(w.fd)
+
+	buffer := make([]byte, 64<<10)
+
+	for !interrupted() {
+		n, err := syscall.Read(w.fd, buffer)
+		if err == syscall.EINTR {
+			continue
+		}
+		if err != nil || n <= 0 {
+			logf.Printf("ディスク(%s)の変更通知が読み込めなくなりました。\n", w.diskInfo.id)
+			logf.Println(err)
+			return
+		}
+
+		for offset := 0; offset+syscall.SizeofInotifyEvent <= n; {
+			event := (*syscall.InotifyEvent)(unsafe.Pointer(&buffer[offset]))
+			nameStart := offset + syscall.SizeofInotifyEvent
+			name := strings.TrimRight(string(buffer[nameStart:nameStart+int(event.Len)]), "\x00")
+			offset = nameStart + int(event.Len)
+
+			if event.Mask&syscall.IN_Q_OVERFLOW != 0 {
+				logf.Printf("ディスク(%s)の変更通知が溢れたので一部の変更を取りこぼした可能性があります。\n", w.diskInfo.id)
+				continue
+			}
+
+			dir, found := w.dirs[event.Wd]
+			if !found || name == "" {
+				continue
+			}
+			path := filepath.Join(dir, name)
+
+			switch {
+			case event.Mask&syscall.IN_ISDIR != 0:
+				if event.Mask&(syscall.IN_CREATE|syscall.IN_MOVED_TO) != 0 {
+					w.addTree(path, true)
+				}
+			case event.Mask&(syscall.IN_CLOSE_WRITE|syscall.IN_MOVED_TO) != 0:
+				w.changes <- watchedChange{w.diskInfo, path}
+			}
+		}
+	}
+}
@@ -0,0 +1,10 @@
+// +build !linux
+
+package bcbc
+
+import "time"
+
+// Linux以外では定期的な走査でディスクを監視する。
+func startWatcher(diskInfo *DiskInfo, pollInterval time.Duration, changes chan<- watchedChange) {
+	startPollingWatcher(diskInfo, pollInterval, changes)
+}
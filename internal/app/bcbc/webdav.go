@@ -0,0 +1,226 @@
+package bcbc
+
+import (
+	"encoding/xml"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"os"
+	"path"
+	"strings"
+	"time"
+)
+
+// WebDAVサーバーの認証情報の環境変数名
+const (
+	// EnvWebDAVUser 環境変数名: ユーザー名
+	EnvWebDAVUser = "BCBC_WEBDAV_USER"
+	// EnvWebDAVPassword 環境変数名: パスワード
+	EnvWebDAVPassword = "BCBC_WEBDAV_PASSWORD"
+)
+
+// WebDAVサーバーへの1回のリクエストのタイムアウト
+const webdavTimeout = 5 * time.Minute
+
+// WebDAVサーバーの保存先
+type webdavStorage struct {
+	// 保存先のURL
+	rootURL string
+	// アルゴリズムのハッシュファイルのディレクトリのURL
+	baseURL string
+	// Basic認証のユーザー名とパスワード。ユーザー名が空なら認証しない。
+	user     string
+	password string
+	client   *http.Client
+}
+
+func newWebDAVHashStore(algorithm string) HashStore {
+	rootURL := strings.TrimSuffix(config.storeURL, "/")
+	baseURL := rootURL
+	if dir := remoteHashDir(algorithm); dir != "" {
+		baseURL += "/" + dir
+	}
+	user, password := webdavCredentials()
+	return &remoteHashStore{
+		&fileHashStore{config.hashDir(algorithm), algorithm},
+		&webdavStorage{rootURL, baseURL, user, password, &http.Client{Timeout: webdavTimeout}},
+	}
+}
+
+// WebDAVサーバーの認証情報を返す。
+// 環境変数になければ、保存先のURLに書かれたユーザー名とパスワードを使う。
+func webdavCredentials() (string, string) {
+	if user := os.Getenv(EnvWebDAVUser); user != "" {
+		return user, os.Getenv(EnvWebDAVPassword)
+	}
+	if config.storeUser == nil {
+		return "", ""
+	}
+	password, _ := config.storeUser.Password()
+	return config.storeUser.Username(), password
+}
+
+func (w *webdavStorage) location(name string) string {
+	if name == "" {
+		return w.baseURL + "/"
+	}
+	segments := strings.Split(name, "/")
+	for i, segment := range segments {
+		segments[i] = url.PathEscape(segment)
+	}
+	return w.baseURL + "/" + strings.Join(segments, "/")
+}
+
+// WebDAVサーバーにリクエストを送る。
+// 認証情報があればBasic認証で送る。
+func (w *webdavStorage) request(method string, target string, body io.Reader, size int64, headers map[string]string) (*http.Response, error) {
+	request, err := http.NewRequest(method, target, body)
+	if err != nil {
+		return nil, err
+	}
+	if body != nil {
+		request.ContentLength = size
+	}
+	if w.user != "" {
+		request.SetBasicAuth(w.user, w.password)
+	}
+	for key, value := range headers {
+		request.Header.Set(key, value)
+	}
+	return w.client.Do(request)
+}
+
+func (w *webdavStorage) get(name string) (io.ReadCloser, error) {
+	response, err := w.request(http.MethodGet, w.location(name), nil, 0, nil)
+	if err != nil {
+		return nil, err
+	}
+
+	switch response.StatusCode {
+	case http.StatusOK:
+		return response.Body, nil
+	case http.StatusNotFound:
+		response.Body.Close()
+		return nil, nil
+	default:
+		response.Body.Close()
+		return nil, fmt.Errorf("%s: %s", w.location(name), response.Status)
+	}
+}
+
+func (w *webdavStorage) stamp(name string) (string, error) {
+	response, err := w.request(http.MethodHead, w.location(name), nil, 0, nil)
+	if err != nil {
+		return "", err
+	}
+	response.Body.Close()
+
+	switch response.StatusCode {
+	case http.StatusOK:
+		return responseStamp(response), nil
+	case http.StatusNotFound:
+		return missingStamp, nil
+	default:
+		return "", fmt.Errorf("%s: %s", w.location(name), response.Status)
+	}
+}
+
+func (w *webdavStorage) put(name string, body io.ReadSeeker, size int64) error {
+	response, err := w.request(http.MethodPut, w.location(name), body, size, nil)
+	if err != nil {
+		return err
+	}
+	response.Body.Close()
+
+	// 親のコレクションがなければ作成して送り直す
+	if response.StatusCode == http.StatusConflict {
+		if err := w.makeCollections(path.Dir(name)); err != nil {
+			return err
+		}
+		if _, err := body.Seek(0, io.SeekStart); err != nil {
+			return err
+		}
+		if response, err = w.request(http.MethodPut, w.location(name), body, size, nil); err != nil {
+			return err
+		}
+		response.Body.Close()
+	}
+
+	if response.StatusCode/100 != 2 {
+		return fmt.Errorf("%s: %s", w.location(name), response.Status)
+	}
+	return nil
+}
+
+func (w *webdavStorage) remove(name string) error {
+	response, err := w.request(http.MethodDelete, w.location(name), nil, 0, nil)
+	if err != nil {
+		return err
+	}
+	response.Body.Close()
+
+	if response.StatusCode/100 != 2 && response.StatusCode != http.StatusNotFound {
+		return fmt.Errorf("%s: %s", w.location(name), response.Status)
+	}
+	return nil
+}
+
+// 保存先のURLから名前のディレクトリまでのコレクションを順に作成する。
+// 既にあるコレクションの作成の失敗は無視する。
+func (w *webdavStorage) makeCollections(dir string) error {
+	targets := []string{w.rootURL, w.baseURL}
+	if dir != "." && dir != "" {
+		parts := strings.Split(dir, "/")
+		for i := range parts {
+			targets = append(targets, w.location(strings.Join(parts[:i+1], "/")))
+		}
+	}
+
+	for _, target := range targets {
+		response, err := w.request("MKCOL", strings.TrimSuffix(target, "/")+"/", nil, 0, nil)
+		if err != nil {
+			return err
+		}
+		response.Body.Close()
+	}
+	return nil
+}
+
+func (w *webdavStorage) list(dir string) ([]string, error) {
+	target := strings.TrimSuffix(w.location(dir), "/") + "/"
+	body := `<?xml version="1.0"?><propfind xmlns="DAV:"><prop><resourcetype/></prop></propfind>`
+
+	response, err := w.request("PROPFIND", target, strings.NewReader(body), int64(len(body)),
+		map[string]string{"Depth": "1", "Content-Type": "application/xml"})
+	if err != nil {
+		return nil, err
+	}
+	defer response.Body.Close()
+
+	if response.StatusCode == http.StatusNotFound {
+		return nil, nil
+	}
+	if response.StatusCode != http.StatusMultiStatus {
+		return nil, fmt.Errorf("%s: %s", target, response.Status)
+	}
+
+	var multistatus struct {
+		Responses []struct {
+			Href string `xml:"href"`
+		} `xml:"response"`
+	}
+	if err := xml.NewDecoder(response.Body).Decode(&multistatus); err != nil {
+		return nil, err
+	}
+
+	var names []string
+	for _, r := range multistatus.Responses {
+		href, err := url.PathUnescape(strings.TrimSuffix(r.Href, "/"))
+		if err != nil {
+			continue
+		}
+		names = append(names, path.Base(href))
+	}
+	return names, nil
+}
@@ -0,0 +1,49 @@
+package bcbc
+
+import (
+	"encoding/binary"
+	"fmt"
+	"sort"
+)
+
+// 拡張属性
+type xattr struct {
+	name  string
+	value []byte
+}
+
+// 拡張属性のダイジェストを指定されたアルゴリズムで計算する。
+// 名前の順に名前と値の長さと値をつなげたもののハッシュにする。
+func xattrDigest(algorithm string, xattrs []xattr) string {
+	sorted := append([]xattr(nil), xattrs...)
+	sort.Slice(sorted, func(i, j int) bool {
+		return sorted[i].name < sorted[j].name
+	})
+
+	hasher := hasherFactories[algorithm]()
+	length := make([]byte, 8)
+	for _, x := range sorted {
+		hasher.Write([]byte(x.name))
+		hasher.Write([]byte{0})
+		binary.BigEndian.PutUint64(length, uint64(len(x.value)))
+		hasher.Write(length)
+		hasher.Write(x.value)
+	}
+
+	return fmt.Sprintf("%x", hasher.Sum(nil))
+}
+
+// NUL区切りの拡張属性の名前の一覧を分割する。
+func splitXattrNames(buffer []byte) []string {
+	var names []string
+	start := 0
+	for i, c := range buffer {
+		if c == 0 {
+			if i > start {
+				names = append(names, string(buffer[start:i]))
+			}
+			start = i + 1
+		}
+	}
+	return names
+}
@@ -0,0 +1,57 @@
+// +build darwin
+
+package bcbc
+
+import (
+	"syscall"
+	"unsafe"
+)
+
+// ファイルの拡張属性を全て取得する。
+// リソースフォークもcom.apple.ResourceForkという名前の拡張属性として取得できる。
+func listXattrs(file string) ([]xattr, error) {
+	path, err := syscall.BytePtrFromString(file)
+	if err != nil {
+		return nil, err
+	}
+
+	size, err := darwinXattrCall(syscall.SYS_LISTXATTR, uintptr(unsafe.Pointer(path)), 0, 0, 0, 0, 0)
+	if err != nil || size == 0 {
+		return nil, err
+	}
+	buffer := make([]byte, size)
+	size, err = darwinXattrCall(syscall.SYS_LISTXATTR, uintptr(unsafe.Pointer(path)), uintptr(unsafe.Pointer(&buffer[0])), uintptr(size), 0, 0, 0)
+	if err != nil {
+		return nil, err
+	}
+
+	var xattrs []xattr
+	for _, name := range splitXattrNames(buffer[:size]) {
+		namePtr, err := syscall.BytePtrFromString(name)
+		if err != nil {
+			return nil, err
+		}
+
+		valueSize, err := darwinXattrCall(syscall.SYS_GETXATTR, uintptr(unsafe.Pointer(path)), uintptr(unsafe.Pointer(namePtr)), 0, 0, 0, 0)
+		if err != nil {
+			return nil, err
+		}
+		value := make([]byte, valueSize+1)
+		valueSize, err = darwinXattrCall(syscall.SYS_GETXATTR, uintptr(unsafe.Pointer(path)), uintptr(unsafe.Pointer(namePtr)), uintptr(unsafe.Pointer(&value[0])), uintptr(valueSize), 0, 0)
+		if err != nil {
+			return nil, err
+		}
+		xattrs = append(xattrs, xattr{name, value[:valueSize]})
+	}
+
+	return xattrs, nil
+}
+
+// listxattrかgetxattrを呼び出して、返されたバイト数を返す。
+func darwinXattrCall(trap uintptr, a1, a2, a3, a4, a5, a6 uintptr) (int, error) {
+	r, _, errno := syscall.Syscall6(trap, a1, a2, a3, a4, a5, a6)
+	if errno != 0 {
+		return 0, errno
+	}
+	return int(r), nil
+}
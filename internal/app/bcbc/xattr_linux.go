@@ -0,0 +1,34 @@
+// +build linux
+
+package bcbc
+
+import "syscall"
+
+// ファイルの拡張属性を全て取得する。
+func listXattrs(file string) ([]xattr, error) {
+	size, err := syscall.Listxattr(file, nil)
+	if err != nil || size == 0 {
+		return nil, err
+	}
+	buffer := make([]byte, size)
+	size, err = syscall.Listxattr(file, buffer)
+	if err != nil {
+		return nil, err
+	}
+
+	var xattrs []xattr
+	for _, name := range splitXattrNames(buffer[:size]) {
+		valueSize, err := syscall.Getxattr(file, name, nil)
+		if err != nil {
+			return nil, err
+		}
+		value := make([]byte, valueSize)
+		valueSize, err = syscall.Getxattr(file, name, value)
+		if err != nil {
+			return nil, err
+		}
+		xattrs = append(xattrs, xattr{name, value[:valueSize]})
+	}
+
+	return xattrs, nil
+}
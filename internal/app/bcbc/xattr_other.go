@@ -0,0 +1,8 @@
+// +build !linux,!darwin
+
+package bcbc
+
+// その他のOSでは拡張属性を取得しない。
+func listXattrs(file string) ([]xattr, error) {
+	return nil, nil
+}
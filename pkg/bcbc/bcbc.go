@@ -0,0 +1,116 @@
+// Package bcbc はbcbcの増分ハッシュ計算を他のプログラムに組み込むためのAPIを提供する。
+package bcbc
+
+import (
+	"io/ioutil"
+	"log"
+
+	app "github.com/solidcopy/bcbc/internal/app/bcbc"
+)
+
+// ハッシュアルゴリズム
+const (
+	AlgorithmMD5        = app.AlgorithmMD5
+	AlgorithmSHA256     = app.AlgorithmSHA256
+	AlgorithmCRC32      = app.AlgorithmCRC32
+	AlgorithmMD5Tree    = app.AlgorithmMD5Tree
+	AlgorithmSHA256Tree = app.AlgorithmSHA256Tree
+)
+
+// ハッシュファイルの保存先
+const (
+	StoreFile   = app.StoreFile
+	StoreWebDAV = app.StoreWebDAV
+	StoreS3     = app.StoreS3
+	StoreSFTP   = app.StoreSFTP
+	StoreServer = app.StoreServer
+)
+
+// シンボリックリンクの扱い
+const (
+	SymlinksSkip   = app.SymlinksSkip
+	SymlinksFollow = app.SymlinksFollow
+)
+
+// HashRun ハッシュ計算の実行内容を組み立てるビルダー
+type HashRun struct {
+	options app.RunOptions
+}
+
+// NewHashRun BCBCホームディレクトリを指定してビルダーを作成する。
+// アルゴリズムの既定値はMD5で、ログは出力しない。
+func NewHashRun(homeDir string) *HashRun {
+	return &HashRun{app.RunOptions{
+		HomeDir:    homeDir,
+		Algorithms: []string{AlgorithmMD5},
+		Logger:     log.New(ioutil.Discard, "", 0),
+	}}
+}
+
+// DiskRoots ハッシュ計算するディスクのルートディレクトリを設定する。
+func (r *HashRun) DiskRoots(diskRoots ...string) *HashRun {
+	r.options.DiskRoots = diskRoots
+	return r
+}
+
+// Algorithms ハッシュアルゴリズムを設定する。
+func (r *HashRun) Algorithms(algorithms ...string) *HashRun {
+	r.options.Algorithms = algorithms
+	return r
+}
+
+// Store ハッシュファイルの保存先を設定する。
+// 出力ディレクトリ以外に保存するときは保存先のURLも指定する。
+func (r *HashRun) Store(store string, storeURL string) *HashRun {
+	r.options.Store = store
+	r.options.StoreURL = storeURL
+	return r
+}
+
+// Symlinks シンボリックリンクの扱いを設定する。
+func (r *HashRun) Symlinks(symlinks string) *HashRun {
+	r.options.Symlinks = symlinks
+	return r
+}
+
+// MaxDepth ディスクルートから走査するディレクトリの最大深さを設定する。0なら制限しない。
+func (r *HashRun) MaxDepth(maxDepth int) *HashRun {
+	r.options.MaxDepth = maxDepth
+	return r
+}
+
+// Logger ログの出力先を設定する。進捗も含めて全ての出力はこのロガーに書き込む。
+func (r *HashRun) Logger(logger *log.Logger) *HashRun {
+	r.options.Logger = logger
+	return r
+}
+
+// Run ハッシュ計算とハッシュファイル統合を実行する。
+// 致命的なエラーで終了するときは、他のディスクのハッシュ計算も打ち切って終了を待ってから返す。
+// 同じプロセスで複数のRunを並行して実行してはいけない。
+func (r *HashRun) Run() error {
+	return app.Run(r.options)
+}
+
+// Error bcbcのエラー
+// errors.Asで取り出してKindでエラーの種類を判定できる。
+type Error = app.Error
+
+// ErrorKind エラーの種類
+type ErrorKind = app.ErrorKind
+
+// エラーの種類
+const (
+	ErrIO             = app.ErrIO
+	ErrConfig         = app.ErrConfig
+	ErrHashFileFormat = app.ErrHashFileFormat
+	ErrDiskFile       = app.ErrDiskFile
+	ErrLocked         = app.ErrLocked
+	ErrInterrupted    = app.ErrInterrupted
+	ErrDisconnected   = app.ErrDisconnected
+)
+
+// IsKind 引数errが指定された種類のErrorを含んでいるか判定する。
+func IsKind(err error, kind ErrorKind) bool {
+	return app.IsKind(err, kind)
+}